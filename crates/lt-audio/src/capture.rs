@@ -1,5 +1,5 @@
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use lt_core::AudioChunk;
+use lt_core::{AudioChunk, AudioFormat};
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::sync::mpsc;
@@ -40,13 +40,22 @@ impl AudioCapture {
         }
     }
 
-    /// Start audio capture
+    /// Start audio capture producing 16kHz mono chunks
     pub fn start(&mut self) -> Result<()> {
+        self.start_with_format(AudioFormat::default())
+    }
+
+    /// Start audio capture, resampling chunks to `output_format`
+    /// (typically the active STT provider's `input_format()`)
+    pub fn start_with_format(&mut self, output_format: AudioFormat) -> Result<()> {
         if self.is_running.load(Ordering::SeqCst) {
             return Err(AudioError::AlreadyRunning);
         }
 
-        info!("Starting audio capture");
+        info!(
+            "Starting audio capture ({} Hz, {} channel(s) out)",
+            output_format.sample_rate, output_format.channels
+        );
 
         // Get default input device
         let host = cpal::default_host();
@@ -117,8 +126,11 @@ impl AudioCapture {
                 raw_rx,
                 chunk_tx,
                 level_tx,
-                sample_rate,
-                channels,
+                AudioFormat {
+                    sample_rate,
+                    channels: channels as u16,
+                },
+                output_format,
                 is_running_clone,
                 session_start,
             )
@@ -157,7 +169,7 @@ impl AudioCapture {
         Ok(())
     }
 
-    /// Subscribe to audio chunks (resampled 16kHz mono with VAD state)
+    /// Subscribe to audio chunks (resampled to the format passed to `start_with_format`)
     pub fn subscribe_chunks(&mut self) -> Option<mpsc::Receiver<AudioChunk>> {
         self.chunk_rx.take()
     }
@@ -252,18 +264,24 @@ impl AudioCapture {
         mut raw_rx: mpsc::Receiver<Vec<i16>>,
         chunk_tx: mpsc::Sender<AudioChunk>,
         level_tx: mpsc::Sender<AudioLevel>,
-        sample_rate: u32,
-        channels: usize,
+        input_format: AudioFormat,
+        output_format: AudioFormat,
         is_running: Arc<AtomicBool>,
         session_start: Arc<AtomicU64>,
     ) {
         debug!(
             "Processing loop started: {} Hz, {} channels",
-            sample_rate, channels
+            input_format.sample_rate, input_format.channels
         );
 
-        // Create resampler (target: 16kHz mono)
-        let mut resampler = match AudioResampler::new(sample_rate, 16000, channels) {
+        // Create resampler (target: the provider's input format)
+        let mut resampler = match AudioResampler::new(
+            input_format.sample_rate,
+            output_format.sample_rate,
+            input_format.channels as usize,
+        )
+        .and_then(|r| r.with_output_channels(output_format.channels as usize))
+        {
             Ok(r) => r,
             Err(e) => {
                 error!("Failed to create resampler: {}", e);
@@ -293,7 +311,7 @@ impl AudioCapture {
                 .as_millis() as u64;
             let timestamp_ms = now_ms - start_ms;
 
-            // Resample to 16kHz mono
+            // Resample to the output format
             let resampled = match resampler.resample(&raw_samples) {
                 Ok(samples) => samples,
                 Err(e) => {
@@ -315,6 +333,8 @@ impl AudioCapture {
             let chunk = AudioChunk {
                 data: resampled,
                 timestamp_ms,
+                sample_rate: output_format.sample_rate,
+                channels: output_format.channels,
             };
 
             if chunk_tx.try_send(chunk).is_err() {
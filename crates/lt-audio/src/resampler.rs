@@ -1,11 +1,13 @@
 use crate::error::{AudioError, Result};
 
-/// Audio resampler for converting to 16kHz mono
+/// Audio resampler for converting capture audio to the STT input format
+/// (16kHz mono unless the provider asks otherwise)
 /// Uses simple linear interpolation for now (can be upgraded to rubato later)
 pub struct AudioResampler {
     input_sample_rate: u32,
     output_sample_rate: u32,
     channels: usize,
+    output_channels: usize,
 }
 
 impl AudioResampler {
@@ -26,19 +28,45 @@ impl AudioResampler {
             input_sample_rate,
             output_sample_rate,
             channels,
+            output_channels: 1,
         })
     }
 
-    /// Resample i16 samples to 16kHz mono using linear interpolation
+    /// Produce `output_channels` interleaved channels (the mono mix duplicated)
+    /// instead of mono
+    pub fn with_output_channels(mut self, output_channels: usize) -> Result<Self> {
+        if output_channels == 0 {
+            return Err(AudioError::UnsupportedFormat(
+                "Number of output channels must be > 0".to_string(),
+            ));
+        }
+        self.output_channels = output_channels;
+        Ok(self)
+    }
+
+    /// Resample i16 samples to the output rate using linear interpolation
     ///
     /// # Arguments
     /// * `input` - Input samples (interleaved if multi-channel)
     ///
     /// # Returns
-    /// Resampled mono i16 samples at target sample rate
+    /// Resampled i16 samples at target sample rate (mono unless
+    /// `with_output_channels` was used)
     pub fn resample(&mut self, input: &[i16]) -> Result<Vec<i16>> {
+        let mono = self.resample_mono(input);
+        if self.output_channels == 1 {
+            return Ok(mono);
+        }
+
+        Ok(mono
+            .iter()
+            .flat_map(|&s| std::iter::repeat_n(s, self.output_channels))
+            .collect())
+    }
+
+    fn resample_mono(&self, input: &[i16]) -> Vec<i16> {
         if input.is_empty() {
-            return Ok(Vec::new());
+            return Vec::new();
         }
 
         // Deinterleave and convert to mono
@@ -46,7 +74,7 @@ impl AudioResampler {
 
         // Resample if needed
         if self.input_sample_rate == self.output_sample_rate {
-            return Ok(mono_input);
+            return mono_input;
         }
 
         let ratio = self.output_sample_rate as f64 / self.input_sample_rate as f64;
@@ -74,7 +102,7 @@ impl AudioResampler {
             output.push(sample);
         }
 
-        Ok(output)
+        output
     }
 
     /// Convert interleaved multi-channel audio to mono
@@ -108,6 +136,10 @@ impl AudioResampler {
     pub fn output_sample_rate(&self) -> u32 {
         self.output_sample_rate
     }
+
+    pub fn output_channels(&self) -> usize {
+        self.output_channels
+    }
 }
 
 #[cfg(test)]
@@ -202,6 +234,30 @@ mod tests {
         assert_eq!(output.len(), 3);
     }
 
+    #[test]
+    fn test_resample_to_24khz() {
+        let mut resampler = AudioResampler::new(48000, 24000, 1).unwrap();
+        let input = vec![1000i16; 4800]; // 100ms at 48kHz
+        let output = resampler.resample(&input).unwrap();
+        assert_eq!(output.len(), 2400);
+    }
+
+    #[test]
+    fn test_output_channels_duplicate_mono() {
+        let mut resampler = AudioResampler::new(16000, 16000, 2)
+            .unwrap()
+            .with_output_channels(2)
+            .unwrap();
+        let input = vec![100i16, 300i16, 500i16, 700i16];
+        let output = resampler.resample(&input).unwrap();
+        assert_eq!(output, vec![200, 200, 600, 600]);
+
+        assert!(AudioResampler::new(16000, 16000, 1)
+            .unwrap()
+            .with_output_channels(0)
+            .is_err());
+    }
+
     #[test]
     fn test_high_sample_rate_conversion() {
         // Test 96kHz to 16kHz (6:1 ratio)
@@ -6,7 +6,7 @@ use crate::error::{MurmurError, Result};
 use crate::output::OutputMode;
 
 /// STT provider type
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum SttProviderType {
     #[default]
@@ -17,6 +17,61 @@ pub enum SttProviderType {
     AppleStt,
     #[serde(rename = "custom_stt")]
     CustomStt,
+    Deepgram,
+    #[serde(rename = "openai_realtime")]
+    OpenAIRealtime,
+    AssemblyAI,
+    #[serde(rename = "custom_streaming")]
+    CustomStreaming,
+    #[serde(rename = "local_whisper")]
+    LocalWhisper,
+    /// Any other id names a Whisper-compatible REST endpoint: a built-in
+    /// vendor preset ("fireworks", "together", "azure_openai", "mistral")
+    /// or an `[stt_endpoints.<id>]` table
+    #[serde(untagged)]
+    Endpoint(String),
+}
+
+impl SttProviderType {
+    /// Providers with their own variant, in picker order
+    pub const BUILT_IN: &'static [Self] = &[
+        Self::ElevenLabs,
+        Self::Deepgram,
+        Self::OpenAIRealtime,
+        Self::AssemblyAI,
+        Self::OpenAI,
+        Self::Groq,
+        Self::AppleStt,
+        Self::LocalWhisper,
+        Self::CustomStt,
+        Self::CustomStreaming,
+    ];
+
+    /// Id of this provider in the config file (also its `api_keys` slot)
+    pub fn id(&self) -> &str {
+        match self {
+            Self::ElevenLabs => "elevenlabs",
+            Self::OpenAI => "openai",
+            Self::Groq => "groq",
+            Self::AppleStt => "apple_stt",
+            Self::CustomStt => "custom_stt",
+            Self::Deepgram => "deepgram",
+            Self::OpenAIRealtime => "openai_realtime",
+            Self::AssemblyAI => "assemblyai",
+            Self::CustomStreaming => "custom_streaming",
+            Self::LocalWhisper => "local_whisper",
+            Self::Endpoint(id) => id,
+        }
+    }
+
+    /// Provider for a config id; ids without a variant are endpoints
+    pub fn from_id(id: &str) -> Self {
+        Self::BUILT_IN
+            .iter()
+            .find(|p| p.id().eq_ignore_ascii_case(id))
+            .cloned()
+            .unwrap_or_else(|| Self::Endpoint(id.to_string()))
+    }
 }
 
 /// LLM processor type
@@ -36,6 +91,63 @@ pub enum LlmProcessorType {
     GeminiApi,
     #[serde(rename = "custom_api")]
     CustomApi,
+    Ollama,
+}
+
+impl LlmProcessorType {
+    /// Id of this processor in the config file
+    pub fn id(&self) -> &'static str {
+        match self {
+            Self::Gemini => "gemini",
+            Self::Copilot => "copilot",
+            Self::AppleLlm => "apple_llm",
+            Self::OpenAiApi => "openai_api",
+            Self::ClaudeApi => "claude_api",
+            Self::GeminiApi => "gemini_api",
+            Self::CustomApi => "custom_api",
+            Self::Ollama => "ollama",
+        }
+    }
+}
+
+/// An LLM processor with an optional model, as listed in the fallback
+/// chain and the task routes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LlmProcessorSpec {
+    pub processor: LlmProcessorType,
+    /// Model override (None = provider default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+impl LlmProcessorSpec {
+    /// Name recorded with the output it produced, e.g. "ollama/llama3.2"
+    pub fn label(&self) -> String {
+        match self.model.as_deref().filter(|m| !m.is_empty()) {
+            Some(model) => format!("{}/{}", self.processor.id(), model),
+            None => self.processor.id().to_string(),
+        }
+    }
+}
+
+/// How failed requests to one HTTP provider are retried (see
+/// `lt_core::retry::RetryPolicy`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RetryConfig {
+    /// Attempts per request including the first, at most 5 (None = 3,
+    /// 1 = no retries)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_attempts: Option<u32>,
+    /// Backoff before the first retry, doubled after each attempt (None = 500)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_delay_ms: Option<u64>,
+    /// Longest backoff between attempts (None = 8000)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_delay_ms: Option<u64>,
+    /// Longest `Retry-After` wait honored; longer waits fail the request
+    /// (None = 20)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_retry_after_secs: Option<u64>,
 }
 
 /// HTTP LLM provider configuration
@@ -47,6 +159,37 @@ pub struct HttpLlmConfig {
     /// Display name for custom endpoint in UI
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub custom_display_name: Option<String>,
+    /// Retry settings per provider id ("openai_api", "claude_api",
+    /// "gemini_api", "custom_api", "ollama")
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub retry: HashMap<String, RetryConfig>,
+    /// Request timeout in seconds per provider id (default 30)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub timeout_secs: HashMap<String, u64>,
+}
+
+/// Native Ollama server configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct OllamaConfig {
+    /// Server URL (None = "http://localhost:11434")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    /// How long the model stays loaded after a request, e.g. "10m", "1h",
+    /// or "-1" for always (None = "30m")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_alive: Option<String>,
+    /// Context window in tokens (None = model default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_ctx: Option<u32>,
+    /// Sampling temperature (None = model default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    /// Further model options passed through as-is (e.g. `top_p`, `num_gpu`)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub options: HashMap<String, serde_json::Value>,
+    /// Request timeout in seconds (None = 120)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
 }
 
 /// HTTP STT provider configuration (for custom_stt)
@@ -64,6 +207,281 @@ pub struct HttpSttConfig {
     /// Language hint (ISO-639-1 code, e.g. "en", "zh", "ja")
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub language: Option<String>,
+    /// Audio (ms) of each chunk repeated at the start of the next upload for
+    /// all REST Whisper providers; words duplicated at the seam are removed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_overlap_ms: Option<u64>,
+    /// Chunks with a smaller share of non-quiet audio are not uploaded by
+    /// the REST Whisper providers (unset = 0.05, 0 = upload everything)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_speech_ratio: Option<f32>,
+    /// Drop phantom phrases ("Thank you for watching.") and repetition
+    /// loops from REST Whisper output (unset = on)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter_hallucinations: Option<bool>,
+    /// Phrases dropped in addition to the built-in list; end one with "…"
+    /// to match it as a prefix
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hallucination_phrases: Vec<String>,
+    /// Whisper prompt sent with every REST chunk ahead of the text so far,
+    /// e.g. "Use British spelling and Oxford commas."
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style_prompt: Option<String>,
+}
+
+/// How a REST STT endpoint expects the API key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SttAuthScheme {
+    /// `Authorization: Bearer <key>`
+    #[default]
+    Bearer,
+    /// The key as-is in a named header (e.g. Azure's `api-key`)
+    Header { name: String },
+    /// No credentials are sent
+    None,
+}
+
+/// Response format requested from a Whisper-compatible endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SttResponseFormat {
+    /// Text only; every compatible server supports it
+    #[default]
+    Json,
+    /// Text plus detected language, segments and word timings
+    VerboseJson,
+}
+
+/// Audio container/codec used for REST uploads
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SttAudioEncoding {
+    /// Uncompressed 16-bit PCM WAV; accepted everywhere
+    #[default]
+    Wav,
+    /// Lossless FLAC, typically half the size of WAV
+    Flac,
+    /// Opus in an Ogg container (~24 kbit/s); needs the `opus` build feature
+    OggOpus,
+}
+
+impl SttAudioEncoding {
+    /// MIME type of the upload
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            SttAudioEncoding::Wav => "audio/wav",
+            SttAudioEncoding::Flac => "audio/flac",
+            SttAudioEncoding::OggOpus => "audio/ogg",
+        }
+    }
+
+    /// File name of the multipart upload; servers detect the format from
+    /// the extension
+    pub fn file_name(self) -> &'static str {
+        match self {
+            SttAudioEncoding::Wav => "audio.wav",
+            SttAudioEncoding::Flac => "audio.flac",
+            SttAudioEncoding::OggOpus => "audio.ogg",
+        }
+    }
+}
+
+/// One REST transcription endpoint, keyed by provider id in
+/// [`AppConfig::stt_endpoints`]. For a built-in id unset fields keep the
+/// preset; any other id defines a new endpoint that can be selected as
+/// `stt_provider`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RestSttEndpointConfig {
+    /// Name shown in the provider picker
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    /// Full transcription URL (required for `azure_openai`, which is
+    /// `https://<resource>.openai.azure.com/openai/deployments/<deployment>/audio/transcriptions?api-version=...`).
+    /// Takes precedence over `base_url` and `path`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// API root such as `https://api.example.com/v1`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    /// Path appended to `base_url` (None = `/audio/transcriptions`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Whether requests need `api_keys.<id>` (None = the preset's setting,
+    /// or true for new endpoints)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requires_api_key: Option<bool>,
+    /// How the API key is sent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<SttAuthScheme>,
+    /// Model name sent in the form
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Language hint (ISO-639-1)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Response format to request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<SttResponseFormat>,
+    /// Per-request timeout in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    /// Uploads allowed in flight at once
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<usize>,
+    /// Target chunk length (ms)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_ms: Option<u64>,
+    /// Overlap between chunks (ms), overriding `http_stt_config.chunk_overlap_ms`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_overlap_ms: Option<u64>,
+    /// Encoding of uploaded chunks (None = wav)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<SttAudioEncoding>,
+    /// How rate-limited or failed uploads are retried
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryConfig>,
+}
+
+/// Wire protocol of a self-hosted streaming STT server
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamingSttProtocol {
+    /// Binary PCM up, `{"text", "is_final"}` messages down, `{"type":"end"}`
+    /// to finish (see `config/default.toml`)
+    #[default]
+    Json,
+    /// Collabora WhisperLive: float PCM and a list of recent `segments`
+    WhisperLive,
+    /// faster-whisper-server / speaches: the transcript so far as `text`
+    FasterWhisper,
+}
+
+/// PCM sample format sent to a streaming STT server
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamingAudioEncoding {
+    /// 16-bit signed little-endian
+    #[default]
+    PcmS16le,
+    /// 32-bit float little-endian, -1.0 - 1.0
+    PcmF32le,
+}
+
+/// Self-hosted streaming STT server (for custom_streaming). Unset fields
+/// keep the defaults of the chosen protocol; message paths are dot-separated
+/// ("segments", "result.text", "alternatives.0.transcript") and an empty
+/// string turns an optional one off.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StreamingSttConfig {
+    /// WebSocket URL, e.g. "ws://localhost:9090"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Display name in the UI
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    /// Protocol preset (None = json)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<StreamingSttProtocol>,
+    /// How the `custom_streaming` API key is sent (None = bearer, if set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<SttAuthScheme>,
+    /// Model name passed to the server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Language hint passed to the server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Sample rate of the PCM sent (None = 16000)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample_rate: Option<u32>,
+    /// Sample format of the PCM sent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<StreamingAudioEncoding>,
+    /// JSON sent after connecting; `{model}`, `{language}`, `{sample_rate}`
+    /// and `{uid}` are replaced with JSON values (`null` when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_message: Option<String>,
+    /// Message sent after the last audio
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_message: Option<String>,
+    /// Send `end_message` as a binary frame instead of text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_message_binary: Option<bool>,
+    /// Path to an array of segments (unset = each message is one segment)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub segments_path: Option<String>,
+    /// Path to the text within a segment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text_path: Option<String>,
+    /// Path to the flag that makes a segment final (unset = text is
+    /// committed when the session ends)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub final_path: Option<String>,
+    /// Path to a value identifying a segment that is repeated across messages
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub segment_key_path: Option<String>,
+    /// Path to an error message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_path: Option<String>,
+}
+
+/// On-device Whisper configuration (for local_whisper, needs a build with
+/// the `local-whisper` feature)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LocalWhisperConfig {
+    /// Path to a GGML/GGUF whisper.cpp model file, e.g. "~/models/ggml-base.en.bin"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_path: Option<String>,
+    /// ISO-639-1 language, or "auto" to detect it (None = auto)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// CPU threads used for decoding (None = up to 4)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threads: Option<u32>,
+    /// How often the open segment is decoded for partials (None = 1000)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step_ms: Option<u64>,
+    /// Longest segment before it is committed without a pause (None = 10000)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub length_ms: Option<u64>,
+}
+
+/// Network settings for the HTTP client shared by the cloud providers
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    /// Proxy for all requests, e.g. "http://proxy.corp:8080" or
+    /// "socks5h://127.0.0.1:1080" (None = system / environment proxy)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    /// Comma-separated hosts that bypass `proxy`, e.g. "localhost,.corp"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub no_proxy: Option<String>,
+    /// PEM bundle of extra root certificates, e.g. a TLS-inspecting
+    /// proxy's CA ("~" expands to the home directory)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_bundle: Option<String>,
+    /// Trust only `ca_bundle`, not the system roots (None = false)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_bundle_only: Option<bool>,
+    /// TCP/TLS connect timeout in seconds (None = 10)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connect_timeout_secs: Option<u64>,
+}
+
+/// Offline queue for dictations recorded while providers are unreachable
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OfflineQueueConfig {
+    /// Save the recording for later instead of failing when the STT or LLM
+    /// provider cannot be reached (None = false)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    /// Seconds between attempts to process queued dictations (None = 60)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_interval_secs: Option<u64>,
+    /// Most dictations kept; the oldest are dropped beyond this (None = 50)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_items: Option<usize>,
 }
 
 /// UI preferences
@@ -87,12 +505,91 @@ impl Default for UiPreferences {
     }
 }
 
+/// Deepgram streaming STT configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct DeepgramConfig {
+    /// Model name (None = "nova-3")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// BCP-47 language code, or "multi" for code-switching (None = server default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Words to boost; "term:2" sets an intensifier for pre-Nova-3 models
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+    /// WebSocket base URL for self-hosted deployments (None = Deepgram cloud)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+}
+
+/// AssemblyAI universal-streaming configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct AssemblyAiConfig {
+    /// Streaming model (None = "universal-streaming-english")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speech_model: Option<String>,
+    /// Terms to boost, in addition to the personal dictionary
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keyterms: Vec<String>,
+    /// Wait for punctuated, cased turns before committing (None = true)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format_turns: Option<bool>,
+    /// Confidence needed to end a turn, 0.0 - 1.0 (None = server default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_of_turn_confidence_threshold: Option<f32>,
+    /// Silence that ends a turn regardless of confidence (None = server default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_turn_silence_ms: Option<u32>,
+    /// Connect with a short-lived token minted from the key (None = false)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temporary_token: Option<bool>,
+    /// WebSocket base URL (None = AssemblyAI)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+}
+
+/// OpenAI Realtime transcription configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct OpenAiRealtimeConfig {
+    /// Transcription model (None = "gpt-4o-transcribe")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// ISO-639-1 language hint (None = automatic)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Prompt to steer spelling and vocabulary
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    /// Let the server split turns at pauses (None = true)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_vad: Option<bool>,
+    /// Server VAD activation threshold, 0.0 - 1.0
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vad_threshold: Option<f32>,
+    /// Silence that ends a turn under server VAD
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub silence_duration_ms: Option<u32>,
+    /// WebSocket base URL (None = OpenAI)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+}
+
 /// Application configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
     /// Selected STT provider
     pub stt_provider: SttProviderType,
 
+    /// Providers tried in order when `stt_provider` fails to connect or
+    /// errors mid-session (empty = no failover)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stt_fallback_providers: Vec<SttProviderType>,
+
+    /// Ask providers that support it to label who said what, and pass a
+    /// speaker-labelled transcript to the LLM (None = false)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diarization: Option<bool>,
+
     /// API keys (provider_name -> api_key)
     pub api_keys: HashMap<String, String>,
 
@@ -106,6 +603,16 @@ pub struct AppConfig {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub llm_model: Option<String>,
 
+    /// Processors tried in order when `llm_processor` fails; the raw
+    /// transcript is output when all of them fail (empty = no fallback)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub llm_fallback_processors: Vec<LlmProcessorSpec>,
+
+    /// Processor per task type ("post_process", "shorten", "change_tone",
+    /// "generate_reply", "translate"), tried before `llm_processor`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub llm_task_routes: HashMap<String, LlmProcessorSpec>,
+
     /// Output mode
     pub output_mode: OutputMode,
 
@@ -124,9 +631,46 @@ pub struct AppConfig {
     #[serde(default)]
     pub http_llm_config: HttpLlmConfig,
 
+    /// Native Ollama server configuration
+    #[serde(default)]
+    pub ollama_config: OllamaConfig,
+
     /// HTTP STT provider configuration (for custom_stt)
     #[serde(default)]
     pub http_stt_config: HttpSttConfig,
+
+    /// Per-endpoint settings for the REST STT providers, keyed by provider
+    /// id (e.g. "groq", "azure_openai")
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub stt_endpoints: HashMap<String, RestSttEndpointConfig>,
+
+    /// Deepgram streaming STT configuration
+    #[serde(default)]
+    pub deepgram_config: DeepgramConfig,
+
+    /// OpenAI Realtime transcription configuration
+    #[serde(default)]
+    pub openai_realtime_config: OpenAiRealtimeConfig,
+
+    /// AssemblyAI streaming configuration
+    #[serde(default)]
+    pub assemblyai_config: AssemblyAiConfig,
+
+    /// Self-hosted streaming STT server
+    #[serde(default)]
+    pub streaming_stt_config: StreamingSttConfig,
+
+    /// On-device Whisper model and decoding settings
+    #[serde(default)]
+    pub local_whisper_config: LocalWhisperConfig,
+
+    /// Proxy and TLS settings for HTTP requests to cloud providers
+    #[serde(default)]
+    pub network: NetworkConfig,
+
+    /// Queueing of dictations recorded without network
+    #[serde(default)]
+    pub offline_queue: OfflineQueueConfig,
 }
 
 fn default_apple_stt_locale() -> String {
@@ -141,16 +685,29 @@ impl Default for AppConfig {
     fn default() -> Self {
         Self {
             stt_provider: SttProviderType::default(),
+            stt_fallback_providers: Vec::new(),
+            diarization: None,
             api_keys: HashMap::new(),
             hotkey: "Ctrl+`".to_string(),
             llm_processor: LlmProcessorType::default(),
             llm_model: None,
+            llm_fallback_processors: Vec::new(),
+            llm_task_routes: HashMap::new(),
             output_mode: OutputMode::default(),
             ui_preferences: UiPreferences::default(),
             apple_stt_locale: default_apple_stt_locale(),
             elevenlabs_language: default_elevenlabs_language(),
             http_llm_config: HttpLlmConfig::default(),
+            ollama_config: OllamaConfig::default(),
             http_stt_config: HttpSttConfig::default(),
+            stt_endpoints: HashMap::new(),
+            deepgram_config: DeepgramConfig::default(),
+            openai_realtime_config: OpenAiRealtimeConfig::default(),
+            assemblyai_config: AssemblyAiConfig::default(),
+            streaming_stt_config: StreamingSttConfig::default(),
+            local_whisper_config: LocalWhisperConfig::default(),
+            network: NetworkConfig::default(),
+            offline_queue: OfflineQueueConfig::default(),
         }
     }
 }
@@ -189,3 +746,136 @@ impl AppConfig {
         Ok(())
     }
 }
+
+/// Expand a leading `~` to the home directory
+pub fn expand_home(path: &str) -> PathBuf {
+    let home = || std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"));
+    match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with(['/', '\\']) => match home() {
+            Some(home) => PathBuf::from(home).join(rest.trim_start_matches(['/', '\\'])),
+            None => PathBuf::from(path),
+        },
+        _ => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_home() {
+        let Some(home) = std::env::var_os("HOME") else {
+            return;
+        };
+        assert_eq!(
+            expand_home("~/models/ggml-base.bin"),
+            PathBuf::from(&home).join("models/ggml-base.bin")
+        );
+        assert_eq!(expand_home("~"), PathBuf::from(&home));
+        assert_eq!(expand_home("~user/x"), PathBuf::from("~user/x"));
+        assert_eq!(expand_home("/opt/x.bin"), PathBuf::from("/opt/x.bin"));
+    }
+
+    #[test]
+    fn test_llm_fallbacks_and_routes_from_toml() {
+        let config: AppConfig = toml::from_str(
+            r#"
+            stt_provider = "elevenlabs"
+            hotkey = "Ctrl+`"
+            llm_processor = "claude_api"
+            output_mode = "clipboard"
+            llm_fallback_processors = [{ processor = "ollama", model = "llama3.2" }]
+
+            [ui_preferences]
+            opacity = 0.9
+            show_waveform = true
+            theme = "dark"
+
+            [api_keys]
+
+            [llm_task_routes.translate]
+            processor = "gemini_api"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.llm_fallback_processors[0].label(), "ollama/llama3.2");
+        let translate = &config.llm_task_routes["translate"];
+        assert_eq!(translate.processor, LlmProcessorType::GeminiApi);
+        assert_eq!(translate.label(), "gemini_api");
+    }
+
+    #[test]
+    fn test_llm_processor_id_matches_config_name() {
+        for processor in [
+            LlmProcessorType::Gemini,
+            LlmProcessorType::Copilot,
+            LlmProcessorType::AppleLlm,
+            LlmProcessorType::OpenAiApi,
+            LlmProcessorType::ClaudeApi,
+            LlmProcessorType::GeminiApi,
+            LlmProcessorType::CustomApi,
+            LlmProcessorType::Ollama,
+        ] {
+            assert_eq!(serde_json::to_value(processor).unwrap(), processor.id());
+        }
+    }
+
+    #[test]
+    fn test_stt_provider_ids_round_trip() {
+        for provider in SttProviderType::BUILT_IN {
+            assert_eq!(serde_json::to_value(provider).unwrap(), provider.id());
+            assert_eq!(&SttProviderType::from_id(provider.id()), provider);
+        }
+
+        // Ids without a variant are endpoints, including user-defined ones
+        let config: AppConfig = toml::from_str(
+            r#"
+            stt_provider = "my_whisper"
+            stt_fallback_providers = ["fireworks", "groq"]
+            hotkey = "Ctrl+`"
+            llm_processor = "gemini"
+            output_mode = "clipboard"
+
+            [ui_preferences]
+            opacity = 0.9
+            show_waveform = true
+            theme = "dark"
+
+            [api_keys]
+
+            [stt_endpoints.my_whisper]
+            display_name = "My Whisper"
+            base_url = "https://stt.example.com/v1"
+            auth = { type = "header", name = "x-api-key" }
+            model = "large-v3"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.stt_provider,
+            SttProviderType::Endpoint("my_whisper".to_string())
+        );
+        assert_eq!(
+            config.stt_fallback_providers,
+            [
+                SttProviderType::Endpoint("fireworks".to_string()),
+                SttProviderType::Groq
+            ]
+        );
+        let endpoint = &config.stt_endpoints["my_whisper"];
+        assert_eq!(
+            endpoint.base_url.as_deref(),
+            Some("https://stt.example.com/v1")
+        );
+        assert_eq!(
+            toml::to_string(&config).unwrap().lines().next(),
+            Some(r#"stt_provider = "my_whisper""#)
+        );
+        assert_eq!(
+            SttProviderType::from_id("Deepgram"),
+            SttProviderType::Deepgram
+        );
+    }
+}
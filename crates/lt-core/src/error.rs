@@ -1,5 +1,166 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
 use thiserror::Error;
 
+/// Classified failure kind for a provider (STT / LLM) call
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProviderErrorKind {
+    /// Missing, invalid or revoked credentials
+    Auth,
+    /// Too many requests; retry after the given delay if the server sent one
+    RateLimited { retry_after_secs: Option<u64> },
+    /// Connection refused, DNS failure, TLS failure, dropped socket
+    Network,
+    /// The request did not complete in time
+    Timeout,
+    /// Account credits or plan quota exhausted
+    Quota,
+    /// The provider rejected the request as malformed
+    BadRequest,
+    /// Provider is down, overloaded, or not installed (e.g. missing CLI)
+    Unavailable,
+    /// Access denied for this account, model or OS permission
+    Permission,
+    /// The request was cancelled locally
+    Cancelled,
+}
+
+impl ProviderErrorKind {
+    /// Whether retrying the same request later can reasonably succeed
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::RateLimited { .. } | Self::Network | Self::Timeout | Self::Unavailable
+        )
+    }
+}
+
+/// Structured provider error, serialized to the frontend as-is
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProviderError {
+    /// What went wrong
+    pub kind: ProviderErrorKind,
+    /// Provider id as used in the config (e.g. "elevenlabs", "claude_api")
+    pub provider: String,
+    /// HTTP status code, when the failure came from an HTTP response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    /// Whether the caller may retry the same request
+    pub retryable: bool,
+    /// How long the server asked the client to wait before retrying (ms)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after_ms: Option<u64>,
+    /// Human-readable message
+    pub message: String,
+}
+
+/// Error codes vendors use for an exhausted balance or plan: OpenAI and
+/// OpenAI-compatible APIs (`insufficient_quota`), Anthropic
+/// (`billing_error`) and ElevenLabs (`quota_exceeded`)
+const QUOTA_ERROR_CODES: &[&str] = &["insufficient_quota", "billing_error", "quota_exceeded"];
+
+impl ProviderError {
+    /// Create an error whose retryable flag is derived from its kind
+    pub fn new(
+        kind: ProviderErrorKind,
+        provider: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            kind,
+            provider: provider.into(),
+            status: None,
+            retryable: kind.is_retryable(),
+            retry_after_ms: None,
+            message: message.into(),
+        }
+    }
+
+    /// Attach the HTTP status code
+    pub fn with_status(mut self, status: u16) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Attach the wait the server asked for (from `Retry-After` and similar
+    /// headers)
+    pub fn with_retry_after(mut self, delay: Option<Duration>) -> Self {
+        self.retry_after_ms = delay.map(|d| d.as_millis() as u64);
+        self
+    }
+
+    /// Override the retryable flag derived from the kind
+    pub fn with_retryable(mut self, retryable: bool) -> Self {
+        self.retryable = retryable;
+        self
+    }
+
+    /// Classify a non-2xx HTTP response.
+    ///
+    /// `body` is only inspected for the vendors' billing error codes, since
+    /// several of them report an exhausted balance with 429 or 401. Per-minute
+    /// and per-day limits stay `RateLimited` even when their message talks
+    /// about quota or billing.
+    pub fn from_http_status(
+        provider: impl Into<String>,
+        status: u16,
+        retry_after_secs: Option<u64>,
+        body: &str,
+    ) -> Self {
+        let quota_code = QUOTA_ERROR_CODES
+            .iter()
+            .any(|code| body.contains(&format!("\"{}\"", code)));
+
+        let (kind, message) = match status {
+            400..=499 if quota_code => (
+                ProviderErrorKind::Quota,
+                "Quota exceeded. Check your plan or billing.".to_string(),
+            ),
+            401 => (
+                ProviderErrorKind::Auth,
+                "Authentication failed. Check your API key.".to_string(),
+            ),
+            402 => (
+                ProviderErrorKind::Quota,
+                "Quota exceeded. Check your plan or billing.".to_string(),
+            ),
+            403 => (
+                ProviderErrorKind::Permission,
+                "Access denied for this account or model.".to_string(),
+            ),
+            408 => (
+                ProviderErrorKind::Timeout,
+                "Request timed out on the server.".to_string(),
+            ),
+            429 => (
+                ProviderErrorKind::RateLimited { retry_after_secs },
+                "Rate limited. Please wait and try again.".to_string(),
+            ),
+            500..=599 => (
+                ProviderErrorKind::Unavailable,
+                "Server error. Try again later.".to_string(),
+            ),
+            _ => (
+                ProviderErrorKind::BadRequest,
+                format!(
+                    "API request failed (HTTP {}): {}",
+                    status,
+                    body.chars().take(200).collect::<String>()
+                ),
+            ),
+        };
+
+        Self::new(kind, provider, message).with_status(status)
+    }
+}
+
+impl std::fmt::Display for ProviderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.message, self.provider)
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum MurmurError {
     #[error("Audio error: {0}")]
@@ -23,6 +184,9 @@ pub enum MurmurError {
     #[error("Invalid state: {0}")]
     InvalidState(String),
 
+    #[error("Provider error: {0}")]
+    Provider(ProviderError),
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -33,6 +197,27 @@ pub enum MurmurError {
     Toml(#[from] toml::de::Error),
 }
 
+impl MurmurError {
+    /// Structured provider error, if this error came from a provider call
+    pub fn provider_error(&self) -> Option<&ProviderError> {
+        match self {
+            Self::Provider(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    /// Whether the failed operation may be retried
+    pub fn is_retryable(&self) -> bool {
+        self.provider_error().is_some_and(|e| e.retryable)
+    }
+}
+
+impl From<ProviderError> for MurmurError {
+    fn from(err: ProviderError) -> Self {
+        MurmurError::Provider(err)
+    }
+}
+
 pub type Result<T> = std::result::Result<T, MurmurError>;
 
 #[cfg(test)]
@@ -75,6 +260,99 @@ mod tests {
         assert!(matches!(err, MurmurError::Toml(_)));
     }
 
+    #[test]
+    fn test_provider_error_from_http_status() {
+        let err = ProviderError::from_http_status("openai", 401, None, "");
+        assert_eq!(err.kind, ProviderErrorKind::Auth);
+        assert_eq!(err.status, Some(401));
+        assert!(!err.retryable);
+
+        let err = ProviderError::from_http_status("groq", 429, Some(7), "slow down");
+        assert_eq!(
+            err.kind,
+            ProviderErrorKind::RateLimited {
+                retry_after_secs: Some(7)
+            }
+        );
+        assert!(err.retryable);
+
+        let err = ProviderError::from_http_status(
+            "openai",
+            429,
+            None,
+            r#"{"error":{"code":"insufficient_quota"}}"#,
+        );
+        assert_eq!(err.kind, ProviderErrorKind::Quota);
+        assert!(!err.retryable);
+
+        let err = ProviderError::from_http_status("deepgram", 402, None, "");
+        assert_eq!(err.kind, ProviderErrorKind::Quota);
+
+        let err = ProviderError::from_http_status(
+            "elevenlabs",
+            401,
+            None,
+            r#"{"detail":{"status":"quota_exceeded","message":"This request exceeds your quota."}}"#,
+        );
+        assert_eq!(err.kind, ProviderErrorKind::Quota);
+
+        let err = ProviderError::from_http_status("claude_api", 503, None, "");
+        assert_eq!(err.kind, ProviderErrorKind::Unavailable);
+        assert!(err.retryable);
+
+        let err = ProviderError::from_http_status("custom_api", 422, None, "bad field");
+        assert_eq!(err.kind, ProviderErrorKind::BadRequest);
+        assert!(err.message.contains("bad field"));
+    }
+
+    #[test]
+    fn test_rate_limits_mentioning_quota_stay_retryable() {
+        let groq = r#"{"error":{"message":"Rate limit reached for model `whisper-large-v3` in organization `org_01h` service tier `on_demand` on seconds of audio per hour (ASPH): Limit 7200, Used 7185, Requested 30. Please try again in 7.5s. Need more tokens? Upgrade to Dev Tier today at https://console.groq.com/settings/billing","type":"seconds","code":"rate_limit_exceeded"}}"#;
+        let gemini = r#"{"error":{"code":429,"message":"You exceeded your current quota, please check your plan and billing details. For more information on this error, head to: https://ai.google.dev/gemini-api/docs/rate-limits.","status":"RESOURCE_EXHAUSTED","details":[{"@type":"type.googleapis.com/google.rpc.QuotaFailure","violations":[{"quotaMetric":"generativelanguage.googleapis.com/generate_content_free_tier_requests","quotaId":"GenerateRequestsPerMinutePerProjectPerModel-FreeTier","quotaDimensions":{"location":"global","model":"gemini-2.0-flash"},"quotaValue":"15"}]},{"@type":"type.googleapis.com/google.rpc.RetryInfo","retryDelay":"7s"}]}}"#;
+
+        for (provider, body) in [("groq", groq), ("gemini_api", gemini)] {
+            let err = ProviderError::from_http_status(provider, 429, Some(7), body);
+            assert_eq!(
+                err.kind,
+                ProviderErrorKind::RateLimited {
+                    retry_after_secs: Some(7)
+                },
+                "{}",
+                provider
+            );
+            assert!(err.retryable);
+        }
+    }
+
+    #[test]
+    fn test_provider_error_into_murmur_error() {
+        let err: MurmurError =
+            ProviderError::new(ProviderErrorKind::Timeout, "elevenlabs", "timed out").into();
+        assert!(err.is_retryable());
+        assert_eq!(
+            err.provider_error().map(|e| e.kind),
+            Some(ProviderErrorKind::Timeout)
+        );
+        assert_eq!(err.to_string(), "Provider error: timed out (elevenlabs)");
+
+        assert!(!MurmurError::Stt("boom".to_string()).is_retryable());
+    }
+
+    #[test]
+    fn test_provider_error_serialization() {
+        let err = ProviderError::from_http_status("groq", 429, Some(3), "");
+        let json = serde_json::to_value(&err).unwrap();
+        assert_eq!(json["kind"]["type"], "rate_limited");
+        assert_eq!(json["kind"]["retry_after_secs"], 3);
+        assert_eq!(json["status"], 429);
+        assert_eq!(json["retryable"], true);
+        assert!(json.get("retry_after_ms").is_none());
+
+        let err = err.with_retry_after(Some(Duration::from_millis(2500)));
+        let json = serde_json::to_value(&err).unwrap();
+        assert_eq!(json["retry_after_ms"], 2500);
+    }
+
     #[test]
     fn test_result_type() {
         let ok_result: Result<String> = Ok("success".to_string());
@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use std::path::Path;
 
 use crate::error::Result;
+use crate::stt::TranscriptWord;
 
 const DEFAULT_MAX_ENTRIES: usize = 500;
 
@@ -22,6 +23,29 @@ pub struct HistoryEntry {
     /// Voice command used, if any
     #[serde(skip_serializing_if = "Option::is_none")]
     pub command_name: Option<String>,
+    /// Word timing/confidence of the raw transcription, when the provider reported it
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub words: Vec<TranscriptWord>,
+    /// Language detected by the STT provider
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// STT provider that produced the raw transcription
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stt_provider: Option<String>,
+    /// LLM processor that produced `final_text` (None = raw transcription,
+    /// or not recorded)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub llm_processor: Option<String>,
+}
+
+impl HistoryEntry {
+    /// Words of the raw transcription worth reviewing (confidence below `threshold`)
+    pub fn low_confidence_words(&self, threshold: f32) -> Vec<&TranscriptWord> {
+        self.words
+            .iter()
+            .filter(|w| w.confidence.is_some_and(|c| c < threshold))
+            .collect()
+    }
 }
 
 /// Persistent transcription history
@@ -123,6 +147,10 @@ mod tests {
             timestamp_ms: id.parse().unwrap_or(0),
             processing_time_ms: 100,
             command_name: None,
+            words: Vec::new(),
+            language: None,
+            stt_provider: None,
+            llm_processor: None,
         }
     }
 
@@ -222,6 +250,38 @@ mod tests {
         assert_eq!(results.len(), 1);
     }
 
+    #[test]
+    fn test_entry_without_words_loads() {
+        let json = r#"{"id":"1","final_text":"a","timestamp_ms":1,"processing_time_ms":2}"#;
+        let entry: HistoryEntry = serde_json::from_str(json).unwrap();
+        assert!(entry.words.is_empty());
+        assert!(entry.language.is_none());
+        assert!(entry.stt_provider.is_none());
+    }
+
+    #[test]
+    fn test_low_confidence_words() {
+        let mut entry = make_entry("1", "fine maybe");
+        entry.words = vec![
+            TranscriptWord {
+                text: "fine".to_string(),
+                start_ms: 0,
+                end_ms: 300,
+                confidence: Some(0.92),
+            },
+            TranscriptWord {
+                text: "maybe".to_string(),
+                start_ms: 300,
+                end_ms: 600,
+                confidence: Some(0.41),
+            },
+        ];
+
+        let low = entry.low_confidence_words(0.6);
+        assert_eq!(low.len(), 1);
+        assert_eq!(low[0].text, "maybe");
+    }
+
     #[test]
     fn test_roundtrip_file() {
         let dir = std::env::temp_dir().join("murmur_test_history");
@@ -4,12 +4,16 @@ pub mod error;
 pub mod history;
 pub mod llm;
 pub mod output;
+pub mod retry;
 pub mod stt;
 
 pub use config::{AppConfig, LlmProcessorType, SttProviderType, UiPreferences};
 pub use dictionary::{DictionaryEntry, PersonalDictionary};
-pub use error::MurmurError;
+pub use error::{MurmurError, ProviderError, ProviderErrorKind};
 pub use history::{HistoryEntry, TranscriptionHistory};
-pub use llm::{LlmProcessor, ProcessingOutput, ProcessingTask};
+pub use llm::{LlmCapabilities, LlmModelInfo, LlmProcessor, ProcessingOutput, ProcessingTask};
 pub use output::{OutputMode, OutputSink};
-pub use stt::{AudioChunk, SttProvider, TranscriptionEvent};
+pub use stt::{
+    AudioChunk, AudioFormat, SessionMetrics, SpeakerSegment, SttCapabilities, SttHealth, SttMode,
+    SttProvider, TranscriptMetadata, TranscriptWord, TranscriptionEvent,
+};
@@ -11,6 +11,10 @@ pub enum ProcessingTask {
     PostProcess {
         text: String,
         dictionary_terms: Vec<String>,
+        /// The transcription as "Speaker 1: …" turns, when diarization found
+        /// more than one speaker
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        speaker_transcript: Option<String>,
     },
     /// Shorten text
     Shorten { text: String },
@@ -25,6 +29,28 @@ pub enum ProcessingTask {
     },
 }
 
+impl ProcessingTask {
+    /// Every task type, as returned by `kind`
+    pub const KINDS: [&'static str; 5] = [
+        "post_process",
+        "shorten",
+        "change_tone",
+        "generate_reply",
+        "translate",
+    ];
+
+    /// Task type as named in the config, e.g. "post_process"
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::PostProcess { .. } => "post_process",
+            Self::Shorten { .. } => "shorten",
+            Self::ChangeTone { .. } => "change_tone",
+            Self::GenerateReply { .. } => "generate_reply",
+            Self::Translate { .. } => "translate",
+        }
+    }
+}
+
 /// LLM processing output
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProcessingOutput {
@@ -36,6 +62,63 @@ pub struct ProcessingOutput {
     pub metadata: Option<serde_json::Value>,
 }
 
+impl ProcessingOutput {
+    /// Metadata key naming the processor that produced the output
+    pub const PROCESSOR_KEY: &'static str = "processor";
+
+    /// The processor that produced this output, when recorded
+    pub fn processor(&self) -> Option<&str> {
+        self.metadata.as_ref()?.get(Self::PROCESSOR_KEY)?.as_str()
+    }
+}
+
+/// What an LLM processor supports (defaults are the conservative baseline:
+/// non-streaming, unknown context window, online)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LlmCapabilities {
+    /// Can stream output tokens as they are generated
+    pub streaming: bool,
+    /// Context window in tokens, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_window: Option<u32>,
+    /// Runs without network access
+    pub offline: bool,
+    /// Needs an API key to be configured
+    pub requires_api_key: bool,
+}
+
+/// A model an LLM processor can be switched to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LlmModelInfo {
+    /// Id to put in `llm_model`, e.g. "gpt-4o-mini"
+    pub id: String,
+    /// Human-readable name, when the provider has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    /// Context window in tokens, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_window: Option<u32>,
+    /// Short extra detail for the picker, e.g. "3.2B Q4_K_M"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+    /// Processor offering the model, when the list spans several (a
+    /// fallback chain)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub processor: Option<String>,
+}
+
+impl LlmModelInfo {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            display_name: None,
+            context_window: None,
+            details: None,
+            processor: None,
+        }
+    }
+}
+
 /// LLM processor trait (via local CLI)
 #[async_trait]
 pub trait LlmProcessor: Send + Sync {
@@ -44,4 +127,59 @@ pub trait LlmProcessor: Send + Sync {
 
     /// Health check (verify CLI is installed and working)
     async fn health_check(&self) -> Result<bool>;
+
+    /// Get ready for an upcoming request, e.g. load a local model while the
+    /// user is still speaking (no-op by default)
+    async fn warm_up(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Models this processor can use. Empty when it has no way to list
+    /// them, in which case any model name is passed through as-is.
+    async fn list_models(&self) -> Result<Vec<LlmModelInfo>> {
+        Ok(Vec::new())
+    }
+
+    /// What this processor supports
+    fn capabilities(&self) -> LlmCapabilities {
+        LlmCapabilities::default()
+    }
+
+    /// What the processor that would handle `task` supports; the same as
+    /// [`capabilities`](Self::capabilities) unless tasks are routed
+    fn task_capabilities(&self, task: &ProcessingTask) -> LlmCapabilities {
+        let _ = task;
+        self.capabilities()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_task_kind_matches_serde_tag() {
+        let text = || "x".to_string();
+        let tasks = [
+            ProcessingTask::PostProcess {
+                text: text(),
+                dictionary_terms: Vec::new(),
+                speaker_transcript: None,
+            },
+            ProcessingTask::Shorten { text: text() },
+            ProcessingTask::ChangeTone {
+                text: text(),
+                target_tone: text(),
+            },
+            ProcessingTask::GenerateReply { context: text() },
+            ProcessingTask::Translate {
+                text: text(),
+                target_language: text(),
+            },
+        ];
+        for (task, kind) in tasks.iter().zip(ProcessingTask::KINDS) {
+            assert_eq!(task.kind(), kind);
+            assert_eq!(serde_json::to_value(task).unwrap()["type"], kind);
+        }
+    }
 }
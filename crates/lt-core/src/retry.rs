@@ -0,0 +1,405 @@
+//! Retry policy shared by the HTTP-based STT and LLM providers.
+//!
+//! Failed requests are retried with exponential backoff and jitter, or after
+//! the delay the server asked for (`Retry-After`, `retry-after-ms`, or the
+//! `x-ratelimit-reset-*` headers). Only failures where the request was
+//! turned away are retried; a request that timed out may still be running on
+//! the server, so it is not sent again.
+
+use crate::config::RetryConfig;
+use crate::error::{MurmurError, ProviderErrorKind, Result};
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::BuildHasher;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Upper bound on attempts per request, whatever the config says
+pub const MAX_ATTEMPTS_LIMIT: u32 = 5;
+
+/// How failed provider requests are retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts per request, including the first (1 = no retries)
+    pub max_attempts: u32,
+    /// Backoff before the first retry; doubles after each attempt
+    pub base_delay: Duration,
+    /// Longest backoff between attempts
+    pub max_delay: Duration,
+    /// Longest server-requested wait that is honored; asking for more fails
+    /// the request instead of stalling the dictation
+    pub max_retry_after: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(8),
+            max_retry_after: Duration::from_secs(20),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy that never retries
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Apply the settings a user configured for a provider
+    pub fn with_overrides(mut self, overrides: &RetryConfig) -> Self {
+        if let Some(attempts) = overrides.max_attempts {
+            self.max_attempts = attempts;
+        }
+        if let Some(ms) = overrides.base_delay_ms {
+            self.base_delay = Duration::from_millis(ms);
+        }
+        if let Some(ms) = overrides.max_delay_ms {
+            self.max_delay = Duration::from_millis(ms);
+        }
+        if let Some(secs) = overrides.max_retry_after_secs {
+            self.max_retry_after = Duration::from_secs(secs);
+        }
+        self
+    }
+
+    /// Attempts actually made, clamped to 1..=[`MAX_ATTEMPTS_LIMIT`]
+    pub fn attempts(&self) -> u32 {
+        self.max_attempts.clamp(1, MAX_ATTEMPTS_LIMIT)
+    }
+
+    /// Backoff before retry number `retry` (1-based): exponential, capped
+    /// at `max_delay`, with "equal jitter" (half fixed, half random)
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponent = retry.saturating_sub(1).min(16);
+        let delay = self
+            .base_delay
+            .saturating_mul(1 << exponent)
+            .min(self.max_delay);
+        let half = delay / 2;
+        half + half.mul_f64(jitter(retry))
+    }
+
+    /// Wait before the next attempt after `attempt` (1-based) failed with
+    /// `error`, or `None` when the error should be returned
+    pub fn delay_after(&self, attempt: u32, error: &MurmurError) -> Option<Duration> {
+        if attempt >= self.attempts() || !is_safe_to_retry(error) {
+            return None;
+        }
+        match server_delay(error) {
+            Some(delay) if delay > self.max_retry_after => None,
+            Some(delay) => Some(delay),
+            None => Some(self.backoff(attempt)),
+        }
+    }
+
+    /// Run `request` until it succeeds, fails in a way retrying cannot fix,
+    /// or runs out of attempts
+    pub async fn run<T, F, Fut>(&self, mut request: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut attempt = 1;
+        loop {
+            let error = match request().await {
+                Ok(value) => return Ok(value),
+                Err(error) => error,
+            };
+            let Some(delay) = self.delay_after(attempt, &error) else {
+                return Err(error);
+            };
+            tracing::warn!(
+                "{}; retrying in {:.1}s (attempt {}/{})",
+                error,
+                delay.as_secs_f32(),
+                attempt + 1,
+                self.attempts()
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+/// True for failures where the server turned the request away (rate limit,
+/// overload, connection failure), so sending it again cannot repeat work
+pub fn is_safe_to_retry(error: &MurmurError) -> bool {
+    error.provider_error().is_some_and(|e| {
+        e.retryable
+            && matches!(
+                e.kind,
+                ProviderErrorKind::RateLimited { .. }
+                    | ProviderErrorKind::Unavailable
+                    | ProviderErrorKind::Network
+            )
+    })
+}
+
+/// Delay the server asked for, if any
+fn server_delay(error: &MurmurError) -> Option<Duration> {
+    let detail = error.provider_error()?;
+    detail
+        .retry_after_ms
+        .map(Duration::from_millis)
+        .or(match detail.kind {
+            ProviderErrorKind::RateLimited {
+                retry_after_secs: Some(secs),
+            } => Some(Duration::from_secs(secs)),
+            _ => None,
+        })
+}
+
+/// Random fraction in 0.0..1.0 for backoff jitter
+fn jitter(seed: u32) -> f64 {
+    let bits = RandomState::new().hash_one((seed, Instant::now()));
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Delay a response asks the client to wait before retrying, from
+/// `retry-after-ms`, `Retry-After` (seconds), or the `x-ratelimit-reset-*`
+/// headers of the limits that are used up. Header names are matched
+/// case-insensitively.
+pub fn retry_after_from_headers<'a>(
+    headers: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Option<Duration> {
+    let mut retry_after_ms = None;
+    let mut retry_after = None;
+    let mut resets: Vec<(String, Duration)> = Vec::new();
+    let mut exhausted: Vec<String> = Vec::new();
+
+    for (name, value) in headers {
+        let name = name.to_ascii_lowercase();
+        let value = value.trim();
+        if name == "retry-after-ms" {
+            retry_after_ms = value.parse::<f64>().ok().map(|ms| ms / 1000.0);
+        } else if name == "retry-after" {
+            retry_after = value.parse::<f64>().ok();
+        } else if let Some(limit) = name.strip_prefix("x-ratelimit-reset") {
+            if let Some(delay) = parse_reset(value) {
+                resets.push((limit.trim_start_matches('-').to_string(), delay));
+            }
+        } else if let Some(limit) = name.strip_prefix("x-ratelimit-remaining") {
+            if value.parse::<f64>().is_ok_and(|remaining| remaining <= 0.0) {
+                exhausted.push(limit.trim_start_matches('-').to_string());
+            }
+        }
+    }
+
+    if let Some(secs) = retry_after_ms.or(retry_after) {
+        return (secs.is_finite() && secs >= 0.0).then(|| Duration::from_secs_f64(secs));
+    }
+    // Wait for every used-up limit to reset; without that information the
+    // soonest reset is the best guess
+    let used_up = resets
+        .iter()
+        .filter(|(limit, _)| exhausted.contains(limit))
+        .map(|(_, delay)| *delay)
+        .max();
+    used_up.or_else(|| resets.iter().map(|(_, delay)| *delay).min())
+}
+
+/// Parse an `x-ratelimit-reset*` value: a duration such as "1s", "6m0s",
+/// "2m59.56s" or "250ms", seconds, or a Unix timestamp in seconds
+fn parse_reset(value: &str) -> Option<Duration> {
+    if let Ok(secs) = value.parse::<f64>() {
+        if !secs.is_finite() || secs < 0.0 {
+            return None;
+        }
+        // Anything this large is a point in time, not a delay
+        if secs > 1_000_000_000.0 {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?;
+            return Some(Duration::from_secs_f64(secs).saturating_sub(now));
+        }
+        return Some(Duration::from_secs_f64(secs));
+    }
+    parse_duration(value)
+}
+
+/// Parse a Go-style duration ("1h2m3.5s", "250ms")
+fn parse_duration(value: &str) -> Option<Duration> {
+    let mut total = 0.0;
+    let mut rest = value;
+    if rest.is_empty() {
+        return None;
+    }
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let number: f64 = rest[..number_len].parse().ok()?;
+        rest = &rest[number_len..];
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(rest.len());
+        let scale = match &rest[..unit_len] {
+            "h" => 3600.0,
+            "m" => 60.0,
+            "s" => 1.0,
+            "ms" => 0.001,
+            _ => return None,
+        };
+        rest = &rest[unit_len..];
+        total += number * scale;
+    }
+    Some(Duration::from_secs_f64(total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ProviderError;
+
+    fn http_error(status: u16) -> MurmurError {
+        ProviderError::from_http_status("openai", status, None, "").into()
+    }
+
+    #[test]
+    fn test_retry_after_headers() {
+        assert_eq!(retry_after_from_headers([]), None);
+        assert_eq!(
+            retry_after_from_headers([("Retry-After", "12")]),
+            Some(Duration::from_secs(12))
+        );
+        // Dates are not supported
+        assert_eq!(
+            retry_after_from_headers([("retry-after", "Wed, 21 Oct 2015 07:28:00 GMT")]),
+            None
+        );
+        // The millisecond header is more precise and wins
+        assert_eq!(
+            retry_after_from_headers([("retry-after", "2"), ("retry-after-ms", "1500")]),
+            Some(Duration::from_millis(1500))
+        );
+    }
+
+    #[test]
+    fn test_rate_limit_reset_headers() {
+        // OpenAI / Groq style: wait for the limit that is used up
+        let headers = [
+            ("x-ratelimit-remaining-requests", "0"),
+            ("x-ratelimit-reset-requests", "2m59.56s"),
+            ("x-ratelimit-remaining-tokens", "1200"),
+            ("x-ratelimit-reset-tokens", "250ms"),
+        ];
+        assert_eq!(
+            retry_after_from_headers(headers),
+            Some(Duration::from_secs_f64(179.56))
+        );
+
+        // No remaining counts: the soonest reset
+        let headers = [
+            ("x-ratelimit-reset-requests", "6m0s"),
+            ("x-ratelimit-reset-tokens", "1s"),
+        ];
+        assert_eq!(
+            retry_after_from_headers(headers),
+            Some(Duration::from_secs(1))
+        );
+
+        assert_eq!(
+            retry_after_from_headers([("X-RateLimit-Reset", "3")]),
+            Some(Duration::from_secs(3))
+        );
+        assert_eq!(parse_duration("1h2m3s"), Some(Duration::from_secs(3723)));
+        assert_eq!(parse_duration("5 apples"), None);
+    }
+
+    #[test]
+    fn test_backoff_grows_with_jitter_and_caps() {
+        let policy = RetryPolicy::default();
+        for retry in 1..=4 {
+            let full = Duration::from_millis(500 * (1 << (retry - 1)));
+            let delay = policy.backoff(retry);
+            assert!(delay >= full / 2 && delay <= full, "{:?}", delay);
+        }
+        assert!(policy.backoff(30) <= policy.max_delay);
+    }
+
+    #[test]
+    fn test_only_turned_away_requests_are_retried() {
+        let policy = RetryPolicy::default();
+        assert!(policy.delay_after(1, &http_error(429)).is_some());
+        assert!(policy.delay_after(1, &http_error(503)).is_some());
+        assert!(policy.delay_after(1, &http_error(401)).is_none());
+        assert!(policy.delay_after(1, &http_error(400)).is_none());
+        // The server may still be working on a timed-out request
+        let timeout: MurmurError =
+            ProviderError::new(ProviderErrorKind::Timeout, "openai", "timed out").into();
+        assert!(policy.delay_after(1, &timeout).is_none());
+        assert!(policy
+            .delay_after(1, &MurmurError::Stt("parse".to_string()))
+            .is_none());
+    }
+
+    #[test]
+    fn test_server_delay_is_honored_up_to_cap() {
+        let policy = RetryPolicy::default();
+        let error: MurmurError = ProviderError::from_http_status("groq", 503, None, "")
+            .with_retry_after(Some(Duration::from_millis(1500)))
+            .into();
+        assert_eq!(
+            policy.delay_after(1, &error),
+            Some(Duration::from_millis(1500))
+        );
+
+        let error: MurmurError = ProviderError::from_http_status("groq", 429, Some(60), "").into();
+        assert_eq!(policy.delay_after(1, &error), None);
+    }
+
+    #[test]
+    fn test_attempts_are_clamped() {
+        let policy = RetryPolicy::default();
+        assert!(policy.delay_after(2, &http_error(429)).is_some());
+        assert!(policy.delay_after(3, &http_error(429)).is_none());
+
+        let policy = RetryPolicy::default().with_overrides(&RetryConfig {
+            max_attempts: Some(50),
+            ..Default::default()
+        });
+        assert_eq!(policy.attempts(), MAX_ATTEMPTS_LIMIT);
+        assert_eq!(RetryPolicy::none().attempts(), 1);
+        let policy = RetryPolicy::default().with_overrides(&RetryConfig {
+            max_attempts: Some(0),
+            ..Default::default()
+        });
+        assert_eq!(policy.attempts(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_run_retries_until_success() {
+        let policy = RetryPolicy {
+            base_delay: Duration::from_millis(1),
+            ..RetryPolicy::default()
+        };
+        let mut calls = 0;
+        let result = policy
+            .run(|| {
+                calls += 1;
+                let call = calls;
+                async move {
+                    match call {
+                        1 => Err(http_error(429)),
+                        2 => Err(http_error(503)),
+                        _ => Ok(call),
+                    }
+                }
+            })
+            .await;
+        assert_eq!(result.unwrap(), 3);
+
+        let mut calls = 0;
+        let result: Result<()> = policy
+            .run(|| {
+                calls += 1;
+                async { Err(http_error(401)) }
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+}
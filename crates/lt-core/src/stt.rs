@@ -1,15 +1,226 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
-use crate::error::Result;
+use crate::error::{MurmurError, ProviderError, Result};
+
+/// Sample rate used when a provider does not ask for anything else
+pub const DEFAULT_SAMPLE_RATE: u32 = 16000;
+
+/// PCM layout of captured audio (always signed 16-bit, interleaved)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AudioFormat {
+    /// Sample rate (Hz)
+    pub sample_rate: u32,
+    /// Number of interleaved channels
+    pub channels: u16,
+}
+
+impl AudioFormat {
+    /// Mono audio at the given sample rate
+    pub const fn mono(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            channels: 1,
+        }
+    }
+
+    /// Number of samples (across all channels) in `duration_ms` of audio
+    pub fn samples_for_ms(&self, duration_ms: u64) -> usize {
+        (self.sample_rate as u64 * self.channels as u64 * duration_ms / 1000) as usize
+    }
+
+    /// Duration of `samples` interleaved samples in milliseconds
+    pub fn duration_ms(&self, samples: usize) -> u64 {
+        let per_second = self.sample_rate as u64 * self.channels.max(1) as u64;
+        if per_second == 0 {
+            return 0;
+        }
+        samples as u64 * 1000 / per_second
+    }
+}
+
+impl Default for AudioFormat {
+    /// 16 kHz mono, what most speech models are trained on
+    fn default() -> Self {
+        Self::mono(DEFAULT_SAMPLE_RATE)
+    }
+}
 
 /// Audio chunk for STT processing
 #[derive(Debug, Clone)]
 pub struct AudioChunk {
-    /// PCM samples (16-bit mono, 16kHz)
+    /// PCM samples (16-bit, interleaved when `channels > 1`)
     pub data: Vec<i16>,
     /// Timestamp (milliseconds from session start)
     pub timestamp_ms: u64,
+    /// Sample rate of `data` (Hz)
+    pub sample_rate: u32,
+    /// Number of interleaved channels in `data`
+    pub channels: u16,
+}
+
+impl AudioChunk {
+    /// Format of the samples in this chunk
+    pub fn format(&self) -> AudioFormat {
+        AudioFormat {
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+        }
+    }
+}
+
+/// A single recognized word with its position in the session audio
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptWord {
+    /// Word text as returned by the provider (without surrounding whitespace)
+    pub text: String,
+    /// Start offset (milliseconds from session start)
+    pub start_ms: u64,
+    /// End offset (milliseconds from session start)
+    pub end_ms: u64,
+    /// Recognition confidence in `0.0..=1.0`, if the provider reports one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+}
+
+/// A turn of one speaker, from the provider's diarization
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpeakerSegment {
+    /// Speaker id, numbered from 0 in the order the provider assigned them
+    pub speaker: u32,
+    /// What the speaker said in this turn
+    pub text: String,
+    /// Start offset (milliseconds from session start)
+    pub start_ms: u64,
+    /// End offset (milliseconds from session start)
+    pub end_ms: u64,
+}
+
+impl SpeakerSegment {
+    /// A turn consisting of a single word
+    pub fn from_word(speaker: u32, word: &TranscriptWord) -> Self {
+        Self {
+            speaker,
+            text: word.text.clone(),
+            start_ms: word.start_ms,
+            end_ms: word.end_ms,
+        }
+    }
+
+    /// Add this turn to `turns`, extending the last one when the speaker is
+    /// the same
+    pub fn append_to(self, turns: &mut Vec<SpeakerSegment>) {
+        match turns.last_mut() {
+            Some(last) if last.speaker == self.speaker => {
+                if !last.text.is_empty() && !self.text.is_empty() {
+                    last.text.push(' ');
+                }
+                last.text.push_str(&self.text);
+                last.end_ms = last.end_ms.max(self.end_ms);
+            }
+            _ => turns.push(self),
+        }
+    }
+}
+
+/// Optional detail attached to partial and committed transcriptions.
+///
+/// Providers fill in whatever they can; everything is empty by default.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptMetadata {
+    /// Word-level timing and confidence
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub words: Vec<TranscriptWord>,
+    /// Language detected by the provider (code or name, as reported)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Segment this event belongs to; partials and the commit of the same
+    /// segment share an id
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub segment_id: Option<u64>,
+    /// What the provider discarded instead of transcribing or reporting
+    #[serde(default, skip_serializing_if = "SessionMetrics::is_empty")]
+    pub metrics: SessionMetrics,
+    /// Id of the provider that produced the text, when a failover chain
+    /// could have picked more than one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    /// Who said what, when diarization was requested and the provider
+    /// supports it
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub speakers: Vec<SpeakerSegment>,
+}
+
+/// Per-session counters for audio and text a provider dropped
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionMetrics {
+    /// Chunks not uploaded because they contained too little speech
+    #[serde(default)]
+    pub skipped_silent_chunks: u32,
+    /// Phantom phrases and repetition loops removed from provider text
+    #[serde(default)]
+    pub filtered_hallucinations: u32,
+}
+
+impl SessionMetrics {
+    /// True when nothing was dropped
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Add another segment's counters to these
+    pub fn add(&mut self, other: &SessionMetrics) {
+        self.skipped_silent_chunks += other.skipped_silent_chunks;
+        self.filtered_hallucinations += other.filtered_hallucinations;
+    }
+}
+
+impl TranscriptMetadata {
+    /// True when the provider supplied no detail at all
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+            && self.language.is_none()
+            && self.segment_id.is_none()
+            && self.metrics.is_empty()
+            && self.provider.is_none()
+            && self.speakers.is_empty()
+    }
+
+    /// Append another segment's detail (words and speaker turns are
+    /// concatenated and counters summed, the newer language, segment id and
+    /// provider win)
+    pub fn append(&mut self, other: &TranscriptMetadata) {
+        self.words.extend(other.words.iter().cloned());
+        for turn in &other.speakers {
+            turn.clone().append_to(&mut self.speakers);
+        }
+        self.metrics.add(&other.metrics);
+        if other.language.is_some() {
+            self.language.clone_from(&other.language);
+        }
+        if other.segment_id.is_some() {
+            self.segment_id = other.segment_id;
+        }
+        if other.provider.is_some() {
+            self.provider.clone_from(&other.provider);
+        }
+    }
+
+    /// Number of distinct speakers in the diarized turns
+    pub fn speaker_count(&self) -> usize {
+        let mut speakers: Vec<u32> = self.speakers.iter().map(|s| s.speaker).collect();
+        speakers.sort_unstable();
+        speakers.dedup();
+        speakers.len()
+    }
+
+    /// Words whose confidence is known and below `threshold`
+    pub fn low_confidence_words(&self, threshold: f32) -> Vec<&TranscriptWord> {
+        self.words
+            .iter()
+            .filter(|w| w.confidence.is_some_and(|c| c < threshold))
+            .collect()
+    }
 }
 
 /// Transcription events from STT provider
@@ -17,11 +228,92 @@ pub struct AudioChunk {
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum TranscriptionEvent {
     /// Partial (interim) transcription
-    Partial { text: String, timestamp_ms: u64 },
+    Partial {
+        text: String,
+        timestamp_ms: u64,
+        #[serde(default, skip_serializing_if = "TranscriptMetadata::is_empty")]
+        metadata: TranscriptMetadata,
+    },
     /// Committed (final) transcription
-    Committed { text: String, timestamp_ms: u64 },
+    Committed {
+        text: String,
+        timestamp_ms: u64,
+        #[serde(default, skip_serializing_if = "TranscriptMetadata::is_empty")]
+        metadata: TranscriptMetadata,
+    },
     /// Error during transcription
-    Error { message: String },
+    Error {
+        message: String,
+        /// Structured classification, when the provider could determine one
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        detail: Option<ProviderError>,
+    },
+}
+
+/// How a provider turns audio into text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SttMode {
+    /// Audio is streamed continuously and text arrives while speaking
+    Streaming,
+    /// Audio is buffered and uploaded in chunks (REST APIs)
+    Chunked,
+}
+
+/// What an STT provider supports.
+///
+/// The app and pipeline decide behaviour from these instead of matching on
+/// the concrete provider type.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SttCapabilities {
+    /// Streaming or chunked transcription
+    pub mode: SttMode,
+    /// Emits partial (interim) results before committing
+    pub partial_results: bool,
+    /// Supported language codes (empty = any language / auto-detect)
+    #[serde(default)]
+    pub languages: Vec<String>,
+    /// Reports word-level timestamps in transcript metadata
+    pub word_timestamps: bool,
+    /// Accepts vocabulary hints (e.g. dictionary terms)
+    pub vocabulary_hints: bool,
+    /// Can label transcript segments by speaker
+    #[serde(default)]
+    pub diarization: bool,
+    /// Runs entirely on this machine, no network needed
+    pub local: bool,
+    /// Needs an API key to be configured
+    pub requires_api_key: bool,
+}
+
+impl SttCapabilities {
+    /// True when `language` is listed, or any language is accepted
+    pub fn supports_language(&self, language: &str) -> bool {
+        self.languages.is_empty() || self.languages.iter().any(|l| l == language)
+    }
+}
+
+impl Default for SttCapabilities {
+    /// Conservative baseline: chunked cloud provider with no extras
+    fn default() -> Self {
+        Self {
+            mode: SttMode::Chunked,
+            partial_results: false,
+            languages: Vec::new(),
+            word_timestamps: false,
+            vocabulary_hints: false,
+            diarization: false,
+            local: false,
+            requires_api_key: true,
+        }
+    }
+}
+
+/// Outcome of a successful provider connection test
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SttHealth {
+    /// Round-trip time of the probe (milliseconds)
+    pub latency_ms: u64,
 }
 
 /// Unified STT provider trait
@@ -39,4 +331,176 @@ pub trait SttProvider: Send + Sync {
     /// Subscribe to transcription events
     /// Returns a channel receiver for events
     async fn subscribe_events(&self) -> tokio::sync::mpsc::Receiver<TranscriptionEvent>;
+
+    /// Audio format this provider wants from capture (16 kHz mono by default)
+    fn input_format(&self) -> AudioFormat {
+        AudioFormat::default()
+    }
+
+    /// What this provider supports
+    fn capabilities(&self) -> SttCapabilities {
+        SttCapabilities::default()
+    }
+
+    /// Validate credentials and reachability with the smallest possible
+    /// request, without starting a session. Failures carry a `ProviderError`
+    /// where the cause could be classified.
+    async fn health_check(&self) -> Result<SttHealth> {
+        Err(MurmurError::Stt(
+            "Connection test is not supported by this provider".to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(text: &str, start_ms: u64, confidence: Option<f32>) -> TranscriptWord {
+        TranscriptWord {
+            text: text.to_string(),
+            start_ms,
+            end_ms: start_ms + 200,
+            confidence,
+        }
+    }
+
+    #[test]
+    fn test_audio_format_durations() {
+        let format = AudioFormat::default();
+        assert_eq!(format.sample_rate, 16000);
+        assert_eq!(format.samples_for_ms(250), 4000);
+        assert_eq!(format.duration_ms(8000), 500);
+
+        let stereo = AudioFormat {
+            sample_rate: 24000,
+            channels: 2,
+        };
+        assert_eq!(stereo.samples_for_ms(1000), 48000);
+        assert_eq!(stereo.duration_ms(48000), 1000);
+    }
+
+    #[test]
+    fn test_capabilities_language_support() {
+        let any = SttCapabilities::default();
+        assert!(any.supports_language("ja"));
+
+        let caps = SttCapabilities {
+            languages: vec!["en".to_string(), "de".to_string()],
+            ..SttCapabilities::default()
+        };
+        assert!(caps.supports_language("de"));
+        assert!(!caps.supports_language("ja"));
+
+        let json = serde_json::to_value(&caps).unwrap();
+        assert_eq!(json["mode"], "chunked");
+    }
+
+    #[test]
+    fn test_event_without_metadata_omits_field() {
+        let event = TranscriptionEvent::Committed {
+            text: "hello".to_string(),
+            timestamp_ms: 10,
+            metadata: TranscriptMetadata::default(),
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert!(json.get("metadata").is_none());
+
+        // Older payloads without metadata still deserialize
+        let parsed: TranscriptionEvent =
+            serde_json::from_str(r#"{"type":"partial","text":"hi","timestamp_ms":0}"#).unwrap();
+        match parsed {
+            TranscriptionEvent::Partial { metadata, .. } => assert!(metadata.is_empty()),
+            _ => panic!("Unexpected event type"),
+        }
+    }
+
+    #[test]
+    fn test_metadata_roundtrip() {
+        let event = TranscriptionEvent::Partial {
+            text: "hello world".to_string(),
+            timestamp_ms: 400,
+            metadata: TranscriptMetadata {
+                words: vec![word("hello", 0, Some(0.9)), word("world", 200, None)],
+                language: Some("en".to_string()),
+                segment_id: Some(3),
+                ..Default::default()
+            },
+        };
+        let json = serde_json::to_string(&event).unwrap();
+        let parsed: TranscriptionEvent = serde_json::from_str(&json).unwrap();
+        match parsed {
+            TranscriptionEvent::Partial { metadata, .. } => {
+                assert_eq!(metadata.words.len(), 2);
+                assert_eq!(metadata.words[1].confidence, None);
+                assert_eq!(metadata.language.as_deref(), Some("en"));
+                assert_eq!(metadata.segment_id, Some(3));
+            }
+            _ => panic!("Unexpected event type"),
+        }
+    }
+
+    #[test]
+    fn test_append_and_low_confidence_words() {
+        let mut acc = TranscriptMetadata::default();
+        acc.append(&TranscriptMetadata {
+            words: vec![word("clear", 0, Some(0.95))],
+            language: Some("en".to_string()),
+            segment_id: Some(0),
+            metrics: SessionMetrics {
+                skipped_silent_chunks: 2,
+                filtered_hallucinations: 0,
+            },
+            provider: Some("elevenlabs".to_string()),
+            speakers: Vec::new(),
+        });
+        acc.append(&TranscriptMetadata {
+            words: vec![word("mumble", 500, Some(0.3)), word("unknown", 700, None)],
+            language: None,
+            segment_id: Some(1),
+            metrics: SessionMetrics {
+                skipped_silent_chunks: 1,
+                filtered_hallucinations: 1,
+            },
+            provider: Some("deepgram".to_string()),
+            speakers: Vec::new(),
+        });
+
+        assert_eq!(acc.words.len(), 3);
+        assert_eq!(acc.language.as_deref(), Some("en"));
+        assert_eq!(acc.segment_id, Some(1));
+        assert_eq!(acc.provider.as_deref(), Some("deepgram"));
+        assert_eq!(acc.metrics.skipped_silent_chunks, 3);
+        assert_eq!(acc.metrics.filtered_hallucinations, 1);
+
+        let low = acc.low_confidence_words(0.5);
+        assert_eq!(low.len(), 1);
+        assert_eq!(low[0].text, "mumble");
+    }
+
+    #[test]
+    fn test_speaker_turns_merge_across_segments() {
+        let turn = |speaker, text: &str, start_ms| SpeakerSegment {
+            speaker,
+            text: text.to_string(),
+            start_ms,
+            end_ms: start_ms + 500,
+        };
+        let mut acc = TranscriptMetadata::default();
+        acc.append(&TranscriptMetadata {
+            speakers: vec![turn(0, "Shall we start?", 0), turn(1, "Sure,", 1000)],
+            ..Default::default()
+        });
+        acc.append(&TranscriptMetadata {
+            speakers: vec![turn(1, "go ahead.", 1500), turn(0, "Thanks.", 2500)],
+            ..Default::default()
+        });
+
+        assert_eq!(acc.speakers.len(), 3);
+        assert_eq!(acc.speakers[1].text, "Sure, go ahead.");
+        assert_eq!(acc.speakers[1].start_ms, 1000);
+        assert_eq!(acc.speakers[1].end_ms, 2000);
+        assert_eq!(acc.speaker_count(), 2);
+        assert!(!acc.is_empty());
+    }
 }
@@ -0,0 +1,398 @@
+//! HTTP client shared by the cloud STT and LLM providers.
+//!
+//! Every REST request goes through one pooled `reqwest::Client`, so
+//! connections (HTTP/2 when the server offers it) are reused across audio
+//! chunks and prompts instead of being set up again for each request. The
+//! client follows the `[network]` config section: an explicit HTTP(S) or
+//! SOCKS proxy, extra root certificates for TLS-inspecting proxies, and the
+//! connect timeout. Request timeouts are per provider and set on each
+//! request.
+//!
+//! The streaming WebSocket providers open their own connections and do not
+//! use this client; they follow the same settings through [`network`],
+//! [`proxy_for`] and [`read_ca_bundle`].
+
+use lt_core::config::{expand_home, NetworkConfig};
+use lt_core::error::{MurmurError, Result};
+use reqwest::{Certificate, Client, NoProxy, Proxy};
+use std::sync::{OnceLock, PoisonError, RwLock};
+use std::time::Duration;
+
+/// Connect timeout when `connect_timeout_secs` is not set
+pub const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 10;
+
+/// How long an idle pooled connection is kept open
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Keepalive interval for pooled connections, so NATs and proxies don't
+/// silently drop them between dictations
+const TCP_KEEPALIVE: Duration = Duration::from_secs(60);
+
+static SHARED: OnceLock<RwLock<Client>> = OnceLock::new();
+
+static NETWORK: RwLock<Option<NetworkConfig>> = RwLock::new(None);
+
+fn shared() -> &'static RwLock<Client> {
+    SHARED.get_or_init(|| {
+        let client = build_client(&NetworkConfig::default()).unwrap_or_else(|e| {
+            tracing::warn!("{}, using a plain HTTP client", e);
+            Client::new()
+        });
+        RwLock::new(client)
+    })
+}
+
+/// The shared client. Clones are cheap and share one connection pool.
+///
+/// Uses default network settings until [`configure`] is called.
+pub fn client() -> Client {
+    shared()
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+/// Rebuild the shared client from `config`.
+///
+/// Requests already in flight finish on the previous client. If `config`
+/// is invalid the previous client stays in use and the error is returned.
+pub fn configure(config: &NetworkConfig) -> Result<()> {
+    let client = build_client(config)?;
+    *shared().write().unwrap_or_else(PoisonError::into_inner) = client;
+    *NETWORK.write().unwrap_or_else(PoisonError::into_inner) = Some(config.clone());
+    Ok(())
+}
+
+/// The settings last passed to [`configure`] (defaults until then)
+pub fn network() -> NetworkConfig {
+    NETWORK
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+        .unwrap_or_default()
+}
+
+/// Proxy URL for a connection to `host` made outside the shared client:
+/// the configured proxy, else the `HTTPS_PROXY` / `HTTP_PROXY` /
+/// `ALL_PROXY` environment, unless `no_proxy` (or `NO_PROXY`) lists the host
+pub fn proxy_for(config: &NetworkConfig, host: &str, secure: bool) -> Option<String> {
+    if let Some(url) = non_empty(&config.proxy) {
+        let bypass = non_empty(&config.no_proxy).is_some_and(|list| bypasses_proxy(list, host));
+        return (!bypass).then(|| url.to_string());
+    }
+
+    let vars: &[&str] = if secure {
+        &["HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy"]
+    } else {
+        &["HTTP_PROXY", "http_proxy", "ALL_PROXY", "all_proxy"]
+    };
+    let url = vars.iter().find_map(|var| env_value(var))?;
+    let no_proxy = env_value("NO_PROXY").or_else(|| env_value("no_proxy"));
+    if no_proxy.is_some_and(|list| bypasses_proxy(&list, host)) {
+        return None;
+    }
+    Some(url)
+}
+
+/// Whether a comma-separated `no_proxy` list covers `host`: `*`, the host
+/// itself, or a domain it is under (".corp" and "corp" both cover
+/// "api.corp")
+fn bypasses_proxy(no_proxy: &str, host: &str) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    no_proxy.split(',').map(str::trim).any(|entry| {
+        let entry = entry.trim_start_matches('.');
+        entry == "*"
+            || (!entry.is_empty()
+                && (host.eq_ignore_ascii_case(entry)
+                    || host.len() > entry.len()
+                        && host.ends_with(entry)
+                        && host.as_bytes()[host.len() - entry.len() - 1] == b'.'))
+    })
+}
+
+fn env_value(var: &str) -> Option<String> {
+    std::env::var(var).ok().filter(|v| !v.trim().is_empty())
+}
+
+/// Build a pooled client for `config`
+pub fn build_client(config: &NetworkConfig) -> Result<Client> {
+    let connect_timeout = config
+        .connect_timeout_secs
+        .unwrap_or(DEFAULT_CONNECT_TIMEOUT_SECS);
+    let mut builder = Client::builder()
+        .connect_timeout(Duration::from_secs(connect_timeout))
+        .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+        .tcp_keepalive(TCP_KEEPALIVE)
+        .http2_adaptive_window(true);
+
+    // An explicit proxy replaces the system / environment proxy
+    if let Some(proxy) = proxy(config)? {
+        builder = builder.proxy(proxy);
+    }
+
+    if let Some(path) = non_empty(&config.ca_bundle) {
+        let certs = load_ca_bundle(path)?;
+        builder = if config.ca_bundle_only.unwrap_or(false) {
+            builder.tls_certs_only(certs)
+        } else {
+            builder.tls_certs_merge(certs)
+        };
+    }
+
+    builder
+        .build()
+        .map_err(|e| MurmurError::Config(format!("Failed to build HTTP client: {}", e)))
+}
+
+/// The configured proxy, if any, with its bypass list
+fn proxy(config: &NetworkConfig) -> Result<Option<Proxy>> {
+    let Some(url) = non_empty(&config.proxy) else {
+        return Ok(None);
+    };
+    let proxy = Proxy::all(url)
+        .map_err(|e| MurmurError::Config(format!("Invalid proxy URL '{}': {}", url, e)))?;
+    let no_proxy = non_empty(&config.no_proxy).and_then(NoProxy::from_string);
+    Ok(Some(proxy.no_proxy(no_proxy)))
+}
+
+/// Read the root certificates in a PEM bundle
+fn load_ca_bundle(path: &str) -> Result<Vec<Certificate>> {
+    let pem = read_ca_bundle(path)?;
+    let certs = Certificate::from_pem_bundle(&pem).map_err(|e| {
+        MurmurError::Config(format!(
+            "Invalid CA bundle {}: {}",
+            expand_home(path).display(),
+            e
+        ))
+    })?;
+    if certs.is_empty() {
+        return Err(MurmurError::Config(format!(
+            "No certificates found in CA bundle {}",
+            expand_home(path).display()
+        )));
+    }
+    Ok(certs)
+}
+
+/// Contents of the PEM bundle at `path` ("~" expands to the home directory)
+pub fn read_ca_bundle(path: &str) -> Result<Vec<u8>> {
+    let path = expand_home(path);
+    std::fs::read(&path).map_err(|e| {
+        MurmurError::Config(format!(
+            "Failed to read CA bundle {}: {}",
+            path.display(),
+            e
+        ))
+    })
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Self-signed P-256 certificate, only parsed, never connected to
+    const TEST_CA: &str = "-----BEGIN CERTIFICATE-----
+MIIBijCCAS+gAwIBAgIUFWKbYv6grYoHW/woxBUXXlXWa2AwCgYIKoZIzj0EAwIw
+GTEXMBUGA1UEAwwOTXVybXVyIFRlc3QgQ0EwIBcNMjYxMDE4MTgzOTA0WhgPMjEy
+NjA5MjQxODM5MDRaMBkxFzAVBgNVBAMMDk11cm11ciBUZXN0IENBMFkwEwYHKoZI
+zj0CAQYIKoZIzj0DAQcDQgAEdFavvveFbBe5W3gv4z35bUB3fsa3ZqyDXYEAtvm8
+24G2kNe7YZfroUZHyAaPGKM0g2WDZx6nKWIvGStoJ4VTjKNTMFEwHQYDVR0OBBYE
+FGrDAI5mtOlOmaKcp82ISqiXE0orMB8GA1UdIwQYMBaAFGrDAI5mtOlOmaKcp82I
+SqiXE0orMA8GA1UdEwEB/wQFMAMBAf8wCgYIKoZIzj0EAwIDSQAwRgIhAML1qoYr
+NFw2OGJ4J4btf8txRnHfmRe/6EWMmpDWeQreAiEA/omhb9fnVQ2YMX1wS3dNCDa5
+kXhRRMZHaxVVRmkD7k0=
+-----END CERTIFICATE-----
+";
+
+    fn pem_file(contents: &str) -> tempfile::NamedTempFile {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(contents.as_bytes()).unwrap();
+        file
+    }
+
+    fn with_ca_bundle(file: &tempfile::NamedTempFile, only: bool) -> NetworkConfig {
+        NetworkConfig {
+            ca_bundle: Some(file.path().to_string_lossy().into_owned()),
+            ca_bundle_only: Some(only),
+            ..Default::default()
+        }
+    }
+
+    /// Answer one plain HTTP request with 200 "ok" and return its request line
+    async fn serve_once() -> (String, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = format!("http://{}", listener.local_addr().unwrap());
+        let task = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut head = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+                let n = stream.read(&mut buf).await.unwrap();
+                if n == 0 {
+                    break;
+                }
+                head.extend_from_slice(&buf[..n]);
+            }
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok")
+                .await
+                .unwrap();
+            let head = String::from_utf8_lossy(&head);
+            head.lines().next().unwrap_or_default().to_string()
+        });
+        (addr, task)
+    }
+
+    #[test]
+    fn test_default_config_builds() {
+        assert!(build_client(&NetworkConfig::default()).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_proxy_is_config_error() {
+        let config = NetworkConfig {
+            proxy: Some("http://bad host:8080".to_string()),
+            ..Default::default()
+        };
+        let err = build_client(&config).unwrap_err();
+        assert!(matches!(&err, MurmurError::Config(msg) if msg.contains("Invalid proxy URL")));
+    }
+
+    #[test]
+    fn test_socks_proxy_accepted() {
+        let config = NetworkConfig {
+            proxy: Some("socks5h://127.0.0.1:1080".to_string()),
+            ..Default::default()
+        };
+        assert!(build_client(&config).is_ok());
+    }
+
+    #[test]
+    fn test_proxy_for_honors_no_proxy() {
+        let config = NetworkConfig {
+            proxy: Some("http://proxy.corp:8080".to_string()),
+            no_proxy: Some("localhost, .internal.corp,10.0.0.5".to_string()),
+            ..Default::default()
+        };
+        let proxy = |host| proxy_for(&config, host, true);
+        assert_eq!(
+            proxy("api.elevenlabs.io").as_deref(),
+            Some("http://proxy.corp:8080")
+        );
+        assert_eq!(proxy("localhost"), None);
+        assert_eq!(proxy("stt.internal.corp"), None);
+        assert_eq!(proxy("internal.corp"), None);
+        assert!(proxy("notinternal.corp").is_some());
+        assert_eq!(proxy("10.0.0.5"), None);
+    }
+
+    #[test]
+    fn test_no_proxy_wildcard_and_ipv6() {
+        assert!(bypasses_proxy("*", "api.deepgram.com"));
+        assert!(bypasses_proxy("::1", "[::1]"));
+        assert!(!bypasses_proxy("", "localhost"));
+    }
+
+    #[test]
+    fn test_ca_bundle_merged_or_exclusive() {
+        let file = pem_file(TEST_CA);
+        assert!(build_client(&with_ca_bundle(&file, false)).is_ok());
+        assert!(build_client(&with_ca_bundle(&file, true)).is_ok());
+    }
+
+    #[test]
+    fn test_missing_ca_bundle_is_config_error() {
+        let config = NetworkConfig {
+            ca_bundle: Some("/nonexistent/murmur-ca.pem".to_string()),
+            ..Default::default()
+        };
+        let err = build_client(&config).unwrap_err();
+        assert!(
+            matches!(&err, MurmurError::Config(msg) if msg.contains("Failed to read CA bundle"))
+        );
+    }
+
+    #[test]
+    fn test_ca_bundle_without_certificates_is_config_error() {
+        let file = pem_file("not a certificate\n");
+        let err = build_client(&with_ca_bundle(&file, false)).unwrap_err();
+        assert!(matches!(&err, MurmurError::Config(msg) if msg.contains("No certificates found")));
+    }
+
+    #[tokio::test]
+    async fn test_requests_go_through_proxy() {
+        let (proxy_url, proxy) = serve_once().await;
+        let client = build_client(&NetworkConfig {
+            proxy: Some(proxy_url),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let response = client
+            .get("http://api.murmur.invalid/v1/models")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.text().await.unwrap(), "ok");
+        // A forward proxy receives the absolute URL
+        assert_eq!(
+            proxy.await.unwrap(),
+            "GET http://api.murmur.invalid/v1/models HTTP/1.1"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_no_proxy_hosts_connect_directly() {
+        let (server_url, server) = serve_once().await;
+        let client = build_client(&NetworkConfig {
+            // Nothing listens here; a proxied request would fail
+            proxy: Some("http://127.0.0.1:9".to_string()),
+            no_proxy: Some("localhost,127.0.0.1".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let response = client
+            .get(format!("{}/health", server_url))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.text().await.unwrap(), "ok");
+        assert_eq!(server.await.unwrap(), "GET /health HTTP/1.1");
+    }
+
+    #[tokio::test]
+    async fn test_configure_replaces_shared_client() {
+        let bad = NetworkConfig {
+            proxy: Some("http://bad host:8080".to_string()),
+            ..Default::default()
+        };
+        assert!(configure(&bad).is_err());
+
+        let (proxy_url, proxy) = serve_once().await;
+        configure(&NetworkConfig {
+            proxy: Some(proxy_url),
+            ..Default::default()
+        })
+        .unwrap();
+        let response = client()
+            .get("http://api.murmur.invalid/ping")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(
+            proxy.await.unwrap(),
+            "GET http://api.murmur.invalid/ping HTTP/1.1"
+        );
+
+        configure(&NetworkConfig::default()).unwrap();
+    }
+}
@@ -1,6 +1,6 @@
 use async_trait::async_trait;
 use lt_core::error::{MurmurError, Result};
-use lt_core::llm::{LlmProcessor, ProcessingOutput, ProcessingTask};
+use lt_core::llm::{LlmCapabilities, LlmProcessor, ProcessingOutput, ProcessingTask};
 use std::ffi::{CStr, CString};
 use std::os::raw::c_char;
 use std::time::Instant;
@@ -161,4 +161,14 @@ impl LlmProcessor for AppleLlmProcessor {
         }
         Ok(available)
     }
+
+    fn capabilities(&self) -> LlmCapabilities {
+        LlmCapabilities {
+            streaming: false,
+            // On-device model session limit
+            context_window: Some(4096),
+            offline: true,
+            requires_api_key: false,
+        }
+    }
 }
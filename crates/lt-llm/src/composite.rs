@@ -0,0 +1,439 @@
+use async_trait::async_trait;
+use lt_core::error::{ProviderErrorKind, Result};
+use lt_core::llm::{LlmCapabilities, LlmModelInfo, LlmProcessor, ProcessingOutput, ProcessingTask};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// A processor in the chain, with the name recorded in its outputs
+struct Member {
+    name: String,
+    processor: Arc<dyn LlmProcessor>,
+}
+
+impl Member {
+    fn new(name: impl Into<String>, processor: Arc<dyn LlmProcessor>) -> Self {
+        Self {
+            name: name.into(),
+            processor,
+        }
+    }
+}
+
+/// Processor that routes each task type to its own processor and falls
+/// back through an ordered list when one fails.
+///
+/// A task is tried on its routed processor (if any), then the primary,
+/// then each fallback. The output metadata records which processor
+/// answered under `ProcessingOutput::PROCESSOR_KEY`, and the ones that
+/// failed before it under `failed_processors`.
+pub struct CompositeProcessor {
+    primary: Member,
+    fallbacks: Vec<Member>,
+    /// Keyed by `ProcessingTask::kind`
+    routes: HashMap<String, Member>,
+}
+
+impl CompositeProcessor {
+    pub fn new(name: impl Into<String>, primary: Arc<dyn LlmProcessor>) -> Self {
+        Self {
+            primary: Member::new(name, primary),
+            fallbacks: Vec::new(),
+            routes: HashMap::new(),
+        }
+    }
+
+    /// Try `processor` when the ones before it fail
+    pub fn with_fallback(
+        mut self,
+        name: impl Into<String>,
+        processor: Arc<dyn LlmProcessor>,
+    ) -> Self {
+        self.fallbacks.push(Member::new(name, processor));
+        self
+    }
+
+    /// Send tasks of type `task_kind` (e.g. "translate") to `processor`
+    /// first
+    pub fn with_route(
+        mut self,
+        task_kind: impl Into<String>,
+        name: impl Into<String>,
+        processor: Arc<dyn LlmProcessor>,
+    ) -> Self {
+        self.routes
+            .insert(task_kind.into(), Member::new(name, processor));
+        self
+    }
+
+    /// Every processor in the composite, each once: the primary, the
+    /// routed ones, then the fallbacks
+    fn members(&self) -> Vec<&Member> {
+        let mut routes: Vec<(&String, &Member)> = self.routes.iter().collect();
+        routes.sort_by(|a, b| a.0.cmp(b.0));
+        let mut members: Vec<&Member> = Vec::new();
+        let candidates = std::iter::once(&self.primary)
+            .chain(routes.into_iter().map(|(_, member)| member))
+            .chain(&self.fallbacks);
+        for member in candidates {
+            if !members.iter().any(|m| m.name == member.name) {
+                members.push(member);
+            }
+        }
+        members
+    }
+
+    /// The processors to try for `task`, in order, each once
+    fn chain(&self, task: &ProcessingTask) -> Vec<&Member> {
+        let mut chain: Vec<&Member> = Vec::new();
+        let candidates = self
+            .routes
+            .get(task.kind())
+            .into_iter()
+            .chain(std::iter::once(&self.primary))
+            .chain(&self.fallbacks);
+        for member in candidates {
+            if !chain.iter().any(|m| m.name == member.name) {
+                chain.push(member);
+            }
+        }
+        chain
+    }
+}
+
+#[async_trait]
+impl LlmProcessor for CompositeProcessor {
+    async fn process(&self, task: ProcessingTask) -> Result<ProcessingOutput> {
+        let chain = self.chain(&task);
+        let mut failed: Vec<&str> = Vec::new();
+        let mut last_error = None;
+
+        for member in &chain {
+            match member.processor.process(task.clone()).await {
+                Ok(mut output) => {
+                    let mut metadata = match output.metadata.take() {
+                        Some(serde_json::Value::Object(map)) => map,
+                        Some(other) => {
+                            let mut map = serde_json::Map::new();
+                            map.insert("details".to_string(), other);
+                            map
+                        }
+                        None => serde_json::Map::new(),
+                    };
+                    metadata.insert(
+                        ProcessingOutput::PROCESSOR_KEY.to_string(),
+                        member.name.clone().into(),
+                    );
+                    if !failed.is_empty() {
+                        metadata.insert("failed_processors".to_string(), failed.into());
+                    }
+                    output.metadata = Some(metadata.into());
+                    return Ok(output);
+                }
+                Err(e) => {
+                    let cancelled = e
+                        .provider_error()
+                        .is_some_and(|d| d.kind == ProviderErrorKind::Cancelled);
+                    if cancelled {
+                        return Err(e);
+                    }
+                    if chain.len() > 1 {
+                        tracing::warn!("LLM processor {} failed: {}", member.name, e);
+                    }
+                    failed.push(&member.name);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        Err(last_error.expect("the chain always holds the primary"))
+    }
+
+    /// Healthy when any processor is, since tasks fall back to it
+    async fn health_check(&self) -> Result<bool> {
+        let mut first_error = None;
+        let mut answered = false;
+        for member in self.members() {
+            match member.processor.health_check().await {
+                Ok(true) => return Ok(true),
+                Ok(false) => answered = true,
+                Err(e) => {
+                    tracing::debug!("LLM health check of {} failed: {}", member.name, e);
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        match first_error {
+            Some(e) if !answered => Err(e),
+            _ => Ok(false),
+        }
+    }
+
+    /// Warm up the primary and the routed processors, the first in line
+    /// for their tasks; fallbacks are only loaded when needed
+    async fn warm_up(&self) -> Result<()> {
+        let mut warmed: Vec<&str> = Vec::new();
+        for member in std::iter::once(&self.primary).chain(self.routes.values()) {
+            if warmed.contains(&member.name.as_str()) {
+                continue;
+            }
+            warmed.push(&member.name);
+            if let Err(e) = member.processor.warm_up().await {
+                tracing::warn!("LLM warm-up of {} failed: {}", member.name, e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Models of every processor, each tagged with the processor's name.
+    /// Processors that cannot list theirs are skipped unless all fail.
+    async fn list_models(&self) -> Result<Vec<LlmModelInfo>> {
+        let mut models = Vec::new();
+        let mut first_error = None;
+        let mut listed = false;
+        for member in self.members() {
+            match member.processor.list_models().await {
+                Ok(member_models) => {
+                    listed = true;
+                    models.extend(member_models.into_iter().map(|model| LlmModelInfo {
+                        processor: Some(member.name.clone()),
+                        ..model
+                    }));
+                }
+                Err(e) => {
+                    tracing::warn!("Listing the models of {} failed: {}", member.name, e);
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        match first_error {
+            Some(e) if !listed => Err(e),
+            _ => Ok(models),
+        }
+    }
+
+    /// The primary's capabilities, which handles every task without a route
+    fn capabilities(&self) -> LlmCapabilities {
+        self.primary.processor.capabilities()
+    }
+
+    /// Capabilities of the processor `process` tries first for `task`
+    fn task_capabilities(&self, task: &ProcessingTask) -> LlmCapabilities {
+        self.chain(task)[0].processor.task_capabilities(task)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lt_core::error::{MurmurError, ProviderError};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Answers with its own name, or fails with `error` when set; lists
+    /// one model named after itself and reports itself offline when healthy
+    struct Stub {
+        name: &'static str,
+        error: Option<ProviderErrorKind>,
+        calls: AtomicUsize,
+    }
+
+    fn stub(name: &'static str, error: Option<ProviderErrorKind>) -> Arc<Stub> {
+        Arc::new(Stub {
+            name,
+            error,
+            calls: AtomicUsize::new(0),
+        })
+    }
+
+    impl Stub {
+        fn result<T>(&self, value: T) -> Result<T> {
+            match self.error {
+                Some(kind) => Err(MurmurError::from(ProviderError::new(
+                    kind, self.name, "failed",
+                ))),
+                None => Ok(value),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl LlmProcessor for Stub {
+        async fn process(&self, _task: ProcessingTask) -> Result<ProcessingOutput> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.result(ProcessingOutput {
+                text: format!("from {}", self.name),
+                processing_time_ms: 1,
+                metadata: Some(serde_json::json!({ "eval_count": 3 })),
+            })
+        }
+
+        async fn health_check(&self) -> Result<bool> {
+            self.result(true)
+        }
+
+        async fn list_models(&self) -> Result<Vec<LlmModelInfo>> {
+            self.result(vec![LlmModelInfo::new(format!("{}-model", self.name))])
+        }
+
+        fn capabilities(&self) -> LlmCapabilities {
+            LlmCapabilities {
+                offline: self.error.is_none(),
+                ..Default::default()
+            }
+        }
+    }
+
+    fn translate() -> ProcessingTask {
+        ProcessingTask::Translate {
+            text: "hallo".to_string(),
+            target_language: "English".to_string(),
+        }
+    }
+
+    fn shorten() -> ProcessingTask {
+        ProcessingTask::Shorten {
+            text: "hello there".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_in_order() {
+        let claude = stub("claude", Some(ProviderErrorKind::Network));
+        let ollama = stub("ollama", Some(ProviderErrorKind::Unavailable));
+        let gemini = stub("gemini", None);
+        let composite = CompositeProcessor::new("claude_api", claude.clone())
+            .with_fallback("ollama", ollama.clone())
+            .with_fallback("gemini_api", gemini.clone());
+
+        let output = composite.process(shorten()).await.unwrap();
+        assert_eq!(output.text, "from gemini");
+        assert_eq!(output.processor(), Some("gemini_api"));
+        let metadata = output.metadata.unwrap();
+        assert_eq!(
+            metadata["failed_processors"],
+            serde_json::json!(["claude_api", "ollama"])
+        );
+        // The answering processor's own metadata is kept
+        assert_eq!(metadata["eval_count"], 3);
+        assert_eq!(claude.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(ollama.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_routes_tasks_before_the_primary() {
+        let claude = stub("claude", None);
+        let gemini = stub(
+            "gemini",
+            Some(ProviderErrorKind::RateLimited {
+                retry_after_secs: None,
+            }),
+        );
+        let composite = CompositeProcessor::new("claude_api", claude.clone()).with_route(
+            "translate",
+            "gemini_api",
+            gemini.clone(),
+        );
+
+        // Other tasks go straight to the primary
+        let output = composite.process(shorten()).await.unwrap();
+        assert_eq!(output.processor(), Some("claude_api"));
+        assert_eq!(gemini.calls.load(Ordering::SeqCst), 0);
+
+        // The routed processor fails, so the primary answers
+        let output = composite.process(translate()).await.unwrap();
+        assert_eq!(output.processor(), Some("claude_api"));
+        assert_eq!(gemini.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_all_failing_returns_last_error() {
+        let composite =
+            CompositeProcessor::new("claude_api", stub("claude", Some(ProviderErrorKind::Auth)))
+                .with_fallback("ollama", stub("ollama", Some(ProviderErrorKind::Network)));
+
+        let err = composite.process(shorten()).await.unwrap_err();
+        assert_eq!(
+            err.provider_error().map(|d| d.kind),
+            Some(ProviderErrorKind::Network)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_stops_the_chain() {
+        let fallback = stub("ollama", None);
+        let composite = CompositeProcessor::new(
+            "claude_api",
+            stub("claude", Some(ProviderErrorKind::Cancelled)),
+        )
+        .with_fallback("ollama", fallback.clone());
+
+        assert!(composite.process(shorten()).await.is_err());
+        assert_eq!(fallback.calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_each_processor_is_tried_once() {
+        let claude = stub("claude", Some(ProviderErrorKind::Network));
+        let composite = CompositeProcessor::new("claude_api", claude.clone())
+            .with_route("translate", "claude_api", claude.clone())
+            .with_fallback("claude_api", claude.clone());
+
+        assert!(composite.process(translate()).await.is_err());
+        assert_eq!(claude.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_healthy_when_any_processor_is() {
+        let composite =
+            CompositeProcessor::new("claude_api", stub("claude", Some(ProviderErrorKind::Auth)))
+                .with_fallback("ollama", stub("ollama", None));
+        assert!(composite.health_check().await.unwrap());
+
+        let composite =
+            CompositeProcessor::new("claude_api", stub("claude", Some(ProviderErrorKind::Auth)))
+                .with_route(
+                    "translate",
+                    "gemini_api",
+                    stub("gemini", Some(ProviderErrorKind::Network)),
+                );
+        let err = composite.health_check().await.unwrap_err();
+        assert_eq!(
+            err.provider_error().map(|d| d.kind),
+            Some(ProviderErrorKind::Auth)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_lists_models_of_every_processor() {
+        let composite = CompositeProcessor::new("claude_api", stub("claude", None))
+            .with_route("translate", "gemini_api", stub("gemini", None))
+            .with_fallback("ollama", stub("ollama", Some(ProviderErrorKind::Network)))
+            .with_fallback("openai_api", stub("openai", None));
+
+        let models: Vec<(String, Option<String>)> = composite
+            .list_models()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|m| (m.id, m.processor))
+            .collect();
+        assert_eq!(
+            models,
+            vec![
+                ("claude-model".to_string(), Some("claude_api".to_string())),
+                ("gemini-model".to_string(), Some("gemini_api".to_string())),
+                ("openai-model".to_string(), Some("openai_api".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_task_capabilities_follow_routes() {
+        let composite =
+            CompositeProcessor::new("claude_api", stub("claude", Some(ProviderErrorKind::Auth)))
+                .with_route("translate", "ollama", stub("ollama", None));
+
+        assert!(!composite.capabilities().offline);
+        assert!(!composite.task_capabilities(&shorten()).offline);
+        assert!(composite.task_capabilities(&translate()).offline);
+    }
+}
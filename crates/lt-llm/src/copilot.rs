@@ -1,6 +1,6 @@
 use async_trait::async_trait;
-use lt_core::error::{MurmurError, Result};
-use lt_core::llm::{LlmProcessor, ProcessingOutput, ProcessingTask};
+use lt_core::error::{MurmurError, ProviderError, ProviderErrorKind, Result};
+use lt_core::llm::{LlmModelInfo, LlmProcessor, ProcessingOutput, ProcessingTask};
 use std::time::Instant;
 
 use crate::executor::CliExecutor;
@@ -50,6 +50,27 @@ impl CopilotProcessor {
     }
 }
 
+/// Model ids listed under `--model` in the CLI's help text, e.g.
+/// `--model <model>  Set the AI model to use (choices: "gpt-5", ...)`
+fn parse_model_choices(help: &str) -> Vec<String> {
+    let Some(option) = help.find("--model") else {
+        return Vec::new();
+    };
+    // The option's entry runs until the next option line
+    let rest = &help[option..];
+    let rest = &rest[..rest.find("\n  -").unwrap_or(rest.len())];
+    let Some(start) = rest.find("(choices:") else {
+        return Vec::new();
+    };
+    let choices = &rest[start + "(choices:".len()..];
+    let choices = &choices[..choices.find(')').unwrap_or(choices.len())];
+    choices
+        .split(',')
+        .map(|c| c.trim().trim_matches('"').to_string())
+        .filter(|c| !c.is_empty())
+        .collect()
+}
+
 impl Default for CopilotProcessor {
     fn default() -> Self {
         Self::new()
@@ -80,9 +101,20 @@ impl LlmProcessor for CopilotProcessor {
         }
         let output = self.executor.execute("copilot", &args).await.map_err(|e| {
             if e.kind() == std::io::ErrorKind::TimedOut {
-                MurmurError::Llm("Copilot CLI timed out".to_string())
+                ProviderError::new(
+                    ProviderErrorKind::Timeout,
+                    "copilot",
+                    "Copilot CLI timed out",
+                )
+                .into()
             } else if e.kind() == std::io::ErrorKind::NotFound {
-                MurmurError::Llm("Copilot CLI not found. Please install copilot-cli.".to_string())
+                ProviderError::new(
+                    ProviderErrorKind::Unavailable,
+                    "copilot",
+                    "Copilot CLI not found. Please install copilot-cli.",
+                )
+                .with_retryable(false)
+                .into()
             } else {
                 MurmurError::Llm(format!("Failed to execute copilot CLI: {}", e))
             }
@@ -130,6 +162,18 @@ impl LlmProcessor for CopilotProcessor {
             Ok(false)
         }
     }
+
+    async fn list_models(&self) -> Result<Vec<LlmModelInfo>> {
+        let output = self
+            .executor
+            .execute("copilot", &["--help"])
+            .await
+            .map_err(|e| MurmurError::Llm(format!("Failed to execute copilot CLI: {}", e)))?;
+        Ok(parse_model_choices(&output.stdout)
+            .into_iter()
+            .map(LlmModelInfo::new)
+            .collect())
+    }
 }
 
 #[cfg(test)]
@@ -142,4 +186,26 @@ mod tests {
         // This will return false if copilot is not installed, which is expected
         let _ = processor.health_check().await;
     }
+
+    #[test]
+    fn test_parse_model_choices() {
+        let help = r#"Options:
+  --banner                 Show the startup banner
+  --model <model>          Set the AI model to use (choices: "claude-sonnet-4.5",
+                           "claude-sonnet-4", "gpt-5", "gpt-5-mini")
+  --no-color               Disable all color output
+"#;
+        assert_eq!(
+            parse_model_choices(help),
+            [
+                "claude-sonnet-4.5",
+                "claude-sonnet-4",
+                "gpt-5",
+                "gpt-5-mini"
+            ]
+        );
+        let without_choices =
+            "  --model <model>  Set the AI model\n  --log-level <level>  (choices: \"info\")\n";
+        assert!(parse_model_choices(without_choices).is_empty());
+    }
 }
@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use lt_core::error::{MurmurError, Result};
+use lt_core::error::{MurmurError, ProviderError, ProviderErrorKind, Result};
 use lt_core::llm::{LlmProcessor, ProcessingOutput, ProcessingTask};
 use std::time::Instant;
 
@@ -110,11 +110,15 @@ impl LlmProcessor for GeminiProcessor {
             .await
             .map_err(|e| {
                 if e.kind() == std::io::ErrorKind::TimedOut {
-                    MurmurError::Llm("Gemini CLI timed out".to_string())
+                    ProviderError::new(ProviderErrorKind::Timeout, "gemini", "Gemini CLI timed out").into()
                 } else if e.kind() == std::io::ErrorKind::NotFound {
-                    MurmurError::Llm(
-                        "Gemini CLI not found. Please install gemini-cli: https://github.com/google/generative-ai-cli".to_string()
+                    ProviderError::new(
+                        ProviderErrorKind::Unavailable,
+                        "gemini",
+                        "Gemini CLI not found. Please install gemini-cli: https://github.com/google/generative-ai-cli",
                     )
+                    .with_retryable(false)
+                    .into()
                 } else {
                     MurmurError::Llm(format!("Failed to execute gemini CLI: {}", e))
                 }
@@ -1,6 +1,7 @@
 use async_trait::async_trait;
-use lt_core::error::{MurmurError, Result};
-use lt_core::llm::{LlmProcessor, ProcessingOutput, ProcessingTask};
+use lt_core::error::{MurmurError, ProviderError, ProviderErrorKind, Result};
+use lt_core::llm::{LlmCapabilities, LlmModelInfo, LlmProcessor, ProcessingOutput, ProcessingTask};
+use lt_core::retry::{self, RetryPolicy};
 use reqwest::Client;
 use std::time::{Duration, Instant};
 
@@ -24,13 +25,19 @@ pub const GEMINI_API_DEFAULT_MODEL: &str = "gemini-2.0-flash";
 
 /// HTTP-based LLM processor supporting multiple API formats
 pub struct HttpLlmProcessor {
-    client: Client,
+    /// Client override; None = the shared `lt_http` client, which follows
+    /// the network settings
+    client: Option<Client>,
     api_format: ApiFormat,
+    /// Config id of this provider, reported in structured errors
+    provider_id: &'static str,
     base_url: String,
     api_key: String,
     model: String,
     prompt_manager: PromptManager,
     timeout_secs: u64,
+    /// How rate-limited or failed requests are retried
+    retry: RetryPolicy,
 }
 
 impl HttpLlmProcessor {
@@ -48,13 +55,15 @@ impl HttpLlmProcessor {
             .filter(|m| !m.is_empty())
             .unwrap_or_else(|| OPENAI_DEFAULT_MODEL.to_string());
         Self {
-            client: Client::new(),
+            client: None,
             api_format: ApiFormat::OpenAi,
+            provider_id: "openai_api",
             base_url: "https://api.openai.com/v1".to_string(),
             api_key,
             model,
             prompt_manager: prompts,
             timeout_secs: 30,
+            retry: RetryPolicy::default(),
         }
     }
 
@@ -72,13 +81,15 @@ impl HttpLlmProcessor {
             .filter(|m| !m.is_empty())
             .unwrap_or_else(|| CLAUDE_DEFAULT_MODEL.to_string());
         Self {
-            client: Client::new(),
+            client: None,
             api_format: ApiFormat::Claude,
+            provider_id: "claude_api",
             base_url: "https://api.anthropic.com".to_string(),
             api_key,
             model,
             prompt_manager: prompts,
             timeout_secs: 30,
+            retry: RetryPolicy::default(),
         }
     }
 
@@ -96,13 +107,15 @@ impl HttpLlmProcessor {
             .filter(|m| !m.is_empty())
             .unwrap_or_else(|| GEMINI_API_DEFAULT_MODEL.to_string());
         Self {
-            client: Client::new(),
+            client: None,
             api_format: ApiFormat::GeminiApi,
+            provider_id: "gemini_api",
             base_url: "https://generativelanguage.googleapis.com".to_string(),
             api_key,
             model,
             prompt_manager: prompts,
             timeout_secs: 30,
+            retry: RetryPolicy::default(),
         }
     }
 
@@ -121,16 +134,40 @@ impl HttpLlmProcessor {
             .filter(|m| !m.is_empty())
             .unwrap_or_else(|| OPENAI_DEFAULT_MODEL.to_string());
         Self {
-            client: Client::new(),
+            client: None,
             api_format: ApiFormat::OpenAi,
+            provider_id: "custom_api",
             base_url,
             api_key,
             model,
             prompt_manager: prompts,
             timeout_secs: 30,
+            retry: RetryPolicy::default(),
         }
     }
 
+    /// Replace the default retry policy
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Replace the default 30 s request timeout
+    pub fn with_timeout(mut self, timeout_secs: u64) -> Self {
+        self.timeout_secs = timeout_secs;
+        self
+    }
+
+    /// Send requests with `client` instead of the shared client
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    fn client(&self) -> Client {
+        self.client.clone().unwrap_or_else(lt_http::client)
+    }
+
     /// Build the HTTP request for the given prompt
     fn build_request(&self, prompt: &str) -> Result<reqwest::RequestBuilder> {
         match &self.api_format {
@@ -144,7 +181,7 @@ impl HttpLlmProcessor {
                     ]
                 });
                 Ok(self
-                    .client
+                    .client()
                     .post(&url)
                     .header("Authorization", format!("Bearer {}", self.api_key))
                     .json(&body))
@@ -160,7 +197,7 @@ impl HttpLlmProcessor {
                     ]
                 });
                 Ok(self
-                    .client
+                    .client()
                     .post(&url)
                     .header("x-api-key", &self.api_key)
                     .header("anthropic-version", "2023-06-01")
@@ -179,11 +216,98 @@ impl HttpLlmProcessor {
                         }
                     ]
                 });
-                Ok(self.client.post(&url).json(&body))
+                Ok(self.client().post(&url).json(&body))
             }
         }
     }
 
+    /// Build the HTTP request listing the provider's models
+    fn models_request(&self) -> reqwest::RequestBuilder {
+        match &self.api_format {
+            ApiFormat::OpenAi => self
+                .client()
+                .get(format!("{}/models", self.base_url))
+                .header("Authorization", format!("Bearer {}", self.api_key)),
+            ApiFormat::Claude => self
+                .client()
+                .get(format!("{}/v1/models?limit=1000", self.base_url))
+                .header("x-api-key", &self.api_key)
+                .header("anthropic-version", "2023-06-01"),
+            ApiFormat::GeminiApi => self.client().get(format!(
+                "{}/v1beta/models?pageSize=1000&key={}",
+                self.base_url, self.api_key
+            )),
+        }
+    }
+
+    /// Extract the text-generation models from the API-specific model list
+    fn extract_models(&self, json: &serde_json::Value) -> Vec<LlmModelInfo> {
+        let str_field = |entry: &serde_json::Value, name: &str| {
+            entry.get(name).and_then(|v| v.as_str()).map(str::to_string)
+        };
+        let entries = |name: &str| {
+            json.get(name)
+                .and_then(|v| v.as_array())
+                .cloned()
+                .unwrap_or_default()
+        };
+        match &self.api_format {
+            ApiFormat::OpenAi => {
+                // OpenAI lists its embedding, audio and image models too;
+                // custom endpoints are taken at their word
+                let openai = self.provider_id == "openai_api";
+                let mut models: Vec<LlmModelInfo> = entries("data")
+                    .iter()
+                    .filter_map(|entry| str_field(entry, "id"))
+                    .filter(|id| !openai || is_openai_chat_model(id))
+                    .map(|id| LlmModelInfo {
+                        context_window: context_window_for_model(&id),
+                        ..LlmModelInfo::new(id)
+                    })
+                    .collect();
+                models.sort_by(|a, b| a.id.cmp(&b.id));
+                models
+            }
+            // Newest first, as the API returns them
+            ApiFormat::Claude => entries("data")
+                .iter()
+                .filter_map(|entry| {
+                    let id = str_field(entry, "id")?;
+                    Some(LlmModelInfo {
+                        display_name: str_field(entry, "display_name"),
+                        context_window: context_window_for_model(&id),
+                        ..LlmModelInfo::new(id)
+                    })
+                })
+                .collect(),
+            ApiFormat::GeminiApi => entries("models")
+                .iter()
+                .filter(|entry| {
+                    entry
+                        .get("supportedGenerationMethods")
+                        .and_then(|v| v.as_array())
+                        .is_some_and(|methods| {
+                            methods
+                                .iter()
+                                .any(|m| m.as_str() == Some("generateContent"))
+                        })
+                })
+                .filter_map(|entry| {
+                    let name = str_field(entry, "name")?;
+                    let id = name.strip_prefix("models/").unwrap_or(&name).to_string();
+                    Some(LlmModelInfo {
+                        display_name: str_field(entry, "displayName"),
+                        context_window: entry
+                            .get("inputTokenLimit")
+                            .and_then(|v| v.as_u64())
+                            .and_then(|n| u32::try_from(n).ok()),
+                        ..LlmModelInfo::new(id)
+                    })
+                })
+                .collect(),
+        }
+    }
+
     /// Extract the response text from the API-specific JSON
     fn extract_response(&self, json: &serde_json::Value) -> Result<String> {
         let text = match &self.api_format {
@@ -216,18 +340,73 @@ impl HttpLlmProcessor {
         })
     }
 
-    /// Map HTTP status codes to user-friendly error messages
-    fn map_http_error(&self, status: reqwest::StatusCode, body: &str) -> MurmurError {
-        match status.as_u16() {
-            401 => MurmurError::Llm("Authentication failed. Check your API key.".to_string()),
-            429 => MurmurError::Llm("Rate limited. Please wait and try again.".to_string()),
-            500..=599 => MurmurError::Llm("Server error. Try again later.".to_string()),
-            _ => MurmurError::Llm(format!(
-                "API request failed (HTTP {}): {}",
-                status,
-                body.chars().take(200).collect::<String>()
-            )),
+    /// Map HTTP status codes to structured, user-friendly errors
+    fn map_http_error(
+        &self,
+        status: reqwest::StatusCode,
+        retry_after: Option<Duration>,
+        body: &str,
+    ) -> MurmurError {
+        let retry_after_secs = retry_after.map(|d| d.as_secs_f64().ceil() as u64);
+        ProviderError::from_http_status(self.provider_id, status.as_u16(), retry_after_secs, body)
+            .with_retry_after(retry_after)
+            .into()
+    }
+
+    /// Map transport failures (no HTTP response) to structured errors
+    fn map_request_error(&self, err: reqwest::Error) -> MurmurError {
+        let (kind, message) = if err.is_timeout() {
+            (
+                ProviderErrorKind::Timeout,
+                format!("Request timed out ({}s).", self.timeout_secs),
+            )
+        } else if err.is_connect() {
+            (
+                ProviderErrorKind::Network,
+                format!(
+                    "Failed to connect to {}. Check your network connection.",
+                    self.base_url
+                ),
+            )
+        } else {
+            (
+                ProviderErrorKind::Network,
+                format!("HTTP request failed: {}", err),
+            )
+        };
+        ProviderError::new(kind, self.provider_id, message).into()
+    }
+
+    /// Send one prompt and return the parsed JSON body
+    async fn send(&self, prompt: &str) -> Result<serde_json::Value> {
+        self.send_request(self.build_request(prompt)?).await
+    }
+
+    /// Send one request and return the parsed JSON body
+    async fn send_request(&self, request: reqwest::RequestBuilder) -> Result<serde_json::Value> {
+        let response = request
+            .timeout(Duration::from_secs(self.timeout_secs))
+            .send()
+            .await
+            .map_err(|e| self.map_request_error(e))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let retry_after = retry::retry_after_from_headers(
+                response
+                    .headers()
+                    .iter()
+                    .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?))),
+            );
+            let body = response.text().await.unwrap_or_default();
+            tracing::error!("API error (HTTP {}): {}", status, body);
+            return Err(self.map_http_error(status, retry_after, &body));
         }
+
+        response
+            .json()
+            .await
+            .map_err(|e| MurmurError::Llm(format!("Failed to parse API response: {}", e)))
     }
 }
 
@@ -245,36 +424,7 @@ impl LlmProcessor for HttpLlmProcessor {
             prompt.len()
         );
 
-        let request = self.build_request(&prompt)?;
-
-        let response = request
-            .timeout(Duration::from_secs(self.timeout_secs))
-            .send()
-            .await
-            .map_err(|e| {
-                if e.is_timeout() {
-                    MurmurError::Llm(format!("Request timed out ({}s).", self.timeout_secs))
-                } else if e.is_connect() {
-                    MurmurError::Llm(format!(
-                        "Failed to connect to {}. Check your network connection.",
-                        self.base_url
-                    ))
-                } else {
-                    MurmurError::Llm(format!("HTTP request failed: {}", e))
-                }
-            })?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let body = response.text().await.unwrap_or_default();
-            tracing::error!("API error (HTTP {}): {}", status, body);
-            return Err(self.map_http_error(status, &body));
-        }
-
-        let json: serde_json::Value = response
-            .json()
-            .await
-            .map_err(|e| MurmurError::Llm(format!("Failed to parse API response: {}", e)))?;
+        let json = self.retry.run(|| self.send(&prompt)).await?;
 
         let processed_text = self.extract_response(&json)?;
         let processing_time_ms = start_time.elapsed().as_millis() as u64;
@@ -296,11 +446,104 @@ impl LlmProcessor for HttpLlmProcessor {
         // Return true if API key is non-empty (no live API call to avoid cost)
         Ok(!self.api_key.is_empty())
     }
+
+    async fn list_models(&self) -> Result<Vec<LlmModelInfo>> {
+        let json = self
+            .retry
+            .run(|| self.send_request(self.models_request()))
+            .await?;
+        Ok(self.extract_models(&json))
+    }
+
+    fn capabilities(&self) -> LlmCapabilities {
+        let custom = self.provider_id == "custom_api";
+        LlmCapabilities {
+            streaming: false,
+            context_window: context_window_for_model(&self.model),
+            // A custom endpoint on this machine (Ollama, LM Studio, ...) works offline
+            offline: custom && is_loopback_url(&self.base_url),
+            requires_api_key: !custom,
+        }
+    }
+}
+
+/// Context window of well-known hosted model families, in tokens
+fn context_window_for_model(model: &str) -> Option<u32> {
+    if model.starts_with("gpt-4.1") {
+        Some(1_047_576)
+    } else if model.starts_with("gpt-4o") || model.starts_with("o1") || model.starts_with("o3") {
+        Some(128_000)
+    } else if model.starts_with("claude-") {
+        Some(200_000)
+    } else if model.starts_with("gemini-1.5") || model.starts_with("gemini-2") {
+        Some(1_048_576)
+    } else {
+        None
+    }
+}
+
+/// True for OpenAI models that work with Chat Completions (not embedding,
+/// audio, image or legacy completion models)
+fn is_openai_chat_model(id: &str) -> bool {
+    const CHAT_PREFIXES: [&str; 7] = ["gpt-", "chatgpt-", "o1", "o3", "o4", "ft:gpt-", "ft:o"];
+    const NON_CHAT: [&str; 6] = [
+        "audio",
+        "realtime",
+        "tts",
+        "transcribe",
+        "image",
+        "instruct",
+    ];
+    CHAT_PREFIXES.iter().any(|p| id.starts_with(p)) && !NON_CHAT.iter().any(|n| id.contains(n))
+}
+
+/// True if `url` points at this machine
+pub(crate) fn is_loopback_url(url: &str) -> bool {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| {
+            u.host_str()
+                .map(|h| matches!(h, "localhost" | "127.0.0.1" | "[::1]"))
+        })
+        .unwrap_or(false)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_capabilities() {
+        let openai = HttpLlmProcessor::openai("k".to_string(), None).capabilities();
+        assert!(openai.requires_api_key);
+        assert!(!openai.offline);
+        assert_eq!(openai.context_window, Some(128_000));
+
+        let claude = HttpLlmProcessor::claude("k".to_string(), None).capabilities();
+        assert_eq!(claude.context_window, Some(200_000));
+
+        let local = HttpLlmProcessor::custom(
+            "http://localhost:11434/v1".to_string(),
+            String::new(),
+            Some("llama3".to_string()),
+        )
+        .capabilities();
+        assert!(local.offline);
+        assert!(!local.requires_api_key);
+        assert_eq!(local.context_window, None);
+
+        let remote = HttpLlmProcessor::custom(
+            "https://llm.example.com/v1".to_string(),
+            String::new(),
+            None,
+        )
+        .capabilities();
+        assert!(!remote.offline);
+    }
 
     #[test]
     fn test_openai_constructor() {
@@ -357,6 +600,238 @@ mod tests {
         assert!(!processor.health_check().await.unwrap());
     }
 
+    #[test]
+    fn test_map_http_error_is_structured() {
+        let processor = HttpLlmProcessor::claude("key".to_string(), None);
+
+        let err = processor.map_http_error(reqwest::StatusCode::UNAUTHORIZED, None, "");
+        let detail = err.provider_error().unwrap();
+        assert_eq!(detail.kind, ProviderErrorKind::Auth);
+        assert_eq!(detail.provider, "claude_api");
+        assert_eq!(detail.status, Some(401));
+        assert!(!detail.retryable);
+
+        let err = processor.map_http_error(
+            reqwest::StatusCode::TOO_MANY_REQUESTS,
+            Some(Duration::from_secs(20)),
+            "",
+        );
+        let detail = err.provider_error().unwrap();
+        assert_eq!(
+            detail.kind,
+            ProviderErrorKind::RateLimited {
+                retry_after_secs: Some(20)
+            }
+        );
+        assert!(detail.retryable);
+    }
+
+    /// OpenAI-compatible stub that answers with `statuses` in turn (each
+    /// with its extra headers), then with a completion; returns the
+    /// processor pointed at it and a request counter
+    async fn scripted_server(
+        statuses: Vec<(&'static str, &'static str)>,
+    ) -> (HttpLlmProcessor, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(AtomicUsize::new(0));
+        let served = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = vec![0u8; 16 * 1024];
+                let _ = stream.read(&mut buf).await;
+                let n = served.fetch_add(1, Ordering::SeqCst);
+                let (status, headers, body) = match statuses.get(n) {
+                    Some((status, headers)) => (*status, *headers, r#"{"error":"busy"}"#),
+                    None => (
+                        "200 OK",
+                        "",
+                        r#"{"choices":[{"message":{"content":"Done"}}]}"#,
+                    ),
+                };
+                let response = format!(
+                    "HTTP/1.1 {}\r\n{}Content-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    headers,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        let processor =
+            HttpLlmProcessor::custom(format!("http://{}/v1", addr), "key".to_string(), None);
+        (processor, requests)
+    }
+
+    fn shorten() -> ProcessingTask {
+        ProcessingTask::Shorten {
+            text: "hello there".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retries_rate_limits_and_overload() {
+        let (processor, requests) = scripted_server(vec![
+            ("429 Too Many Requests", "retry-after-ms: 30\r\n"),
+            (
+                "429 Too Many Requests",
+                "x-ratelimit-remaining-requests: 0\r\nx-ratelimit-reset-requests: 20ms\r\n",
+            ),
+            ("503 Service Unavailable", "Retry-After: 0\r\n"),
+        ])
+        .await;
+        let processor = processor.with_retry(RetryPolicy {
+            max_attempts: 4,
+            ..RetryPolicy::default()
+        });
+
+        let output = processor.process(shorten()).await.unwrap();
+        assert_eq!(output.text, "Done");
+        assert_eq!(requests.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_attempts() {
+        let (processor, requests) = scripted_server(vec![
+            ("503 Service Unavailable", ""),
+            ("503 Service Unavailable", ""),
+            ("503 Service Unavailable", ""),
+        ])
+        .await;
+        let processor = processor.with_retry(RetryPolicy {
+            base_delay: Duration::from_millis(5),
+            ..RetryPolicy::default()
+        });
+
+        let err = processor.process(shorten()).await.unwrap_err();
+        assert_eq!(
+            err.provider_error().map(|e| e.kind),
+            Some(ProviderErrorKind::Unavailable)
+        );
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_does_not_retry_auth_errors_or_long_waits() {
+        let (processor, requests) = scripted_server(vec![("401 Unauthorized", "")]).await;
+        let err = processor.process(shorten()).await.unwrap_err();
+        assert_eq!(
+            err.provider_error().map(|e| e.kind),
+            Some(ProviderErrorKind::Auth)
+        );
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        let (processor, requests) =
+            scripted_server(vec![("429 Too Many Requests", "Retry-After: 90\r\n")]).await;
+        let err = processor.process(shorten()).await.unwrap_err();
+        let detail = err.provider_error().unwrap();
+        assert_eq!(detail.retry_after_ms, Some(90_000));
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_extract_openai_models() {
+        let json = serde_json::json!({
+            "object": "list",
+            "data": [
+                { "id": "gpt-4o-mini", "object": "model" },
+                { "id": "text-embedding-3-small", "object": "model" },
+                { "id": "gpt-4o-realtime-preview", "object": "model" },
+                { "id": "whisper-1", "object": "model" },
+                { "id": "o3-mini", "object": "model" },
+                { "id": "gpt-4.1", "object": "model" }
+            ]
+        });
+        let openai = HttpLlmProcessor::openai("key".to_string(), None);
+        let ids: Vec<String> = openai
+            .extract_models(&json)
+            .into_iter()
+            .map(|m| m.id)
+            .collect();
+        assert_eq!(ids, ["gpt-4.1", "gpt-4o-mini", "o3-mini"]);
+
+        // A custom endpoint serves whatever it lists
+        let custom =
+            HttpLlmProcessor::custom("http://localhost:1234/v1".to_string(), String::new(), None);
+        assert_eq!(custom.extract_models(&json).len(), 6);
+    }
+
+    #[test]
+    fn test_extract_claude_models() {
+        let json = serde_json::json!({
+            "data": [
+                { "type": "model", "id": "claude-opus-4-1-20250805", "display_name": "Claude Opus 4.1" },
+                { "type": "model", "id": "claude-3-5-haiku-20241022", "display_name": "Claude Haiku 3.5" }
+            ],
+            "has_more": false
+        });
+        let processor = HttpLlmProcessor::claude("key".to_string(), None);
+        let models = processor.extract_models(&json);
+        assert_eq!(models[0].id, "claude-opus-4-1-20250805");
+        assert_eq!(models[0].display_name.as_deref(), Some("Claude Opus 4.1"));
+        assert_eq!(models[1].context_window, Some(200_000));
+    }
+
+    #[test]
+    fn test_extract_gemini_models() {
+        let json = serde_json::json!({
+            "models": [
+                {
+                    "name": "models/gemini-2.0-flash",
+                    "displayName": "Gemini 2.0 Flash",
+                    "inputTokenLimit": 1048576,
+                    "supportedGenerationMethods": ["generateContent", "countTokens"]
+                },
+                {
+                    "name": "models/text-embedding-004",
+                    "displayName": "Text Embedding 004",
+                    "supportedGenerationMethods": ["embedContent"]
+                }
+            ]
+        });
+        let processor = HttpLlmProcessor::gemini_api("key".to_string(), None);
+        let models = processor.extract_models(&json);
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].id, "gemini-2.0-flash");
+        assert_eq!(models[0].display_name.as_deref(), Some("Gemini 2.0 Flash"));
+        assert_eq!(models[0].context_window, Some(1_048_576));
+    }
+
+    #[tokio::test]
+    async fn test_list_models_from_custom_endpoint() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 16 * 1024];
+            let n = stream.read(&mut buf).await.unwrap();
+            let body = r#"{"data":[{"id":"qwen2.5-7b-instruct"},{"id":"llama-3.2-3b"}]}"#;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&buf[..n]).to_string()
+        });
+
+        let processor =
+            HttpLlmProcessor::custom(format!("http://{}/v1", addr), "key".to_string(), None);
+        let ids: Vec<String> = processor
+            .list_models()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|m| m.id)
+            .collect();
+        assert_eq!(ids, ["llama-3.2-3b", "qwen2.5-7b-instruct"]);
+
+        let request = server.await.unwrap();
+        assert!(request.starts_with("GET /v1/models "));
+        assert!(request.contains("authorization: Bearer key"));
+    }
+
     #[test]
     fn test_extract_openai_response() {
         let processor = HttpLlmProcessor::openai("key".to_string(), None);
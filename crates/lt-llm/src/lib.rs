@@ -1,17 +1,23 @@
+pub mod composite;
 pub mod copilot;
 pub mod executor;
 pub mod gemini;
 pub mod http_api;
+pub mod models;
+pub mod ollama;
 pub mod prompt_store;
 pub mod prompts;
 
 #[cfg(target_os = "macos")]
 pub mod apple;
 
+pub use composite::CompositeProcessor;
 pub use copilot::CopilotProcessor;
 pub use executor::CliExecutor;
 pub use gemini::GeminiProcessor;
 pub use http_api::HttpLlmProcessor;
+pub use models::ModelCache;
+pub use ollama::{OllamaModel, OllamaProcessor};
 pub use prompt_store::PromptStore;
 pub use prompts::{PromptManager, PromptName, PromptSet};
 
@@ -0,0 +1,131 @@
+use lt_core::error::Result;
+use lt_core::llm::{LlmModelInfo, LlmProcessor};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// How long a fetched model list is reused
+pub const DEFAULT_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Model lists per processor, fetched on first use and reused until they
+/// expire. Failed fetches are not cached.
+pub struct ModelCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (Instant, Vec<LlmModelInfo>)>>,
+}
+
+impl ModelCache {
+    pub fn new() -> Self {
+        Self::with_ttl(DEFAULT_TTL)
+    }
+
+    pub fn with_ttl(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The models of `processor`, cached under `key` (its config id).
+    /// `refresh` skips the cache.
+    pub async fn models(
+        &self,
+        key: &str,
+        processor: &dyn LlmProcessor,
+        refresh: bool,
+    ) -> Result<Vec<LlmModelInfo>> {
+        if !refresh {
+            if let Some((fetched, models)) = self.entries.lock().await.get(key) {
+                if fetched.elapsed() < self.ttl {
+                    return Ok(models.clone());
+                }
+            }
+        }
+
+        // Not locked while fetching, so one slow provider doesn't hold up
+        // the others
+        let models = processor.list_models().await?;
+        tracing::debug!("Fetched {} models for {}", models.len(), key);
+        self.entries
+            .lock()
+            .await
+            .insert(key.to_string(), (Instant::now(), models.clone()));
+        Ok(models)
+    }
+
+    /// Forget all lists, e.g. after an API key or endpoint changed
+    pub async fn clear(&self) {
+        self.entries.lock().await.clear();
+    }
+}
+
+impl Default for ModelCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use lt_core::error::MurmurError;
+    use lt_core::llm::{ProcessingOutput, ProcessingTask};
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    /// Lists one model per call made so far; fails while `failing` is set
+    #[derive(Default)]
+    struct CountingProcessor {
+        calls: AtomicUsize,
+        failing: AtomicBool,
+    }
+
+    #[async_trait]
+    impl LlmProcessor for CountingProcessor {
+        async fn process(&self, _task: ProcessingTask) -> Result<ProcessingOutput> {
+            unreachable!()
+        }
+
+        async fn health_check(&self) -> Result<bool> {
+            Ok(true)
+        }
+
+        async fn list_models(&self) -> Result<Vec<LlmModelInfo>> {
+            if self.failing.load(Ordering::SeqCst) {
+                return Err(MurmurError::Llm("offline".to_string()));
+            }
+            let n = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok((0..n)
+                .map(|i| LlmModelInfo::new(format!("m{}", i)))
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reuses_lists_until_refreshed_or_cleared() {
+        let cache = ModelCache::new();
+        let processor = CountingProcessor::default();
+
+        assert_eq!(cache.models("a", &processor, false).await.unwrap().len(), 1);
+        assert_eq!(cache.models("a", &processor, false).await.unwrap().len(), 1);
+        assert_eq!(cache.models("b", &processor, false).await.unwrap().len(), 2);
+        assert_eq!(cache.models("a", &processor, true).await.unwrap().len(), 3);
+
+        cache.clear().await;
+        assert_eq!(cache.models("b", &processor, false).await.unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_expired_and_failed_fetches() {
+        let cache = ModelCache::with_ttl(Duration::ZERO);
+        let processor = CountingProcessor::default();
+
+        assert_eq!(cache.models("a", &processor, false).await.unwrap().len(), 1);
+        assert_eq!(cache.models("a", &processor, false).await.unwrap().len(), 2);
+
+        processor.failing.store(true, Ordering::SeqCst);
+        assert!(cache.models("a", &processor, true).await.is_err());
+        processor.failing.store(false, Ordering::SeqCst);
+        assert_eq!(cache.models("a", &processor, false).await.unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,703 @@
+use async_trait::async_trait;
+use lt_core::error::{MurmurError, ProviderError, ProviderErrorKind, Result};
+use lt_core::llm::{LlmCapabilities, LlmModelInfo, LlmProcessor, ProcessingOutput, ProcessingTask};
+use lt_core::retry::{self, RetryPolicy};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+use crate::http_api::is_loopback_url;
+use crate::prompts::PromptManager;
+
+pub const DEFAULT_BASE_URL: &str = "http://localhost:11434";
+pub const DEFAULT_MODEL: &str = "llama3.2";
+/// How long the model stays loaded after a request when not configured;
+/// long enough to span the pauses between dictations
+pub const DEFAULT_KEEP_ALIVE: &str = "30m";
+pub const DEFAULT_TIMEOUT_SECS: u64 = 120;
+
+/// Timeout for the version and model-list requests, which never load a model
+const METADATA_TIMEOUT: Duration = Duration::from_secs(5);
+
+const SYSTEM_PROMPT: &str = "You are a helpful text processing assistant. Follow the instructions precisely and return only the processed text.";
+
+/// A model installed on the Ollama server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OllamaModel {
+    /// Name to request it by, e.g. "llama3.2:latest"
+    pub name: String,
+    /// Size on disk in bytes
+    #[serde(default)]
+    pub size: u64,
+    /// Parameter count as reported by Ollama, e.g. "3.2B"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parameter_size: Option<String>,
+    /// Quantization, e.g. "Q4_K_M"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quantization_level: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TagsResponse {
+    #[serde(default)]
+    models: Vec<TagsModel>,
+}
+
+#[derive(Deserialize)]
+struct TagsModel {
+    name: String,
+    #[serde(default)]
+    size: u64,
+    #[serde(default)]
+    details: TagsDetails,
+}
+
+#[derive(Default, Deserialize)]
+struct TagsDetails {
+    parameter_size: Option<String>,
+    quantization_level: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct VersionResponse {
+    version: String,
+}
+
+/// One line of a streamed `/api/chat` response
+#[derive(Deserialize)]
+struct ChatChunk {
+    #[serde(default)]
+    message: Option<ChatMessage>,
+    #[serde(default)]
+    done: bool,
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    prompt_eval_count: Option<u64>,
+    #[serde(default)]
+    eval_count: Option<u64>,
+    /// Time spent loading the model, in nanoseconds
+    #[serde(default)]
+    load_duration: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct ChatMessage {
+    #[serde(default)]
+    content: String,
+}
+
+/// LLM processor talking to Ollama's native API, which (unlike its
+/// OpenAI-compatible endpoint) controls how long the model stays loaded
+/// and takes model options such as the context size
+pub struct OllamaProcessor {
+    /// Client override; None = the shared `lt_http` client, which follows
+    /// the network settings
+    client: Option<Client>,
+    base_url: String,
+    model: String,
+    prompt_manager: PromptManager,
+    keep_alive: String,
+    /// Model options sent with every request (`num_ctx`, `temperature`, ...)
+    options: serde_json::Map<String, serde_json::Value>,
+    timeout_secs: u64,
+    /// How failed requests are retried
+    retry: RetryPolicy,
+}
+
+impl OllamaProcessor {
+    /// Create a processor for the server at `base_url` (None = localhost)
+    pub fn new(base_url: Option<String>, model: Option<String>) -> Self {
+        Self::with_prompts(base_url, model, PromptManager::new())
+    }
+
+    pub fn with_prompts(
+        base_url: Option<String>,
+        model: Option<String>,
+        prompts: PromptManager,
+    ) -> Self {
+        let base_url = base_url
+            .map(|u| u.trim().trim_end_matches('/').to_string())
+            .filter(|u| !u.is_empty())
+            .unwrap_or_else(|| DEFAULT_BASE_URL.to_string());
+        let model = model
+            .filter(|m| !m.is_empty())
+            .unwrap_or_else(|| DEFAULT_MODEL.to_string());
+        Self {
+            client: None,
+            base_url,
+            model,
+            prompt_manager: prompts,
+            keep_alive: DEFAULT_KEEP_ALIVE.to_string(),
+            options: serde_json::Map::new(),
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /// Keep the model loaded this long after each request ("10m", "-1", ...)
+    pub fn with_keep_alive(mut self, keep_alive: impl Into<String>) -> Self {
+        self.keep_alive = keep_alive.into();
+        self
+    }
+
+    /// Set a model option (see Ollama's Modelfile parameters)
+    pub fn with_option(mut self, name: impl Into<String>, value: serde_json::Value) -> Self {
+        self.options.insert(name.into(), value);
+        self
+    }
+
+    /// Set the context window in tokens
+    pub fn with_num_ctx(self, num_ctx: u32) -> Self {
+        self.with_option("num_ctx", num_ctx.into())
+    }
+
+    /// Replace the default retry policy
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Replace the default 120 s request timeout
+    pub fn with_timeout(mut self, timeout_secs: u64) -> Self {
+        self.timeout_secs = timeout_secs;
+        self
+    }
+
+    /// Send requests with `client` instead of the shared client
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    fn client(&self) -> Client {
+        self.client.clone().unwrap_or_else(lt_http::client)
+    }
+
+    /// The models installed on the server (`/api/tags`)
+    pub async fn installed_models(&self) -> Result<Vec<OllamaModel>> {
+        let response = self
+            .client()
+            .get(format!("{}/api/tags", self.base_url))
+            .timeout(METADATA_TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| self.map_request_error(e))?;
+        let response = self.check_status(response).await?;
+        let tags: TagsResponse = response
+            .json()
+            .await
+            .map_err(|e| MurmurError::Llm(format!("Failed to parse Ollama model list: {}", e)))?;
+
+        let mut models: Vec<OllamaModel> = tags
+            .models
+            .into_iter()
+            .map(|m| OllamaModel {
+                name: m.name,
+                size: m.size,
+                parameter_size: m.details.parameter_size,
+                quantization_level: m.details.quantization_level,
+            })
+            .collect();
+        models.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(models)
+    }
+
+    /// The server's version (`/api/version`)
+    pub async fn version(&self) -> Result<String> {
+        let response = self
+            .client()
+            .get(format!("{}/api/version", self.base_url))
+            .timeout(METADATA_TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| self.map_request_error(e))?;
+        let response = self.check_status(response).await?;
+        let version: VersionResponse = response
+            .json()
+            .await
+            .map_err(|e| MurmurError::Llm(format!("Failed to parse Ollama version: {}", e)))?;
+        Ok(version.version)
+    }
+
+    /// Body of a `/api/chat` request; no messages only loads the model
+    fn chat_body(&self, prompt: Option<&str>) -> serde_json::Value {
+        let messages = match prompt {
+            Some(prompt) => serde_json::json!([
+                { "role": "system", "content": SYSTEM_PROMPT },
+                { "role": "user", "content": prompt }
+            ]),
+            None => serde_json::json!([]),
+        };
+        let mut body = serde_json::json!({
+            "model": self.model,
+            "messages": messages,
+            "stream": prompt.is_some(),
+            "keep_alive": self.keep_alive,
+        });
+        if !self.options.is_empty() {
+            body["options"] = serde_json::Value::Object(self.options.clone());
+        }
+        body
+    }
+
+    /// Send one chat request and collect the streamed reply
+    async fn chat(&self, prompt: &str) -> Result<(String, Option<serde_json::Value>)> {
+        let response = self
+            .client()
+            .post(format!("{}/api/chat", self.base_url))
+            .timeout(Duration::from_secs(self.timeout_secs))
+            .json(&self.chat_body(Some(prompt)))
+            .send()
+            .await
+            .map_err(|e| self.map_request_error(e))?;
+        let mut response = self.check_status(response).await?;
+
+        let mut text = String::new();
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut stats = None;
+        while let Some(bytes) = response
+            .chunk()
+            .await
+            .map_err(|e| self.map_request_error(e))?
+        {
+            buffer.extend_from_slice(&bytes);
+            while let Some(end) = buffer.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=end).collect();
+                if let Some(done) = self.read_chunk(&line, &mut text)? {
+                    stats = Some(done);
+                }
+            }
+        }
+        // The final line may come without a trailing newline
+        if let Some(done) = self.read_chunk(&buffer, &mut text)? {
+            stats = Some(done);
+        }
+
+        if stats.is_none() {
+            return Err(ProviderError::new(
+                ProviderErrorKind::Network,
+                "ollama",
+                "Ollama closed the response before it was complete.",
+            )
+            .into());
+        }
+        Ok((text, stats))
+    }
+
+    /// Append the content of one NDJSON line to `text`; returns the
+    /// generation stats once the final line arrives
+    fn read_chunk(&self, line: &[u8], text: &mut String) -> Result<Option<serde_json::Value>> {
+        let line = String::from_utf8_lossy(line);
+        let line = line.trim();
+        if line.is_empty() {
+            return Ok(None);
+        }
+        let chunk: ChatChunk = serde_json::from_str(line)
+            .map_err(|e| MurmurError::Llm(format!("Failed to parse Ollama response: {}", e)))?;
+        if let Some(error) = chunk.error {
+            return Err(ProviderError::new(
+                ProviderErrorKind::Unavailable,
+                "ollama",
+                format!("Ollama failed while generating: {}", error),
+            )
+            .into());
+        }
+        if let Some(message) = chunk.message {
+            text.push_str(&message.content);
+        }
+        if !chunk.done {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::json!({
+            "model": self.model,
+            "prompt_eval_count": chunk.prompt_eval_count,
+            "eval_count": chunk.eval_count,
+            "load_duration_ms": chunk.load_duration.map(|ns| ns / 1_000_000),
+        })))
+    }
+
+    /// Pass successful responses through; map the others to structured errors
+    async fn check_status(&self, response: reqwest::Response) -> Result<reqwest::Response> {
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let retry_after = retry::retry_after_from_headers(
+            response
+                .headers()
+                .iter()
+                .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?))),
+        );
+        let body = response.text().await.unwrap_or_default();
+        tracing::error!("Ollama error (HTTP {}): {}", status, body);
+        Err(self.map_http_error(status, retry_after, &body))
+    }
+
+    /// Map HTTP status codes to structured, user-friendly errors
+    fn map_http_error(
+        &self,
+        status: reqwest::StatusCode,
+        retry_after: Option<Duration>,
+        body: &str,
+    ) -> MurmurError {
+        if status == reqwest::StatusCode::NOT_FOUND && body.contains("not found") {
+            return ProviderError::new(
+                ProviderErrorKind::BadRequest,
+                "ollama",
+                format!(
+                    "Model '{}' is not installed. Run `ollama pull {}`.",
+                    self.model, self.model
+                ),
+            )
+            .with_status(404)
+            .into();
+        }
+        let retry_after_secs = retry_after.map(|d| d.as_secs_f64().ceil() as u64);
+        ProviderError::from_http_status("ollama", status.as_u16(), retry_after_secs, body)
+            .with_retry_after(retry_after)
+            .into()
+    }
+
+    /// Map transport failures (no HTTP response) to structured errors
+    fn map_request_error(&self, err: reqwest::Error) -> MurmurError {
+        let (kind, message) = if err.is_timeout() {
+            (
+                ProviderErrorKind::Timeout,
+                format!("Request timed out ({}s).", self.timeout_secs),
+            )
+        } else if err.is_connect() {
+            (
+                ProviderErrorKind::Network,
+                format!(
+                    "Failed to connect to Ollama at {}. Is `ollama serve` running?",
+                    self.base_url
+                ),
+            )
+        } else {
+            (
+                ProviderErrorKind::Network,
+                format!("HTTP request failed: {}", err),
+            )
+        };
+        ProviderError::new(kind, "ollama", message).into()
+    }
+}
+
+#[async_trait]
+impl LlmProcessor for OllamaProcessor {
+    async fn process(&self, task: ProcessingTask) -> Result<ProcessingOutput> {
+        let start_time = Instant::now();
+
+        let prompt = self.prompt_manager.build_prompt(&task).await;
+
+        tracing::debug!(
+            "Sending Ollama chat request (model: {}, prompt length: {} chars)",
+            self.model,
+            prompt.len()
+        );
+
+        let (text, metadata) = self.retry.run(|| self.chat(&prompt)).await?;
+        let processing_time_ms = start_time.elapsed().as_millis() as u64;
+
+        tracing::info!(
+            "Ollama processing completed in {}ms (output: {} chars)",
+            processing_time_ms,
+            text.len()
+        );
+
+        Ok(ProcessingOutput {
+            text: text.trim().to_string(),
+            processing_time_ms,
+            metadata,
+        })
+    }
+
+    async fn health_check(&self) -> Result<bool> {
+        match self.version().await {
+            Ok(version) => {
+                tracing::debug!("Ollama {} is running at {}", version, self.base_url);
+                Ok(true)
+            }
+            Err(e) => {
+                tracing::debug!("Ollama health check failed: {}", e);
+                Ok(false)
+            }
+        }
+    }
+
+    async fn list_models(&self) -> Result<Vec<LlmModelInfo>> {
+        let models = self.installed_models().await?;
+        Ok(models
+            .into_iter()
+            .map(|m| {
+                let details = [m.parameter_size, m.quantization_level]
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                LlmModelInfo {
+                    details: (!details.is_empty()).then_some(details),
+                    ..LlmModelInfo::new(m.name)
+                }
+            })
+            .collect())
+    }
+
+    /// Load the model and keep it loaded for `keep_alive`, so the first
+    /// request of a dictation doesn't wait for it
+    async fn warm_up(&self) -> Result<()> {
+        let start_time = Instant::now();
+        let response = self
+            .client()
+            .post(format!("{}/api/chat", self.base_url))
+            .timeout(Duration::from_secs(self.timeout_secs))
+            .json(&self.chat_body(None))
+            .send()
+            .await
+            .map_err(|e| self.map_request_error(e))?;
+        self.check_status(response).await?;
+        tracing::debug!(
+            "Ollama model {} ready in {}ms",
+            self.model,
+            start_time.elapsed().as_millis()
+        );
+        Ok(())
+    }
+
+    fn capabilities(&self) -> LlmCapabilities {
+        LlmCapabilities {
+            streaming: true,
+            context_window: self
+                .options
+                .get("num_ctx")
+                .and_then(|v| v.as_u64())
+                .and_then(|n| u32::try_from(n).ok()),
+            offline: is_loopback_url(&self.base_url),
+            requires_api_key: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Ollama stub answering every request with `status` and `body`;
+    /// returns the processor pointed at it and the request bodies received
+    async fn stub_server(
+        status: &'static str,
+        body: String,
+    ) -> (OllamaProcessor, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let received = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = Vec::new();
+                let mut buf = vec![0u8; 16 * 1024];
+                // Read the head, then as much body as Content-Length says
+                loop {
+                    let n = stream.read(&mut buf).await.unwrap_or(0);
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&request);
+                    if let Some(head_end) = text.find("\r\n\r\n") {
+                        let length = text[..head_end]
+                            .lines()
+                            .find_map(|l| {
+                                l.to_lowercase()
+                                    .strip_prefix("content-length: ")
+                                    .map(|v| v.parse::<usize>().unwrap_or(0))
+                            })
+                            .unwrap_or(0);
+                        if request.len() >= head_end + 4 + length {
+                            break;
+                        }
+                    }
+                }
+                let text = String::from_utf8_lossy(&request).to_string();
+                let (head, payload) = text.split_once("\r\n\r\n").unwrap_or((&text, ""));
+                let request_line = head.lines().next().unwrap_or_default();
+                received
+                    .lock()
+                    .unwrap()
+                    .push(format!("{}\n{}", request_line, payload));
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/x-ndjson\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        let processor = OllamaProcessor::new(Some(format!("http://{}/", addr)), None);
+        (processor, requests)
+    }
+
+    fn without_retries(processor: OllamaProcessor) -> OllamaProcessor {
+        processor.with_retry(RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        })
+    }
+
+    fn shorten() -> ProcessingTask {
+        ProcessingTask::Shorten {
+            text: "hello there".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_defaults() {
+        let processor = OllamaProcessor::new(None, Some(String::new()));
+        assert_eq!(processor.base_url, DEFAULT_BASE_URL);
+        assert_eq!(processor.model, DEFAULT_MODEL);
+        assert_eq!(processor.keep_alive, DEFAULT_KEEP_ALIVE);
+
+        let capabilities = processor.capabilities();
+        assert!(capabilities.streaming);
+        assert!(capabilities.offline);
+        assert!(!capabilities.requires_api_key);
+        assert_eq!(capabilities.context_window, None);
+
+        let remote = OllamaProcessor::new(Some("https://gpu.example.com:11434".to_string()), None)
+            .with_num_ctx(8192);
+        assert!(!remote.capabilities().offline);
+        assert_eq!(remote.capabilities().context_window, Some(8192));
+    }
+
+    #[test]
+    fn test_chat_body_carries_keep_alive_and_options() {
+        let processor = OllamaProcessor::new(None, Some("qwen2.5:7b".to_string()))
+            .with_keep_alive("-1")
+            .with_num_ctx(4096)
+            .with_option("temperature", serde_json::json!(0.2));
+
+        let body = processor.chat_body(Some("Fix this"));
+        assert_eq!(body["model"], "qwen2.5:7b");
+        assert_eq!(body["stream"], true);
+        assert_eq!(body["keep_alive"], "-1");
+        assert_eq!(body["options"]["num_ctx"], 4096);
+        assert_eq!(body["options"]["temperature"], 0.2);
+        assert_eq!(body["messages"][1]["content"], "Fix this");
+
+        // Warm-up sends no messages, which only loads the model
+        let warm = processor.chat_body(None);
+        assert_eq!(warm["messages"], serde_json::json!([]));
+        assert_eq!(warm["stream"], false);
+        assert_eq!(warm["keep_alive"], "-1");
+    }
+
+    #[tokio::test]
+    async fn test_process_collects_streamed_chunks() {
+        let body = [
+            r#"{"model":"llama3.2","message":{"role":"assistant","content":"Hello"},"done":false}"#,
+            r#"{"model":"llama3.2","message":{"role":"assistant","content":" world"},"done":false}"#,
+            r#"{"model":"llama3.2","message":{"role":"assistant","content":""},"done":true,"prompt_eval_count":12,"eval_count":3,"load_duration":250000000}"#,
+        ]
+        .join("\n");
+        let (processor, requests) = stub_server("200 OK", body).await;
+
+        let output = processor.process(shorten()).await.unwrap();
+        assert_eq!(output.text, "Hello world");
+        let metadata = output.metadata.unwrap();
+        assert_eq!(metadata["eval_count"], 3);
+        assert_eq!(metadata["load_duration_ms"], 250);
+
+        let requests = requests.lock().unwrap();
+        assert!(requests[0].starts_with("POST /api/chat "));
+        assert!(requests[0].contains(r#""keep_alive":"30m""#));
+    }
+
+    #[tokio::test]
+    async fn test_stream_error_and_truncation() {
+        let body = r#"{"error":"out of memory"}"#.to_string();
+        let (processor, _) = stub_server("200 OK", body).await;
+        let err = without_retries(processor)
+            .process(shorten())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("out of memory"));
+
+        let body = r#"{"message":{"content":"Hel"},"done":false}"#.to_string();
+        let (processor, _) = stub_server("200 OK", body).await;
+        let processor = without_retries(processor);
+        let err = processor.process(shorten()).await.unwrap_err();
+        assert_eq!(
+            err.provider_error().map(|e| e.kind),
+            Some(ProviderErrorKind::Network)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_missing_model_suggests_pull() {
+        let body = r#"{"error":"model 'mistral' not found"}"#.to_string();
+        let (processor, _) = stub_server("404 Not Found", body).await;
+        let processor = OllamaProcessor {
+            model: "mistral".to_string(),
+            ..processor
+        };
+        let err = processor.process(shorten()).await.unwrap_err();
+        let detail = err.provider_error().unwrap();
+        assert_eq!(detail.kind, ProviderErrorKind::BadRequest);
+        assert_eq!(detail.status, Some(404));
+        assert!(detail.message.contains("ollama pull mistral"));
+    }
+
+    #[tokio::test]
+    async fn test_list_models() {
+        let body = r#"{"models":[
+            {"name":"qwen2.5:7b","size":4683087332,"details":{"parameter_size":"7.6B","quantization_level":"Q4_K_M"}},
+            {"name":"llama3.2:latest","size":2019393189,"details":{"parameter_size":"3.2B"}}
+        ]}"#
+        .to_string();
+        let (processor, requests) = stub_server("200 OK", body).await;
+
+        let models = processor.installed_models().await.unwrap();
+        assert_eq!(models.len(), 2);
+        assert_eq!(models[0].name, "llama3.2:latest");
+        assert_eq!(models[0].parameter_size.as_deref(), Some("3.2B"));
+        assert_eq!(models[0].quantization_level, None);
+        assert_eq!(models[1].size, 4683087332);
+        assert!(requests.lock().unwrap()[0].starts_with("GET /api/tags "));
+
+        let models = processor.list_models().await.unwrap();
+        assert_eq!(models[0].details.as_deref(), Some("3.2B"));
+        assert_eq!(models[1].id, "qwen2.5:7b");
+        assert_eq!(models[1].details.as_deref(), Some("7.6B Q4_K_M"));
+    }
+
+    #[tokio::test]
+    async fn test_health_check_and_warm_up() {
+        let (processor, requests) =
+            stub_server("200 OK", r#"{"version":"0.6.5"}"#.to_string()).await;
+        assert_eq!(processor.version().await.unwrap(), "0.6.5");
+        assert!(processor.health_check().await.unwrap());
+
+        processor.warm_up().await.unwrap();
+        let requests = requests.lock().unwrap();
+        assert!(requests[0].starts_with("GET /api/version "));
+        assert!(requests[2].starts_with("POST /api/chat "));
+        assert!(requests[2].contains(r#""messages":[]"#));
+    }
+
+    #[tokio::test]
+    async fn test_health_check_when_not_running() {
+        // Nothing listens on the discard port
+        let processor = OllamaProcessor::new(Some("http://127.0.0.1:9".to_string()), None);
+        assert!(!processor.health_check().await.unwrap());
+        let err = processor.installed_models().await.unwrap_err();
+        assert_eq!(
+            err.provider_error().map(|e| e.kind),
+            Some(ProviderErrorKind::Network)
+        );
+    }
+}
@@ -58,16 +58,32 @@ impl PromptName {
         }
     }
 
-    pub fn required_placeholders(self) -> &'static [&'static str] {
+    /// Placeholders a template needs; each entry is met by any one of its
+    /// alternatives
+    pub fn required_placeholders(self) -> &'static [&'static [&'static str]] {
         match self {
-            Self::PostProcess => &["{dictionary_terms}", "{raw_text}"],
-            Self::Shorten => &["{text}"],
-            Self::ChangeTone => &["{tone}", "{text}"],
-            Self::GenerateReply => &["{context}"],
-            Self::Translate => &["{language}", "{text}"],
+            // Overrides written before diarization only have {raw_text}
+            Self::PostProcess => &[
+                &["{dictionary_terms}"],
+                &["{raw_text}", "{speaker_transcript}"],
+            ],
+            Self::Shorten => &[&["{text}"]],
+            Self::ChangeTone => &[&["{tone}"], &["{text}"]],
+            Self::GenerateReply => &[&["{context}"]],
+            Self::Translate => &[&["{language}"], &["{text}"]],
         }
     }
 
+    /// Required placeholders missing from `template`, alternatives joined
+    /// with " or "
+    pub fn missing_placeholders(self, template: &str) -> Vec<String> {
+        self.required_placeholders()
+            .iter()
+            .filter(|alternatives| !alternatives.iter().any(|ph| template.contains(ph)))
+            .map(|alternatives| alternatives.join(" or "))
+            .collect()
+    }
+
     pub fn display_title(self) -> &'static str {
         match self {
             Self::PostProcess => "Post-Process Transcription",
@@ -81,7 +97,7 @@ impl PromptName {
     pub fn description(self) -> &'static str {
         match self {
             Self::PostProcess => {
-                "Cleans raw STT output: removes fillers, fixes grammar, applies the personal dictionary. Runs when no voice command prefix is detected."
+                "Cleans raw STT output: removes fillers, fixes grammar, applies the personal dictionary. Runs when no voice command prefix is detected. Takes the transcription as {raw_text} or, optionally, {speaker_transcript}: the transcription as \"Speaker 1: …\" turns when diarization found several speakers, otherwise the same as {raw_text}."
             }
             Self::Shorten => "Condenses the transcription while preserving meaning.",
             Self::ChangeTone => "Rewrites the transcription in a target tone (formal / casual).",
@@ -182,6 +198,7 @@ impl PromptManager {
             ProcessingTask::PostProcess {
                 text,
                 dictionary_terms,
+                speaker_transcript,
             } => {
                 let dict_terms_str = if dictionary_terms.is_empty() {
                     "No custom terms defined.".to_string()
@@ -190,6 +207,10 @@ impl PromptManager {
                 };
                 set.get(PromptName::PostProcess)
                     .replace("{dictionary_terms}", &dict_terms_str)
+                    .replace(
+                        "{speaker_transcript}",
+                        speaker_transcript.as_deref().unwrap_or(text),
+                    )
                     .replace("{raw_text}", text)
             }
             ProcessingTask::Shorten { text } => {
@@ -234,6 +255,7 @@ mod tests {
         let task = ProcessingTask::PostProcess {
             text: "um so like hello".to_string(),
             dictionary_terms: vec!["API".to_string(), "STT".to_string()],
+            speaker_transcript: None,
         };
 
         let prompt = manager.build_prompt(&task).await;
@@ -241,6 +263,51 @@ mod tests {
         assert!(prompt.contains("API, STT"));
     }
 
+    #[tokio::test]
+    async fn test_build_post_process_prompt_with_speakers() {
+        let manager = PromptManager::new();
+        let task = ProcessingTask::PostProcess {
+            text: "so um are we ready yes".to_string(),
+            dictionary_terms: Vec::new(),
+            speaker_transcript: Some("Speaker 1: so um are we ready\nSpeaker 2: yes".to_string()),
+        };
+
+        let prompt = manager.build_prompt(&task).await;
+        assert!(prompt.contains("Speaker 1: so um are we ready\nSpeaker 2: yes"));
+        assert!(!prompt.contains("{speaker_transcript}"));
+
+        manager.shared().write().await.set_override(
+            PromptName::PostProcess,
+            "{raw_text} | {speaker_transcript}".to_string(),
+        );
+        assert_eq!(
+            manager.build_prompt(&task).await,
+            "so um are we ready yes | Speaker 1: so um are we ready\nSpeaker 2: yes"
+        );
+    }
+
+    #[test]
+    fn test_missing_placeholders() {
+        let name = PromptName::PostProcess;
+        // A legacy override from before {speaker_transcript} existed
+        assert!(name
+            .missing_placeholders("Clean up: {raw_text}\nTerms: {dictionary_terms}")
+            .is_empty());
+        assert_eq!(
+            name.missing_placeholders("Terms: {dictionary_terms}"),
+            vec!["{raw_text} or {speaker_transcript}"]
+        );
+        assert_eq!(
+            PromptName::Translate.missing_placeholders("{text}"),
+            vec!["{language}"]
+        );
+        for name in PromptName::ALL {
+            assert!(name
+                .missing_placeholders(name.default_template())
+                .is_empty());
+        }
+    }
+
     #[tokio::test]
     async fn test_build_shorten_prompt() {
         let manager = PromptManager::new();
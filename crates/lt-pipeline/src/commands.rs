@@ -1,4 +1,7 @@
 use lt_core::llm::ProcessingTask;
+use lt_core::stt::TranscriptMetadata;
+
+use crate::transcript::speaker_labelled_text;
 
 /// Voice command detection result
 #[derive(Debug, Clone, PartialEq)]
@@ -11,6 +14,20 @@ pub struct CommandDetection {
     pub command_name: Option<String>,
 }
 
+impl CommandDetection {
+    /// Give default post-processing the speaker-labelled transcript when the
+    /// provider diarized the session
+    pub fn with_speakers(mut self, metadata: &TranscriptMetadata) -> Self {
+        if let ProcessingTask::PostProcess {
+            speaker_transcript, ..
+        } = &mut self.task
+        {
+            *speaker_transcript = speaker_labelled_text(metadata);
+        }
+        self
+    }
+}
+
 /// Detect voice commands in transcribed text
 ///
 /// Supported commands:
@@ -120,6 +137,7 @@ pub fn detect_command(text: &str, dictionary_terms: Vec<String>) -> CommandDetec
         task: ProcessingTask::PostProcess {
             text: trimmed.to_string(),
             dictionary_terms,
+            speaker_transcript: None,
         },
         content: trimmed.to_string(),
         command_name: None,
@@ -129,6 +147,7 @@ pub fn detect_command(text: &str, dictionary_terms: Vec<String>) -> CommandDetec
 #[cfg(test)]
 mod tests {
     use super::*;
+    use lt_core::stt::SpeakerSegment;
 
     #[test]
     fn test_shorten_this_command() {
@@ -395,6 +414,34 @@ mod tests {
         assert_eq!(result.command_name, None);
     }
 
+    #[test]
+    fn test_speaker_transcript_only_for_post_processing() {
+        let turn = |speaker, text: &str| SpeakerSegment {
+            speaker,
+            text: text.to_string(),
+            start_ms: 0,
+            end_ms: 0,
+        };
+        let metadata = TranscriptMetadata {
+            speakers: vec![turn(0, "Are we done?"), turn(1, "Yes.")],
+            ..Default::default()
+        };
+
+        let result = detect_command("are we done yes", vec![]).with_speakers(&metadata);
+        match result.task {
+            ProcessingTask::PostProcess {
+                speaker_transcript, ..
+            } => assert_eq!(
+                speaker_transcript.as_deref(),
+                Some("Speaker 1: Are we done?\nSpeaker 2: Yes.")
+            ),
+            other => panic!("Expected PostProcess task, got {:?}", other),
+        }
+
+        let result = detect_command("shorten: are we done yes", vec![]).with_speakers(&metadata);
+        assert!(matches!(result.task, ProcessingTask::Shorten { .. }));
+    }
+
     #[test]
     fn test_case_insensitive() {
         let text = "SHORTEN THIS: LOUD TEXT";
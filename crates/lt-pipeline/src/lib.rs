@@ -1,8 +1,13 @@
 pub mod commands;
 pub mod orchestrator;
+pub mod queue;
 pub mod state;
 mod text_normalization;
+mod transcript;
 
 pub use commands::{detect_command, CommandDetection};
 pub use orchestrator::PipelineOrchestrator;
+pub use queue::{
+    OfflineQueue, ProcessedDictation, QueueOutcome, QueuePass, QueueRecording, QueuedDictation,
+};
 pub use state::{PipelineEvent, PipelineState};
@@ -2,21 +2,31 @@ use lt_audio::AudioCapture;
 use lt_core::error::{MurmurError, Result};
 use lt_core::llm::LlmProcessor;
 use lt_core::output::OutputSink;
-use lt_core::stt::{SttProvider, TranscriptionEvent};
+use lt_core::stt::{SttCapabilities, SttMode, SttProvider, TranscriptionEvent};
 use lt_core::PersonalDictionary;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tokio::sync::{broadcast, Mutex, RwLock};
+use tokio::sync::{broadcast, oneshot, Mutex, RwLock};
 use tokio::task::JoinHandle;
 
 use crate::commands::detect_command;
+use crate::queue::{self, OfflineQueue, QueueRecording};
 use crate::state::{PipelineEvent, PipelineState};
 use crate::text_normalization::normalize_final_output;
+use crate::transcript::SessionTranscript;
+
+/// Words below this confidence are reported as worth reviewing
+pub const LOW_CONFIDENCE_THRESHOLD: f32 = 0.6;
 
 /// Pipeline orchestrator coordinating the full flow
 pub struct PipelineOrchestrator {
     audio_capture: Arc<Mutex<Option<AudioCapture>>>,
     stt_provider: Arc<Mutex<Option<Box<dyn SttProvider>>>>,
+    /// Capabilities of the provider used by the current recording
+    stt_capabilities: Arc<Mutex<Option<SttCapabilities>>>,
     llm_processor: Arc<RwLock<Arc<dyn LlmProcessor>>>,
+    /// Where sessions go when a provider is unreachable (None = fail them)
+    offline_queue: Arc<RwLock<Option<Arc<OfflineQueue>>>>,
     output_sink: Arc<dyn OutputSink>,
     dictionary: Arc<Mutex<PersonalDictionary>>,
     state: Arc<Mutex<PipelineState>>,
@@ -39,7 +49,9 @@ impl PipelineOrchestrator {
         Self {
             audio_capture: Arc::new(Mutex::new(None)),
             stt_provider: Arc::new(Mutex::new(None)),
+            stt_capabilities: Arc::new(Mutex::new(None)),
             llm_processor: Arc::new(RwLock::new(llm_processor)),
+            offline_queue: Arc::new(RwLock::new(None)),
             output_sink,
             dictionary,
             state: Arc::new(Mutex::new(PipelineState::Idle)),
@@ -73,6 +85,17 @@ impl PipelineOrchestrator {
         tracing::info!("LLM processor hot-swapped (takes effect on next recording)");
     }
 
+    /// The LLM processor the next recording will use
+    pub async fn llm_processor(&self) -> Arc<dyn LlmProcessor> {
+        self.llm_processor.read().await.clone()
+    }
+
+    /// Queue sessions whose STT or LLM provider is unreachable instead of
+    /// failing them (None = off). Takes effect on the next recording.
+    pub async fn set_offline_queue(&self, queue: Option<Arc<OfflineQueue>>) {
+        *self.offline_queue.write().await = queue;
+    }
+
     /// Start the pipeline with the provided STT provider
     pub async fn start(&self, stt_provider: Box<dyn SttProvider>) -> Result<()> {
         let mut state = self.state.lock().await;
@@ -100,48 +123,101 @@ impl PipelineOrchestrator {
         self.emit_state_change(PipelineState::Recording);
         drop(state);
 
+        let capabilities = stt_provider.capabilities();
+        tracing::info!("STT provider capabilities: {:?}", capabilities);
+        *self.stt_capabilities.lock().await = Some(capabilities);
+
         // Store STT provider
         let mut stt_guard = self.stt_provider.lock().await;
         *stt_guard = Some(stt_provider);
         let mut stt = stt_guard.take().unwrap();
         drop(stt_guard);
 
-        // Start STT session
-        stt.start_session().await.map_err(|e| {
-            tracing::error!("Failed to start STT session: {}", e);
-            e
-        })?;
+        // Start STT session. With the offline queue on, an unreachable
+        // provider gets the session recorded for later instead of failed.
+        let offline_queue = self.offline_queue.read().await.clone();
+        let unreachable = match stt.start_session().await {
+            Ok(()) => None,
+            Err(e) if offline_queue.is_some() && queue::is_unreachable(&e) => {
+                tracing::warn!(
+                    "STT provider unreachable, recording for the offline queue: {}",
+                    e
+                );
+                Some(e.to_string())
+            }
+            Err(e) => {
+                tracing::error!("Failed to start STT session: {}", e);
+                return Err(e);
+            }
+        };
+        let stt_connected = unreachable.is_none();
+
+        // With the queue on, the audio task writes the session to the queue
+        // directory and hands the recording over when it ends; once the
+        // session is headed for the queue, audio is only recorded, not sent
+        let recording = offline_queue.as_ref().and_then(|queue| {
+            queue
+                .record()
+                .inspect_err(|e| tracing::warn!("Cannot record for the offline queue: {}", e))
+                .ok()
+        });
+        let (recording_tx, recording_rx) = oneshot::channel::<QueueRecording>();
+        let stt_abandoned = Arc::new(AtomicBool::new(!stt_connected));
+        let abandon_stt = stt_abandoned.clone();
 
-        // Subscribe to transcription events
-        let mut event_rx = stt.subscribe_events().await;
         let event_tx = self.event_tx.clone();
         // Clone the processor under a read lock so the current recording
         // uses a snapshot; hot-swaps take effect on the next recording.
         let llm_processor = self.llm_processor.read().await.clone();
+        // Let local processors load their model while the user speaks
+        if stt_connected {
+            let processor = llm_processor.clone();
+            tokio::spawn(async move {
+                if let Err(e) = processor.warm_up().await {
+                    tracing::warn!("LLM warm-up failed: {}", e);
+                }
+            });
+        }
         let output_sink = self.output_sink.clone();
         let dictionary = self.dictionary.clone();
         let state_arc = self.state.clone();
 
+        // Subscribe to transcription events
+        let event_rx = if stt_connected {
+            Some(stt.subscribe_events().await)
+        } else {
+            None
+        };
+
         // Spawn transcription event handler
         let transcription_task = tokio::spawn(async move {
-            let mut full_transcription = String::new();
-            let mut last_partial_text = String::new();
+            let Some(mut event_rx) = event_rx else {
+                let queue = offline_queue.expect("queue is on when STT is unreachable");
+                let reason = unreachable.unwrap_or_default();
+                queue_recording(&queue, recording_rx, &reason, &event_tx, &state_arc).await;
+                return;
+            };
+
+            let mut transcript = SessionTranscript::default();
             let mut last_timestamp = 0u64;
+            // Set when the provider became unreachable mid-session
+            let mut queue_reason: Option<String> = None;
 
             while let Some(event) = event_rx.recv().await {
                 match &event {
-                    TranscriptionEvent::Partial { text, timestamp_ms } => {
+                    TranscriptionEvent::Partial {
+                        text,
+                        timestamp_ms,
+                        metadata,
+                    } => {
                         tracing::debug!("Partial transcript: {}", text);
                         let _ = event_tx.send(PipelineEvent::PartialTranscription {
                             text: text.clone(),
                             timestamp_ms: *timestamp_ms,
+                            metadata: metadata.clone(),
                         });
                         last_timestamp = *timestamp_ms;
-
-                        // Track latest partial for fallback (Apple STT only sends partials)
-                        if !text.is_empty() {
-                            last_partial_text = text.clone();
-                        }
+                        transcript.partial(text, metadata);
 
                         // Transition to Transcribing if we have text
                         if !text.is_empty() {
@@ -155,49 +231,66 @@ impl PipelineOrchestrator {
                             }
                         }
                     }
-                    TranscriptionEvent::Committed { text, timestamp_ms } => {
+                    TranscriptionEvent::Committed {
+                        text,
+                        timestamp_ms,
+                        metadata,
+                    } => {
                         tracing::info!("Committed transcript: {}", text);
                         let _ = event_tx.send(PipelineEvent::CommittedTranscription {
                             text: text.clone(),
                             timestamp_ms: *timestamp_ms,
+                            metadata: metadata.clone(),
                         });
 
-                        // Accumulate transcription
-                        if !full_transcription.is_empty() {
-                            full_transcription.push(' ');
-                        }
-                        full_transcription.push_str(text);
+                        transcript.commit(text, metadata);
                         last_timestamp = *timestamp_ms;
-
-                        // Reset partial tracker so it only holds text
-                        // from partials AFTER this commit (the uncommitted tail)
-                        last_partial_text.clear();
                     }
-                    TranscriptionEvent::Error { message } => {
+                    TranscriptionEvent::Error { message, detail } => {
+                        if offline_queue.is_some()
+                            && detail.as_ref().is_some_and(queue::is_unreachable_provider)
+                        {
+                            tracing::warn!(
+                                "STT provider unreachable, queueing the recording: {}",
+                                message
+                            );
+                            queue_reason = Some(message.clone());
+                            break;
+                        }
                         tracing::error!("STT error: {}", message);
                         let _ = event_tx.send(PipelineEvent::Error {
                             message: message.clone(),
                             recoverable: false,
+                            detail: detail.clone(),
                         });
                         break; // Exit loop — let post-processing run or transition to Idle
                     }
                 }
             }
 
+            if let (Some(queue), Some(reason)) = (offline_queue.as_ref(), queue_reason) {
+                // Stop sending audio and stop listening, so the provider
+                // never blocks on events nobody reads; the recording is
+                // queued once the user stops
+                abandon_stt.store(true, Ordering::SeqCst);
+                drop(event_rx);
+                queue_recording(queue, recording_rx, &reason, &event_tx, &state_arc).await;
+                return;
+            }
+            drop(recording_rx);
+
             // Append any uncommitted trailing partial text.
             // Covers two cases:
             // 1. No commits at all (e.g. Apple STT only sent partials) — partial becomes the full text
             // 2. Commits + trailing partials — appends the uncommitted tail after the last commit
-            if !last_partial_text.is_empty() {
+            let (full_transcription, full_metadata) = transcript.finish();
+
+            if !full_metadata.metrics.is_empty() {
                 tracing::info!(
-                    "Appending trailing partial text ({} chars, had_commits={})",
-                    last_partial_text.len(),
-                    !full_transcription.is_empty()
+                    "Session metrics: {} silent chunks skipped, {} hallucinations filtered",
+                    full_metadata.metrics.skipped_silent_chunks,
+                    full_metadata.metrics.filtered_hallucinations
                 );
-                if !full_transcription.is_empty() {
-                    full_transcription.push(' ');
-                }
-                full_transcription.push_str(&last_partial_text);
             }
 
             // When transcription finishes (channel closed), trigger LLM processing
@@ -211,7 +304,8 @@ impl PipelineOrchestrator {
                 };
 
                 // Detect voice commands in the transcription
-                let detection = detect_command(&full_transcription, dictionary_terms);
+                let detection = detect_command(&full_transcription, dictionary_terms)
+                    .with_speakers(&full_metadata);
 
                 // Emit command detection event
                 let _ = event_tx.send(PipelineEvent::CommandDetected {
@@ -243,6 +337,16 @@ impl PipelineOrchestrator {
                 );
                 tracing::debug!("LLM input text: {:?}", &full_transcription);
 
+                let low_confidence = full_metadata.low_confidence_words(LOW_CONFIDENCE_THRESHOLD);
+                if !low_confidence.is_empty() {
+                    tracing::info!(
+                        "{} of {} words below confidence {}",
+                        low_confidence.len(),
+                        full_metadata.words.len(),
+                        LOW_CONFIDENCE_THRESHOLD
+                    );
+                }
+
                 let start_time = std::time::Instant::now();
 
                 match llm_processor.process(task).await {
@@ -261,6 +365,7 @@ impl PipelineOrchestrator {
                             let _ = event_tx.send(PipelineEvent::Error {
                                 message: format!("Output failed: {}", e),
                                 recoverable: true,
+                                detail: None,
                             });
                         }
 
@@ -268,6 +373,8 @@ impl PipelineOrchestrator {
                         let _ = event_tx.send(PipelineEvent::FinalResult {
                             text: final_text,
                             processing_time_ms: start_time.elapsed().as_millis() as u64,
+                            raw_metadata: full_metadata,
+                            llm_processor: output.processor().map(str::to_string),
                         });
 
                         // Transition to Done state
@@ -280,6 +387,23 @@ impl PipelineOrchestrator {
                             timestamp_ms: last_timestamp,
                         });
                     }
+                    Err(e) if offline_queue.is_some() && queue::is_unreachable(&e) => {
+                        tracing::warn!("LLM provider unreachable, queueing the transcript: {}", e);
+                        let queue = offline_queue.as_ref().expect("checked above");
+                        let queued = queue.enqueue_transcript(
+                            &full_transcription,
+                            full_metadata,
+                            &e.to_string(),
+                        );
+                        report_queued(
+                            queued,
+                            &e.to_string(),
+                            &event_tx,
+                            &state_arc,
+                            last_timestamp,
+                        )
+                        .await;
+                    }
                     Err(e) => {
                         tracing::error!("LLM processing failed: {}", e);
                         let fallback_text = normalize_final_output(&full_transcription);
@@ -291,6 +415,7 @@ impl PipelineOrchestrator {
                                 e
                             ),
                             recoverable: true,
+                            detail: e.provider_error().cloned(),
                         });
 
                         // Output raw transcription as fallback
@@ -302,6 +427,8 @@ impl PipelineOrchestrator {
                         let _ = event_tx.send(PipelineEvent::FinalResult {
                             text: fallback_text,
                             processing_time_ms: start_time.elapsed().as_millis() as u64,
+                            raw_metadata: full_metadata,
+                            llm_processor: None,
                         });
 
                         // Transition to Error state
@@ -335,8 +462,9 @@ impl PipelineOrchestrator {
         *self.transcription_task.lock().await = Some(transcription_task);
 
         // Create audio capture
+        // Capture in whatever format the provider wants (16kHz mono by default)
         let mut capture = AudioCapture::new();
-        capture.start().map_err(|e| {
+        capture.start_with_format(stt.input_format()).map_err(|e| {
             tracing::error!("Failed to start audio capture: {}", e);
             MurmurError::Audio(e.to_string())
         })?;
@@ -362,16 +490,32 @@ impl PipelineOrchestrator {
         // Subscribe to audio chunks and forward to STT
         if let Some(mut chunk_rx) = capture.subscribe_chunks() {
             let audio_task = tokio::spawn(async move {
+                let mut recording = recording;
                 while let Some(chunk) = chunk_rx.recv().await {
+                    if let Some(recording) = recording.as_mut() {
+                        recording.push(&chunk);
+                    }
+                    if stt_abandoned.load(Ordering::SeqCst) {
+                        continue;
+                    }
                     if let Err(e) = stt.send_audio(chunk).await {
                         tracing::error!("Failed to send audio to STT: {}", e);
-                        break;
+                        if recording.is_none() {
+                            break;
+                        }
+                        // Keep recording in case the session gets queued
+                        stt_abandoned.store(true, Ordering::SeqCst);
                     }
                 }
                 tracing::debug!("Audio forwarding task finished");
 
                 // Stop STT session when audio ends
-                let _ = stt.stop_session().await;
+                if stt_connected {
+                    let _ = stt.stop_session().await;
+                }
+                if let Some(recording) = recording {
+                    let _ = recording_tx.send(recording);
+                }
             });
 
             *self.audio_task.lock().await = Some(audio_task);
@@ -399,6 +543,23 @@ impl PipelineOrchestrator {
                 .map_err(|e| MurmurError::Audio(e.to_string()))?;
         }
 
+        // Chunked providers upload the tail of the recording only now, so
+        // show that we are waiting on transcription rather than recording.
+        let chunked = self
+            .stt_capabilities
+            .lock()
+            .await
+            .as_ref()
+            .is_some_and(|caps| caps.mode == SttMode::Chunked);
+        if chunked {
+            let mut state = self.state.lock().await;
+            if *state == PipelineState::Recording {
+                *state = PipelineState::Transcribing;
+                drop(state);
+                self.emit_state_change(PipelineState::Transcribing);
+            }
+        }
+
         // Cancel level task (just UI, safe to abort)
         if let Some(task) = self.level_task.lock().await.take() {
             task.abort();
@@ -442,6 +603,69 @@ impl PipelineOrchestrator {
     }
 }
 
+/// Queue the session audio once recording stops
+async fn queue_recording(
+    queue: &OfflineQueue,
+    recording_rx: oneshot::Receiver<QueueRecording>,
+    reason: &str,
+    event_tx: &broadcast::Sender<PipelineEvent>,
+    state_arc: &Mutex<PipelineState>,
+) {
+    let recording = recording_rx.await.ok();
+    let timestamp_ms = recording
+        .as_ref()
+        .map_or(0, QueueRecording::last_timestamp_ms);
+    let Some(recording) = recording.filter(|r| !r.is_empty()) else {
+        tracing::info!("No audio to queue");
+        set_state(state_arc, event_tx, PipelineState::Idle, timestamp_ms).await;
+        return;
+    };
+    let queued = queue.enqueue_recording(recording, reason);
+    report_queued(queued, reason, event_tx, state_arc, timestamp_ms).await;
+}
+
+/// Announce a queued dictation and finish the session
+async fn report_queued(
+    queued: Result<queue::QueuedDictation>,
+    reason: &str,
+    event_tx: &broadcast::Sender<PipelineEvent>,
+    state_arc: &Mutex<PipelineState>,
+    timestamp_ms: u64,
+) {
+    match queued {
+        Ok(item) => {
+            let _ = event_tx.send(PipelineEvent::Queued {
+                id: item.id,
+                reason: reason.to_string(),
+                timestamp_ms,
+            });
+            set_state(state_arc, event_tx, PipelineState::Done, timestamp_ms).await;
+        }
+        Err(e) => {
+            tracing::error!("Failed to queue dictation: {}", e);
+            let _ = event_tx.send(PipelineEvent::Error {
+                message: format!("{}. Saving for later failed: {}", reason, e),
+                recoverable: false,
+                detail: None,
+            });
+            set_state(state_arc, event_tx, PipelineState::Error, timestamp_ms).await;
+        }
+    }
+}
+
+async fn set_state(
+    state_arc: &Mutex<PipelineState>,
+    event_tx: &broadcast::Sender<PipelineEvent>,
+    state: PipelineState,
+    timestamp_ms: u64,
+) {
+    *state_arc.lock().await = state;
+    let _ = event_tx.send(PipelineEvent::StateChanged {
+        state,
+        timestamp_ms,
+    });
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,950 @@
+//! Offline queue for dictations recorded while a provider is unreachable.
+//!
+//! A queued dictation is a JSON record in the queue directory plus, until it
+//! has been transcribed, a WAV file of the session audio, so the queue
+//! survives restarts. [`OfflineQueue::process_pending`] works through the
+//! queue oldest first and stops at the first provider that still cannot be
+//! reached; the caller runs it whenever connectivity may be back.
+
+use lt_audio::resampler::AudioResampler;
+use lt_core::error::{MurmurError, ProviderError, ProviderErrorKind, Result};
+use lt_core::llm::LlmProcessor;
+use lt_core::stt::{AudioChunk, AudioFormat, SttProvider, TranscriptMetadata, TranscriptionEvent};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::commands::detect_command;
+use crate::text_normalization::normalize_final_output;
+use crate::transcript::SessionTranscript;
+
+/// Dictations kept when no limit is configured
+pub const DEFAULT_MAX_ITEMS: usize = 50;
+
+/// Seconds between passes over the queue when no interval is configured
+pub const DEFAULT_RETRY_INTERVAL_SECS: u64 = 60;
+
+/// Length of the chunks queued audio is replayed to the STT provider in
+const REPLAY_CHUNK_MS: u64 = 100;
+
+/// Whether `error` means the provider could not be reached: no connection,
+/// or no response in time
+pub fn is_unreachable(error: &MurmurError) -> bool {
+    error.provider_error().is_some_and(is_unreachable_provider)
+}
+
+pub(crate) fn is_unreachable_provider(error: &ProviderError) -> bool {
+    matches!(
+        error.kind,
+        ProviderErrorKind::Network | ProviderErrorKind::Timeout
+    )
+}
+
+/// A dictation waiting for its providers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueuedDictation {
+    /// Unique id (recording time in epoch ms, suffixed on collision)
+    pub id: String,
+    /// When the dictation was recorded (epoch ms)
+    pub created_ms: u64,
+    /// Length of the recorded audio; 0 when only post-processing is left
+    #[serde(default)]
+    pub duration_ms: u64,
+    /// Why the dictation was queued
+    pub reason: String,
+    /// Raw transcript, once transcription succeeded; only LLM
+    /// post-processing is left then
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transcript: Option<String>,
+    /// Words/language of `transcript`
+    #[serde(default, skip_serializing_if = "TranscriptMetadata::is_empty")]
+    pub metadata: TranscriptMetadata,
+    /// Processing attempts that failed
+    #[serde(default)]
+    pub attempts: u32,
+    /// Error of the last failed attempt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    /// The last attempt failed for another reason than connectivity; only
+    /// a manual pass retries it
+    #[serde(default)]
+    pub held: bool,
+}
+
+/// A queued dictation that has been transcribed and post-processed
+#[derive(Debug, Clone)]
+pub struct ProcessedDictation {
+    /// The queue record, now removed from the queue
+    pub item: QueuedDictation,
+    /// Transcription before LLM processing (empty if nothing was said)
+    pub raw_text: String,
+    /// LLM-processed text (empty if nothing was said)
+    pub final_text: String,
+    /// Voice command used, if any
+    pub command_name: Option<String>,
+    /// Words/language of the raw transcription
+    pub metadata: TranscriptMetadata,
+    /// Transcription and LLM processing time
+    pub processing_time_ms: u64,
+    /// LLM processor that produced `final_text`, when recorded (None = raw
+    /// transcription)
+    pub llm_processor: Option<String>,
+}
+
+/// Which dictations a pass over the queue picks up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueuePass {
+    /// Background retry: skips held dictations
+    Automatic,
+    /// Requested by the user: retries every dictation
+    Manual,
+}
+
+/// What happened to a queued dictation during a pass
+#[derive(Debug)]
+pub enum QueueOutcome {
+    /// Transcribed and post-processed, and removed from the queue
+    Processed(ProcessedDictation),
+    /// Failed for another reason than connectivity; stays queued, held
+    /// from automatic passes
+    Failed {
+        item: QueuedDictation,
+        error: MurmurError,
+    },
+}
+
+/// Dictations persisted in a directory until their providers are reachable
+pub struct OfflineQueue {
+    dir: PathBuf,
+    max_items: AtomicUsize,
+    /// Held during a pass so two passes never process the same dictation
+    pass: tokio::sync::Mutex<()>,
+}
+
+impl OfflineQueue {
+    /// Queue stored in `dir` (created on first use)
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            max_items: AtomicUsize::new(DEFAULT_MAX_ITEMS),
+            pass: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Keep at most `max_items` dictations; the oldest are dropped beyond it
+    pub fn set_max_items(&self, max_items: usize) {
+        self.max_items.store(max_items.max(1), Ordering::Relaxed);
+    }
+
+    /// Queued dictations, oldest first
+    pub fn list(&self) -> Result<Vec<QueuedDictation>> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut items = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            match std::fs::read_to_string(&path)
+                .map_err(MurmurError::from)
+                .and_then(|json| Ok(serde_json::from_str::<QueuedDictation>(&json)?))
+            {
+                Ok(item) => items.push(item),
+                Err(e) => tracing::warn!("Skipping unreadable queue record {:?}: {}", path, e),
+            }
+        }
+        items.sort_by(|a, b| a.created_ms.cmp(&b.created_ms).then(a.id.cmp(&b.id)));
+        Ok(items)
+    }
+
+    /// Queue the audio of a session that could not be transcribed
+    pub fn enqueue_audio(&self, chunks: &[AudioChunk], reason: &str) -> Result<QueuedDictation> {
+        let mut recording = self.record()?;
+        for chunk in chunks {
+            recording.push(chunk);
+        }
+        self.enqueue_recording(recording, reason)
+    }
+
+    /// Start writing session audio to the queue directory, so it can be
+    /// queued if the session fails without being held in memory
+    pub fn record(&self) -> Result<QueueRecording> {
+        let item = self.new_item("")?;
+        Ok(QueueRecording {
+            path: self.partial_audio_path(&item.id),
+            item,
+            writer: None,
+            samples: 0,
+            last_timestamp_ms: 0,
+            failed: None,
+        })
+    }
+
+    /// Queue a recording made with [`OfflineQueue::record`]
+    pub fn enqueue_recording(
+        &self,
+        mut recording: QueueRecording,
+        reason: &str,
+    ) -> Result<QueuedDictation> {
+        let format = recording.format();
+        let writer = recording.writer.take();
+        if let Some(e) = recording.failed.take() {
+            return Err(MurmurError::Audio(format!(
+                "Failed to write queued audio: {}",
+                e
+            )));
+        }
+        let (Some(writer), Some(format)) = (writer, format) else {
+            return Err(MurmurError::Audio("No audio to queue".to_string()));
+        };
+        writer
+            .finalize()
+            .map_err(|e| MurmurError::Audio(format!("Failed to write queued audio: {}", e)))?;
+
+        let mut item = recording.item.clone();
+        item.reason = reason.to_string();
+        item.duration_ms = format.duration_ms(recording.samples as usize);
+        std::fs::rename(&recording.path, self.audio_path(&item.id))?;
+        self.save(&item)?;
+        self.prune();
+        tracing::info!(
+            "Queued {} ms of audio as dictation {}",
+            item.duration_ms,
+            item.id
+        );
+        Ok(item)
+    }
+
+    /// Queue a transcript whose LLM post-processing could not run
+    pub fn enqueue_transcript(
+        &self,
+        transcript: &str,
+        metadata: TranscriptMetadata,
+        reason: &str,
+    ) -> Result<QueuedDictation> {
+        let mut item = self.new_item(reason)?;
+        item.transcript = Some(transcript.to_string());
+        item.metadata = metadata;
+        self.save(&item)?;
+        self.prune();
+        tracing::info!("Queued transcript as dictation {}", item.id);
+        Ok(item)
+    }
+
+    /// Remove a dictation and its audio. Returns false if it was not queued.
+    pub fn remove(&self, id: &str) -> Result<bool> {
+        if !is_valid_id(id) {
+            return Ok(false);
+        }
+        let removed = match std::fs::remove_file(self.record_path(id)) {
+            Ok(()) => true,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => false,
+            Err(e) => return Err(e.into()),
+        };
+        match std::fs::remove_file(self.audio_path(id)) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        Ok(removed)
+    }
+
+    /// Transcribe and post-process queued dictations, oldest first.
+    ///
+    /// `make_stt` creates a fresh STT provider per dictation. The pass stops
+    /// at the first provider that is still unreachable; dictations failing
+    /// for other reasons are reported once and kept, held until a manual
+    /// pass or deletion. Returns no outcomes when another pass is already
+    /// running.
+    pub async fn process_pending<F>(
+        &self,
+        pass: QueuePass,
+        make_stt: F,
+        llm: &dyn LlmProcessor,
+        dictionary_terms: &[String],
+    ) -> Result<Vec<QueueOutcome>>
+    where
+        F: Fn() -> Result<Box<dyn SttProvider>>,
+    {
+        let Ok(_pass) = self.pass.try_lock() else {
+            tracing::debug!("Offline queue pass already running");
+            return Ok(Vec::new());
+        };
+
+        let mut outcomes = Vec::new();
+        for mut item in self.list()? {
+            if item.held && pass == QueuePass::Automatic {
+                continue;
+            }
+            match self
+                .process(&mut item, &make_stt, llm, dictionary_terms)
+                .await
+            {
+                Ok(processed) => {
+                    self.remove(&item.id)?;
+                    tracing::info!("Processed queued dictation {}", item.id);
+                    outcomes.push(QueueOutcome::Processed(processed));
+                }
+                Err(error) => {
+                    item.attempts += 1;
+                    item.last_error = Some(error.to_string());
+                    item.held = !is_unreachable(&error);
+                    // Unless it was deleted while the attempt ran
+                    if self.record_path(&item.id).exists() {
+                        self.save(&item)?;
+                    }
+                    if is_unreachable(&error) {
+                        tracing::info!("Providers still unreachable: {}", error);
+                        break;
+                    }
+                    tracing::warn!("Queued dictation {} failed: {}", item.id, error);
+                    outcomes.push(QueueOutcome::Failed { item, error });
+                }
+            }
+        }
+        Ok(outcomes)
+    }
+
+    async fn process<F>(
+        &self,
+        item: &mut QueuedDictation,
+        make_stt: &F,
+        llm: &dyn LlmProcessor,
+        dictionary_terms: &[String],
+    ) -> Result<ProcessedDictation>
+    where
+        F: Fn() -> Result<Box<dyn SttProvider>>,
+    {
+        let start_time = Instant::now();
+
+        let (raw_text, metadata) = match &item.transcript {
+            Some(transcript) => (transcript.clone(), item.metadata.clone()),
+            None => {
+                let stt = make_stt()?;
+                let chunks = self.load_audio(&item.id, stt.input_format())?;
+                let (text, metadata) = transcribe(stt, chunks).await?;
+                // Kept so an LLM outage doesn't cost another transcription
+                item.transcript = Some(text.clone());
+                item.metadata = metadata.clone();
+                self.save(item)?;
+                (text, metadata)
+            }
+        };
+
+        let mut processed = ProcessedDictation {
+            item: item.clone(),
+            raw_text: raw_text.clone(),
+            final_text: String::new(),
+            command_name: None,
+            metadata,
+            processing_time_ms: 0,
+            llm_processor: None,
+        };
+        if raw_text.trim().is_empty() {
+            return Ok(processed);
+        }
+
+        let detection =
+            detect_command(&raw_text, dictionary_terms.to_vec()).with_speakers(&processed.metadata);
+        processed.command_name = detection.command_name;
+        processed.final_text = match llm.process(detection.task).await {
+            Ok(output) => {
+                processed.llm_processor = output.processor().map(str::to_string);
+                normalize_final_output(&output.text)
+            }
+            Err(e) if is_unreachable(&e) => return Err(e),
+            Err(e) => {
+                tracing::warn!(
+                    "LLM processing of queued dictation {} failed: {}. Using raw transcription.",
+                    item.id,
+                    e
+                );
+                normalize_final_output(&raw_text)
+            }
+        };
+        processed.processing_time_ms = start_time.elapsed().as_millis() as u64;
+        Ok(processed)
+    }
+
+    fn new_item(&self, reason: &str) -> Result<QueuedDictation> {
+        std::fs::create_dir_all(&self.dir)?;
+        let created_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        let mut id = created_ms.to_string();
+        let mut suffix = 1;
+        while self.record_path(&id).exists()
+            || self.audio_path(&id).exists()
+            || self.partial_audio_path(&id).exists()
+        {
+            id = format!("{}-{}", created_ms, suffix);
+            suffix += 1;
+        }
+        Ok(QueuedDictation {
+            id,
+            created_ms,
+            duration_ms: 0,
+            reason: reason.to_string(),
+            transcript: None,
+            metadata: TranscriptMetadata::default(),
+            attempts: 0,
+            last_error: None,
+            held: false,
+        })
+    }
+
+    /// Write the record through a temporary file so a crash never leaves a
+    /// half-written one behind
+    fn save(&self, item: &QueuedDictation) -> Result<()> {
+        let path = self.record_path(&item.id);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(item)?)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    /// Drop the oldest dictations beyond the limit
+    fn prune(&self) {
+        let max_items = self.max_items.load(Ordering::Relaxed);
+        let Ok(items) = self.list() else {
+            return;
+        };
+        for item in items.iter().take(items.len().saturating_sub(max_items)) {
+            tracing::warn!(
+                "Offline queue holds more than {} dictations, dropping {}",
+                max_items,
+                item.id
+            );
+            if let Err(e) = self.remove(&item.id) {
+                tracing::warn!("Failed to drop queued dictation {}: {}", item.id, e);
+            }
+        }
+    }
+
+    /// Queued audio converted to `format`, in replay-sized chunks
+    fn load_audio(&self, id: &str, format: AudioFormat) -> Result<Vec<AudioChunk>> {
+        let path = self.audio_path(id);
+        let mut reader = hound::WavReader::open(&path).map_err(|e| {
+            MurmurError::Audio(format!("Failed to read queued audio {:?}: {}", path, e))
+        })?;
+        let spec = reader.spec();
+        let samples = reader
+            .samples::<i16>()
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| {
+                MurmurError::Audio(format!("Failed to read queued audio {:?}: {}", path, e))
+            })?;
+
+        let recorded = AudioFormat {
+            sample_rate: spec.sample_rate,
+            channels: spec.channels,
+        };
+        let samples = if recorded == format {
+            samples
+        } else {
+            AudioResampler::new(
+                recorded.sample_rate,
+                format.sample_rate,
+                recorded.channels as usize,
+            )
+            .and_then(|r| r.with_output_channels(format.channels as usize))
+            .and_then(|mut r| r.resample(&samples))
+            .map_err(|e| MurmurError::Audio(e.to_string()))?
+        };
+
+        let chunk_len = format.samples_for_ms(REPLAY_CHUNK_MS).max(1);
+        Ok(samples
+            .chunks(chunk_len)
+            .enumerate()
+            .map(|(i, data)| AudioChunk {
+                data: data.to_vec(),
+                timestamp_ms: (i as u64 + 1) * REPLAY_CHUNK_MS,
+                sample_rate: format.sample_rate,
+                channels: format.channels,
+            })
+            .collect())
+    }
+
+    fn record_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+
+    fn audio_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.wav", id))
+    }
+
+    /// Audio of a session still being recorded
+    fn partial_audio_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.wav.part", id))
+    }
+}
+
+/// Session audio written to the queue directory as it is captured. Nothing
+/// is queued until it is passed to [`OfflineQueue::enqueue_recording`];
+/// dropping it deletes the file.
+pub struct QueueRecording {
+    path: PathBuf,
+    item: QueuedDictation,
+    writer: Option<hound::WavWriter<BufWriter<File>>>,
+    samples: u64,
+    last_timestamp_ms: u64,
+    /// First write error; the rest of the session is not written
+    failed: Option<String>,
+}
+
+impl QueueRecording {
+    /// Append a chunk. The first chunk sets the file's format.
+    pub fn push(&mut self, chunk: &AudioChunk) {
+        if self.failed.is_some() {
+            return;
+        }
+        self.last_timestamp_ms = chunk.timestamp_ms;
+        if let Err(e) = self.write(chunk) {
+            tracing::warn!("Failed to record audio for the offline queue: {}", e);
+            self.failed = Some(e.to_string());
+        }
+    }
+
+    fn write(&mut self, chunk: &AudioChunk) -> std::result::Result<(), hound::Error> {
+        let writer = match &mut self.writer {
+            Some(writer) => writer,
+            None => {
+                let spec = hound::WavSpec {
+                    channels: chunk.channels,
+                    sample_rate: chunk.sample_rate,
+                    bits_per_sample: 16,
+                    sample_format: hound::SampleFormat::Int,
+                };
+                self.writer
+                    .insert(hound::WavWriter::create(&self.path, spec)?)
+            }
+        };
+        for &sample in &chunk.data {
+            writer.write_sample(sample)?;
+        }
+        self.samples += chunk.data.len() as u64;
+        Ok(())
+    }
+
+    /// Whether no audio was recorded
+    pub fn is_empty(&self) -> bool {
+        self.samples == 0
+    }
+
+    /// Timestamp of the last chunk recorded
+    pub fn last_timestamp_ms(&self) -> u64 {
+        self.last_timestamp_ms
+    }
+
+    fn format(&self) -> Option<AudioFormat> {
+        self.writer.as_ref().map(|writer| AudioFormat {
+            sample_rate: writer.spec().sample_rate,
+            channels: writer.spec().channels,
+        })
+    }
+}
+
+impl Drop for QueueRecording {
+    fn drop(&mut self) {
+        // Close the file first; after a successful enqueue it is gone
+        drop(self.writer.take());
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Ids come from the frontend when deleting; never let one escape the dir
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+/// Run a whole recording through `stt` and return the transcript
+async fn transcribe(
+    mut stt: Box<dyn SttProvider>,
+    chunks: Vec<AudioChunk>,
+) -> Result<(String, TranscriptMetadata)> {
+    stt.start_session().await?;
+    let mut event_rx = stt.subscribe_events().await;
+
+    // Read events while sending so the provider never blocks on a full channel
+    let collector = tokio::spawn(async move {
+        let mut transcript = SessionTranscript::default();
+        while let Some(event) = event_rx.recv().await {
+            match event {
+                TranscriptionEvent::Partial { text, metadata, .. } => {
+                    transcript.partial(&text, &metadata)
+                }
+                TranscriptionEvent::Committed { text, metadata, .. } => {
+                    transcript.commit(&text, &metadata)
+                }
+                TranscriptionEvent::Error { message, detail } => {
+                    return Err(match detail {
+                        Some(detail) => MurmurError::Provider(detail),
+                        None => MurmurError::Stt(message),
+                    });
+                }
+            }
+        }
+        Ok(transcript.finish())
+    });
+
+    let mut sent = Ok(());
+    for chunk in chunks {
+        if let Err(e) = stt.send_audio(chunk).await {
+            sent = Err(e);
+            break;
+        }
+    }
+    let _ = stt.stop_session().await;
+
+    // A provider error event explains a failed send better than the send error
+    let transcript = collector
+        .await
+        .map_err(|e| MurmurError::Stt(format!("Transcript task failed: {}", e)))??;
+    sent?;
+    Ok(transcript)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use lt_core::llm::{ProcessingOutput, ProcessingTask};
+    use std::sync::atomic::AtomicBool;
+    use std::sync::{Arc, Mutex};
+    use tokio::sync::mpsc;
+
+    fn queue_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("murmur_test_queue_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn recording(ms: u64) -> Vec<AudioChunk> {
+        (0..ms / 100)
+            .map(|i| AudioChunk {
+                data: vec![(i % 100) as i16; 1600],
+                timestamp_ms: (i + 1) * 100,
+                sample_rate: 16000,
+                channels: 1,
+            })
+            .collect()
+    }
+
+    fn network_error(provider: &str) -> MurmurError {
+        ProviderError::new(ProviderErrorKind::Network, provider, "Connection refused").into()
+    }
+
+    /// STT provider that commits "queued words" once the session stops, after
+    /// counting the samples it was sent
+    struct ScriptedStt {
+        online: bool,
+        samples: Arc<Mutex<usize>>,
+        event_tx: Option<mpsc::Sender<TranscriptionEvent>>,
+        event_rx: Mutex<Option<mpsc::Receiver<TranscriptionEvent>>>,
+    }
+
+    impl ScriptedStt {
+        fn boxed(online: bool, samples: Arc<Mutex<usize>>) -> Box<dyn SttProvider> {
+            Box::new(Self {
+                online,
+                samples,
+                event_tx: None,
+                event_rx: Mutex::new(None),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl SttProvider for ScriptedStt {
+        async fn start_session(&mut self) -> Result<()> {
+            if !self.online {
+                return Err(network_error("scripted"));
+            }
+            let (tx, rx) = mpsc::channel(4);
+            self.event_tx = Some(tx);
+            *self.event_rx.lock().unwrap() = Some(rx);
+            Ok(())
+        }
+
+        async fn send_audio(&mut self, chunk: AudioChunk) -> Result<()> {
+            *self.samples.lock().unwrap() += chunk.data.len();
+            Ok(())
+        }
+
+        async fn stop_session(&mut self) -> Result<()> {
+            if let Some(tx) = self.event_tx.take() {
+                let _ = tx
+                    .send(TranscriptionEvent::Committed {
+                        text: "queued words".to_string(),
+                        timestamp_ms: 0,
+                        metadata: TranscriptMetadata::default(),
+                    })
+                    .await;
+            }
+            Ok(())
+        }
+
+        async fn subscribe_events(&self) -> mpsc::Receiver<TranscriptionEvent> {
+            self.event_rx.lock().unwrap().take().unwrap()
+        }
+    }
+
+    /// LLM that upper-cases its input, or fails while offline
+    struct ScriptedLlm {
+        online: AtomicBool,
+    }
+
+    #[async_trait]
+    impl LlmProcessor for ScriptedLlm {
+        async fn process(&self, task: ProcessingTask) -> Result<ProcessingOutput> {
+            if !self.online.load(Ordering::SeqCst) {
+                return Err(network_error("claude_api"));
+            }
+            match task {
+                ProcessingTask::PostProcess { text, .. } => Ok(ProcessingOutput {
+                    text: text.to_uppercase(),
+                    processing_time_ms: 1,
+                    metadata: None,
+                }),
+                _ => unimplemented!(),
+            }
+        }
+
+        async fn health_check(&self) -> Result<bool> {
+            Ok(true)
+        }
+    }
+
+    #[test]
+    fn test_unreachable_errors() {
+        assert!(is_unreachable(&network_error("groq")));
+        assert!(is_unreachable(
+            &ProviderError::new(ProviderErrorKind::Timeout, "groq", "timed out").into()
+        ));
+        assert!(!is_unreachable(
+            &ProviderError::new(ProviderErrorKind::Auth, "groq", "bad key").into()
+        ));
+        assert!(!is_unreachable(&MurmurError::Stt("failed".to_string())));
+    }
+
+    #[test]
+    fn test_queue_survives_restart() {
+        let dir = queue_dir("restart");
+        let queue = OfflineQueue::new(&dir);
+        let first = queue.enqueue_audio(&recording(1000), "offline").unwrap();
+        let second = queue
+            .enqueue_transcript("raw words", TranscriptMetadata::default(), "llm offline")
+            .unwrap();
+        assert_eq!(first.duration_ms, 1000);
+        assert_ne!(first.id, second.id);
+
+        let reopened = OfflineQueue::new(&dir);
+        let items = reopened.list().unwrap();
+        assert_eq!(items, vec![first.clone(), second.clone()]);
+
+        let audio = reopened
+            .load_audio(&first.id, AudioFormat::mono(16000))
+            .unwrap();
+        assert_eq!(audio.iter().map(|c| c.data.len()).sum::<usize>(), 16000);
+
+        assert!(reopened.remove(&first.id).unwrap());
+        assert!(!reopened.remove(&first.id).unwrap());
+        assert!(!dir.join(format!("{}.wav", first.id)).exists());
+        assert!(!reopened.remove("../history").unwrap());
+        assert_eq!(reopened.list().unwrap(), vec![second]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_recording_streams_to_disk_until_queued_or_dropped() {
+        let dir = queue_dir("record");
+        let queue = OfflineQueue::new(&dir);
+        let files = || std::fs::read_dir(&dir).unwrap().count();
+
+        let mut session = queue.record().unwrap();
+        assert!(session.is_empty());
+        for chunk in recording(300) {
+            session.push(&chunk);
+        }
+        assert!(!session.is_empty());
+        assert_eq!(session.last_timestamp_ms(), 300);
+        assert_eq!(files(), 1);
+        assert!(queue.list().unwrap().is_empty());
+        drop(session);
+        assert_eq!(files(), 0);
+
+        let mut session = queue.record().unwrap();
+        for chunk in recording(500) {
+            session.push(&chunk);
+        }
+        let item = queue.enqueue_recording(session, "offline").unwrap();
+        assert_eq!(item.duration_ms, 500);
+        assert_eq!(item.reason, "offline");
+        assert_eq!(queue.list().unwrap(), vec![item.clone()]);
+        let audio = queue
+            .load_audio(&item.id, AudioFormat::mono(16000))
+            .unwrap();
+        assert_eq!(audio.iter().map(|c| c.data.len()).sum::<usize>(), 8000);
+        assert_eq!(files(), 2);
+
+        let err = queue
+            .enqueue_recording(queue.record().unwrap(), "offline")
+            .unwrap_err();
+        assert!(err.to_string().contains("No audio to queue"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_oldest_dropped_beyond_max_items() {
+        let dir = queue_dir("prune");
+        let queue = OfflineQueue::new(&dir);
+        queue.set_max_items(2);
+        let ids: Vec<String> = (0..3)
+            .map(|i| {
+                queue
+                    .enqueue_transcript(&format!("text {}", i), TranscriptMetadata::default(), "")
+                    .unwrap()
+                    .id
+            })
+            .collect();
+
+        let kept: Vec<String> = queue.list().unwrap().into_iter().map(|i| i.id).collect();
+        assert_eq!(kept, ids[1..]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_audio_resampled_for_provider_format() {
+        let dir = queue_dir("resample");
+        let queue = OfflineQueue::new(&dir);
+        let item = queue.enqueue_audio(&recording(500), "offline").unwrap();
+
+        let audio = queue
+            .load_audio(&item.id, AudioFormat::mono(24000))
+            .unwrap();
+        let samples: usize = audio.iter().map(|c| c.data.len()).sum();
+        assert_eq!(samples, 12000);
+        assert!(audio.iter().all(|c| c.sample_rate == 24000));
+        assert_eq!(audio[0].data.len(), 2400);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_pass_stops_while_offline_and_completes_later() {
+        let dir = queue_dir("pass");
+        let queue = OfflineQueue::new(&dir);
+        let audio_item = queue.enqueue_audio(&recording(1000), "offline").unwrap();
+        let llm = ScriptedLlm {
+            online: AtomicBool::new(false),
+        };
+        let samples = Arc::new(Mutex::new(0));
+
+        // STT offline: nothing processed, the attempt is recorded
+        let offline = || Ok(ScriptedStt::boxed(false, samples.clone()));
+        let outcomes = queue
+            .process_pending(QueuePass::Automatic, offline, &llm, &[])
+            .await
+            .unwrap();
+        assert!(outcomes.is_empty());
+        let items = queue.list().unwrap();
+        assert_eq!(items[0].attempts, 1);
+        assert!(items[0].last_error.is_some());
+
+        // STT back, LLM still offline: the transcript is kept
+        let online = || Ok(ScriptedStt::boxed(true, samples.clone()));
+        let outcomes = queue
+            .process_pending(QueuePass::Automatic, online, &llm, &[])
+            .await
+            .unwrap();
+        assert!(outcomes.is_empty());
+        assert_eq!(*samples.lock().unwrap(), 16000);
+        let items = queue.list().unwrap();
+        assert_eq!(items[0].transcript.as_deref(), Some("queued words"));
+
+        // Both back: processed without transcribing again
+        llm.online.store(true, Ordering::SeqCst);
+        let outcomes = queue
+            .process_pending(QueuePass::Automatic, online, &llm, &[])
+            .await
+            .unwrap();
+        assert_eq!(*samples.lock().unwrap(), 16000);
+        assert_eq!(outcomes.len(), 1);
+        match &outcomes[0] {
+            QueueOutcome::Processed(processed) => {
+                assert_eq!(processed.item.id, audio_item.id);
+                assert_eq!(processed.raw_text, "queued words");
+                assert_eq!(processed.final_text, "QUEUED WORDS");
+            }
+            other => panic!("unexpected outcome: {:?}", other),
+        }
+        assert!(queue.list().unwrap().is_empty());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_other_failures_are_reported_once_and_held() {
+        let dir = queue_dir("failed");
+        let queue = OfflineQueue::new(&dir);
+        let broken = queue.enqueue_audio(&recording(300), "offline").unwrap();
+        let ready = queue
+            .enqueue_transcript("later words", TranscriptMetadata::default(), "llm offline")
+            .unwrap();
+        let llm = ScriptedLlm {
+            online: AtomicBool::new(true),
+        };
+
+        let make_stt = || Err(MurmurError::Config("Missing API key".to_string()));
+        let outcomes = queue
+            .process_pending(QueuePass::Automatic, make_stt, &llm, &[])
+            .await
+            .unwrap();
+
+        assert_eq!(outcomes.len(), 2);
+        assert!(matches!(&outcomes[0], QueueOutcome::Failed { item, .. } if item.id == broken.id));
+        assert!(
+            matches!(&outcomes[1], QueueOutcome::Processed(p) if p.item.id == ready.id && p.final_text == "LATER WORDS")
+        );
+        let items = queue.list().unwrap();
+        assert_eq!(items.len(), 1);
+        assert!(items[0].held);
+
+        // Held: the next automatic pass neither retries nor reports it
+        let calls = AtomicUsize::new(0);
+        let counted = || {
+            calls.fetch_add(1, Ordering::SeqCst);
+            make_stt()
+        };
+        let outcomes = queue
+            .process_pending(QueuePass::Automatic, &counted, &llm, &[])
+            .await
+            .unwrap();
+        assert!(outcomes.is_empty());
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+        assert_eq!(queue.list().unwrap()[0].attempts, 1);
+
+        // A manual pass retries it
+        let outcomes = queue
+            .process_pending(QueuePass::Manual, &counted, &llm, &[])
+            .await
+            .unwrap();
+        assert!(matches!(&outcomes[..], [QueueOutcome::Failed { item, .. }] if item.attempts == 2));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
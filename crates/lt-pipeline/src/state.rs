@@ -1,3 +1,5 @@
+use lt_core::error::ProviderError;
+use lt_core::stt::TranscriptMetadata;
 use serde::{Deserialize, Serialize};
 
 /// Pipeline state machine
@@ -35,9 +37,19 @@ pub enum PipelineEvent {
         timestamp_ms: u64,
     },
     /// Partial transcription
-    PartialTranscription { text: String, timestamp_ms: u64 },
+    PartialTranscription {
+        text: String,
+        timestamp_ms: u64,
+        #[serde(default, skip_serializing_if = "TranscriptMetadata::is_empty")]
+        metadata: TranscriptMetadata,
+    },
     /// Committed transcription
-    CommittedTranscription { text: String, timestamp_ms: u64 },
+    CommittedTranscription {
+        text: String,
+        timestamp_ms: u64,
+        #[serde(default, skip_serializing_if = "TranscriptMetadata::is_empty")]
+        metadata: TranscriptMetadata,
+    },
     /// Command detected in transcription
     CommandDetected {
         command_name: Option<String>,
@@ -47,9 +59,31 @@ pub enum PipelineEvent {
     FinalResult {
         text: String,
         processing_time_ms: u64,
+        /// Words/language of the raw transcription the result was produced from
+        #[serde(default, skip_serializing_if = "TranscriptMetadata::is_empty")]
+        raw_metadata: TranscriptMetadata,
+        /// LLM processor that produced `text` (None = raw transcription, or
+        /// not recorded)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        llm_processor: Option<String>,
+    },
+    /// A provider was unreachable; the dictation was saved to the offline
+    /// queue and will be processed when connectivity returns
+    Queued {
+        /// Id of the queued dictation
+        id: String,
+        /// Why it was queued
+        reason: String,
+        timestamp_ms: u64,
     },
     /// Error occurred
-    Error { message: String, recoverable: bool },
+    Error {
+        message: String,
+        recoverable: bool,
+        /// Structured provider error (auth, rate limit, timeout, ...), if known
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        detail: Option<ProviderError>,
+    },
 }
 
 #[cfg(test)]
@@ -99,4 +133,65 @@ mod tests {
             _ => panic!("Unexpected event type"),
         }
     }
+
+    #[test]
+    fn test_final_result_carries_raw_metadata() {
+        use lt_core::stt::TranscriptWord;
+
+        let event = PipelineEvent::FinalResult {
+            text: "Hello.".to_string(),
+            processing_time_ms: 12,
+            raw_metadata: TranscriptMetadata {
+                words: vec![TranscriptWord {
+                    text: "hello".to_string(),
+                    start_ms: 0,
+                    end_ms: 300,
+                    confidence: Some(0.3),
+                }],
+                language: Some("en".to_string()),
+                ..Default::default()
+            },
+            llm_processor: Some("claude_api".to_string()),
+        };
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["raw_metadata"]["words"][0]["text"], "hello");
+        assert_eq!(json["raw_metadata"]["language"], "en");
+        assert_eq!(json["llm_processor"], "claude_api");
+
+        let plain = serde_json::to_value(PipelineEvent::FinalResult {
+            text: "x".to_string(),
+            processing_time_ms: 0,
+            raw_metadata: TranscriptMetadata::default(),
+            llm_processor: None,
+        })
+        .unwrap();
+        assert!(plain.get("raw_metadata").is_none());
+        assert!(plain.get("llm_processor").is_none());
+    }
+
+    #[test]
+    fn test_error_event_exposes_provider_detail() {
+        use lt_core::error::ProviderErrorKind;
+
+        let event = PipelineEvent::Error {
+            message: "Authentication failed".to_string(),
+            recoverable: false,
+            detail: Some(ProviderError::from_http_status("elevenlabs", 401, None, "")),
+        };
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "error");
+        assert_eq!(json["detail"]["kind"]["type"], "auth");
+        assert_eq!(json["detail"]["provider"], "elevenlabs");
+        assert_eq!(json["detail"]["status"], 401);
+
+        let deserialized: PipelineEvent = serde_json::from_value(json).unwrap();
+        match deserialized {
+            PipelineEvent::Error { detail, .. } => {
+                assert_eq!(detail.unwrap().kind, ProviderErrorKind::Auth);
+            }
+            _ => panic!("Unexpected event type"),
+        }
+    }
 }
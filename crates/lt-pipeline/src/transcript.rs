@@ -0,0 +1,134 @@
+use lt_core::stt::TranscriptMetadata;
+
+/// Transcript of one session assembled from provider events: the commits in
+/// order, followed by the latest partial that has not been committed yet
+#[derive(Debug, Default)]
+pub(crate) struct SessionTranscript {
+    text: String,
+    metadata: TranscriptMetadata,
+    /// Latest non-empty partial after the last commit (the uncommitted tail)
+    tail_text: String,
+    tail_metadata: TranscriptMetadata,
+}
+
+impl SessionTranscript {
+    /// Track a partial; providers that never commit (e.g. Apple STT) are
+    /// transcribed from their last partial
+    pub fn partial(&mut self, text: &str, metadata: &TranscriptMetadata) {
+        if !text.is_empty() {
+            self.tail_text = text.to_string();
+            self.tail_metadata = metadata.clone();
+        }
+    }
+
+    /// Append a commit; partials before it are covered by it
+    pub fn commit(&mut self, text: &str, metadata: &TranscriptMetadata) {
+        if !self.text.is_empty() {
+            self.text.push(' ');
+        }
+        self.text.push_str(text);
+        self.metadata.append(metadata);
+        self.tail_text.clear();
+        self.tail_metadata = TranscriptMetadata::default();
+    }
+
+    /// The full transcript, with the uncommitted tail appended
+    pub fn finish(mut self) -> (String, TranscriptMetadata) {
+        if !self.tail_text.is_empty() {
+            tracing::info!(
+                "Appending trailing partial text ({} chars, had_commits={})",
+                self.tail_text.len(),
+                !self.text.is_empty()
+            );
+            if !self.text.is_empty() {
+                self.text.push(' ');
+            }
+            self.text.push_str(&self.tail_text);
+            self.metadata.append(&self.tail_metadata);
+        }
+        (self.text, self.metadata)
+    }
+}
+
+/// The diarized turns as "Speaker 1: …" lines, numbering speakers from 1.
+/// None unless more than one speaker was found.
+pub(crate) fn speaker_labelled_text(metadata: &TranscriptMetadata) -> Option<String> {
+    if metadata.speaker_count() < 2 {
+        return None;
+    }
+    let lines: Vec<String> = metadata
+        .speakers
+        .iter()
+        .filter(|turn| !turn.text.trim().is_empty())
+        .map(|turn| format!("Speaker {}: {}", turn.speaker + 1, turn.text.trim()))
+        .collect();
+    Some(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lt_core::stt::SpeakerSegment;
+
+    #[test]
+    fn test_commits_then_trailing_partial() {
+        let meta = TranscriptMetadata::default();
+        let mut transcript = SessionTranscript::default();
+        transcript.partial("hel", &meta);
+        transcript.commit("hello", &meta);
+        transcript.partial("wor", &meta);
+        transcript.commit("world", &meta);
+        transcript.partial("again", &meta);
+        transcript.partial("", &meta);
+
+        assert_eq!(transcript.finish().0, "hello world again");
+    }
+
+    #[test]
+    fn test_partials_only() {
+        let meta = TranscriptMetadata::default();
+        let mut transcript = SessionTranscript::default();
+        transcript.partial("just", &meta);
+        transcript.partial("just partials", &meta);
+
+        assert_eq!(transcript.finish().0, "just partials");
+    }
+
+    #[test]
+    fn test_speaker_labelled_text() {
+        let turn = |speaker, text: &str| SpeakerSegment {
+            speaker,
+            text: text.to_string(),
+            start_ms: 0,
+            end_ms: 0,
+        };
+        let mut transcript = SessionTranscript::default();
+        transcript.commit(
+            "Shall we start? Yes.",
+            &TranscriptMetadata {
+                speakers: vec![turn(0, "Shall we start?"), turn(1, "Yes.")],
+                ..Default::default()
+            },
+        );
+        transcript.commit(
+            "Good.",
+            &TranscriptMetadata {
+                speakers: vec![turn(0, "Good.")],
+                ..Default::default()
+            },
+        );
+        let (_, metadata) = transcript.finish();
+
+        assert_eq!(
+            speaker_labelled_text(&metadata).as_deref(),
+            Some("Speaker 1: Shall we start?\nSpeaker 2: Yes.\nSpeaker 1: Good.")
+        );
+
+        // A single speaker needs no labels
+        let solo = TranscriptMetadata {
+            speakers: vec![turn(0, "Just me.")],
+            ..Default::default()
+        };
+        assert_eq!(speaker_labelled_text(&solo), None);
+    }
+}
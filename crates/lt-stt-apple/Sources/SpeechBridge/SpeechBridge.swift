@@ -62,9 +62,13 @@ final class SpeechSession: @unchecked Sendable {
             return nil
         }
 
+        // Same options as the `.progressiveTranscription` preset, plus
+        // per-run audio time ranges and confidence for word-level detail.
         let transcriber = SpeechTranscriber(
             locale: self.locale,
-            preset: .progressiveTranscription
+            transcriptionOptions: [],
+            reportingOptions: [.volatileResults, .fastResults],
+            attributeOptions: [.audioTimeRange, .transcriptionConfidence]
         )
 
         // Capture Sendable values for the task closure.
@@ -92,8 +96,18 @@ final class SpeechSession: @unchecked Sendable {
                     let startSeconds = CMTimeGetSeconds(result.range.start)
                     let timestampMs = UInt64(max(0, startSeconds) * 1000)
 
+                    let wordsJson = SpeechSession.wordsJson(from: result.text)
+
                     text.withCString { cstr in
-                        capturedOnTranscription.fn(capturedCtx.value, cstr, timestampMs, isFinal)
+                        if let wordsJson = wordsJson {
+                            wordsJson.withCString { wordsCstr in
+                                capturedOnTranscription.fn(
+                                    capturedCtx.value, cstr, timestampMs, isFinal, wordsCstr)
+                            }
+                        } else {
+                            capturedOnTranscription.fn(
+                                capturedCtx.value, cstr, timestampMs, isFinal, nil)
+                        }
                     }
                 }
                 // Keep analyzer alive until results are exhausted.
@@ -107,6 +121,34 @@ final class SpeechSession: @unchecked Sendable {
         }
     }
 
+    /// Serialize the attributed runs of a result as a JSON word array.
+    /// Returns nil when no run carries an audio time range.
+    static func wordsJson(from text: AttributedString) -> String? {
+        var words: [[String: Any]] = []
+        for run in text.runs {
+            guard let range = run.audioTimeRange else { continue }
+            let word = String(text[run.range].characters)
+                .trimmingCharacters(in: .whitespacesAndNewlines)
+            guard !word.isEmpty else { continue }
+
+            var entry: [String: Any] = [
+                "text": word,
+                "start_ms": UInt64(max(0, CMTimeGetSeconds(range.start)) * 1000),
+                "end_ms": UInt64(max(0, CMTimeGetSeconds(range.end)) * 1000),
+            ]
+            if let confidence = run.transcriptionConfidence {
+                entry["confidence"] = confidence
+            }
+            words.append(entry)
+        }
+
+        guard !words.isEmpty,
+              let data = try? JSONSerialization.data(withJSONObject: words) else {
+            return nil
+        }
+        return String(data: data, encoding: .utf8)
+    }
+
     /// Convert PCM i16 16kHz mono samples into an `AVAudioPCMBuffer`,
     /// then push it into the async stream as an `AnalyzerInput`.
     func sendAudio(samples: UnsafePointer<Int16>, count: Int, timestampMs: UInt64) -> Bool {
@@ -14,7 +14,8 @@ public typealias TranscriptionCallback = @convention(c) (
     _ ctx: UnsafeMutableRawPointer?,
     _ text: UnsafePointer<CChar>?,
     _ timestampMs: UInt64,
-    _ isFinal: Bool
+    _ isFinal: Bool,
+    _ wordsJson: UnsafePointer<CChar>?
 ) -> Void
 
 public typealias ErrorCallback = @convention(c) (
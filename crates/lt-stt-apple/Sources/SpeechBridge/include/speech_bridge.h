@@ -21,11 +21,15 @@ typedef enum {
 /// `text` is a UTF-8 C string (caller must NOT free it).
 /// `timestamp_ms` is the time offset in the audio stream.
 /// `is_final` indicates whether this is a committed (final) result.
+/// `words_json` is a UTF-8 JSON array of
+/// `{"text", "start_ms", "end_ms", "confidence"}` objects, or NULL when the
+/// result carries no word timing (caller must NOT free it).
 typedef void (*SpeechTranscriptionCallback)(
     void *ctx,
     const char *text,
     uint64_t timestamp_ms,
-    bool is_final
+    bool is_final,
+    const char *words_json
 );
 
 /// Callback for errors.
@@ -1,8 +1,12 @@
 use async_trait::async_trait;
-use lt_core::error::{MurmurError, Result};
-use lt_core::stt::{AudioChunk, SttProvider, TranscriptionEvent};
+use lt_core::error::{MurmurError, ProviderError, ProviderErrorKind, Result};
+use lt_core::stt::{
+    AudioChunk, AudioFormat, SttCapabilities, SttHealth, SttMode, SttProvider, TranscriptMetadata,
+    TranscriptWord, TranscriptionEvent,
+};
 use std::ffi::{CStr, CString};
 use std::ptr;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Mutex;
 use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
@@ -16,6 +20,7 @@ type SpeechTranscriptionCallback = unsafe extern "C" fn(
     text: *const std::ffi::c_char,
     timestamp_ms: u64,
     is_final: bool,
+    words_json: *const std::ffi::c_char,
 );
 
 type SpeechErrorCallback =
@@ -110,6 +115,10 @@ pub fn check_model_status(locale: &str) -> SpeechModelStatus {
 
 struct CallbackContext {
     event_tx: mpsc::Sender<TranscriptionEvent>,
+    /// Locale the session was created with, reported as the transcript language
+    locale: String,
+    /// Incremented on every final result
+    segment_id: AtomicU64,
 }
 
 /// Trampoline: called from Swift when a transcription result arrives.
@@ -118,6 +127,7 @@ unsafe extern "C" fn on_transcription(
     text: *const std::ffi::c_char,
     timestamp_ms: u64,
     is_final: bool,
+    words_json: *const std::ffi::c_char,
 ) {
     if ctx.is_null() || text.is_null() {
         return;
@@ -127,15 +137,38 @@ unsafe extern "C" fn on_transcription(
         .to_string_lossy()
         .to_string();
 
+    let words: Vec<TranscriptWord> = if words_json.is_null() {
+        Vec::new()
+    } else {
+        let json = unsafe { CStr::from_ptr(words_json) }.to_string_lossy();
+        serde_json::from_str(&json).unwrap_or_else(|e| {
+            warn!("Apple STT: ignoring malformed word timings: {}", e);
+            Vec::new()
+        })
+    };
+    let segment_id = if is_final {
+        cb.segment_id.fetch_add(1, Ordering::Relaxed)
+    } else {
+        cb.segment_id.load(Ordering::Relaxed)
+    };
+    let metadata = TranscriptMetadata {
+        words,
+        language: Some(cb.locale.clone()),
+        segment_id: Some(segment_id),
+        ..Default::default()
+    };
+
     let event = if is_final {
         TranscriptionEvent::Committed {
             text: text_str,
             timestamp_ms,
+            metadata,
         }
     } else {
         TranscriptionEvent::Partial {
             text: text_str,
             timestamp_ms,
+            metadata,
         }
     };
 
@@ -157,7 +190,11 @@ unsafe extern "C" fn on_error(ctx: *mut std::ffi::c_void, message: *const std::f
 
     error!("Apple STT error: {}", msg);
 
-    let event = TranscriptionEvent::Error { message: msg };
+    // Analyzer failures are not classified further by the Swift bridge.
+    let event = TranscriptionEvent::Error {
+        message: msg,
+        detail: None,
+    };
     if let Err(e) = cb.event_tx.blocking_send(event) {
         error!("Apple STT: failed to send error event: {}", e);
     }
@@ -271,7 +308,11 @@ impl SttProvider for AppleSttProvider {
         *self.event_rx.lock().unwrap() = Some(event_rx);
 
         // Allocate callback context on the heap.
-        let ctx = Box::new(CallbackContext { event_tx });
+        let ctx = Box::new(CallbackContext {
+            event_tx,
+            locale: locale.clone(),
+            segment_id: AtomicU64::new(0),
+        });
         let ctx_ptr = Box::into_raw(ctx);
         *self.callback_ctx.lock().unwrap() = Some(ctx_ptr);
 
@@ -291,10 +332,12 @@ impl SttProvider for AppleSttProvider {
                 let _ = Box::from_raw(ctx_ptr);
             }
             *self.callback_ctx.lock().unwrap() = None;
-            return Err(MurmurError::Stt(
-                "Failed to create Apple STT session. Is macOS 26+ and the speech model installed?"
-                    .to_string(),
-            ));
+            return Err(ProviderError::new(
+                ProviderErrorKind::Unavailable,
+                "apple_stt",
+                "Failed to create Apple STT session. Is macOS 26+ and the speech model installed?",
+            )
+            .into());
         }
 
         *self.session.lock().unwrap() = session_ptr;
@@ -358,6 +401,50 @@ impl SttProvider for AppleSttProvider {
             .take()
             .expect("subscribe_events called multiple times or before start_session")
     }
+
+    fn input_format(&self) -> AudioFormat {
+        // speech_bridge_send_audio builds a 16 kHz mono AVAudioFormat
+        AudioFormat::mono(16000)
+    }
+
+    async fn health_check(&self) -> Result<SttHealth> {
+        // Nothing to reach over the network: the test passes when the
+        // framework is present and the locale's model is installed.
+        let started = std::time::Instant::now();
+        if !is_available() {
+            return Err(ProviderError::new(
+                ProviderErrorKind::Unavailable,
+                "apple_stt",
+                "SpeechTranscriber is not available (requires macOS 26+)",
+            )
+            .into());
+        }
+        let locale = self.resolve_locale();
+        match check_model_status(&locale) {
+            SpeechModelStatus::Installed => Ok(SttHealth {
+                latency_ms: started.elapsed().as_millis() as u64,
+            }),
+            status => Err(ProviderError::new(
+                ProviderErrorKind::Unavailable,
+                "apple_stt",
+                format!("Speech model for {} is not ready ({:?})", locale, status),
+            )
+            .into()),
+        }
+    }
+
+    fn capabilities(&self) -> SttCapabilities {
+        SttCapabilities {
+            mode: SttMode::Streaming,
+            partial_results: true,
+            languages: get_supported_locales(),
+            word_timestamps: true,
+            vocabulary_hints: false,
+            diarization: false,
+            local: true,
+            requires_api_key: false,
+        }
+    }
 }
 
 impl Drop for AppleSttProvider {
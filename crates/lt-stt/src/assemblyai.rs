@@ -0,0 +1,1068 @@
+//! AssemblyAI universal-streaming (v3) transcription over WebSocket
+//!
+//! Raw 16 kHz PCM goes up as binary frames of at least 50 ms and `Turn`
+//! messages come back. A turn is reported as partials while it grows and
+//! committed once the server marks `end_of_turn` (and, with `format_turns`,
+//! sends the punctuated version). `Terminate` makes the server finish the
+//! current turn and answer with `Termination` before it closes the socket.
+
+use crate::error;
+use crate::ws::{self, HEALTH_CHECK_TIMEOUT};
+use async_trait::async_trait;
+use futures_util::{SinkExt, StreamExt};
+use lt_core::error::{MurmurError, ProviderError, ProviderErrorKind, Result};
+use lt_core::stt::{
+    AudioChunk, AudioFormat, SpeakerSegment, SttCapabilities, SttHealth, SttMode, SttProvider,
+    TranscriptMetadata, TranscriptWord, TranscriptionEvent,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, Mutex};
+use tokio_tungstenite::tungstenite::{handshake::client::Request, protocol::CloseFrame, Message};
+use tracing::{debug, error, info, warn};
+use url::Url;
+
+/// AssemblyAI streaming API; also serves `/v3/token` over HTTPS
+pub const DEFAULT_BASE_URL: &str = "wss://streaming.assemblyai.com";
+
+/// Model used when none is configured
+pub const DEFAULT_SPEECH_MODEL: &str = "universal-streaming-english";
+
+/// Languages the multilingual model detects on its own
+const MULTILINGUAL_LANGUAGES: [&str; 6] = ["en", "es", "fr", "de", "it", "pt"];
+
+/// PCM rate announced in the `sample_rate` query parameter
+const INPUT_SAMPLE_RATE: u32 = 16000;
+
+/// The server rejects audio frames shorter than 50 ms
+const MIN_FRAME_SAMPLES: usize = INPUT_SAMPLE_RATE as usize / 20;
+
+/// Key terms beyond this are rejected, as are terms over 50 characters
+const MAX_KEYTERMS: usize = 100;
+const MAX_KEYTERM_CHARS: usize = 50;
+
+/// Lifetime of a temporary token; it only has to outlive the handshake
+const TOKEN_EXPIRES_IN_SECS: u64 = 60;
+
+/// How long to wait for the last turn after `Terminate`
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Control messages sent as text frames
+#[derive(Debug, Serialize)]
+#[serde(tag = "type")]
+enum AssemblyAiControl {
+    Terminate,
+}
+
+impl AssemblyAiControl {
+    fn to_message(&self) -> Message {
+        Message::Text(serde_json::to_string(self).unwrap().into())
+    }
+}
+
+/// AssemblyAI WebSocket response types
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum AssemblyAiResponse {
+    Typed(AssemblyAiMessage),
+
+    /// Rejected sessions get a bare `{"error": ...}` before the close frame
+    Error {
+        error: String,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type")]
+enum AssemblyAiMessage {
+    Begin {},
+
+    Turn(AssemblyAiTurn),
+
+    /// Sent once the session is over
+    Termination {},
+}
+
+#[derive(Debug, Deserialize)]
+struct AssemblyAiTurn {
+    /// Turn index within the session, starting at 0
+    #[serde(default)]
+    turn_order: u64,
+    /// Finalized words of the turn so far
+    #[serde(default)]
+    transcript: String,
+    #[serde(default)]
+    end_of_turn: bool,
+    /// Punctuated and cased (only sent with `format_turns`)
+    #[serde(default)]
+    turn_is_formatted: bool,
+    /// All words of the turn, including ones that may still change
+    #[serde(default)]
+    words: Vec<AssemblyAiWord>,
+    /// Detected language, from the multilingual model
+    #[serde(default)]
+    language_code: Option<String>,
+    /// "A", "B", ... (or "UNKNOWN") with `speaker_labels=true`
+    #[serde(default)]
+    speaker_label: Option<String>,
+}
+
+impl AssemblyAiTurn {
+    /// Speaker id from the label: "A" is 0, "B" is 1 and so on
+    fn speaker(&self) -> Option<u32> {
+        match self.speaker_label.as_deref()?.as_bytes() {
+            [letter @ b'A'..=b'Z'] => Some(u32::from(letter - b'A')),
+            _ => None,
+        }
+    }
+}
+
+/// Word entry of a turn (times in milliseconds)
+#[derive(Debug, Deserialize)]
+struct AssemblyAiWord {
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    start: u64,
+    #[serde(default)]
+    end: u64,
+    #[serde(default)]
+    confidence: Option<f64>,
+    #[serde(default)]
+    word_is_final: bool,
+}
+
+impl AssemblyAiWord {
+    fn to_transcript_word(&self) -> Option<TranscriptWord> {
+        let text = self.text.trim();
+        if text.is_empty() {
+            return None;
+        }
+        Some(TranscriptWord {
+            text: text.to_string(),
+            start_ms: self.start,
+            end_ms: self.end,
+            confidence: self.confidence.map(|c| c.clamp(0.0, 1.0) as f32),
+        })
+    }
+}
+
+/// Answer of `GET /v3/token`
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    token: String,
+}
+
+/// Classify the close frame the server ends a session with; `None` for a
+/// normal close
+fn classify_close(frame: Option<&CloseFrame>) -> Option<ProviderError> {
+    let frame = frame?;
+    let reason = frame.reason.to_string();
+    let kind = match u16::from(frame.code) {
+        1000 => return None,
+        // 1008 covers both a bad key and malformed parameters
+        1008 if reason.to_lowercase().contains("unauthorized") => ProviderErrorKind::Auth,
+        1008 => ProviderErrorKind::BadRequest,
+        // 3005: the session ran past its maximum duration
+        3005 => ProviderErrorKind::Timeout,
+        // 3007: audio frames too short, too long or too fast
+        3007 => ProviderErrorKind::BadRequest,
+        // 3008: too many concurrent sessions
+        3008 => ProviderErrorKind::RateLimited {
+            retry_after_secs: None,
+        },
+        1011 => ProviderErrorKind::Unavailable,
+        _ => ProviderErrorKind::Network,
+    };
+    Some(ProviderError::new(
+        kind,
+        "assemblyai",
+        format!(
+            "AssemblyAI closed the session: {} {}",
+            u16::from(frame.code),
+            reason
+        ),
+    ))
+}
+
+/// Turns `Turn` messages into events, using the turn order as segment id.
+///
+/// With `format_turns` the server sends each ended turn twice, unformatted
+/// first; only the formatted one is committed.
+#[derive(Debug)]
+struct TurnTracker {
+    format_turns: bool,
+    /// Latest state of the turn that has not been committed yet
+    pending: Option<AssemblyAiTurn>,
+    /// Turns below this one are committed
+    next_turn: u64,
+}
+
+impl TurnTracker {
+    fn new(format_turns: bool) -> Self {
+        Self {
+            format_turns,
+            pending: None,
+            next_turn: 0,
+        }
+    }
+
+    fn turn(&mut self, turn: AssemblyAiTurn) -> Vec<TranscriptionEvent> {
+        if turn.turn_order < self.next_turn {
+            debug!("Ignoring update of committed turn {}", turn.turn_order);
+            return Vec::new();
+        }
+
+        // A turn that moved on without its formatted version keeps its text
+        let mut events = Vec::new();
+        if self
+            .pending
+            .as_ref()
+            .is_some_and(|p| p.turn_order != turn.turn_order)
+        {
+            warn!("Turn moved on before it was committed");
+            events.extend(self.commit());
+        }
+
+        let complete = turn.end_of_turn && (turn.turn_is_formatted || !self.format_turns);
+        self.pending = Some(turn);
+        events.extend(if complete {
+            self.commit()
+        } else {
+            self.partial()
+        });
+        events
+    }
+
+    /// The pending turn including words that may still change
+    fn partial(&self) -> Option<TranscriptionEvent> {
+        let turn = self.pending.as_ref()?;
+        let text = if turn.end_of_turn || turn.words.is_empty() {
+            turn.transcript.trim().to_string()
+        } else {
+            turn.words
+                .iter()
+                .map(|w| w.text.trim())
+                .filter(|w| !w.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        (!text.is_empty()).then(|| TranscriptionEvent::Partial {
+            text,
+            timestamp_ms: turn.words.last().map_or(0, |w| w.end),
+            metadata: TranscriptMetadata {
+                segment_id: Some(turn.turn_order),
+                ..Default::default()
+            },
+        })
+    }
+
+    /// Emit the finalized text of the pending turn, if any
+    fn commit(&mut self) -> Option<TranscriptionEvent> {
+        let turn = self.pending.take()?;
+        self.next_turn = turn.turn_order + 1;
+        let text = turn.transcript.trim().to_string();
+        if text.is_empty() {
+            return None;
+        }
+        let words: Vec<TranscriptWord> = turn
+            .words
+            .iter()
+            .filter(|w| w.word_is_final)
+            .filter_map(AssemblyAiWord::to_transcript_word)
+            .collect();
+        // A turn is spoken by one speaker
+        let speakers = turn
+            .speaker()
+            .map(|speaker| SpeakerSegment {
+                speaker,
+                text: text.clone(),
+                start_ms: words.first().map_or(0, |w| w.start_ms),
+                end_ms: words.last().map_or(0, |w| w.end_ms),
+            })
+            .into_iter()
+            .collect();
+        Some(TranscriptionEvent::Committed {
+            text,
+            timestamp_ms: words.last().map_or(0, |w| w.end_ms),
+            metadata: TranscriptMetadata {
+                words,
+                language: turn.language_code,
+                segment_id: Some(turn.turn_order),
+                speakers,
+                ..Default::default()
+            },
+        })
+    }
+}
+
+/// Collects PCM until a frame is long enough to send
+#[derive(Debug, Default)]
+struct FrameBuffer {
+    samples: Vec<i16>,
+}
+
+impl FrameBuffer {
+    /// Add a chunk; returns a frame once at least 50 ms are buffered
+    fn push(&mut self, data: &[i16]) -> Option<Message> {
+        self.samples.extend_from_slice(data);
+        (self.samples.len() >= MIN_FRAME_SAMPLES).then(|| self.take())
+    }
+
+    /// The rest of the audio, padded with silence to the minimum length
+    fn flush(&mut self) -> Option<Message> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        self.samples
+            .resize(self.samples.len().max(MIN_FRAME_SAMPLES), 0);
+        Some(self.take())
+    }
+
+    fn take(&mut self) -> Message {
+        let pcm_bytes: Vec<u8> = std::mem::take(&mut self.samples)
+            .iter()
+            .flat_map(|s| s.to_le_bytes())
+            .collect();
+        Message::Binary(pcm_bytes.into())
+    }
+}
+
+/// AssemblyAI universal-streaming client
+pub struct AssemblyAiProvider {
+    api_key: String,
+    speech_model: String,
+    keyterms: Vec<String>,
+    format_turns: bool,
+    end_of_turn_confidence_threshold: Option<f32>,
+    max_turn_silence_ms: Option<u32>,
+    temporary_token: bool,
+    speaker_labels: bool,
+    base_url: String,
+    ws_tx: Arc<Mutex<Option<mpsc::Sender<AudioChunk>>>>,
+    event_tx: Arc<Mutex<Option<mpsc::Sender<TranscriptionEvent>>>>,
+    event_rx: Arc<Mutex<Option<mpsc::Receiver<TranscriptionEvent>>>>,
+    ws_task: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
+}
+
+impl AssemblyAiProvider {
+    /// Create a provider for AssemblyAI with the default English model
+    pub fn new(api_key: String) -> Self {
+        Self {
+            api_key,
+            speech_model: DEFAULT_SPEECH_MODEL.to_string(),
+            keyterms: Vec::new(),
+            format_turns: true,
+            end_of_turn_confidence_threshold: None,
+            max_turn_silence_ms: None,
+            temporary_token: false,
+            speaker_labels: false,
+            base_url: DEFAULT_BASE_URL.to_string(),
+            ws_tx: Arc::new(Mutex::new(None)),
+            event_tx: Arc::new(Mutex::new(None)),
+            event_rx: Arc::new(Mutex::new(None)),
+            ws_task: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Use `speech_model` instead of the default (empty keeps the default)
+    pub fn with_speech_model(mut self, speech_model: Option<String>) -> Self {
+        if let Some(speech_model) = speech_model.filter(|m| !m.is_empty()) {
+            self.speech_model = speech_model;
+        }
+        self
+    }
+
+    /// Boost recognition of these terms; duplicates, terms over 50
+    /// characters and anything past the first 100 are dropped
+    pub fn with_keyterms<I, S>(mut self, keyterms: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut terms: Vec<String> = Vec::new();
+        for term in keyterms {
+            let term = term.as_ref().trim();
+            if term.is_empty() || term.chars().count() > MAX_KEYTERM_CHARS {
+                continue;
+            }
+            if !terms.iter().any(|t| t.eq_ignore_ascii_case(term)) {
+                terms.push(term.to_string());
+            }
+        }
+        terms.truncate(MAX_KEYTERMS);
+        self.keyterms = terms;
+        self
+    }
+
+    /// Commit turns as soon as they end instead of waiting for formatting
+    pub fn with_format_turns(mut self, format_turns: bool) -> Self {
+        self.format_turns = format_turns;
+        self
+    }
+
+    /// Tune when the server ends a turn (`None` keeps server defaults)
+    pub fn with_turn_detection(
+        mut self,
+        end_of_turn_confidence_threshold: Option<f32>,
+        max_turn_silence_ms: Option<u32>,
+    ) -> Self {
+        self.end_of_turn_confidence_threshold = end_of_turn_confidence_threshold;
+        self.max_turn_silence_ms = max_turn_silence_ms;
+        self
+    }
+
+    /// Connect with a short-lived token instead of sending the key itself
+    pub fn with_temporary_token(mut self, temporary_token: bool) -> Self {
+        self.temporary_token = temporary_token;
+        self
+    }
+
+    /// Label each turn with its speaker
+    pub fn with_speaker_labels(mut self, speaker_labels: bool) -> Self {
+        self.speaker_labels = speaker_labels;
+        self
+    }
+
+    /// Connect somewhere other than AssemblyAI (e.g. a proxy)
+    pub fn with_base_url(mut self, base_url: Option<String>) -> Self {
+        if let Some(base_url) = base_url.filter(|u| !u.is_empty()) {
+            self.base_url = base_url;
+        }
+        self
+    }
+
+    fn is_multilingual(&self) -> bool {
+        self.speech_model.contains("multilingual")
+    }
+
+    /// Build WebSocket URL, carrying the temporary token if there is one
+    fn build_ws_url(&self, token: Option<&str>) -> Result<Url> {
+        let mut url = Url::parse(&format!("{}/v3/ws", self.base_url.trim_end_matches('/')))
+            .map_err(|e| MurmurError::Stt(format!("Invalid URL: {}", e)))?;
+
+        {
+            let mut query = url.query_pairs_mut();
+            query
+                .append_pair("sample_rate", &INPUT_SAMPLE_RATE.to_string())
+                .append_pair("encoding", "pcm_s16le")
+                .append_pair("speech_model", &self.speech_model)
+                .append_pair("format_turns", &self.format_turns.to_string());
+            if self.is_multilingual() {
+                query.append_pair("language_detection", "true");
+            }
+            if let Some(threshold) = self.end_of_turn_confidence_threshold {
+                query.append_pair("end_of_turn_confidence_threshold", &threshold.to_string());
+            }
+            if let Some(silence_ms) = self.max_turn_silence_ms {
+                query.append_pair("max_turn_silence", &silence_ms.to_string());
+            }
+            if self.speaker_labels {
+                query.append_pair("speaker_labels", "true");
+            }
+            if !self.keyterms.is_empty() {
+                let keyterms = serde_json::to_string(&self.keyterms).unwrap();
+                query.append_pair("keyterms_prompt", &keyterms);
+            }
+            if let Some(token) = token {
+                query.append_pair("token", token);
+            }
+        }
+
+        Ok(url)
+    }
+
+    /// HTTP URL of the temporary token endpoint, on the same host
+    fn token_url(&self) -> Result<Url> {
+        let mut url = Url::parse(&format!("{}/v3/token", self.base_url.trim_end_matches('/')))
+            .map_err(|e| MurmurError::Stt(format!("Invalid URL: {}", e)))?;
+        let scheme = match url.scheme() {
+            "ws" => "http",
+            _ => "https",
+        };
+        url.set_scheme(scheme)
+            .map_err(|_| MurmurError::Stt(format!("Invalid token URL: {}", url)))?;
+        url.query_pairs_mut()
+            .append_pair("expires_in_seconds", &TOKEN_EXPIRES_IN_SECS.to_string());
+        Ok(url)
+    }
+
+    /// Mint a temporary token with the API key
+    async fn fetch_token(&self) -> Result<String> {
+        let response = lt_http::client()
+            .get(self.token_url()?)
+            .header("Authorization", &self.api_key)
+            .send()
+            .await
+            .map_err(|e| error::from_request_error("assemblyai", &e))?;
+        if !response.status().is_success() {
+            return Err(error::from_response("assemblyai", response).await.into());
+        }
+        let token: TokenResponse = response.json().await.map_err(|e| {
+            MurmurError::Stt(format!("Failed to parse AssemblyAI token response: {}", e))
+        })?;
+        Ok(token.token)
+    }
+
+    /// WebSocket handshake request, authenticated by token or by key
+    async fn connect_request(&self) -> Result<Request> {
+        let token = if self.temporary_token {
+            Some(self.fetch_token().await?)
+        } else {
+            None
+        };
+        let auth = token
+            .is_none()
+            .then_some(("Authorization", self.api_key.as_str()));
+        ws::handshake_request(self.build_ws_url(token.as_deref())?.as_str(), auth)
+    }
+}
+
+#[async_trait]
+impl SttProvider for AssemblyAiProvider {
+    async fn start_session(&mut self) -> Result<()> {
+        info!(
+            "Starting AssemblyAI STT session (model {})",
+            self.speech_model
+        );
+
+        let (audio_tx, mut audio_rx) = mpsc::channel::<AudioChunk>(32);
+        *self.ws_tx.lock().await = Some(audio_tx);
+
+        let (event_tx, event_rx) = mpsc::channel::<TranscriptionEvent>(32);
+        *self.event_tx.lock().await = Some(event_tx.clone());
+        *self.event_rx.lock().await = Some(event_rx);
+
+        let ws_stream = ws::connect("assemblyai", self.connect_request().await?).await?;
+        info!("WebSocket connected to AssemblyAI");
+
+        let (mut ws_write, mut ws_read) = ws_stream.split();
+        let format_turns = self.format_turns;
+
+        let task = tokio::spawn(async move {
+            let event_tx_clone = event_tx.clone();
+            let mut receiver_task = tokio::spawn(async move {
+                let mut turns = TurnTracker::new(format_turns);
+                while let Some(msg) = ws_read.next().await {
+                    let events = match msg {
+                        Ok(Message::Text(text)) => {
+                            debug!("Received message: {}", text);
+                            match serde_json::from_str::<AssemblyAiResponse>(&text) {
+                                Ok(AssemblyAiResponse::Typed(AssemblyAiMessage::Begin {})) => {
+                                    debug!("AssemblyAI session started");
+                                    Vec::new()
+                                }
+                                Ok(AssemblyAiResponse::Typed(AssemblyAiMessage::Turn(turn))) => {
+                                    turns.turn(turn)
+                                }
+                                Ok(AssemblyAiResponse::Typed(
+                                    AssemblyAiMessage::Termination {},
+                                )) => {
+                                    debug!("AssemblyAI session terminated");
+                                    break;
+                                }
+                                Ok(AssemblyAiResponse::Error { error: message }) => {
+                                    error!("AssemblyAI error: {}", message);
+                                    vec![TranscriptionEvent::Error {
+                                        detail: Some(ProviderError::new(
+                                            ProviderErrorKind::Unavailable,
+                                            "assemblyai",
+                                            message.clone(),
+                                        )),
+                                        message,
+                                    }]
+                                }
+                                Err(e) => {
+                                    warn!("Failed to parse message: {} - {}", e, text);
+                                    Vec::new()
+                                }
+                            }
+                        }
+                        Ok(Message::Close(frame)) => {
+                            info!("WebSocket closed by server");
+                            if let Some(detail) = classify_close(frame.as_ref()) {
+                                error!("AssemblyAI error: {}", detail);
+                                let event = TranscriptionEvent::Error {
+                                    message: detail.message.clone(),
+                                    detail: Some(detail),
+                                };
+                                let _ = event_tx_clone.send(event).await;
+                            }
+                            break;
+                        }
+                        Ok(_) => {
+                            debug!("Received non-text message");
+                            Vec::new()
+                        }
+                        Err(e) => {
+                            error!("WebSocket error: {}", e);
+                            let message = format!("WebSocket error: {}", e);
+                            let event = TranscriptionEvent::Error {
+                                detail: Some(ProviderError::new(
+                                    ProviderErrorKind::Network,
+                                    "assemblyai",
+                                    message.clone(),
+                                )),
+                                message,
+                            };
+                            let _ = event_tx_clone.send(event).await;
+                            break;
+                        }
+                    };
+
+                    for event in events {
+                        if let Err(e) = event_tx_clone.send(event).await {
+                            error!("Failed to send transcription event: {}", e);
+                        }
+                    }
+                }
+                if let Some(event) = turns.commit() {
+                    let _ = event_tx_clone.send(event).await;
+                }
+                debug!("WebSocket receiver task finished");
+            });
+
+            // Send audio as raw little-endian PCM in frames of at least 50 ms
+            let mut frames = FrameBuffer::default();
+            while let Some(chunk) = audio_rx.recv().await {
+                if let Some(frame) = frames.push(&chunk.data) {
+                    if let Err(e) = ws_write.send(frame).await {
+                        error!("Failed to send audio chunk: {}", e);
+                        break;
+                    }
+                }
+            }
+
+            // The server answers Terminate with the rest of the current turn
+            // and a Termination message, then closes the socket itself
+            debug!("Audio sender finished, sending Terminate");
+            if let Some(frame) = frames.flush() {
+                let _ = ws_write.send(frame).await;
+            }
+            if let Err(e) = ws_write
+                .send(AssemblyAiControl::Terminate.to_message())
+                .await
+            {
+                warn!("Failed to send Terminate: {}", e);
+            }
+
+            match tokio::time::timeout(CLOSE_TIMEOUT, &mut receiver_task).await {
+                Ok(Err(e)) => warn!("Receiver task error: {}", e),
+                Ok(Ok(())) => {}
+                Err(_) => {
+                    warn!("Receiver task timed out during shutdown");
+                    receiver_task.abort();
+                }
+            }
+
+            let _ = ws_write.close().await;
+            info!("WebSocket task finished");
+        });
+
+        *self.ws_task.lock().await = Some(task);
+
+        Ok(())
+    }
+
+    async fn send_audio(&mut self, chunk: AudioChunk) -> Result<()> {
+        let tx_lock = self.ws_tx.lock().await;
+        if let Some(tx) = tx_lock.as_ref() {
+            tx.send(chunk)
+                .await
+                .map_err(|e| MurmurError::Stt(format!("Failed to send audio chunk: {}", e)))?;
+            Ok(())
+        } else {
+            Err(MurmurError::Stt("Session not started".to_string()))
+        }
+    }
+
+    async fn stop_session(&mut self) -> Result<()> {
+        info!("Stopping AssemblyAI STT session");
+
+        // Closing the audio channel makes the task send Terminate
+        *self.ws_tx.lock().await = None;
+
+        if let Some(task) = self.ws_task.lock().await.take() {
+            let _ = task.await;
+        }
+
+        info!("AssemblyAI STT session stopped");
+        Ok(())
+    }
+
+    async fn subscribe_events(&self) -> mpsc::Receiver<TranscriptionEvent> {
+        let mut rx_lock = self.event_rx.lock().await;
+        rx_lock
+            .take()
+            .expect("subscribe_events called multiple times")
+    }
+
+    fn input_format(&self) -> AudioFormat {
+        AudioFormat::mono(INPUT_SAMPLE_RATE)
+    }
+
+    async fn health_check(&self) -> Result<SttHealth> {
+        let timed_out = || ws::health_check_timed_out("assemblyai");
+
+        // A session that is accepted starts with Begin; a rejected one gets
+        // an error message or close frame instead
+        let started = Instant::now();
+        let request = tokio::time::timeout(HEALTH_CHECK_TIMEOUT, self.connect_request())
+            .await
+            .map_err(|_| timed_out())??;
+        let mut ws_stream =
+            tokio::time::timeout(HEALTH_CHECK_TIMEOUT, ws::connect("assemblyai", request))
+                .await
+                .map_err(|_| timed_out())??;
+        let first = tokio::time::timeout(HEALTH_CHECK_TIMEOUT, ws_stream.next())
+            .await
+            .map_err(|_| timed_out())?;
+        let latency_ms = started.elapsed().as_millis() as u64;
+        let _ = ws_stream
+            .send(AssemblyAiControl::Terminate.to_message())
+            .await;
+        let _ = ws_stream.close(None).await;
+
+        match first {
+            Some(Ok(Message::Text(text))) => match serde_json::from_str(&text) {
+                Ok(AssemblyAiResponse::Error { error }) => {
+                    Err(
+                        ProviderError::new(ProviderErrorKind::Unavailable, "assemblyai", error)
+                            .into(),
+                    )
+                }
+                _ => Ok(SttHealth { latency_ms }),
+            },
+            Some(Ok(Message::Close(frame))) => match classify_close(frame.as_ref()) {
+                Some(e) => Err(e.into()),
+                None => Ok(SttHealth { latency_ms }),
+            },
+            Some(Ok(_)) => Ok(SttHealth { latency_ms }),
+            Some(Err(e)) => Err(ProviderError::new(
+                ProviderErrorKind::Network,
+                "assemblyai",
+                format!("WebSocket error: {}", e),
+            )
+            .into()),
+            None => Err(ProviderError::new(
+                ProviderErrorKind::Network,
+                "assemblyai",
+                "Connection closed before the session began",
+            )
+            .into()),
+        }
+    }
+
+    fn capabilities(&self) -> SttCapabilities {
+        SttCapabilities {
+            mode: SttMode::Streaming,
+            partial_results: true,
+            languages: if self.is_multilingual() {
+                MULTILINGUAL_LANGUAGES
+                    .iter()
+                    .map(|l| l.to_string())
+                    .collect()
+            } else {
+                vec!["en".to_string()]
+            },
+            word_timestamps: true,
+            vocabulary_hints: true,
+            diarization: true,
+            local: false,
+            requires_api_key: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+
+    fn turn(json: serde_json::Value) -> AssemblyAiTurn {
+        match serde_json::from_value(json).unwrap() {
+            AssemblyAiResponse::Typed(AssemblyAiMessage::Turn(turn)) => turn,
+            other => panic!("Unexpected response {:?}", other),
+        }
+    }
+
+    fn turn_json(order: u64, transcript: &str, end: bool, formatted: bool) -> AssemblyAiTurn {
+        turn(serde_json::json!({
+            "type": "Turn",
+            "turn_order": order,
+            "transcript": transcript,
+            "end_of_turn": end,
+            "turn_is_formatted": formatted,
+            "words": []
+        }))
+    }
+
+    fn describe(event: TranscriptionEvent) -> String {
+        match event {
+            TranscriptionEvent::Partial { text, .. } => format!("partial {}", text),
+            TranscriptionEvent::Committed { text, metadata, .. } => {
+                format!("committed {} {}", metadata.segment_id.unwrap(), text)
+            }
+            TranscriptionEvent::Error { message, .. } => format!("error {}", message),
+        }
+    }
+
+    fn texts_of(events: impl IntoIterator<Item = TranscriptionEvent>) -> Vec<String> {
+        events.into_iter().map(describe).collect()
+    }
+
+    #[test]
+    fn test_url_carries_model_turn_settings_and_keyterms() {
+        let provider = AssemblyAiProvider::new("key".to_string())
+            .with_keyterms(["Murmur", "murmur", "Tauri", " ", &"x".repeat(51)])
+            .with_turn_detection(Some(0.7), Some(1500));
+        let url = provider.build_ws_url(None).unwrap();
+        assert_eq!(
+            url.as_str().split('?').next(),
+            Some("wss://streaming.assemblyai.com/v3/ws")
+        );
+
+        let pairs: Vec<(String, String)> = url
+            .query_pairs()
+            .map(|(k, v)| (k.into_owned(), v.into_owned()))
+            .collect();
+        let get = |k: &str| {
+            pairs
+                .iter()
+                .find(|(pk, _)| pk == k)
+                .map(|(_, v)| v.as_str())
+        };
+        assert_eq!(get("sample_rate"), Some("16000"));
+        assert_eq!(get("encoding"), Some("pcm_s16le"));
+        assert_eq!(get("speech_model"), Some("universal-streaming-english"));
+        assert_eq!(get("format_turns"), Some("true"));
+        assert_eq!(get("end_of_turn_confidence_threshold"), Some("0.7"));
+        assert_eq!(get("max_turn_silence"), Some("1500"));
+        assert_eq!(get("keyterms_prompt"), Some(r#"["Murmur","Tauri"]"#));
+        assert_eq!(get("language_detection"), None);
+        assert_eq!(get("speaker_labels"), None);
+        assert_eq!(get("token"), None);
+
+        let url = provider
+            .with_speaker_labels(true)
+            .build_ws_url(None)
+            .unwrap();
+        assert!(url
+            .query_pairs()
+            .any(|(k, v)| k == "speaker_labels" && v == "true"));
+    }
+
+    #[test]
+    fn test_keyterms_are_capped() {
+        let terms: Vec<String> = (0..150).map(|i| format!("term{}", i)).collect();
+        let provider = AssemblyAiProvider::new("key".to_string()).with_keyterms(&terms);
+        assert_eq!(provider.keyterms.len(), MAX_KEYTERMS);
+        assert_eq!(provider.keyterms[0], "term0");
+    }
+
+    #[test]
+    fn test_token_url_follows_base_url() {
+        let provider = AssemblyAiProvider::new("key".to_string());
+        assert_eq!(
+            provider.token_url().unwrap().as_str(),
+            "https://streaming.assemblyai.com/v3/token?expires_in_seconds=60"
+        );
+
+        let provider = AssemblyAiProvider::new("key".to_string())
+            .with_base_url(Some("ws://127.0.0.1:9000/".to_string()));
+        assert_eq!(
+            provider.token_url().unwrap().as_str(),
+            "http://127.0.0.1:9000/v3/token?expires_in_seconds=60"
+        );
+        let url = provider.build_ws_url(Some("tmp-token")).unwrap();
+        assert!(url.as_str().starts_with("ws://127.0.0.1:9000/v3/ws?"));
+        assert!(url.as_str().ends_with("&token=tmp-token"));
+    }
+
+    #[test]
+    fn test_formatted_turn_is_committed_once() {
+        let mut turns = TurnTracker::new(true);
+        let partial = turn(serde_json::json!({
+            "type": "Turn", "turn_order": 0, "transcript": "hello",
+            "end_of_turn": false, "turn_is_formatted": false,
+            "words": [
+                {"text": "hello", "start": 100, "end": 400, "confidence": 0.9, "word_is_final": true},
+                {"text": "wor", "start": 500, "end": 700, "confidence": 0.5, "word_is_final": false}
+            ]
+        }));
+        // Partials include words that may still change
+        assert_eq!(texts_of(turns.turn(partial)), ["partial hello wor"]);
+
+        // The unformatted end of turn only updates the partial
+        assert_eq!(
+            texts_of(turns.turn(turn_json(0, "hello world", true, false))),
+            ["partial hello world"]
+        );
+        assert_eq!(
+            texts_of(turns.turn(turn_json(0, "Hello, world.", true, true))),
+            ["committed 0 Hello, world."]
+        );
+        // Late duplicates of a committed turn are ignored
+        assert!(turns
+            .turn(turn_json(0, "Hello, world.", true, true))
+            .is_empty());
+        assert!(turns.commit().is_none());
+
+        assert_eq!(
+            texts_of(turns.turn(turn_json(1, "next", false, false))),
+            ["partial next"]
+        );
+        assert_eq!(texts_of(turns.commit()), ["committed 1 next"]);
+    }
+
+    #[test]
+    fn test_unformatted_turns_commit_on_end_of_turn() {
+        let mut turns = TurnTracker::new(false);
+        assert_eq!(
+            texts_of(turns.turn(turn_json(0, "hello world", true, false))),
+            ["committed 0 hello world"]
+        );
+        // An empty turn gives nothing but still advances
+        assert!(turns.turn(turn_json(1, "", true, false)).is_empty());
+        assert!(turns.turn(turn_json(1, "late", true, false)).is_empty());
+    }
+
+    #[test]
+    fn test_missing_formatted_turn_commits_unformatted_text() {
+        let mut turns = TurnTracker::new(true);
+        turns.turn(turn_json(0, "first turn", true, false));
+        assert_eq!(
+            texts_of(turns.turn(turn_json(1, "sec", false, false))),
+            ["committed 0 first turn", "partial sec"]
+        );
+        assert_eq!(
+            texts_of(turns.turn(turn_json(1, "Second.", true, true))),
+            ["committed 1 Second."]
+        );
+    }
+
+    #[test]
+    fn test_committed_words_and_language() {
+        let mut turns = TurnTracker::new(false);
+        let mut events = turns.turn(turn(serde_json::json!({
+            "type": "Turn", "turn_order": 3, "transcript": "hola mundo",
+            "end_of_turn": true, "language_code": "es",
+            "words": [
+                {"text": "hola", "start": 100, "end": 400, "confidence": 0.9, "word_is_final": true},
+                {"text": "mundo", "start": 500, "end": 900, "confidence": 1.2, "word_is_final": true}
+            ]
+        })));
+        assert_eq!(events.len(), 1);
+        match events.remove(0) {
+            TranscriptionEvent::Committed {
+                metadata,
+                timestamp_ms,
+                ..
+            } => {
+                assert_eq!(timestamp_ms, 900);
+                assert_eq!(metadata.segment_id, Some(3));
+                assert_eq!(metadata.language.as_deref(), Some("es"));
+                assert_eq!(metadata.words.len(), 2);
+                assert_eq!(metadata.words[1].confidence, Some(1.0));
+            }
+            other => panic!("Unexpected event {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_turn_speaker_labels() {
+        let mut turns = TurnTracker::new(false);
+        let mut speaker_of = |order: u64, label: &str| {
+            let events = turns.turn(turn(serde_json::json!({
+                "type": "Turn", "turn_order": order, "transcript": "Right.",
+                "end_of_turn": true, "speaker_label": label,
+                "words": [
+                    {"text": "Right.", "start": 200, "end": 600, "word_is_final": true}
+                ]
+            })));
+            match &events[0] {
+                TranscriptionEvent::Committed { metadata, .. } => metadata
+                    .speakers
+                    .first()
+                    .map(|s| (s.speaker, s.text.clone(), s.start_ms, s.end_ms)),
+                other => panic!("Unexpected event {:?}", other),
+            }
+        };
+        assert_eq!(
+            speaker_of(0, "A"),
+            Some((0, "Right.".to_string(), 200, 600))
+        );
+        assert_eq!(
+            speaker_of(1, "C"),
+            Some((2, "Right.".to_string(), 200, 600))
+        );
+        assert_eq!(speaker_of(2, "UNKNOWN"), None);
+    }
+
+    #[test]
+    fn test_frames_are_at_least_50ms() {
+        let mut frames = FrameBuffer::default();
+        assert!(frames.push(&[1; 480]).is_none());
+        match frames.push(&[1; 480]) {
+            Some(Message::Binary(bytes)) => assert_eq!(bytes.len(), 960 * 2),
+            other => panic!("Unexpected frame {:?}", other),
+        }
+        assert!(frames.flush().is_none());
+
+        frames.push(&[1; 100]);
+        match frames.flush() {
+            Some(Message::Binary(bytes)) => {
+                assert_eq!(bytes.len(), MIN_FRAME_SAMPLES * 2);
+                assert_eq!(&bytes[198..202], &[1, 0, 0, 0]);
+            }
+            other => panic!("Unexpected frame {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_close_frames_and_errors_are_classified() {
+        let frame = |code: u16, reason: &str| CloseFrame {
+            code: CloseCode::from(code),
+            reason: reason.to_string().into(),
+        };
+        assert!(classify_close(Some(&frame(1000, ""))).is_none());
+        assert_eq!(
+            classify_close(Some(&frame(1008, "Unauthorized connection")))
+                .unwrap()
+                .kind,
+            ProviderErrorKind::Auth
+        );
+        assert_eq!(
+            classify_close(Some(&frame(3005, "Session expired")))
+                .unwrap()
+                .kind,
+            ProviderErrorKind::Timeout
+        );
+        assert!(matches!(
+            classify_close(Some(&frame(3008, "Too many sessions")))
+                .unwrap()
+                .kind,
+            ProviderErrorKind::RateLimited { .. }
+        ));
+
+        let response: AssemblyAiResponse =
+            serde_json::from_str(r#"{"error":"Invalid sample rate"}"#).unwrap();
+        assert!(matches!(response, AssemblyAiResponse::Error { .. }));
+    }
+
+    #[test]
+    fn test_capabilities_follow_speech_model() {
+        let caps = AssemblyAiProvider::new("key".to_string()).capabilities();
+        assert_eq!(caps.mode, SttMode::Streaming);
+        assert!(caps.vocabulary_hints);
+        assert!(caps.supports_language("en"));
+        assert!(!caps.supports_language("fr"));
+
+        let provider = AssemblyAiProvider::new("key".to_string())
+            .with_speech_model(Some("universal-streaming-multilingual".to_string()));
+        assert!(provider.capabilities().supports_language("fr"));
+        let url = provider.build_ws_url(None).unwrap();
+        assert!(url.as_str().contains("language_detection=true"));
+    }
+}
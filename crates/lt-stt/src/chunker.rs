@@ -1,42 +1,138 @@
-use lt_core::error::{MurmurError, Result};
-use lt_core::stt::AudioChunk;
-use std::io::Cursor;
-use tracing::debug;
+use crate::encoding;
+use lt_core::config::SttAudioEncoding;
+use lt_core::error::Result;
+use lt_core::stt::{AudioChunk, AudioFormat};
+use tracing::{debug, warn};
+
+/// Length of the analysis frames used to find pauses
+const FRAME_MS: u64 = 20;
+
+/// Share of non-quiet frames below which the app skips a chunk
+pub const DEFAULT_MIN_SPEECH_RATIO: f32 = 0.05;
+
+/// Where the chunker is allowed to cut the audio stream
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChunkBoundaryPolicy {
+    /// Preferred chunk length (ms)
+    pub target_ms: u64,
+    /// Pauses this far either side of the target are acceptable cut points (ms)
+    pub window_ms: u64,
+    /// Hard maximum; the chunk is cut here even without a pause (ms)
+    pub max_ms: u64,
+    /// Trailing quiet audio needed to count as a pause (ms)
+    pub pause_ms: u64,
+    /// RMS level (0.0-1.0) below which a frame counts as quiet
+    pub silence_threshold: f32,
+    /// Audio from the end of the previous chunk repeated at the start of the
+    /// next one, so words at the seam are heard in context (0 = off)
+    pub overlap_ms: u64,
+    /// Chunks with a smaller share of non-quiet frames are dropped instead
+    /// of uploaded (0.0 = upload everything)
+    pub min_speech_ratio: f32,
+}
+
+impl ChunkBoundaryPolicy {
+    /// Policy around `target_ms`: a window of a quarter of the target on
+    /// either side, with the hard maximum at the end of the window
+    pub fn new(target_ms: u64) -> Self {
+        let window_ms = target_ms / 4;
+        Self {
+            target_ms,
+            window_ms,
+            max_ms: target_ms + window_ms,
+            pause_ms: 200,
+            silence_threshold: 0.01,
+            overlap_ms: 0,
+            min_speech_ratio: 0.0,
+        }
+    }
+
+    /// Same policy with `overlap_ms` of the previous chunk repeated
+    pub fn with_overlap(mut self, overlap_ms: u64) -> Self {
+        self.overlap_ms = overlap_ms;
+        self
+    }
+
+    /// Same policy, skipping chunks that are mostly silence
+    pub fn with_min_speech_ratio(mut self, ratio: f32) -> Self {
+        self.min_speech_ratio = ratio.clamp(0.0, 1.0);
+        self
+    }
+}
 
 /// Audio chunker for REST APIs
-/// Accumulates audio samples and encodes them as WAV when flushed
+/// Accumulates audio samples and encodes them (WAV by default) when flushed.
+///
+/// Chunks are cut at a pause near the target length rather than at a fixed
+/// duration, so words are not split across two uploads.
 pub struct AudioChunker {
     /// Accumulated audio samples
     buffer: Vec<i16>,
-    /// Sample rate (Hz)
-    sample_rate: u32,
-    /// Chunk duration in milliseconds
-    chunk_duration_ms: u64,
-    /// Last flush timestamp
-    last_flush_ms: u64,
+    /// Format of the buffered samples (taken from the chunks)
+    format: AudioFormat,
+    /// Where chunks may be cut
+    policy: ChunkBoundaryPolicy,
+    /// Session timestamp of the first buffered sample
+    chunk_start_ms: Option<u64>,
+    /// Timestamp of the most recent chunk added
+    latest_timestamp_ms: u64,
+    /// Audio duration handed out by previous flushes
+    flushed_ms: u64,
+    /// Tail of the previous chunk, prepended to the next one
+    overlap: Vec<i16>,
+    /// Chunks dropped for containing too little speech
+    skipped_silent_chunks: u32,
+    /// Codec of flushed chunks
+    encoding: SttAudioEncoding,
 }
 
 impl AudioChunker {
     /// Create a new audio chunker
     ///
     /// # Arguments
-    /// * `chunk_duration_ms` - Duration in milliseconds before auto-flush (e.g., 3000-5000ms)
+    /// * `chunk_duration_ms` - Target duration in milliseconds (e.g., 3000-5000ms)
     pub fn new(chunk_duration_ms: u64) -> Self {
+        Self::with_policy(ChunkBoundaryPolicy::new(chunk_duration_ms))
+    }
+
+    /// Create a chunker with explicit boundary rules
+    pub fn with_policy(policy: ChunkBoundaryPolicy) -> Self {
         Self {
             buffer: Vec::new(),
-            sample_rate: 16000, // 16kHz as per spec
-            chunk_duration_ms,
-            last_flush_ms: 0,
+            format: AudioFormat::default(),
+            policy,
+            chunk_start_ms: None,
+            latest_timestamp_ms: 0,
+            flushed_ms: 0,
+            overlap: Vec::new(),
+            skipped_silent_chunks: 0,
+            encoding: SttAudioEncoding::default(),
         }
     }
 
+    /// Encode flushed chunks with `encoding` instead of WAV
+    pub fn with_encoding(mut self, encoding: SttAudioEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
     /// Add an audio chunk to the buffer
     pub fn add_chunk(&mut self, chunk: &AudioChunk) {
+        if self.buffer.is_empty() {
+            self.format = chunk.format();
+        } else if chunk.format() != self.format {
+            warn!(
+                "Audio format changed mid-chunk ({:?} -> {:?}); keeping {:?}",
+                self.format,
+                chunk.format(),
+                self.format
+            );
+        }
         self.buffer.extend_from_slice(&chunk.data);
+        self.latest_timestamp_ms = chunk.timestamp_ms;
 
-        // Update last flush timestamp if this is the first chunk
-        if self.last_flush_ms == 0 {
-            self.last_flush_ms = chunk.timestamp_ms;
+        if self.chunk_start_ms.is_none() {
+            self.chunk_start_ms = Some(chunk.timestamp_ms);
         }
 
         debug!(
@@ -46,79 +142,228 @@ impl AudioChunker {
         );
     }
 
-    /// Check if the buffer should be flushed based on duration
+    /// Check if a chunk should be cut: once past the target at the first
+    /// pause, and unconditionally at the hard maximum
     pub fn should_flush(&self, current_timestamp_ms: u64) -> bool {
         if self.buffer.is_empty() {
             return false;
         }
+        let Some(start_ms) = self.chunk_start_ms else {
+            return false;
+        };
 
-        let elapsed_ms = current_timestamp_ms.saturating_sub(self.last_flush_ms);
-        elapsed_ms >= self.chunk_duration_ms
+        let elapsed_ms = current_timestamp_ms.saturating_sub(start_ms);
+        if elapsed_ms >= self.policy.max_ms {
+            return true;
+        }
+        elapsed_ms >= self.policy.target_ms && self.ends_in_pause()
     }
 
-    /// Flush the buffer and encode as WAV
-    /// Returns the WAV bytes ready for upload
+    /// Flush the next chunk after `should_flush` returned true.
+    ///
+    /// Cuts at the buffer end if it is a pause, otherwise at the quietest
+    /// point in the window around the target. Audio after the cut stays
+    /// buffered for the next chunk.
+    pub fn flush_chunk(&mut self) -> Result<Vec<u8>> {
+        let cut = if self.ends_in_pause() {
+            self.buffer.len()
+        } else {
+            self.boundary_sample()
+        };
+        self.flush_samples(cut)
+    }
+
+    /// Flush the whole buffer and encode it
+    /// Returns the encoded bytes ready for upload
     pub fn flush(&mut self) -> Result<Vec<u8>> {
-        if self.buffer.is_empty() {
+        self.flush_samples(self.buffer.len())
+    }
+
+    /// Encode the first `count` buffered samples and drop them from the buffer
+    fn flush_samples(&mut self, count: usize) -> Result<Vec<u8>> {
+        if self.buffer.is_empty() || count == 0 {
             return Ok(Vec::new());
         }
+        let count = count.min(self.buffer.len());
+
+        let speech_ratio = self.speech_ratio(&self.buffer[..count]);
+        let wav_bytes = if speech_ratio < self.policy.min_speech_ratio {
+            debug!(
+                "Skipping {} samples with speech ratio {:.2}",
+                count, speech_ratio
+            );
+            self.skipped_silent_chunks += 1;
+            // The next chunk follows silence, so there is nothing to repeat
+            self.overlap.clear();
+            Vec::new()
+        } else {
+            debug!(
+                "Flushing {} of {} samples as {:?}",
+                count,
+                self.buffer.len(),
+                self.encoding
+            );
+
+            // Encode behind the previous chunk's tail
+            let mut samples = std::mem::take(&mut self.overlap);
+            samples.extend_from_slice(&self.buffer[..count]);
+            let wav_bytes = encoding::encode(self.encoding, self.format, &samples)?;
+
+            let overlap_samples = self.format.samples_for_ms(self.policy.overlap_ms);
+            if overlap_samples > 0 {
+                let keep_from = samples.len().saturating_sub(overlap_samples);
+                samples.drain(..keep_from);
+                self.overlap = samples;
+            }
+            wav_bytes
+        };
 
-        debug!("Flushing {} samples as WAV", self.buffer.len());
-
-        // Encode as WAV using hound
-        let wav_bytes = self.encode_wav(&self.buffer)?;
+        self.flushed_ms += self.format.duration_ms(count);
+        self.buffer.drain(..count);
 
-        // Clear the buffer
-        self.buffer.clear();
-        self.last_flush_ms = 0;
+        // The remainder starts a new chunk
+        self.chunk_start_ms = if self.buffer.is_empty() {
+            None
+        } else {
+            let remainder_ms = self.format.duration_ms(self.buffer.len());
+            Some(self.latest_timestamp_ms.saturating_sub(remainder_ms))
+        };
 
         Ok(wav_bytes)
     }
 
-    /// Get the current buffer size in samples
-    pub fn buffer_size(&self) -> usize {
-        self.buffer.len()
+    /// Share of 20 ms frames in `samples` louder than the silence threshold
+    fn speech_ratio(&self, samples: &[i16]) -> f32 {
+        let frame_len = self.format.samples_for_ms(FRAME_MS).max(1);
+        let frames = samples.len().div_ceil(frame_len);
+        if frames == 0 {
+            return 0.0;
+        }
+        let speech = samples
+            .chunks(frame_len)
+            .filter(|frame| rms(frame) >= self.policy.silence_threshold)
+            .count();
+        speech as f32 / frames as f32
     }
 
-    /// Encode PCM samples as WAV bytes
-    fn encode_wav(&self, samples: &[i16]) -> Result<Vec<u8>> {
-        let mut cursor = Cursor::new(Vec::new());
+    /// True when the trailing `pause_ms` of buffered audio is quiet
+    fn ends_in_pause(&self) -> bool {
+        let pause_samples = self.format.samples_for_ms(self.policy.pause_ms).max(1);
+        let tail_start = self.buffer.len().saturating_sub(pause_samples);
+        let tail = &self.buffer[tail_start..];
+        !tail.is_empty() && rms(tail) < self.policy.silence_threshold
+    }
 
-        {
-            let spec = hound::WavSpec {
-                channels: 1,
-                sample_rate: self.sample_rate,
-                bits_per_sample: 16,
-                sample_format: hound::SampleFormat::Int,
+    /// Sample index to cut at: the quiet frame closest to the target inside
+    /// the window, or the least loud frame there if nothing is quiet
+    fn boundary_sample(&self) -> usize {
+        let frame_len = self.format.samples_for_ms(FRAME_MS).max(1);
+        let window_start = self.policy.target_ms.saturating_sub(self.policy.window_ms);
+        let window_end = self.policy.target_ms + self.policy.window_ms;
+
+        let mut best: Option<(bool, u64, f32, usize)> = None;
+        for (index, frame) in self.buffer.chunks(frame_len).enumerate() {
+            if frame.len() < frame_len {
+                break;
+            }
+            let start = index * frame_len;
+            let position_ms = self.format.duration_ms(start + frame_len / 2);
+            if position_ms < window_start || position_ms > window_end {
+                continue;
+            }
+
+            let level = rms(frame);
+            let quiet = level < self.policy.silence_threshold;
+            let distance = position_ms.abs_diff(self.policy.target_ms);
+            let better = match best {
+                None => true,
+                Some((best_quiet, best_distance, best_level, _)) => match (quiet, best_quiet) {
+                    (true, false) => true,
+                    (false, true) => false,
+                    (true, true) => distance < best_distance,
+                    (false, false) => {
+                        level < best_level || (level == best_level && distance < best_distance)
+                    }
+                },
             };
+            if better {
+                best = Some((quiet, distance, level, start + frame_len / 2));
+            }
+        }
 
-            let mut writer = hound::WavWriter::new(&mut cursor, spec)
-                .map_err(|e| MurmurError::Stt(format!("Failed to create WAV writer: {}", e)))?;
+        best.map(|(_, _, _, sample)| sample - sample % self.format.channels.max(1) as usize)
+            .unwrap_or(self.buffer.len())
+    }
 
-            for &sample in samples {
-                writer
-                    .write_sample(sample)
-                    .map_err(|e| MurmurError::Stt(format!("Failed to write WAV sample: {}", e)))?;
-            }
+    /// Position in the session audio (ms) where the next flushed WAV starts,
+    /// including any overlap repeated from the previous chunk
+    pub fn buffer_offset_ms(&self) -> u64 {
+        self.flushed_ms
+            .saturating_sub(self.format.duration_ms(self.overlap.len()))
+    }
 
-            writer
-                .finalize()
-                .map_err(|e| MurmurError::Stt(format!("Failed to finalize WAV: {}", e)))?;
-        }
+    /// Position (ms) where new audio starts in the next flushed WAV, when it
+    /// begins with overlap from the previous chunk
+    pub fn seam_ms(&self) -> Option<u64> {
+        (!self.overlap.is_empty()).then_some(self.flushed_ms)
+    }
+
+    /// Chunks flushed so far that were dropped as silence (their flush
+    /// returned no bytes)
+    pub fn skipped_silent_chunks(&self) -> u32 {
+        self.skipped_silent_chunks
+    }
+
+    /// Get the current buffer size in samples
+    pub fn buffer_size(&self) -> usize {
+        self.buffer.len()
+    }
+}
 
-        Ok(cursor.into_inner())
+/// Root-mean-square level of `samples`, normalized to 0.0-1.0
+fn rms(samples: &[i16]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
     }
+    let sum: f64 = samples
+        .iter()
+        .map(|&s| {
+            let v = s as f64 / i16::MAX as f64;
+            v * v
+        })
+        .sum();
+    (sum / samples.len() as f64).sqrt() as f32
+}
+
+/// WAV clip of digital silence (16 kHz mono)
+pub fn silent_wav(duration_ms: u64) -> Result<Vec<u8>> {
+    silent_clip(SttAudioEncoding::Wav, duration_ms)
+}
+
+/// Clip of digital silence (16 kHz mono) in `encoding`, used to probe REST
+/// endpoints
+pub fn silent_clip(encoding: SttAudioEncoding, duration_ms: u64) -> Result<Vec<u8>> {
+    let format = AudioFormat::default();
+    let mut chunker = AudioChunker::new(duration_ms).with_encoding(encoding);
+    chunker.add_chunk(&AudioChunk {
+        data: vec![0; format.samples_for_ms(duration_ms)],
+        timestamp_ms: 0,
+        sample_rate: format.sample_rate,
+        channels: format.channels,
+    });
+    chunker.flush()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::io::Cursor;
 
     #[test]
     fn test_chunker_creation() {
         let chunker = AudioChunker::new(3000);
         assert_eq!(chunker.buffer_size(), 0);
-        assert_eq!(chunker.chunk_duration_ms, 3000);
+        assert_eq!(chunker.policy.target_ms, 3000);
     }
 
     #[test]
@@ -128,6 +373,8 @@ mod tests {
         let chunk = AudioChunk {
             data: vec![1, 2, 3, 4, 5],
             timestamp_ms: 100,
+            sample_rate: 16000,
+            channels: 1,
         };
 
         chunker.add_chunk(&chunk);
@@ -145,6 +392,8 @@ mod tests {
         let chunk = AudioChunk {
             data: vec![1, 2, 3],
             timestamp_ms: 1000,
+            sample_rate: 16000,
+            channels: 1,
         };
         chunker.add_chunk(&chunk);
 
@@ -158,6 +407,157 @@ mod tests {
         assert!(chunker.should_flush(5000));
     }
 
+    /// 400 Hz tone ("speech") or digital silence, 16 kHz mono
+    fn synthetic(speech: bool, duration_ms: u64) -> Vec<i16> {
+        (0..duration_ms * 16)
+            .map(|i| {
+                if speech {
+                    let t = i as f32 / 16000.0;
+                    ((t * 400.0 * std::f32::consts::TAU).sin() * 8000.0) as i16
+                } else {
+                    0
+                }
+            })
+            .collect()
+    }
+
+    /// Feed `pattern` in 100 ms chunks until `should_flush` fires; returns the
+    /// timestamp of the chunk that triggered it
+    fn feed_until_flush(chunker: &mut AudioChunker, pattern: &[(bool, u64)]) -> Option<u64> {
+        let audio: Vec<i16> = pattern
+            .iter()
+            .flat_map(|&(speech, ms)| synthetic(speech, ms))
+            .collect();
+        for (i, data) in audio.chunks(1600).enumerate() {
+            let timestamp_ms = i as u64 * 100;
+            chunker.add_chunk(&AudioChunk {
+                data: data.to_vec(),
+                timestamp_ms,
+                sample_rate: 16000,
+                channels: 1,
+            });
+            if chunker.should_flush(timestamp_ms) {
+                return Some(timestamp_ms);
+            }
+        }
+        None
+    }
+
+    fn wav_duration_ms(wav: Vec<u8>) -> u64 {
+        let reader = hound::WavReader::new(Cursor::new(wav)).unwrap();
+        reader.duration() as u64 * 1000 / reader.spec().sample_rate as u64
+    }
+
+    #[test]
+    fn test_flushes_at_first_pause_after_target() {
+        let mut chunker = AudioChunker::new(4000);
+        let flushed_at =
+            feed_until_flush(&mut chunker, &[(true, 4200), (false, 300), (true, 1000)]);
+
+        // Not at 4000 ms (mid-word), but once 200 ms of the pause are buffered
+        assert_eq!(flushed_at, Some(4300));
+        let wav = chunker.flush_chunk().unwrap();
+        assert_eq!(wav_duration_ms(wav), 4400);
+        assert_eq!(chunker.buffer_size(), 0);
+    }
+
+    #[test]
+    fn test_hard_max_cuts_at_pause_inside_window() {
+        let mut chunker = AudioChunker::new(4000);
+        let flushed_at =
+            feed_until_flush(&mut chunker, &[(true, 3400), (false, 300), (true, 3000)]);
+
+        // The pause came before the target, so the hard maximum triggers...
+        assert_eq!(flushed_at, Some(5000));
+        // ...and the cut goes back to that pause instead of mid-speech
+        let flushed_ms = wav_duration_ms(chunker.flush_chunk().unwrap());
+        assert!(
+            (3400..=3700).contains(&flushed_ms),
+            "cut at {} ms",
+            flushed_ms
+        );
+
+        // The speech after the pause is kept for the next chunk
+        assert_eq!(chunker.buffer_offset_ms(), flushed_ms);
+        assert_eq!(chunker.buffer_size() as u64, (5100 - flushed_ms) * 16);
+        assert!(!chunker.should_flush(5100));
+    }
+
+    #[test]
+    fn test_hard_max_without_pause_stays_in_window() {
+        let mut chunker = AudioChunker::new(4000);
+        let flushed_at = feed_until_flush(&mut chunker, &[(true, 8000)]);
+        assert_eq!(flushed_at, Some(5000));
+
+        let flushed_ms = wav_duration_ms(chunker.flush_chunk().unwrap());
+        assert!(
+            (3000..=5000).contains(&flushed_ms),
+            "cut at {} ms",
+            flushed_ms
+        );
+        assert!(chunker.buffer_size() > 0);
+    }
+
+    #[test]
+    fn test_final_flush_drains_everything() {
+        let mut chunker = AudioChunker::new(4000);
+        feed_until_flush(&mut chunker, &[(true, 1500)]);
+        let flushed_ms = wav_duration_ms(chunker.flush().unwrap());
+        assert_eq!(flushed_ms, 1500);
+        assert_eq!(chunker.buffer_size(), 0);
+    }
+
+    #[test]
+    fn test_overlap_repeats_previous_tail() {
+        let mut chunker =
+            AudioChunker::with_policy(ChunkBoundaryPolicy::new(4000).with_overlap(300));
+        feed_until_flush(&mut chunker, &[(true, 4200), (false, 300), (true, 1000)]);
+        assert_eq!(chunker.seam_ms(), None);
+        assert_eq!(wav_duration_ms(chunker.flush_chunk().unwrap()), 4400);
+
+        // The next WAV starts 300 ms early and says where the new audio begins
+        assert_eq!(chunker.buffer_offset_ms(), 4100);
+        assert_eq!(chunker.seam_ms(), Some(4400));
+        chunker.add_chunk(&AudioChunk {
+            data: synthetic(true, 1000),
+            timestamp_ms: 4400,
+            sample_rate: 16000,
+            channels: 1,
+        });
+        assert_eq!(wav_duration_ms(chunker.flush().unwrap()), 1300);
+        assert_eq!(chunker.buffer_offset_ms(), 5100);
+    }
+
+    #[test]
+    fn test_skips_chunks_without_speech() {
+        let policy = ChunkBoundaryPolicy::new(4000)
+            .with_overlap(300)
+            .with_min_speech_ratio(DEFAULT_MIN_SPEECH_RATIO);
+        let mut chunker = AudioChunker::with_policy(policy);
+
+        feed_until_flush(&mut chunker, &[(false, 4500)]);
+        assert!(chunker.flush_chunk().unwrap().is_empty());
+        assert_eq!(chunker.skipped_silent_chunks(), 1);
+        // Skipped audio still advances the session position, without overlap
+        assert_eq!(chunker.seam_ms(), None);
+        assert_eq!(chunker.buffer_offset_ms(), 4100);
+
+        // A short word in otherwise quiet audio is still uploaded
+        let mut chunker = AudioChunker::with_policy(policy);
+        feed_until_flush(&mut chunker, &[(false, 2000), (true, 300), (false, 2000)]);
+        assert!(!chunker.flush_chunk().unwrap().is_empty());
+        assert_eq!(chunker.skipped_silent_chunks(), 0);
+    }
+
+    #[test]
+    fn test_silent_wav() {
+        let wav = silent_wav(500).unwrap();
+        let reader = hound::WavReader::new(Cursor::new(wav)).unwrap();
+        assert_eq!(reader.spec().sample_rate, 16000);
+        assert_eq!(reader.spec().channels, 1);
+        assert_eq!(reader.len(), 8000);
+    }
+
     #[test]
     fn test_flush_wav_encoding() {
         let mut chunker = AudioChunker::new(3000);
@@ -166,6 +566,8 @@ mod tests {
         let chunk = AudioChunk {
             data: vec![100, 200, -100, -200, 0],
             timestamp_ms: 1000,
+            sample_rate: 16000,
+            channels: 1,
         };
         chunker.add_chunk(&chunk);
 
@@ -183,6 +585,60 @@ mod tests {
         assert_eq!(chunker.buffer_size(), 0);
     }
 
+    #[test]
+    fn test_flush_flac_encoding() {
+        let mut chunker = AudioChunker::new(3000).with_encoding(SttAudioEncoding::Flac);
+        let data: Vec<i16> = (0..1600).map(|i| ((i % 64) * 100 - 3200) as i16).collect();
+        chunker.add_chunk(&AudioChunk {
+            data: data.clone(),
+            timestamp_ms: 0,
+            sample_rate: 16000,
+            channels: 1,
+        });
+
+        let flac = chunker.flush().expect("Failed to flush");
+        let mut reader = claxon::FlacReader::new(Cursor::new(flac)).unwrap();
+        assert_eq!(reader.streaminfo().sample_rate, 16000);
+        let decoded: Vec<i16> = reader.samples().map(|s| s.unwrap() as i16).collect();
+        assert_eq!(decoded, data);
+    }
+
+    #[test]
+    fn test_buffer_offset_tracks_flushed_audio() {
+        let mut chunker = AudioChunker::new(3000);
+        assert_eq!(chunker.buffer_offset_ms(), 0);
+
+        chunker.add_chunk(&AudioChunk {
+            data: vec![0; 8000],
+            timestamp_ms: 500,
+            sample_rate: 16000,
+            channels: 1,
+        });
+        assert_eq!(chunker.buffer_offset_ms(), 0);
+
+        chunker.flush().expect("Failed to flush");
+        assert_eq!(chunker.buffer_offset_ms(), 500);
+    }
+
+    #[test]
+    fn test_wav_uses_chunk_format() {
+        let mut chunker = AudioChunker::new(3000);
+        chunker.add_chunk(&AudioChunk {
+            data: vec![0; 4800],
+            timestamp_ms: 0,
+            sample_rate: 24000,
+            channels: 2,
+        });
+
+        let wav_bytes = chunker.flush().expect("Failed to flush");
+        let reader = hound::WavReader::new(Cursor::new(wav_bytes)).unwrap();
+        assert_eq!(reader.spec().sample_rate, 24000);
+        assert_eq!(reader.spec().channels, 2);
+
+        // 4800 stereo samples at 24kHz = 100ms
+        assert_eq!(chunker.buffer_offset_ms(), 100);
+    }
+
     #[test]
     fn test_flush_empty_buffer() {
         let mut chunker = AudioChunker::new(3000);
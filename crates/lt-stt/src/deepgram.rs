@@ -0,0 +1,876 @@
+//! Deepgram live transcription over WebSocket
+//!
+//! Raw 16 kHz PCM goes up as binary frames and `Results` messages come back.
+//! Interim results (`is_final: false`) and finalized pieces of an utterance
+//! are reported as partials; `speech_final` (or `UtteranceEnd`) commits the
+//! utterance. `CloseStream` makes the server flush what it still holds
+//! before it closes the socket.
+
+use async_trait::async_trait;
+use futures_util::{SinkExt, StreamExt};
+use lt_core::error::{MurmurError, ProviderError, ProviderErrorKind, Result};
+use lt_core::stt::{
+    AudioChunk, AudioFormat, SpeakerSegment, SttCapabilities, SttHealth, SttMode, SttProvider,
+    TranscriptMetadata, TranscriptWord, TranscriptionEvent,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, Mutex};
+use tokio_tungstenite::tungstenite::{
+    handshake::client::Request,
+    protocol::{frame::coding::CloseCode, CloseFrame},
+    Message,
+};
+use tracing::{debug, error, info, warn};
+use url::Url;
+
+use crate::ws::{self, HEALTH_CHECK_TIMEOUT};
+
+/// Deepgram cloud; self-hosted deployments serve the same `/v1/listen` path
+pub const DEFAULT_BASE_URL: &str = "wss://api.deepgram.com";
+
+/// Model used when none is configured
+pub const DEFAULT_MODEL: &str = "nova-3";
+
+/// PCM rate announced in the `sample_rate` query parameter
+const INPUT_SAMPLE_RATE: u32 = 16000;
+
+/// Silence after which the server sends `UtteranceEnd`
+const UTTERANCE_END_MS: u32 = 1000;
+
+/// Deepgram drops streams that receive nothing for 10 s
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long to wait for the final results after `CloseStream`
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Control messages sent as text frames
+#[derive(Debug, Serialize)]
+#[serde(tag = "type")]
+enum DeepgramControl {
+    KeepAlive,
+    CloseStream,
+}
+
+impl DeepgramControl {
+    fn to_message(&self) -> Message {
+        Message::Text(serde_json::to_string(self).unwrap().into())
+    }
+}
+
+/// Deepgram WebSocket response types
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type")]
+enum DeepgramResponse {
+    Results(DeepgramResults),
+
+    /// Sent once the stream is closed
+    Metadata {},
+
+    UtteranceEnd {},
+
+    SpeechStarted {},
+
+    Error {
+        #[serde(default)]
+        description: String,
+        #[serde(default)]
+        message: String,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+struct DeepgramResults {
+    /// Offset of this result in the stream (seconds)
+    #[serde(default)]
+    start: f64,
+    #[serde(default)]
+    duration: f64,
+    /// The text of this span will not change any more
+    #[serde(default)]
+    is_final: bool,
+    /// The speaker paused; the utterance is complete
+    #[serde(default)]
+    speech_final: bool,
+    #[serde(default)]
+    channel: DeepgramChannel,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct DeepgramChannel {
+    #[serde(default)]
+    alternatives: Vec<DeepgramAlternative>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeepgramAlternative {
+    #[serde(default)]
+    transcript: String,
+    #[serde(default)]
+    words: Vec<DeepgramWord>,
+    /// Detected languages, with `language=multi`
+    #[serde(default)]
+    languages: Vec<String>,
+}
+
+/// Word entry of a result (times in seconds)
+#[derive(Debug, Deserialize)]
+struct DeepgramWord {
+    #[serde(default)]
+    word: String,
+    #[serde(default)]
+    punctuated_word: Option<String>,
+    #[serde(default)]
+    start: f64,
+    #[serde(default)]
+    end: f64,
+    #[serde(default)]
+    confidence: Option<f64>,
+    /// Speaker index, with `diarize=true`
+    #[serde(default)]
+    speaker: Option<u32>,
+}
+
+impl DeepgramWord {
+    fn into_transcript_word(self) -> Option<TranscriptWord> {
+        let text = self.punctuated_word.unwrap_or(self.word);
+        if text.trim().is_empty() {
+            return None;
+        }
+        Some(TranscriptWord {
+            text: text.trim().to_string(),
+            start_ms: (self.start.max(0.0) * 1000.0).round() as u64,
+            end_ms: (self.end.max(0.0) * 1000.0).round() as u64,
+            confidence: self.confidence.map(|c| c.clamp(0.0, 1.0) as f32),
+        })
+    }
+}
+
+impl DeepgramResponse {
+    /// Classify server-sent error messages; `None` for everything else
+    fn into_provider_error(self) -> Option<ProviderError> {
+        match self {
+            Self::Error {
+                description,
+                message,
+            } => {
+                let text = if description.is_empty() {
+                    message
+                } else {
+                    description
+                };
+                Some(ProviderError::new(
+                    ProviderErrorKind::Unavailable,
+                    "deepgram",
+                    text,
+                ))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Classify the close frame the server ends a stream with; `None` for a
+/// normal close
+fn classify_close(frame: Option<&CloseFrame>) -> Option<ProviderError> {
+    let frame = frame?;
+    let reason = frame.reason.to_string();
+    let kind = match frame.code {
+        CloseCode::Normal => return None,
+        // NET-0001: no audio arrived in time
+        _ if reason.contains("NET-0001") => ProviderErrorKind::Timeout,
+        // DATA-0000: the audio could not be decoded
+        CloseCode::Policy => ProviderErrorKind::BadRequest,
+        CloseCode::Error => ProviderErrorKind::Unavailable,
+        _ => ProviderErrorKind::Network,
+    };
+    Some(ProviderError::new(
+        kind,
+        "deepgram",
+        format!(
+            "Deepgram closed the stream: {} {}",
+            u16::from(frame.code),
+            reason
+        ),
+    ))
+}
+
+/// Turns results into events with segment ids.
+///
+/// Finalized pieces of the current utterance are held until it ends, so
+/// partials show the whole utterance and the commit carries all its words.
+#[derive(Debug, Default)]
+struct UtteranceTracker {
+    segment_id: u64,
+    finals: Vec<String>,
+    words: Vec<TranscriptWord>,
+    speakers: Vec<SpeakerSegment>,
+    language: Option<String>,
+    end_ms: u64,
+}
+
+impl UtteranceTracker {
+    fn metadata(&self) -> TranscriptMetadata {
+        TranscriptMetadata {
+            segment_id: Some(self.segment_id),
+            ..Default::default()
+        }
+    }
+
+    /// Finalized text of the utterance followed by `interim`
+    fn text_with(&self, interim: &str) -> String {
+        self.finals
+            .iter()
+            .map(String::as_str)
+            .chain(Some(interim).filter(|t| !t.is_empty()))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn result(&mut self, results: DeepgramResults) -> Option<TranscriptionEvent> {
+        let end_ms = ((results.start + results.duration).max(0.0) * 1000.0).round() as u64;
+        let alternative = results.channel.alternatives.into_iter().next();
+        let (transcript, words, language) = match alternative {
+            Some(a) => (
+                a.transcript.trim().to_string(),
+                a.words,
+                a.languages.into_iter().next(),
+            ),
+            None => (String::new(), Vec::new(), None),
+        };
+
+        if !results.is_final {
+            return (!transcript.is_empty()).then(|| TranscriptionEvent::Partial {
+                text: self.text_with(&transcript),
+                timestamp_ms: end_ms,
+                metadata: self.metadata(),
+            });
+        }
+
+        if !transcript.is_empty() {
+            self.finals.push(transcript);
+            for word in words {
+                let speaker = word.speaker;
+                let Some(word) = word.into_transcript_word() else {
+                    continue;
+                };
+                if let Some(speaker) = speaker {
+                    SpeakerSegment::from_word(speaker, &word).append_to(&mut self.speakers);
+                }
+                self.words.push(word);
+            }
+            self.language = language.or(self.language.take());
+            self.end_ms = end_ms;
+        }
+
+        if results.speech_final {
+            self.commit()
+        } else if self.finals.is_empty() {
+            None
+        } else {
+            Some(TranscriptionEvent::Partial {
+                text: self.text_with(""),
+                timestamp_ms: end_ms,
+                metadata: self.metadata(),
+            })
+        }
+    }
+
+    /// Emit the utterance collected so far, if any
+    fn commit(&mut self) -> Option<TranscriptionEvent> {
+        if self.finals.is_empty() {
+            return None;
+        }
+        let event = TranscriptionEvent::Committed {
+            text: self.text_with(""),
+            timestamp_ms: self.end_ms,
+            metadata: TranscriptMetadata {
+                words: std::mem::take(&mut self.words),
+                language: self.language.take(),
+                segment_id: Some(self.segment_id),
+                speakers: std::mem::take(&mut self.speakers),
+                ..Default::default()
+            },
+        };
+        self.finals.clear();
+        self.segment_id += 1;
+        Some(event)
+    }
+}
+
+/// Deepgram live transcription client
+pub struct DeepgramProvider {
+    api_key: String,
+    model: String,
+    language: Option<String>,
+    keywords: Vec<String>,
+    diarize: bool,
+    base_url: String,
+    ws_tx: Arc<Mutex<Option<mpsc::Sender<AudioChunk>>>>,
+    event_tx: Arc<Mutex<Option<mpsc::Sender<TranscriptionEvent>>>>,
+    event_rx: Arc<Mutex<Option<mpsc::Receiver<TranscriptionEvent>>>>,
+    ws_task: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
+}
+
+impl DeepgramProvider {
+    /// Create a provider for Deepgram cloud with the default model
+    pub fn new(api_key: String) -> Self {
+        Self {
+            api_key,
+            model: DEFAULT_MODEL.to_string(),
+            language: None,
+            keywords: Vec::new(),
+            diarize: false,
+            base_url: DEFAULT_BASE_URL.to_string(),
+            ws_tx: Arc::new(Mutex::new(None)),
+            event_tx: Arc::new(Mutex::new(None)),
+            event_rx: Arc::new(Mutex::new(None)),
+            ws_task: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Use `model` instead of the default (empty keeps the default)
+    pub fn with_model(mut self, model: Option<String>) -> Self {
+        if let Some(model) = model.filter(|m| !m.is_empty()) {
+            self.model = model;
+        }
+        self
+    }
+
+    /// Pin the language ("auto" or empty leaves it to the server)
+    pub fn with_language(mut self, language: Option<String>) -> Self {
+        self.language = language.filter(|l| !l.is_empty() && l != "auto");
+        self
+    }
+
+    /// Boost recognition of these words ("term" or "term:intensifier")
+    pub fn with_keywords<I, S>(mut self, keywords: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.keywords = keywords
+            .into_iter()
+            .map(|k| k.as_ref().trim().to_string())
+            .filter(|k| !k.is_empty())
+            .collect();
+        self
+    }
+
+    /// Label words by speaker
+    pub fn with_diarize(mut self, diarize: bool) -> Self {
+        self.diarize = diarize;
+        self
+    }
+
+    /// Connect to a self-hosted deployment instead of Deepgram cloud
+    pub fn with_base_url(mut self, base_url: Option<String>) -> Self {
+        if let Some(base_url) = base_url.filter(|u| !u.is_empty()) {
+            self.base_url = base_url;
+        }
+        self
+    }
+
+    /// Build WebSocket URL
+    fn build_ws_url(&self) -> Result<Url> {
+        let mut url = Url::parse(&format!(
+            "{}/v1/listen",
+            self.base_url.trim_end_matches('/')
+        ))
+        .map_err(|e| MurmurError::Stt(format!("Invalid URL: {}", e)))?;
+
+        {
+            let mut query = url.query_pairs_mut();
+            query
+                .append_pair("model", &self.model)
+                .append_pair("encoding", "linear16")
+                .append_pair("sample_rate", &INPUT_SAMPLE_RATE.to_string())
+                .append_pair("channels", "1")
+                .append_pair("interim_results", "true")
+                .append_pair("utterance_end_ms", &UTTERANCE_END_MS.to_string())
+                .append_pair("punctuate", "true")
+                .append_pair("smart_format", "true");
+            if let Some(language) = &self.language {
+                query.append_pair("language", language);
+            }
+            if self.diarize {
+                query.append_pair("diarize", "true");
+            }
+
+            // Nova-3 takes plain key terms; older models take `word:boost`
+            let key_terms = self.model.starts_with("nova-3");
+            for keyword in &self.keywords {
+                if key_terms {
+                    let term = keyword
+                        .rsplit_once(':')
+                        .filter(|(_, boost)| boost.parse::<f32>().is_ok())
+                        .map_or(keyword.as_str(), |(term, _)| term);
+                    query.append_pair("keyterm", term);
+                } else {
+                    query.append_pair("keywords", keyword);
+                }
+            }
+        }
+
+        Ok(url)
+    }
+
+    /// Authenticated WebSocket handshake request
+    fn connect_request(&self) -> Result<Request> {
+        ws::handshake_request(
+            self.build_ws_url()?.as_str(),
+            Some(("Authorization", &format!("Token {}", self.api_key))),
+        )
+    }
+}
+
+#[async_trait]
+impl SttProvider for DeepgramProvider {
+    async fn start_session(&mut self) -> Result<()> {
+        info!("Starting Deepgram STT session (model {})", self.model);
+
+        let (audio_tx, mut audio_rx) = mpsc::channel::<AudioChunk>(32);
+        *self.ws_tx.lock().await = Some(audio_tx);
+
+        let (event_tx, event_rx) = mpsc::channel::<TranscriptionEvent>(32);
+        *self.event_tx.lock().await = Some(event_tx.clone());
+        *self.event_rx.lock().await = Some(event_rx);
+
+        let ws_stream = ws::connect("deepgram", self.connect_request()?).await?;
+        info!("WebSocket connected to Deepgram");
+
+        let (mut ws_write, mut ws_read) = ws_stream.split();
+
+        let task = tokio::spawn(async move {
+            let event_tx_clone = event_tx.clone();
+            let mut receiver_task = tokio::spawn(async move {
+                let mut utterance = UtteranceTracker::default();
+                while let Some(msg) = ws_read.next().await {
+                    let event = match msg {
+                        Ok(Message::Text(text)) => {
+                            debug!("Received message: {}", text);
+                            match serde_json::from_str::<DeepgramResponse>(&text) {
+                                Ok(DeepgramResponse::Results(results)) => utterance.result(results),
+                                Ok(DeepgramResponse::UtteranceEnd {}) => utterance.commit(),
+                                Ok(DeepgramResponse::Metadata {}) => {
+                                    debug!("Deepgram stream metadata received");
+                                    None
+                                }
+                                Ok(DeepgramResponse::SpeechStarted {}) => None,
+                                Ok(other) => other.into_provider_error().map(|detail| {
+                                    error!("Deepgram error: {}", detail);
+                                    TranscriptionEvent::Error {
+                                        message: detail.message.clone(),
+                                        detail: Some(detail),
+                                    }
+                                }),
+                                Err(e) => {
+                                    warn!("Failed to parse message: {} - {}", e, text);
+                                    None
+                                }
+                            }
+                        }
+                        Ok(Message::Close(frame)) => {
+                            info!("WebSocket closed by server");
+                            if let Some(detail) = classify_close(frame.as_ref()) {
+                                error!("Deepgram error: {}", detail);
+                                let event = TranscriptionEvent::Error {
+                                    message: detail.message.clone(),
+                                    detail: Some(detail),
+                                };
+                                let _ = event_tx_clone.send(event).await;
+                            }
+                            break;
+                        }
+                        Ok(_) => {
+                            debug!("Received non-text message");
+                            None
+                        }
+                        Err(e) => {
+                            error!("WebSocket error: {}", e);
+                            let message = format!("WebSocket error: {}", e);
+                            let event = TranscriptionEvent::Error {
+                                detail: Some(ProviderError::new(
+                                    ProviderErrorKind::Network,
+                                    "deepgram",
+                                    message.clone(),
+                                )),
+                                message,
+                            };
+                            let _ = event_tx_clone.send(event).await;
+                            break;
+                        }
+                    };
+
+                    if let Some(event) = event {
+                        if let Err(e) = event_tx_clone.send(event).await {
+                            error!("Failed to send transcription event: {}", e);
+                        }
+                    }
+                }
+                if let Some(event) = utterance.commit() {
+                    let _ = event_tx_clone.send(event).await;
+                }
+                debug!("WebSocket receiver task finished");
+            });
+
+            // Send audio as raw little-endian PCM, keeping the stream alive
+            // through pauses in capture
+            loop {
+                let message = match tokio::time::timeout(KEEPALIVE_INTERVAL, audio_rx.recv()).await
+                {
+                    Ok(Some(chunk)) => {
+                        let pcm_bytes: Vec<u8> =
+                            chunk.data.iter().flat_map(|s| s.to_le_bytes()).collect();
+                        Message::Binary(pcm_bytes.into())
+                    }
+                    Ok(None) => break,
+                    Err(_) => DeepgramControl::KeepAlive.to_message(),
+                };
+
+                if let Err(e) = ws_write.send(message).await {
+                    error!("Failed to send audio chunk: {}", e);
+                    break;
+                }
+            }
+
+            // The server answers CloseStream with the remaining results and
+            // a Metadata message, then closes the socket itself
+            debug!("Audio sender finished, sending CloseStream");
+            if let Err(e) = ws_write
+                .send(DeepgramControl::CloseStream.to_message())
+                .await
+            {
+                warn!("Failed to send CloseStream: {}", e);
+            }
+
+            match tokio::time::timeout(CLOSE_TIMEOUT, &mut receiver_task).await {
+                Ok(Err(e)) => warn!("Receiver task error: {}", e),
+                Ok(Ok(())) => {}
+                Err(_) => {
+                    warn!("Receiver task timed out during shutdown");
+                    receiver_task.abort();
+                }
+            }
+
+            let _ = ws_write.close().await;
+            info!("WebSocket task finished");
+        });
+
+        *self.ws_task.lock().await = Some(task);
+
+        Ok(())
+    }
+
+    async fn send_audio(&mut self, chunk: AudioChunk) -> Result<()> {
+        let tx_lock = self.ws_tx.lock().await;
+        if let Some(tx) = tx_lock.as_ref() {
+            tx.send(chunk)
+                .await
+                .map_err(|e| MurmurError::Stt(format!("Failed to send audio chunk: {}", e)))?;
+            Ok(())
+        } else {
+            Err(MurmurError::Stt("Session not started".to_string()))
+        }
+    }
+
+    async fn stop_session(&mut self) -> Result<()> {
+        info!("Stopping Deepgram STT session");
+
+        // Closing the audio channel makes the task send CloseStream
+        *self.ws_tx.lock().await = None;
+
+        if let Some(task) = self.ws_task.lock().await.take() {
+            let _ = task.await;
+        }
+
+        info!("Deepgram STT session stopped");
+        Ok(())
+    }
+
+    async fn subscribe_events(&self) -> mpsc::Receiver<TranscriptionEvent> {
+        let mut rx_lock = self.event_rx.lock().await;
+        rx_lock
+            .take()
+            .expect("subscribe_events called multiple times")
+    }
+
+    fn input_format(&self) -> AudioFormat {
+        AudioFormat::mono(INPUT_SAMPLE_RATE)
+    }
+
+    async fn health_check(&self) -> Result<SttHealth> {
+        let timed_out = || ws::health_check_timed_out("deepgram");
+
+        // The handshake checks the key; closing the empty stream right away
+        // makes the server answer with Metadata (or an error)
+        let started = Instant::now();
+        let mut ws_stream = tokio::time::timeout(
+            HEALTH_CHECK_TIMEOUT,
+            ws::connect("deepgram", self.connect_request()?),
+        )
+        .await
+        .map_err(|_| timed_out())??;
+        let _ = ws_stream
+            .send(DeepgramControl::CloseStream.to_message())
+            .await;
+        let first = tokio::time::timeout(HEALTH_CHECK_TIMEOUT, ws_stream.next())
+            .await
+            .map_err(|_| timed_out())?;
+        let latency_ms = started.elapsed().as_millis() as u64;
+        let _ = ws_stream.close(None).await;
+
+        match first {
+            Some(Ok(Message::Text(text))) => {
+                let server_error = serde_json::from_str::<DeepgramResponse>(&text)
+                    .ok()
+                    .and_then(DeepgramResponse::into_provider_error);
+                match server_error {
+                    Some(e) => Err(e.into()),
+                    None => Ok(SttHealth { latency_ms }),
+                }
+            }
+            Some(Ok(Message::Close(frame))) => match classify_close(frame.as_ref()) {
+                Some(e) => Err(e.into()),
+                None => Ok(SttHealth { latency_ms }),
+            },
+            Some(Ok(_)) => Ok(SttHealth { latency_ms }),
+            Some(Err(e)) => Err(ProviderError::new(
+                ProviderErrorKind::Network,
+                "deepgram",
+                format!("WebSocket error: {}", e),
+            )
+            .into()),
+            None => Err(ProviderError::new(
+                ProviderErrorKind::Network,
+                "deepgram",
+                "Connection closed before the stream was acknowledged",
+            )
+            .into()),
+        }
+    }
+
+    fn capabilities(&self) -> SttCapabilities {
+        SttCapabilities {
+            mode: SttMode::Streaming,
+            partial_results: true,
+            // A pinned language is all the stream will be asked for
+            languages: self.language.iter().cloned().collect(),
+            word_timestamps: true,
+            vocabulary_hints: true,
+            diarization: true,
+            local: false,
+            requires_api_key: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn results(json: &str) -> DeepgramResults {
+        match serde_json::from_str(json).unwrap() {
+            DeepgramResponse::Results(results) => results,
+            other => panic!("Unexpected response {:?}", other),
+        }
+    }
+
+    fn result_json(transcript: &str, is_final: bool, speech_final: bool) -> String {
+        serde_json::json!({
+            "type": "Results",
+            "start": 1.0,
+            "duration": 0.5,
+            "is_final": is_final,
+            "speech_final": speech_final,
+            "channel": {"alternatives": [{"transcript": transcript, "words": []}]}
+        })
+        .to_string()
+    }
+
+    fn query_pairs(provider: &DeepgramProvider) -> Vec<(String, String)> {
+        provider
+            .build_ws_url()
+            .unwrap()
+            .query_pairs()
+            .map(|(k, v)| (k.into_owned(), v.into_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn test_url_carries_model_language_and_key_terms() {
+        let provider = DeepgramProvider::new("key".to_string())
+            .with_language(Some("de".to_string()))
+            .with_keywords(["Murmur:2", "Tauri", " "]);
+        let url = provider.build_ws_url().unwrap();
+        assert_eq!(
+            url.as_str().split('?').next(),
+            Some("wss://api.deepgram.com/v1/listen")
+        );
+
+        let pairs = query_pairs(&provider);
+        let has = |k: &str, v: &str| pairs.iter().any(|(pk, pv)| pk == k && pv == v);
+        assert!(has("model", "nova-3"));
+        assert!(has("language", "de"));
+        assert!(has("interim_results", "true"));
+        assert!(has("sample_rate", "16000"));
+        // Nova-3 drops the intensifier
+        assert!(has("keyterm", "Murmur"));
+        assert!(has("keyterm", "Tauri"));
+        assert!(!pairs.iter().any(|(k, _)| k == "keywords"));
+        assert!(!pairs.iter().any(|(k, _)| k == "diarize"));
+
+        let pairs = query_pairs(&provider.with_diarize(true));
+        assert!(pairs.contains(&("diarize".to_string(), "true".to_string())));
+    }
+
+    #[test]
+    fn test_older_models_use_boosted_keywords() {
+        let provider = DeepgramProvider::new("key".to_string())
+            .with_model(Some("nova-2".to_string()))
+            .with_language(Some("auto".to_string()))
+            .with_keywords(["Murmur:2"])
+            .with_base_url(Some("ws://127.0.0.1:9000/".to_string()));
+        let url = provider.build_ws_url().unwrap();
+        assert!(url.as_str().starts_with("ws://127.0.0.1:9000/v1/listen?"));
+
+        let pairs = query_pairs(&provider);
+        assert!(pairs.contains(&("keywords".to_string(), "Murmur:2".to_string())));
+        assert!(!pairs.iter().any(|(k, _)| k == "language"));
+    }
+
+    #[test]
+    fn test_interim_and_final_results_build_one_utterance() {
+        let mut utterance = UtteranceTracker::default();
+
+        let event = utterance
+            .result(results(&result_json("hel", false, false)))
+            .unwrap();
+        assert!(matches!(event, TranscriptionEvent::Partial { ref text, .. } if text == "hel"));
+
+        let event = utterance
+            .result(results(&result_json("hello there", true, false)))
+            .unwrap();
+        assert!(
+            matches!(event, TranscriptionEvent::Partial { ref text, .. } if text == "hello there")
+        );
+
+        // Interim text of the next span follows the finalized text
+        let event = utterance
+            .result(results(&result_json("gen", false, false)))
+            .unwrap();
+        assert!(matches!(
+            event,
+            TranscriptionEvent::Partial { ref text, timestamp_ms: 1500, .. }
+                if text == "hello there gen"
+        ));
+
+        let event = utterance
+            .result(results(&result_json("general Kenobi", true, true)))
+            .unwrap();
+        match event {
+            TranscriptionEvent::Committed { text, metadata, .. } => {
+                assert_eq!(text, "hello there general Kenobi");
+                assert_eq!(metadata.segment_id, Some(0));
+            }
+            other => panic!("Unexpected event {:?}", other),
+        }
+
+        // Empty results carry nothing; the next utterance gets a new id
+        assert!(utterance
+            .result(results(&result_json("", true, true)))
+            .is_none());
+        utterance.result(results(&result_json("next", true, false)));
+        match utterance.commit().unwrap() {
+            TranscriptionEvent::Committed { metadata, .. } => {
+                assert_eq!(metadata.segment_id, Some(1))
+            }
+            other => panic!("Unexpected event {:?}", other),
+        }
+        assert!(utterance.commit().is_none());
+    }
+
+    #[test]
+    fn test_committed_words_prefer_punctuated_text() {
+        let mut utterance = UtteranceTracker::default();
+        let json = r#"{"type":"Results","start":0.0,"duration":1.0,"is_final":true,
+            "speech_final":true,"channel":{"alternatives":[{"transcript":"Hello, world.",
+            "languages":["en"],"words":[
+                {"word":"hello","punctuated_word":"Hello,","start":0.1,"end":0.4,"confidence":0.98},
+                {"word":"world","punctuated_word":"world.","start":0.5,"end":0.9,"confidence":0.4}
+            ]}]}}"#;
+        match utterance.result(results(json)).unwrap() {
+            TranscriptionEvent::Committed { metadata, .. } => {
+                assert_eq!(metadata.words.len(), 2);
+                assert_eq!(metadata.words[0].text, "Hello,");
+                assert_eq!(metadata.words[1].start_ms, 500);
+                assert!(metadata.words[1].confidence.unwrap() < 0.5);
+                assert_eq!(metadata.language.as_deref(), Some("en"));
+            }
+            other => panic!("Unexpected event {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_diarized_words_become_speaker_turns() {
+        let mut utterance = UtteranceTracker::default();
+        let json = r#"{"type":"Results","start":0.0,"duration":2.0,"is_final":true,
+            "speech_final":true,"channel":{"alternatives":[{"transcript":"Ready? Yes, go.",
+            "words":[
+                {"word":"ready","punctuated_word":"Ready?","start":0.1,"end":0.4,"speaker":0},
+                {"word":"yes","punctuated_word":"Yes,","start":0.8,"end":1.0,"speaker":1},
+                {"word":"go","punctuated_word":"go.","start":1.1,"end":1.3,"speaker":1}
+            ]}]}}"#;
+        match utterance.result(results(json)).unwrap() {
+            TranscriptionEvent::Committed { metadata, .. } => {
+                assert_eq!(metadata.speakers.len(), 2);
+                assert_eq!(metadata.speakers[0].speaker, 0);
+                assert_eq!(metadata.speakers[0].text, "Ready?");
+                assert_eq!(metadata.speakers[1].speaker, 1);
+                assert_eq!(metadata.speakers[1].text, "Yes, go.");
+                assert_eq!(metadata.speakers[1].start_ms, 800);
+                assert_eq!(metadata.speakers[1].end_ms, 1300);
+            }
+            other => panic!("Unexpected event {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_close_frames_are_classified() {
+        let frame = |code: u16, reason: &str| CloseFrame {
+            code: CloseCode::from(code),
+            reason: reason.to_string().into(),
+        };
+        assert!(classify_close(None).is_none());
+        assert!(classify_close(Some(&frame(1000, ""))).is_none());
+        assert_eq!(
+            classify_close(Some(&frame(1008, "DATA-0000")))
+                .unwrap()
+                .kind,
+            ProviderErrorKind::BadRequest
+        );
+        assert_eq!(
+            classify_close(Some(&frame(1011, "NET-0001"))).unwrap().kind,
+            ProviderErrorKind::Timeout
+        );
+        assert_eq!(
+            classify_close(Some(&frame(1011, "NET-0000"))).unwrap().kind,
+            ProviderErrorKind::Unavailable
+        );
+    }
+
+    #[test]
+    fn test_capabilities() {
+        let caps = DeepgramProvider::new("key".to_string()).capabilities();
+        assert_eq!(caps.mode, SttMode::Streaming);
+        assert!(caps.vocabulary_hints);
+        assert!(caps.supports_language("fr"));
+
+        let caps = DeepgramProvider::new("key".to_string())
+            .with_language(Some("en-US".to_string()))
+            .capabilities();
+        assert!(caps.supports_language("en-US"));
+        assert!(!caps.supports_language("fr"));
+    }
+}
@@ -1,17 +1,145 @@
+use crate::stitch;
 use async_trait::async_trait;
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use futures_util::{SinkExt, StreamExt};
-use lt_core::error::{MurmurError, Result};
-use lt_core::stt::{AudioChunk, SttProvider, TranscriptionEvent};
+use lt_core::error::{MurmurError, ProviderError, ProviderErrorKind, Result};
+use lt_core::stt::{
+    AudioChunk, AudioFormat, SpeakerSegment, SttCapabilities, SttHealth, SttMode, SttProvider,
+    TranscriptMetadata, TranscriptWord, TranscriptionEvent,
+};
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::{mpsc, Mutex};
-use tokio_tungstenite::{
-    connect_async, tungstenite::client::IntoClientRequest, tungstenite::Message,
-};
+use tokio_tungstenite::tungstenite::{handshake::client::Request, Message};
 use tracing::{debug, error, info, warn};
 use url::Url;
 
+use crate::ws::{self, WsStream, HEALTH_CHECK_TIMEOUT};
+
+/// PCM rate announced in the `audio_format=pcm_16000` query parameter
+const INPUT_SAMPLE_RATE: u32 = 16000;
+
+/// ElevenLabs API; the realtime endpoint lives under `/v1/speech-to-text`
+pub const DEFAULT_BASE_URL: &str = "wss://api.elevenlabs.io";
+
+/// Uncommitted audio kept for replay after a reconnect
+const REPLAY_BUFFER_MS: u64 = 60_000;
+
+/// Audio kept before the point a commit arrived at when the server sent no
+/// word times; repeated words are dropped at the seam
+const REPLAY_OVERLAP_MS: u64 = 3000;
+
+/// Wait after the final commit before closing the socket
+const COMMIT_GRACE: Duration = Duration::from_millis(500);
+
+/// How long to wait for the server to close after we do
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Languages supported by Scribe v2 realtime (ISO 639-3 code, display name)
+pub const SUPPORTED_LANGUAGES: &[(&str, &str)] = &[
+    ("afr", "Afrikaans"),
+    ("amh", "Amharic"),
+    ("ara", "Arabic"),
+    ("hye", "Armenian"),
+    ("asm", "Assamese"),
+    ("ast", "Asturian"),
+    ("aze", "Azerbaijani"),
+    ("bel", "Belarusian"),
+    ("ben", "Bengali"),
+    ("bos", "Bosnian"),
+    ("bul", "Bulgarian"),
+    ("mya", "Burmese"),
+    ("yue", "Cantonese"),
+    ("cat", "Catalan"),
+    ("ceb", "Cebuano"),
+    ("nya", "Chichewa"),
+    ("hrv", "Croatian"),
+    ("ces", "Czech"),
+    ("dan", "Danish"),
+    ("nld", "Dutch"),
+    ("eng", "English"),
+    ("est", "Estonian"),
+    ("fil", "Filipino"),
+    ("fin", "Finnish"),
+    ("fra", "French"),
+    ("ful", "Fulah"),
+    ("glg", "Galician"),
+    ("lug", "Ganda"),
+    ("kat", "Georgian"),
+    ("deu", "German"),
+    ("ell", "Greek"),
+    ("guj", "Gujarati"),
+    ("hau", "Hausa"),
+    ("heb", "Hebrew"),
+    ("hin", "Hindi"),
+    ("hun", "Hungarian"),
+    ("isl", "Icelandic"),
+    ("ibo", "Igbo"),
+    ("ind", "Indonesian"),
+    ("gle", "Irish"),
+    ("ita", "Italian"),
+    ("jpn", "Japanese"),
+    ("jav", "Javanese"),
+    ("kea", "Kabuverdianu"),
+    ("kan", "Kannada"),
+    ("kaz", "Kazakh"),
+    ("khm", "Khmer"),
+    ("kor", "Korean"),
+    ("kur", "Kurdish"),
+    ("kir", "Kyrgyz"),
+    ("lao", "Lao"),
+    ("lav", "Latvian"),
+    ("lin", "Lingala"),
+    ("lit", "Lithuanian"),
+    ("luo", "Luo"),
+    ("ltz", "Luxembourgish"),
+    ("mkd", "Macedonian"),
+    ("msa", "Malay"),
+    ("mal", "Malayalam"),
+    ("mlt", "Maltese"),
+    ("zho", "Mandarin Chinese"),
+    ("mri", "Māori"),
+    ("mar", "Marathi"),
+    ("mon", "Mongolian"),
+    ("nep", "Nepali"),
+    ("nso", "Northern Sotho"),
+    ("nor", "Norwegian"),
+    ("oci", "Occitan"),
+    ("ori", "Odia"),
+    ("pus", "Pashto"),
+    ("fas", "Persian"),
+    ("pol", "Polish"),
+    ("por", "Portuguese"),
+    ("pan", "Punjabi"),
+    ("ron", "Romanian"),
+    ("rus", "Russian"),
+    ("srp", "Serbian"),
+    ("sna", "Shona"),
+    ("snd", "Sindhi"),
+    ("slk", "Slovak"),
+    ("slv", "Slovenian"),
+    ("som", "Somali"),
+    ("spa", "Spanish"),
+    ("swa", "Swahili"),
+    ("swe", "Swedish"),
+    ("tgk", "Tajik"),
+    ("tam", "Tamil"),
+    ("tel", "Telugu"),
+    ("tha", "Thai"),
+    ("tur", "Turkish"),
+    ("ukr", "Ukrainian"),
+    ("umb", "Umbundu"),
+    ("urd", "Urdu"),
+    ("uzb", "Uzbek"),
+    ("vie", "Vietnamese"),
+    ("cym", "Welsh"),
+    ("wol", "Wolof"),
+    ("xho", "Xhosa"),
+    ("zul", "Zulu"),
+];
+
 /// ElevenLabs WebSocket message types
 #[derive(Debug, Serialize)]
 struct ElevenLabsMessage {
@@ -42,17 +170,210 @@ enum ElevenLabsResponse {
         text: String,
     },
 
-    #[serde(rename = "error")]
+    /// Sent after `committed_transcript` when `include_timestamps=true`
+    #[serde(rename = "committed_transcript_with_timestamps")]
+    CommittedTranscriptWithTimestamps {
+        #[serde(default)]
+        text: String,
+        #[serde(default)]
+        language_code: Option<String>,
+        #[serde(default)]
+        words: Vec<ElevenLabsWord>,
+    },
+
+    #[serde(rename = "error", alias = "transcriber_error")]
     Error {
         #[serde(default)]
         error: String,
     },
 
-    #[serde(rename = "invalid_request")]
+    #[serde(
+        rename = "invalid_request",
+        alias = "input_error",
+        alias = "chunk_size_exceeded"
+    )]
     InvalidRequest {
         #[serde(default)]
         error: String,
     },
+
+    #[serde(rename = "auth_error")]
+    AuthError {
+        #[serde(default)]
+        error: String,
+    },
+
+    #[serde(rename = "quota_exceeded")]
+    QuotaExceeded {
+        #[serde(default)]
+        error: String,
+    },
+
+    #[serde(
+        rename = "rate_limited",
+        alias = "commit_throttled",
+        alias = "queue_overflow",
+        alias = "resource_exhausted"
+    )]
+    RateLimited {
+        #[serde(default)]
+        error: String,
+    },
+
+    #[serde(rename = "unaccepted_terms")]
+    UnacceptedTerms {
+        #[serde(default)]
+        error: String,
+    },
+}
+
+/// Word entry of `committed_transcript_with_timestamps` (times in seconds)
+#[derive(Debug, Deserialize)]
+struct ElevenLabsWord {
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    start: f64,
+    #[serde(default)]
+    end: f64,
+    /// "word", "spacing" or "audio_event"
+    #[serde(default, rename = "type")]
+    kind: Option<String>,
+    #[serde(default)]
+    logprob: Option<f64>,
+    /// "speaker_0", "speaker_1", ... with `diarize=true`
+    #[serde(default)]
+    speaker_id: Option<String>,
+}
+
+impl ElevenLabsWord {
+    fn speaker(&self) -> Option<u32> {
+        self.speaker_id
+            .as_deref()?
+            .strip_prefix("speaker_")?
+            .parse()
+            .ok()
+    }
+
+    fn into_transcript_word(self) -> Option<TranscriptWord> {
+        if self.kind.as_deref().is_some_and(|k| k != "word") || self.text.trim().is_empty() {
+            return None;
+        }
+        Some(TranscriptWord {
+            text: self.text.trim().to_string(),
+            start_ms: (self.start.max(0.0) * 1000.0).round() as u64,
+            end_ms: (self.end.max(0.0) * 1000.0).round() as u64,
+            confidence: self.logprob.map(|lp| lp.exp().clamp(0.0, 1.0) as f32),
+        })
+    }
+}
+
+/// Turns server transcripts into events with segment ids.
+///
+/// With timestamps enabled the server commits every segment twice (plain,
+/// then with words), so the plain commit is held back until the timestamped
+/// one replaces it or something else arrives.
+#[derive(Debug, Default)]
+struct SegmentTracker {
+    segment_id: u64,
+    pending_commit: Option<String>,
+}
+
+impl SegmentTracker {
+    fn metadata(&self) -> TranscriptMetadata {
+        TranscriptMetadata {
+            segment_id: Some(self.segment_id),
+            ..Default::default()
+        }
+    }
+
+    /// Emit the held-back plain commit, if any
+    fn flush(&mut self) -> Option<TranscriptionEvent> {
+        let text = self.pending_commit.take()?;
+        let event = TranscriptionEvent::Committed {
+            text,
+            timestamp_ms: 0,
+            metadata: self.metadata(),
+        };
+        self.segment_id += 1;
+        Some(event)
+    }
+
+    fn partial(&mut self, text: String) -> Vec<TranscriptionEvent> {
+        let mut events: Vec<_> = self.flush().into_iter().collect();
+        events.push(TranscriptionEvent::Partial {
+            text,
+            timestamp_ms: 0,
+            metadata: self.metadata(),
+        });
+        events
+    }
+
+    fn committed(&mut self, text: String) -> Option<TranscriptionEvent> {
+        let previous = self.flush();
+        self.pending_commit = Some(text);
+        previous
+    }
+
+    fn committed_with_timestamps(
+        &mut self,
+        text: String,
+        language_code: Option<String>,
+        words: Vec<ElevenLabsWord>,
+    ) -> Option<TranscriptionEvent> {
+        // Prefer the server's timestamped text; fall back to the held commit
+        let held = self.pending_commit.take();
+        let text = if text.is_empty() { held? } else { text };
+        let mut transcript_words = Vec::new();
+        let mut speakers = Vec::new();
+        for word in words {
+            let speaker = word.speaker();
+            let Some(word) = word.into_transcript_word() else {
+                continue;
+            };
+            if let Some(speaker) = speaker {
+                SpeakerSegment::from_word(speaker, &word).append_to(&mut speakers);
+            }
+            transcript_words.push(word);
+        }
+        let event = TranscriptionEvent::Committed {
+            text,
+            timestamp_ms: 0,
+            metadata: TranscriptMetadata {
+                words: transcript_words,
+                language: language_code.filter(|l| !l.is_empty()),
+                segment_id: Some(self.segment_id),
+                speakers,
+                ..Default::default()
+            },
+        };
+        self.segment_id += 1;
+        Some(event)
+    }
+}
+
+impl ElevenLabsResponse {
+    /// Classify server-sent error messages; `None` for transcript/session messages
+    fn into_provider_error(self) -> Option<ProviderError> {
+        let (kind, error) = match self {
+            Self::SessionStarted {}
+            | Self::PartialTranscript { .. }
+            | Self::CommittedTranscript { .. }
+            | Self::CommittedTranscriptWithTimestamps { .. } => return None,
+            Self::Error { error } => (ProviderErrorKind::Unavailable, error),
+            Self::InvalidRequest { error } => (ProviderErrorKind::BadRequest, error),
+            Self::AuthError { error } => (ProviderErrorKind::Auth, error),
+            Self::QuotaExceeded { error } => (ProviderErrorKind::Quota, error),
+            Self::RateLimited { error } => (
+                ProviderErrorKind::RateLimited {
+                    retry_after_secs: None,
+                },
+                error,
+            ),
+            Self::UnacceptedTerms { error } => (ProviderErrorKind::Permission, error),
+        };
+        Some(ProviderError::new(kind, "elevenlabs", error))
+    }
 }
 
 /// Reconnection configuration
@@ -73,11 +394,467 @@ impl Default for ReconnectConfig {
     }
 }
 
+/// Opens (and re-opens) the session's WebSocket
+#[derive(Clone)]
+struct Connector {
+    url: Url,
+    api_key: String,
+    reconnect_config: ReconnectConfig,
+    should_reconnect: Arc<Mutex<bool>>,
+}
+
+impl Connector {
+    /// Authenticated WebSocket handshake request
+    fn request(&self) -> Result<Request> {
+        ws::handshake_request(self.url.as_str(), Some(("xi-api-key", &self.api_key)))
+    }
+
+    /// Connect to WebSocket with retry logic. Once the session is stopping
+    /// a failed attempt is not retried.
+    async fn connect_with_retry(&self) -> Result<WsStream> {
+        let mut retry_count = 0;
+
+        loop {
+            match ws::connect("elevenlabs", self.request()?).await {
+                Ok(ws_stream) => {
+                    info!("WebSocket connected to ElevenLabs");
+                    return Ok(ws_stream);
+                }
+                Err(classified) => {
+                    if !classified.retryable {
+                        error!("WebSocket connection rejected: {}", classified);
+                        return Err(classified.into());
+                    }
+
+                    if retry_count >= self.reconnect_config.max_retries
+                        || !*self.should_reconnect.lock().await
+                    {
+                        error!("Failed to connect after {} retries", retry_count);
+                        return Err(ProviderError {
+                            message: format!(
+                                "{} (gave up after {} retries)",
+                                classified.message, retry_count
+                            ),
+                            ..classified
+                        }
+                        .into());
+                    }
+
+                    let delay = std::cmp::min(
+                        self.reconnect_config.base_delay_ms * 2u64.pow(retry_count),
+                        self.reconnect_config.max_delay_ms,
+                    );
+
+                    warn!(
+                        "WebSocket connection failed (attempt {}/{}), retrying in {}ms: {}",
+                        retry_count + 1,
+                        self.reconnect_config.max_retries,
+                        delay,
+                        classified
+                    );
+
+                    tokio::time::sleep(tokio::time::Duration::from_millis(delay)).await;
+                    retry_count += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Audio the server has not committed yet, resent after a reconnect.
+///
+/// Bounded to `max_ms`; older audio is dropped and cannot be recovered.
+struct ReplayBuffer {
+    /// Chunks with their offset in the session (ms)
+    chunks: VecDeque<(u64, AudioChunk)>,
+    /// Session audio received so far (ms)
+    end_ms: u64,
+    max_ms: u64,
+}
+
+impl ReplayBuffer {
+    fn new(max_ms: u64) -> Self {
+        Self {
+            chunks: VecDeque::new(),
+            end_ms: 0,
+            max_ms,
+        }
+    }
+
+    fn push(&mut self, chunk: AudioChunk) {
+        let duration_ms = chunk.format().duration_ms(chunk.data.len());
+        self.chunks.push_back((self.end_ms, chunk));
+        self.end_ms += duration_ms;
+        let keep_from_ms = self.end_ms.saturating_sub(self.max_ms);
+        self.release_until(keep_from_ms);
+    }
+
+    /// Drop chunks that end at or before `until_ms`
+    fn release_until(&mut self, until_ms: u64) {
+        while let Some((offset_ms, chunk)) = self.chunks.front() {
+            if offset_ms + chunk.format().duration_ms(chunk.data.len()) > until_ms {
+                break;
+            }
+            self.chunks.pop_front();
+        }
+    }
+
+    /// Session offset the buffered audio starts at
+    fn start_ms(&self) -> u64 {
+        self.chunks
+            .front()
+            .map_or(self.end_ms, |(offset_ms, _)| *offset_ms)
+    }
+}
+
+/// Why a connection ended
+enum ConnectionEnd {
+    /// The session finished (final commit sent, socket closed)
+    Finished,
+    /// The socket dropped before the session finished
+    Dropped(String),
+}
+
+/// Session state that survives reconnects
+struct Session {
+    event_tx: mpsc::Sender<TranscriptionEvent>,
+    segments: SegmentTracker,
+    replay: ReplayBuffer,
+    /// Session offset the current connection's audio starts at (ms);
+    /// server word times are relative to it
+    connection_start_ms: u64,
+    /// Text committed so far, to drop what replayed audio repeats
+    committed_text: String,
+    /// Set after a reconnect until the first commit
+    dedupe_seam: bool,
+    /// The server reported an error; reconnecting would not help
+    server_error: bool,
+    /// The audio channel is closed and the final commit is due
+    audio_done: bool,
+    /// A commit arrived after the final commit was requested
+    final_commit_received: bool,
+}
+
+impl Session {
+    fn new(event_tx: mpsc::Sender<TranscriptionEvent>) -> Self {
+        Self {
+            event_tx,
+            segments: SegmentTracker::default(),
+            replay: ReplayBuffer::new(REPLAY_BUFFER_MS),
+            connection_start_ms: 0,
+            committed_text: String::new(),
+            dedupe_seam: false,
+            server_error: false,
+            audio_done: false,
+            final_commit_received: false,
+        }
+    }
+
+    /// Run the session over `ws_stream`, reconnecting and replaying the
+    /// uncommitted audio whenever the socket drops
+    async fn run(
+        mut self,
+        connector: Connector,
+        mut ws_stream: WsStream,
+        mut audio_rx: mpsc::Receiver<AudioChunk>,
+    ) {
+        let mut reconnects = 0;
+        loop {
+            let reason = match self.run_connection(ws_stream, &mut audio_rx).await {
+                ConnectionEnd::Finished => break,
+                ConnectionEnd::Dropped(reason) => reason,
+            };
+            if let Some(event) = self.segments.flush() {
+                self.emit(event).await;
+            }
+            if self.server_error {
+                break;
+            }
+            if reconnects >= connector.reconnect_config.max_retries {
+                self.fail(format!(
+                    "{} (gave up after {} reconnects)",
+                    reason, reconnects
+                ))
+                .await;
+                break;
+            }
+            reconnects += 1;
+            warn!(
+                "ElevenLabs connection lost ({}), reconnecting (attempt {}/{})",
+                reason, reconnects, connector.reconnect_config.max_retries
+            );
+
+            // Keep taking audio while reconnecting so capture never stalls
+            let connect = connector.connect_with_retry();
+            tokio::pin!(connect);
+            let connected = loop {
+                tokio::select! {
+                    result = &mut connect => break result,
+                    chunk = audio_rx.recv(), if !self.audio_done => match chunk {
+                        Some(chunk) => self.replay.push(chunk),
+                        None => self.audio_done = true,
+                    },
+                }
+            };
+            match connected {
+                Ok(stream) => {
+                    ws_stream = stream;
+                    self.connection_start_ms = self.replay.start_ms();
+                    self.dedupe_seam = !self.committed_text.is_empty();
+                }
+                Err(e) => {
+                    let detail = e.provider_error().cloned();
+                    let _ = self
+                        .event_tx
+                        .send(TranscriptionEvent::Error {
+                            message: e.to_string(),
+                            detail,
+                        })
+                        .await;
+                    break;
+                }
+            }
+        }
+
+        if let Some(event) = self.segments.flush() {
+            self.emit(event).await;
+        }
+        info!("WebSocket task finished");
+    }
+
+    /// Stream audio over one connection until the session finishes or the
+    /// socket drops. Audio buffered from an earlier connection goes first.
+    async fn run_connection(
+        &mut self,
+        ws_stream: WsStream,
+        audio_rx: &mut mpsc::Receiver<AudioChunk>,
+    ) -> ConnectionEnd {
+        let (mut ws_write, mut ws_read) = ws_stream.split();
+
+        if !self.replay.chunks.is_empty() {
+            info!(
+                "Replaying {} ms of uncommitted audio",
+                self.replay.end_ms - self.replay.start_ms()
+            );
+        }
+        for (_, chunk) in &self.replay.chunks {
+            if let Err(e) = ws_write.send(audio_message(chunk)).await {
+                return ConnectionEnd::Dropped(format!("Failed to send audio chunk: {}", e));
+            }
+        }
+
+        // Time to wait for the final commit before closing, then for the close
+        let mut commit_deadline = None;
+        let mut close_deadline = None;
+        if self.audio_done {
+            if let Err(e) = ws_write.send(commit_message()).await {
+                return ConnectionEnd::Dropped(format!("Failed to send commit message: {}", e));
+            }
+            commit_deadline = Some(tokio::time::Instant::now() + COMMIT_GRACE);
+        }
+
+        loop {
+            tokio::select! {
+                chunk = audio_rx.recv(), if !self.audio_done => match chunk {
+                    Some(chunk) => {
+                        let message = audio_message(&chunk);
+                        self.replay.push(chunk);
+                        if let Err(e) = ws_write.send(message).await {
+                            return ConnectionEnd::Dropped(format!("Failed to send audio chunk: {}", e));
+                        }
+                    }
+                    None => {
+                        debug!("Audio sender finished, sending commit signal");
+                        self.audio_done = true;
+                        // Send a final commit message to flush the server's
+                        // transcription buffer. With vad_commit_strategy
+                        // disabled, the server won't auto-commit; we must
+                        // explicitly request it.
+                        if let Err(e) = ws_write.send(commit_message()).await {
+                            return ConnectionEnd::Dropped(format!("Failed to send commit message: {}", e));
+                        }
+                        commit_deadline = Some(tokio::time::Instant::now() + COMMIT_GRACE);
+                    }
+                },
+                msg = ws_read.next() => match msg {
+                    Some(Ok(Message::Text(text))) => {
+                        debug!("Received message: {}", text);
+                        self.message(&text).await;
+                    }
+                    Some(Ok(Message::Close(_))) | None
+                        if close_deadline.is_some() || self.final_commit_received =>
+                    {
+                        return ConnectionEnd::Finished;
+                    }
+                    Some(Ok(Message::Close(_))) | None => {
+                        info!("WebSocket closed by server");
+                        return ConnectionEnd::Dropped("Connection closed by server".to_string());
+                    }
+                    Some(Ok(_)) => {
+                        debug!("Received non-text message");
+                    }
+                    Some(Err(e)) if close_deadline.is_some() => {
+                        debug!("WebSocket error while closing: {}", e);
+                        return ConnectionEnd::Finished;
+                    }
+                    Some(Err(e)) => {
+                        error!("WebSocket error: {}", e);
+                        return ConnectionEnd::Dropped(format!("WebSocket error: {}", e));
+                    }
+                },
+                // Give the server time to process the commit and send back a
+                // committed_transcript before we tear down the connection
+                _ = sleep_until(commit_deadline) => {
+                    debug!("Closing WebSocket");
+                    commit_deadline = None;
+                    let _ = ws_write.close().await;
+                    close_deadline = Some(tokio::time::Instant::now() + CLOSE_TIMEOUT);
+                }
+                _ = sleep_until(close_deadline) => {
+                    warn!("Receiver timed out during shutdown");
+                    return ConnectionEnd::Finished;
+                }
+            }
+        }
+    }
+
+    async fn message(&mut self, text: &str) {
+        let response = match serde_json::from_str::<ElevenLabsResponse>(text) {
+            Ok(response) => response,
+            Err(e) => {
+                warn!("Failed to parse message: {} - {}", e, text);
+                return;
+            }
+        };
+        let events = match response {
+            ElevenLabsResponse::SessionStarted {} => {
+                info!("ElevenLabs session started");
+                Vec::new()
+            }
+            ElevenLabsResponse::PartialTranscript { text } if text.is_empty() => Vec::new(),
+            ElevenLabsResponse::PartialTranscript { text } => self.segments.partial(text),
+            ElevenLabsResponse::CommittedTranscript { text } if text.is_empty() => Vec::new(),
+            ElevenLabsResponse::CommittedTranscript { text } => {
+                self.final_commit_received = self.audio_done;
+                self.segments.committed(text).into_iter().collect()
+            }
+            ElevenLabsResponse::CommittedTranscriptWithTimestamps {
+                text,
+                language_code,
+                words,
+            } => {
+                self.final_commit_received = self.audio_done;
+                self.segments
+                    .committed_with_timestamps(text, language_code, words)
+                    .into_iter()
+                    .collect()
+            }
+            other => {
+                let Some(detail) = other.into_provider_error() else {
+                    return;
+                };
+                error!("ElevenLabs error: {}", detail);
+                self.server_error = true;
+                vec![TranscriptionEvent::Error {
+                    message: detail.message.clone(),
+                    detail: Some(detail),
+                }]
+            }
+        };
+        for event in events {
+            self.emit(event).await;
+        }
+    }
+
+    /// Send an event, dropping what a reconnect repeated of committed text
+    async fn emit(&mut self, mut event: TranscriptionEvent) {
+        match &mut event {
+            TranscriptionEvent::Partial { text, .. } if self.dedupe_seam => {
+                *text = stitch::dedupe_seam(&self.committed_text, text).to_string();
+            }
+            TranscriptionEvent::Committed { text, metadata, .. } => {
+                if self.dedupe_seam {
+                    *text = stitch::dedupe_seam(&self.committed_text, text).to_string();
+                    self.dedupe_seam = false;
+                }
+                for word in &mut metadata.words {
+                    word.start_ms += self.connection_start_ms;
+                    word.end_ms += self.connection_start_ms;
+                }
+
+                // Audio up to the last committed word is done with; without
+                // word times keep an overlap for the seam de-duplication
+                let committed_ms = metadata.words.last().map_or_else(
+                    || self.replay.end_ms.saturating_sub(REPLAY_OVERLAP_MS),
+                    |word| word.end_ms,
+                );
+                self.replay.release_until(committed_ms);
+
+                if !text.is_empty() {
+                    if !self.committed_text.is_empty() {
+                        self.committed_text.push(' ');
+                    }
+                    self.committed_text.push_str(text);
+                }
+            }
+            _ => {}
+        }
+        if let Err(e) = self.event_tx.send(event).await {
+            error!("Failed to send transcription event: {}", e);
+        }
+    }
+
+    async fn fail(&mut self, message: String) {
+        error!("{}", message);
+        let event = TranscriptionEvent::Error {
+            detail: Some(ProviderError::new(
+                ProviderErrorKind::Network,
+                "elevenlabs",
+                message.clone(),
+            )),
+            message,
+        };
+        let _ = self.event_tx.send(event).await;
+    }
+}
+
+/// Sleep until `deadline`; pending forever without one
+async fn sleep_until(deadline: Option<tokio::time::Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
+fn audio_message(chunk: &AudioChunk) -> Message {
+    // Convert i16 PCM to raw bytes
+    let pcm_bytes: Vec<u8> = chunk.data.iter().flat_map(|s| s.to_le_bytes()).collect();
+    let msg = ElevenLabsMessage {
+        message_type: "input_audio_chunk".to_string(),
+        audio_base_64: BASE64.encode(&pcm_bytes),
+        sample_rate: Some(chunk.sample_rate),
+        commit: None,
+    };
+    Message::Text(serde_json::to_string(&msg).unwrap().into())
+}
+
+fn commit_message() -> Message {
+    let msg = ElevenLabsMessage {
+        message_type: "input_audio_chunk".to_string(),
+        audio_base_64: String::new(),
+        sample_rate: Some(INPUT_SAMPLE_RATE),
+        commit: Some(true),
+    };
+    Message::Text(serde_json::to_string(&msg).unwrap().into())
+}
+
 /// ElevenLabs Scribe v2 WebSocket client
 pub struct ElevenLabsProvider {
     api_key: String,
     model_id: String,
     language_code: String,
+    diarize: bool,
+    base_url: String,
     ws_tx: Arc<Mutex<Option<mpsc::Sender<AudioChunk>>>>,
     event_tx: Arc<Mutex<Option<mpsc::Sender<TranscriptionEvent>>>>,
     event_rx: Arc<Mutex<Option<mpsc::Receiver<TranscriptionEvent>>>>,
@@ -93,6 +870,8 @@ impl ElevenLabsProvider {
             api_key,
             model_id: "scribe_v2_realtime".to_string(),
             language_code: "en".to_string(),
+            diarize: false,
+            base_url: DEFAULT_BASE_URL.to_string(),
             ws_tx: Arc::new(Mutex::new(None)),
             event_tx: Arc::new(Mutex::new(None)),
             event_rx: Arc::new(Mutex::new(None)),
@@ -108,6 +887,8 @@ impl ElevenLabsProvider {
             api_key,
             model_id,
             language_code,
+            diarize: false,
+            base_url: DEFAULT_BASE_URL.to_string(),
             ws_tx: Arc::new(Mutex::new(None)),
             event_tx: Arc::new(Mutex::new(None)),
             event_rx: Arc::new(Mutex::new(None)),
@@ -117,77 +898,47 @@ impl ElevenLabsProvider {
         }
     }
 
+    /// Label words by speaker
+    pub fn with_diarize(mut self, diarize: bool) -> Self {
+        self.diarize = diarize;
+        self
+    }
+
+    /// Connect somewhere other than ElevenLabs (e.g. a proxy)
+    pub fn with_base_url(mut self, base_url: Option<String>) -> Self {
+        if let Some(base_url) = base_url.filter(|u| !u.is_empty()) {
+            self.base_url = base_url;
+        }
+        self
+    }
+
     /// Build WebSocket URL
     fn build_ws_url(&self) -> Result<Url> {
-        let url = if self.language_code == "auto" {
-            format!(
-                "wss://api.elevenlabs.io/v1/speech-to-text/realtime?model_id={}&audio_format=pcm_16000",
-                self.model_id
-            )
-        } else {
-            format!(
-                "wss://api.elevenlabs.io/v1/speech-to-text/realtime?model_id={}&language_code={}&audio_format=pcm_16000",
-                self.model_id, self.language_code
-            )
-        };
+        let mut url = format!(
+            "{}/v1/speech-to-text/realtime?model_id={}",
+            self.base_url.trim_end_matches('/'),
+            self.model_id
+        );
+        if self.language_code != "auto" {
+            url.push_str(&format!("&language_code={}", self.language_code));
+        }
+        url.push_str(&format!(
+            "&audio_format=pcm_{}&include_timestamps=true",
+            INPUT_SAMPLE_RATE
+        ));
+        if self.diarize {
+            url.push_str("&diarize=true");
+        }
         Url::parse(&url).map_err(|e| MurmurError::Stt(format!("Invalid URL: {}", e)))
     }
 
-    /// Connect to WebSocket with retry logic
-    async fn connect_with_retry(
-        &self,
-    ) -> Result<
-        tokio_tungstenite::WebSocketStream<
-            tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
-        >,
-    > {
-        let ws_url = self.build_ws_url()?;
-        let mut retry_count = 0;
-
-        loop {
-            let mut request = ws_url
-                .as_str()
-                .into_client_request()
-                .map_err(|e| MurmurError::Stt(format!("Failed to build request: {}", e)))?;
-            request.headers_mut().insert(
-                "xi-api-key",
-                self.api_key
-                    .parse()
-                    .map_err(|_| MurmurError::Stt("Invalid API key header value".to_string()))?,
-            );
-
-            match connect_async(request).await {
-                Ok((ws_stream, _)) => {
-                    info!("WebSocket connected to ElevenLabs");
-                    return Ok(ws_stream);
-                }
-                Err(e) => {
-                    if retry_count >= self.reconnect_config.max_retries {
-                        error!("Failed to connect after {} retries", retry_count);
-                        return Err(MurmurError::Stt(format!(
-                            "WebSocket connection failed after {} retries: {}",
-                            retry_count, e
-                        )));
-                    }
-
-                    let delay = std::cmp::min(
-                        self.reconnect_config.base_delay_ms * 2u64.pow(retry_count),
-                        self.reconnect_config.max_delay_ms,
-                    );
-
-                    warn!(
-                        "WebSocket connection failed (attempt {}/{}), retrying in {}ms: {}",
-                        retry_count + 1,
-                        self.reconnect_config.max_retries,
-                        delay,
-                        e
-                    );
-
-                    tokio::time::sleep(tokio::time::Duration::from_millis(delay)).await;
-                    retry_count += 1;
-                }
-            }
-        }
+    fn connector(&self) -> Result<Connector> {
+        Ok(Connector {
+            url: self.build_ws_url()?,
+            api_key: self.api_key.clone(),
+            reconnect_config: self.reconnect_config.clone(),
+            should_reconnect: self.should_reconnect.clone(),
+        })
     }
 }
 
@@ -200,7 +951,7 @@ impl SttProvider for ElevenLabsProvider {
         *self.should_reconnect.lock().await = true;
 
         // Create channel for audio chunks
-        let (audio_tx, mut audio_rx) = mpsc::channel::<AudioChunk>(32);
+        let (audio_tx, audio_rx) = mpsc::channel::<AudioChunk>(32);
         *self.ws_tx.lock().await = Some(audio_tx);
 
         // Create channel for transcription events
@@ -209,141 +960,12 @@ impl SttProvider for ElevenLabsProvider {
         *self.event_rx.lock().await = Some(event_rx);
 
         // Connect to WebSocket with retry
-        let ws_stream = self.connect_with_retry().await?;
+        let connector = self.connector()?;
+        let ws_stream = connector.connect_with_retry().await?;
 
-        let (mut ws_write, mut ws_read) = ws_stream.split();
-
-        // Spawn task to send audio and receive transcription
-        let task = tokio::spawn(async move {
-            // Spawn receiver task
-            let event_tx_clone = event_tx.clone();
-            let receiver_task = tokio::spawn(async move {
-                while let Some(msg) = ws_read.next().await {
-                    match msg {
-                        Ok(Message::Text(text)) => {
-                            debug!("Received message: {}", text);
-
-                            match serde_json::from_str::<ElevenLabsResponse>(&text) {
-                                Ok(response) => match response {
-                                    ElevenLabsResponse::SessionStarted {} => {
-                                        info!("ElevenLabs session started");
-                                    }
-                                    ElevenLabsResponse::PartialTranscript { text } => {
-                                        if !text.is_empty() {
-                                            let event = TranscriptionEvent::Partial {
-                                                text,
-                                                timestamp_ms: 0,
-                                            };
-                                            if let Err(e) = event_tx_clone.send(event).await {
-                                                error!("Failed to send partial event: {}", e);
-                                            }
-                                        }
-                                    }
-                                    ElevenLabsResponse::CommittedTranscript { text } => {
-                                        if !text.is_empty() {
-                                            let event = TranscriptionEvent::Committed {
-                                                text,
-                                                timestamp_ms: 0,
-                                            };
-                                            if let Err(e) = event_tx_clone.send(event).await {
-                                                error!("Failed to send committed event: {}", e);
-                                            }
-                                        }
-                                    }
-                                    ElevenLabsResponse::Error { error }
-                                    | ElevenLabsResponse::InvalidRequest { error } => {
-                                        error!("ElevenLabs error: {}", error);
-                                        let event = TranscriptionEvent::Error { message: error };
-                                        if let Err(e) = event_tx_clone.send(event).await {
-                                            error!("Failed to send error event: {}", e);
-                                        }
-                                    }
-                                },
-                                Err(e) => {
-                                    warn!("Failed to parse message: {} - {}", e, text);
-                                }
-                            }
-                        }
-                        Ok(Message::Close(_)) => {
-                            info!("WebSocket closed by server");
-                            break;
-                        }
-                        Ok(_) => {
-                            debug!("Received non-text message");
-                        }
-                        Err(e) => {
-                            error!("WebSocket error: {}", e);
-                            let event = TranscriptionEvent::Error {
-                                message: format!("WebSocket error: {}", e),
-                            };
-                            let _ = event_tx_clone.send(event).await;
-                            break;
-                        }
-                    }
-                }
-                debug!("WebSocket receiver task finished");
-            });
-
-            // Send audio chunks
-            while let Some(chunk) = audio_rx.recv().await {
-                // Convert i16 PCM to raw bytes
-                let pcm_bytes: Vec<u8> = chunk.data.iter().flat_map(|s| s.to_le_bytes()).collect();
-
-                let audio_base_64 = BASE64.encode(&pcm_bytes);
-
-                // Create JSON message
-                let msg = ElevenLabsMessage {
-                    message_type: "input_audio_chunk".to_string(),
-                    audio_base_64,
-                    sample_rate: Some(16000),
-                    commit: None,
-                };
-                let json = serde_json::to_string(&msg).unwrap();
-
-                // Send to WebSocket
-                if let Err(e) = ws_write.send(Message::Text(json.into())).await {
-                    error!("Failed to send audio chunk: {}", e);
-                    break;
-                }
-            }
-
-            debug!("Audio sender finished, sending commit signal");
-
-            // Send a final commit message to flush the server's transcription buffer.
-            // With vad_commit_strategy disabled, the server won't auto-commit;
-            // we must explicitly request it.
-            let commit_msg = ElevenLabsMessage {
-                message_type: "input_audio_chunk".to_string(),
-                audio_base_64: String::new(),
-                sample_rate: Some(16000),
-                commit: Some(true),
-            };
-            let json = serde_json::to_string(&commit_msg).unwrap();
-            if let Err(e) = ws_write.send(Message::Text(json.into())).await {
-                warn!("Failed to send commit message: {}", e);
-            }
-
-            // Give the server time to process the commit and send back
-            // a committed_transcript before we tear down the connection.
-            tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-
-            debug!("Closing WebSocket");
-            let _ = ws_write.close().await;
-
-            // Wait for receiver with timeout to avoid hanging
-            match tokio::time::timeout(tokio::time::Duration::from_secs(2), receiver_task).await {
-                Ok(result) => {
-                    if let Err(e) = result {
-                        warn!("Receiver task error: {}", e);
-                    }
-                }
-                Err(_) => {
-                    warn!("Receiver task timed out during shutdown");
-                }
-            }
-
-            info!("WebSocket task finished");
-        });
+        // Spawn task to send audio and receive transcription, reconnecting
+        // if the socket drops mid-session
+        let task = tokio::spawn(Session::new(event_tx).run(connector, ws_stream, audio_rx));
 
         *self.ws_task.lock().await = Some(task);
 
@@ -386,4 +1008,302 @@ impl SttProvider for ElevenLabsProvider {
             .take()
             .expect("subscribe_events called multiple times")
     }
+    fn input_format(&self) -> AudioFormat {
+        AudioFormat::mono(INPUT_SAMPLE_RATE)
+    }
+
+    async fn health_check(&self) -> Result<SttHealth> {
+        let timed_out = || ws::health_check_timed_out("elevenlabs");
+
+        // Open the socket once (no retries) and wait for the server's first
+        // message: session_started on success, or an auth/quota error.
+        let started = Instant::now();
+        let mut ws_stream = tokio::time::timeout(
+            HEALTH_CHECK_TIMEOUT,
+            ws::connect("elevenlabs", self.connector()?.request()?),
+        )
+        .await
+        .map_err(|_| timed_out())??;
+        let first = tokio::time::timeout(HEALTH_CHECK_TIMEOUT, ws_stream.next())
+            .await
+            .map_err(|_| timed_out())?;
+        let latency_ms = started.elapsed().as_millis() as u64;
+        let _ = ws_stream.close(None).await;
+
+        match first {
+            Some(Ok(Message::Text(text))) => {
+                let server_error = serde_json::from_str::<ElevenLabsResponse>(&text)
+                    .ok()
+                    .and_then(ElevenLabsResponse::into_provider_error);
+                match server_error {
+                    Some(e) => Err(e.into()),
+                    None => Ok(SttHealth { latency_ms }),
+                }
+            }
+            Some(Ok(Message::Close(frame))) => Err(ProviderError::new(
+                ProviderErrorKind::Unavailable,
+                "elevenlabs",
+                format!(
+                    "Server closed the connection: {}",
+                    frame.map(|f| f.reason.to_string()).unwrap_or_default()
+                ),
+            )
+            .into()),
+            Some(Ok(_)) => Ok(SttHealth { latency_ms }),
+            Some(Err(e)) => Err(ProviderError::new(
+                ProviderErrorKind::Network,
+                "elevenlabs",
+                format!("WebSocket error: {}", e),
+            )
+            .into()),
+            None => Err(ProviderError::new(
+                ProviderErrorKind::Network,
+                "elevenlabs",
+                "Connection closed before the session started",
+            )
+            .into()),
+        }
+    }
+
+    fn capabilities(&self) -> SttCapabilities {
+        SttCapabilities {
+            mode: SttMode::Streaming,
+            partial_results: true,
+            languages: SUPPORTED_LANGUAGES
+                .iter()
+                .map(|(code, _)| code.to_string())
+                .collect(),
+            word_timestamps: true,
+            vocabulary_hints: false,
+            diarization: true,
+            local: false,
+            requires_api_key: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> ElevenLabsResponse {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_capabilities_list_supported_languages() {
+        let caps = ElevenLabsProvider::new("key".to_string()).capabilities();
+        assert_eq!(caps.mode, SttMode::Streaming);
+        assert!(caps.partial_results);
+        assert!(caps.supports_language("eng"));
+        assert!(caps.supports_language("jpn"));
+        assert!(!caps.supports_language("auto"));
+    }
+
+    #[test]
+    fn test_transcripts_are_not_errors() {
+        let response = parse(r#"{"message_type":"partial_transcript","text":"hi"}"#);
+        assert!(response.into_provider_error().is_none());
+    }
+
+    fn segment_of(event: &TranscriptionEvent) -> Option<u64> {
+        match event {
+            TranscriptionEvent::Partial { metadata, .. }
+            | TranscriptionEvent::Committed { metadata, .. } => metadata.segment_id,
+            TranscriptionEvent::Error { .. } => None,
+        }
+    }
+
+    #[test]
+    fn test_diarized_words_become_speaker_turns() {
+        let mut segments = SegmentTracker::default();
+        let response = parse(
+            r#"{"message_type":"committed_transcript_with_timestamps","text":"Hi. Hello there.",
+                "words":[
+                    {"text":"Hi.","start":0.0,"end":0.3,"type":"word","speaker_id":"speaker_0"},
+                    {"text":" ","start":0.3,"end":0.6,"type":"spacing","speaker_id":"speaker_0"},
+                    {"text":"Hello","start":0.6,"end":0.9,"type":"word","speaker_id":"speaker_1"},
+                    {"text":"there.","start":1.0,"end":1.4,"type":"word","speaker_id":"speaker_1"}
+                ]}"#,
+        );
+        let ElevenLabsResponse::CommittedTranscriptWithTimestamps {
+            text,
+            language_code,
+            words,
+        } = response
+        else {
+            panic!("Unexpected response type");
+        };
+        match segments
+            .committed_with_timestamps(text, language_code, words)
+            .unwrap()
+        {
+            TranscriptionEvent::Committed { metadata, .. } => {
+                let turns: Vec<_> = metadata
+                    .speakers
+                    .iter()
+                    .map(|s| (s.speaker, s.text.as_str()))
+                    .collect();
+                assert_eq!(turns, vec![(0, "Hi."), (1, "Hello there.")]);
+                assert_eq!(metadata.speakers[1].end_ms, 1400);
+            }
+            _ => panic!("Unexpected event type"),
+        }
+    }
+
+    #[test]
+    fn test_timestamped_commit_replaces_plain_commit() {
+        let mut segments = SegmentTracker::default();
+
+        let partials = segments.partial("hello".to_string());
+        assert_eq!(partials.len(), 1);
+        assert_eq!(segment_of(&partials[0]), Some(0));
+
+        assert!(segments.committed("hello world".to_string()).is_none());
+
+        let response = parse(
+            r#"{"message_type":"committed_transcript_with_timestamps","text":"hello world",
+                "language_code":"en","words":[
+                    {"text":"hello","start":0.0,"end":0.4,"type":"word","logprob":-0.05},
+                    {"text":" ","start":0.4,"end":0.5,"type":"spacing"},
+                    {"text":"world","start":0.5,"end":0.9,"type":"word","logprob":-1.2}
+                ]}"#,
+        );
+        let ElevenLabsResponse::CommittedTranscriptWithTimestamps {
+            text,
+            language_code,
+            words,
+        } = response
+        else {
+            panic!("Unexpected response type");
+        };
+        let event = segments
+            .committed_with_timestamps(text, language_code, words)
+            .unwrap();
+        match &event {
+            TranscriptionEvent::Committed { text, metadata, .. } => {
+                assert_eq!(text, "hello world");
+                assert_eq!(metadata.segment_id, Some(0));
+                assert_eq!(metadata.language.as_deref(), Some("en"));
+                assert_eq!(metadata.words.len(), 2);
+                assert_eq!(metadata.words[1].start_ms, 500);
+                assert!(metadata.words[1].confidence.unwrap() < 0.5);
+            }
+            _ => panic!("Unexpected event type"),
+        }
+
+        // Nothing is left to flush, and the next segment gets a new id
+        assert!(segments.flush().is_none());
+        let partials = segments.partial("next".to_string());
+        assert_eq!(segment_of(&partials[0]), Some(1));
+    }
+
+    #[test]
+    fn test_plain_commit_is_flushed_without_timestamps() {
+        let mut segments = SegmentTracker::default();
+        assert!(segments.committed("first".to_string()).is_none());
+
+        // A new partial releases the held commit before itself
+        let events = segments.partial("second".to_string());
+        assert_eq!(events.len(), 2);
+        assert!(
+            matches!(&events[0], TranscriptionEvent::Committed { text, .. } if text == "first")
+        );
+        assert_eq!(segment_of(&events[1]), Some(1));
+
+        assert!(segments.committed("second".to_string()).is_none());
+        let last = segments.flush().unwrap();
+        assert_eq!(segment_of(&last), Some(1));
+    }
+
+    fn chunk_ms(duration_ms: u64) -> AudioChunk {
+        AudioChunk {
+            data: vec![0; (INPUT_SAMPLE_RATE as u64 * duration_ms / 1000) as usize],
+            timestamp_ms: 0,
+            sample_rate: INPUT_SAMPLE_RATE,
+            channels: 1,
+        }
+    }
+
+    #[test]
+    fn test_replay_buffer_is_bounded_and_released_by_commits() {
+        let mut replay = ReplayBuffer::new(1000);
+        for _ in 0..6 {
+            replay.push(chunk_ms(250));
+        }
+        // Only the newest second is kept
+        assert_eq!(replay.end_ms, 1500);
+        assert_eq!(replay.start_ms(), 500);
+        assert_eq!(replay.chunks.len(), 4);
+
+        // A commit up to 1100 ms frees the chunks that end before it
+        replay.release_until(1100);
+        assert_eq!(replay.start_ms(), 1000);
+        replay.release_until(1500);
+        assert!(replay.chunks.is_empty());
+        assert_eq!(replay.start_ms(), 1500);
+    }
+
+    #[test]
+    fn test_ws_url_follows_base_url_and_language() {
+        let provider = ElevenLabsProvider::with_config(
+            "key".to_string(),
+            "scribe_v2_realtime".to_string(),
+            "auto".to_string(),
+        )
+        .with_base_url(Some("ws://127.0.0.1:9000/".to_string()));
+        assert_eq!(
+            provider.build_ws_url().unwrap().as_str(),
+            "ws://127.0.0.1:9000/v1/speech-to-text/realtime?model_id=scribe_v2_realtime&audio_format=pcm_16000&include_timestamps=true"
+        );
+
+        let url = ElevenLabsProvider::new("key".to_string())
+            .build_ws_url()
+            .unwrap();
+        assert!(url.as_str().starts_with("wss://api.elevenlabs.io/v1/"));
+        assert!(url.as_str().contains("language_code=en&"));
+        assert!(!url.as_str().contains("diarize"));
+
+        let url = ElevenLabsProvider::new("key".to_string())
+            .with_diarize(true)
+            .build_ws_url()
+            .unwrap();
+        assert!(url
+            .as_str()
+            .ends_with("&include_timestamps=true&diarize=true"));
+    }
+
+    #[test]
+    fn test_server_errors_are_classified() {
+        let cases = [
+            (
+                r#"{"message_type":"auth_error","error":"bad key"}"#,
+                ProviderErrorKind::Auth,
+            ),
+            (
+                r#"{"message_type":"quota_exceeded","error":"no credits"}"#,
+                ProviderErrorKind::Quota,
+            ),
+            (
+                r#"{"message_type":"commit_throttled","error":"slow down"}"#,
+                ProviderErrorKind::RateLimited {
+                    retry_after_secs: None,
+                },
+            ),
+            (
+                r#"{"message_type":"input_error","error":"bad audio"}"#,
+                ProviderErrorKind::BadRequest,
+            ),
+            (
+                r#"{"message_type":"transcriber_error","error":"oops"}"#,
+                ProviderErrorKind::Unavailable,
+            ),
+        ];
+
+        for (json, expected) in cases {
+            let detail = parse(json).into_provider_error().unwrap();
+            assert_eq!(detail.kind, expected, "{}", json);
+            assert_eq!(detail.provider, "elevenlabs");
+        }
+    }
 }
@@ -0,0 +1,347 @@
+//! Chunk encoders for REST uploads.
+//!
+//! WAV is the default every server accepts. FLAC is lossless at about half
+//! the size; Ogg Opus (behind the `opus` feature, which builds libopus) is
+//! lossy speech coding at roughly a tenth of the size.
+
+use flacenc::component::BitRepr;
+use flacenc::error::Verify;
+use lt_core::config::SttAudioEncoding;
+use lt_core::error::{MurmurError, Result};
+use lt_core::stt::AudioFormat;
+use std::io::Cursor;
+
+/// Encode interleaved 16-bit samples for upload
+pub fn encode(encoding: SttAudioEncoding, format: AudioFormat, samples: &[i16]) -> Result<Vec<u8>> {
+    match encoding {
+        SttAudioEncoding::Wav => encode_wav(format, samples),
+        SttAudioEncoding::Flac => encode_flac(format, samples),
+        SttAudioEncoding::OggOpus => encode_ogg_opus(format, samples),
+    }
+}
+
+/// True when this build can produce `encoding`
+pub fn is_supported(encoding: SttAudioEncoding) -> bool {
+    match encoding {
+        SttAudioEncoding::Wav | SttAudioEncoding::Flac => true,
+        SttAudioEncoding::OggOpus => cfg!(feature = "opus"),
+    }
+}
+
+/// Encode PCM samples as WAV bytes
+pub fn encode_wav(format: AudioFormat, samples: &[i16]) -> Result<Vec<u8>> {
+    let mut cursor = Cursor::new(Vec::new());
+
+    {
+        let spec = hound::WavSpec {
+            channels: format.channels,
+            sample_rate: format.sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+
+        let mut writer = hound::WavWriter::new(&mut cursor, spec)
+            .map_err(|e| MurmurError::Stt(format!("Failed to create WAV writer: {}", e)))?;
+
+        for &sample in samples {
+            writer
+                .write_sample(sample)
+                .map_err(|e| MurmurError::Stt(format!("Failed to write WAV sample: {}", e)))?;
+        }
+
+        writer
+            .finalize()
+            .map_err(|e| MurmurError::Stt(format!("Failed to finalize WAV: {}", e)))?;
+    }
+
+    Ok(cursor.into_inner())
+}
+
+/// Encode PCM samples as a FLAC stream
+pub fn encode_flac(format: AudioFormat, samples: &[i16]) -> Result<Vec<u8>> {
+    let config = flacenc::config::Encoder::default()
+        .into_verified()
+        .map_err(|(_, e)| MurmurError::Stt(format!("Invalid FLAC encoder config: {}", e)))?;
+
+    let samples: Vec<i32> = samples.iter().map(|&s| s as i32).collect();
+    let source = flacenc::source::MemSource::from_samples(
+        &samples,
+        format.channels as usize,
+        16,
+        format.sample_rate as usize,
+    );
+    let stream = flacenc::encode_with_fixed_block_size(&config, source, config.block_size)
+        .map_err(|e| MurmurError::Stt(format!("Failed to encode FLAC: {}", e)))?;
+
+    let mut sink = flacenc::bitsink::ByteSink::new();
+    stream
+        .write(&mut sink)
+        .map_err(|e| MurmurError::Stt(format!("Failed to write FLAC: {}", e)))?;
+    Ok(sink.as_slice().to_vec())
+}
+
+/// Ogg Opus is unavailable without the `opus` feature
+#[cfg(not(feature = "opus"))]
+pub fn encode_ogg_opus(_format: AudioFormat, _samples: &[i16]) -> Result<Vec<u8>> {
+    Err(MurmurError::Config(
+        "Ogg Opus uploads need a build with the `opus` feature".to_string(),
+    ))
+}
+
+#[cfg(feature = "opus")]
+pub use self::ogg_opus::encode_ogg_opus;
+
+#[cfg(feature = "opus")]
+mod ogg_opus {
+    use super::*;
+    use ogg::writing::{PacketWriteEndInfo, PacketWriter};
+
+    /// Length of each Opus packet
+    const FRAME_MS: u32 = 20;
+    /// Target bitrate per channel; plenty for speech recognition
+    const BITRATE_PER_CHANNEL: i32 = 24_000;
+    /// Ogg granule positions always count 48 kHz samples
+    const GRANULE_RATE: u64 = 48_000;
+    /// Logical stream serial; each upload holds a single stream
+    const STREAM_SERIAL: u32 = 1;
+
+    /// Encode PCM samples as Opus packets in an Ogg container (RFC 7845)
+    pub fn encode_ogg_opus(format: AudioFormat, samples: &[i16]) -> Result<Vec<u8>> {
+        let opus_error = |e: opus::Error| MurmurError::Stt(format!("Opus encoding failed: {}", e));
+
+        let channels = match format.channels {
+            1 => opus::Channels::Mono,
+            2 => opus::Channels::Stereo,
+            n => {
+                return Err(MurmurError::Stt(format!(
+                    "Opus supports mono or stereo audio, not {} channels",
+                    n
+                )))
+            }
+        };
+        if ![8000, 12000, 16000, 24000, 48000].contains(&format.sample_rate) {
+            return Err(MurmurError::Stt(format!(
+                "Opus does not support {} Hz audio",
+                format.sample_rate
+            )));
+        }
+
+        let mut encoder = opus::Encoder::new(format.sample_rate, channels, opus::Application::Voip)
+            .map_err(opus_error)?;
+        encoder
+            .set_bitrate(opus::Bitrate::Bits(
+                BITRATE_PER_CHANNEL * format.channels as i32,
+            ))
+            .map_err(opus_error)?;
+
+        let to_granule = |frames: u64| frames * GRANULE_RATE / format.sample_rate as u64;
+        // Lookahead in input frames; decoders drop this much from the start
+        let lookahead = encoder.get_lookahead().map_err(opus_error)?.max(0) as usize;
+        let pre_skip = to_granule(lookahead as u64);
+
+        let mut writer = PacketWriter::new(Cursor::new(Vec::new()));
+        let write_error =
+            |e: std::io::Error| MurmurError::Stt(format!("Failed to write Ogg: {}", e));
+        writer
+            .write_packet(
+                opus_head(format, pre_skip as u16),
+                STREAM_SERIAL,
+                PacketWriteEndInfo::EndPage,
+                0,
+            )
+            .map_err(write_error)?;
+        writer
+            .write_packet(opus_tags(), STREAM_SERIAL, PacketWriteEndInfo::EndPage, 0)
+            .map_err(write_error)?;
+
+        let channel_count = format.channels as usize;
+        let frame_len = (format.sample_rate / 1000 * FRAME_MS) as usize * channel_count;
+        let input_frames = (samples.len() / channel_count) as u64;
+
+        // Pad with silence so the lookahead-delayed tail is coded too
+        let mut padded = samples.to_vec();
+        padded.resize(samples.len() + lookahead * channel_count, 0);
+        let packets = padded.len().div_ceil(frame_len).max(1);
+        padded.resize(packets * frame_len, 0);
+
+        let mut packet = vec![0u8; 4000];
+        for (index, frame) in padded.chunks(frame_len).enumerate() {
+            let size = encoder.encode(frame, &mut packet).map_err(opus_error)?;
+            let last = index + 1 == packets;
+            let coded_frames = ((index + 1) * frame_len / channel_count) as u64;
+            // Granules count decoded audio including the pre-skip; the final
+            // one marks where real audio ends, trimming the padding
+            let granule = if last {
+                pre_skip + to_granule(input_frames)
+            } else {
+                to_granule(coded_frames)
+            };
+            let end = if last {
+                PacketWriteEndInfo::EndStream
+            } else {
+                PacketWriteEndInfo::NormalPacket
+            };
+            writer
+                .write_packet(packet[..size].to_vec(), STREAM_SERIAL, end, granule)
+                .map_err(write_error)?;
+        }
+
+        Ok(writer.into_inner().into_inner())
+    }
+
+    /// Identification header
+    fn opus_head(format: AudioFormat, pre_skip: u16) -> Vec<u8> {
+        let mut head = Vec::with_capacity(19);
+        head.extend_from_slice(b"OpusHead");
+        head.push(1); // version
+        head.push(format.channels as u8);
+        head.extend_from_slice(&pre_skip.to_le_bytes());
+        head.extend_from_slice(&format.sample_rate.to_le_bytes());
+        head.extend_from_slice(&0i16.to_le_bytes()); // output gain
+        head.push(0); // mapping family: mono/stereo
+        head
+    }
+
+    /// Comment header with the vendor string and no comments
+    fn opus_tags() -> Vec<u8> {
+        let vendor = b"murmur";
+        let mut tags = Vec::with_capacity(16 + vendor.len());
+        tags.extend_from_slice(b"OpusTags");
+        tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+        tags.extend_from_slice(vendor);
+        tags.extend_from_slice(&0u32.to_le_bytes());
+        tags
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 300 Hz tone with a slow amplitude sweep, interleaved across channels
+    fn tone(format: AudioFormat, duration_ms: u64) -> Vec<i16> {
+        let frames = format.sample_rate as u64 * duration_ms / 1000;
+        (0..frames)
+            .flat_map(|i| {
+                let t = i as f32 / format.sample_rate as f32;
+                let level = 4000.0 + 4000.0 * (t * 2.0).sin();
+                let sample = ((t * 300.0 * std::f32::consts::TAU).sin() * level) as i16;
+                std::iter::repeat_n(sample, format.channels as usize)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_mime_types_and_file_names() {
+        assert_eq!(SttAudioEncoding::Wav.mime_type(), "audio/wav");
+        assert_eq!(SttAudioEncoding::Flac.file_name(), "audio.flac");
+        assert_eq!(SttAudioEncoding::OggOpus.mime_type(), "audio/ogg");
+        assert_eq!(SttAudioEncoding::OggOpus.file_name(), "audio.ogg");
+    }
+
+    #[test]
+    fn test_wav_round_trip() {
+        let format = AudioFormat::mono(16000);
+        let samples = tone(format, 250);
+        let wav = encode(SttAudioEncoding::Wav, format, &samples).unwrap();
+
+        let mut reader = hound::WavReader::new(Cursor::new(wav)).unwrap();
+        let decoded: Vec<i16> = reader.samples::<i16>().map(|s| s.unwrap()).collect();
+        assert_eq!(decoded, samples);
+    }
+
+    #[test]
+    fn test_flac_round_trip_is_lossless() {
+        for format in [
+            AudioFormat::mono(16000),
+            AudioFormat {
+                sample_rate: 24000,
+                channels: 2,
+            },
+        ] {
+            let samples = tone(format, 1500);
+            let flac = encode(SttAudioEncoding::Flac, format, &samples).unwrap();
+            assert_eq!(&flac[..4], b"fLaC");
+
+            let mut reader = claxon::FlacReader::new(Cursor::new(flac.as_slice())).unwrap();
+            let info = reader.streaminfo();
+            assert_eq!(info.sample_rate, format.sample_rate);
+            assert_eq!(info.channels, format.channels as u32);
+            assert_eq!(info.bits_per_sample, 16);
+            let decoded: Vec<i16> = reader.samples().map(|s| s.unwrap() as i16).collect();
+            assert_eq!(decoded, samples);
+        }
+    }
+
+    #[test]
+    fn test_flac_is_smaller_than_wav() {
+        let format = AudioFormat::mono(16000);
+        let samples = tone(format, 3000);
+        let wav = encode(SttAudioEncoding::Wav, format, &samples).unwrap();
+        let flac = encode(SttAudioEncoding::Flac, format, &samples).unwrap();
+        assert!(
+            flac.len() < wav.len() / 2,
+            "{} vs {}",
+            flac.len(),
+            wav.len()
+        );
+    }
+
+    #[cfg(not(feature = "opus"))]
+    #[test]
+    fn test_ogg_opus_needs_feature() {
+        assert!(!is_supported(SttAudioEncoding::OggOpus));
+        let result = encode(
+            SttAudioEncoding::OggOpus,
+            AudioFormat::mono(16000),
+            &[0; 320],
+        );
+        assert!(matches!(result, Err(MurmurError::Config(_))));
+    }
+
+    #[cfg(feature = "opus")]
+    #[test]
+    fn test_ogg_opus_round_trip() {
+        let format = AudioFormat::mono(16000);
+        let samples = tone(format, 1000);
+        let encoded = encode(SttAudioEncoding::OggOpus, format, &samples).unwrap();
+        assert!(encoded.len() < samples.len() * 2 / 5);
+
+        let mut reader = ogg::PacketReader::new(Cursor::new(encoded));
+        let head = reader.read_packet_expected().unwrap();
+        assert_eq!(&head.data[..8], b"OpusHead");
+        assert_eq!(head.data[9], 1);
+        let pre_skip = u16::from_le_bytes([head.data[10], head.data[11]]) as usize;
+        let tags = reader.read_packet_expected().unwrap();
+        assert_eq!(&tags.data[..8], b"OpusTags");
+
+        let mut decoder = opus::Decoder::new(16000, opus::Channels::Mono).unwrap();
+        let mut decoded = Vec::new();
+        let mut last_granule = 0;
+        let mut buffer = vec![0i16; 5760];
+        while let Some(packet) = reader.read_packet().unwrap() {
+            let len = decoder.decode(&packet.data, &mut buffer, false).unwrap();
+            decoded.extend_from_slice(&buffer[..len]);
+            last_granule = packet.absgp_page();
+        }
+
+        // Granules are 48 kHz; pre-skip and the end trim give back the input length
+        let pre_skip = pre_skip / 3;
+        assert_eq!(last_granule as usize / 3 - pre_skip, samples.len());
+        let decoded = &decoded[pre_skip..pre_skip + samples.len()];
+
+        let energy = |s: &[i16]| s.iter().map(|&v| (v as f64).powi(2)).sum::<f64>();
+        let error: f64 = decoded
+            .iter()
+            .zip(&samples)
+            .map(|(&d, &s)| (d as f64 - s as f64).powi(2))
+            .sum();
+        // Lossy, but the waveform survives: error well below the signal
+        assert!(
+            error < energy(&samples) * 0.25,
+            "{} vs {}",
+            error,
+            energy(&samples)
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use lt_core::error::{ProviderError, ProviderErrorKind};
+use lt_core::retry;
+use std::time::Duration;
+
+/// Classify a transport-level reqwest failure (no HTTP response received)
+pub(crate) fn from_request_error(provider: &str, err: &reqwest::Error) -> ProviderError {
+    let kind = if err.is_timeout() {
+        ProviderErrorKind::Timeout
+    } else {
+        ProviderErrorKind::Network
+    };
+    ProviderError::new(kind, provider, format!("Request failed: {}", err))
+}
+
+/// Classify a non-2xx HTTP response, consuming its body
+pub(crate) async fn from_response(provider: &str, response: reqwest::Response) -> ProviderError {
+    let status = response.status().as_u16();
+    let retry_after = retry_after(response.headers());
+    let body = response
+        .text()
+        .await
+        .unwrap_or_else(|_| "Unknown error".to_string());
+    tracing::error!("{} API error (HTTP {}): {}", provider, status, body);
+    let retry_after_secs = retry_after.map(|d| d.as_secs_f64().ceil() as u64);
+    ProviderError::from_http_status(provider, status, retry_after_secs, &body)
+        .with_retry_after(retry_after)
+}
+
+/// Wait requested by `Retry-After` and the rate-limit headers
+pub(crate) fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    retry::retry_after_from_headers(
+        headers
+            .iter()
+            .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?))),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+
+    #[test]
+    fn test_retry_after() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("12"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(12)));
+
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(retry_after(&headers), None);
+
+        headers.remove(RETRY_AFTER);
+        headers.insert(
+            "x-ratelimit-reset-requests",
+            HeaderValue::from_static("1.5s"),
+        );
+        assert_eq!(retry_after(&headers), Some(Duration::from_millis(1500)));
+    }
+}
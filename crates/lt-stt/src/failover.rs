@@ -0,0 +1,855 @@
+//! Failover across an ordered chain of STT providers
+//!
+//! [`FailoverSttProvider`] runs the session on the first provider that
+//! connects. Captured audio not committed yet is kept (up to a minute); when
+//! the active provider errors, the next one is started and that audio is
+//! replayed to it. Every partial and commit carries the
+//! id of the provider that produced it in `metadata.provider`.
+
+use crate::resample::Resampler;
+use crate::stitch;
+use async_trait::async_trait;
+use lt_core::error::{MurmurError, Result};
+use lt_core::stt::{
+    AudioChunk, AudioFormat, SttCapabilities, SttHealth, SttProvider, TranscriptMetadata,
+    TranscriptionEvent,
+};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex, OwnedMutexGuard};
+use tracing::{info, warn};
+
+/// Audio kept before the end of what the last commit covers, so the tail of
+/// what is committed is replayed and the repeated words are dropped at the
+/// seam.
+const REPLAY_OVERLAP_MS: u64 = 3000;
+
+/// Uncommitted audio kept for replay after a failover
+const REPLAY_BUFFER_MS: u64 = 60_000;
+
+/// A provider in the chain with the id its events are tagged with
+struct ChainLink {
+    id: String,
+    provider: Box<dyn SttProvider>,
+}
+
+/// STT provider that fails over to the next provider in a chain
+pub struct FailoverSttProvider {
+    chain: Arc<Mutex<Vec<ChainLink>>>,
+    capabilities: SttCapabilities,
+    input_format: AudioFormat,
+    audio_tx: Option<mpsc::Sender<AudioChunk>>,
+    event_rx: Mutex<Option<mpsc::Receiver<TranscriptionEvent>>>,
+    session_task: Option<tokio::task::JoinHandle<()>>,
+}
+
+impl FailoverSttProvider {
+    /// Create a chain starting with `provider`. Capabilities and the capture
+    /// format are the primary's; fallbacks that want another format get the
+    /// audio converted.
+    pub fn new(id: impl Into<String>, provider: Box<dyn SttProvider>) -> Self {
+        Self {
+            capabilities: provider.capabilities(),
+            input_format: provider.input_format(),
+            chain: Arc::new(Mutex::new(vec![ChainLink {
+                id: id.into(),
+                provider,
+            }])),
+            audio_tx: None,
+            event_rx: Mutex::new(None),
+            session_task: None,
+        }
+    }
+
+    /// Append a provider tried after the ones already in the chain
+    pub fn with_fallback(mut self, id: impl Into<String>, provider: Box<dyn SttProvider>) -> Self {
+        Arc::get_mut(&mut self.chain)
+            .expect("fallbacks are added before the first session")
+            .get_mut()
+            .push(ChainLink {
+                id: id.into(),
+                provider,
+            });
+        self
+    }
+}
+
+#[async_trait]
+impl SttProvider for FailoverSttProvider {
+    async fn start_session(&mut self) -> Result<()> {
+        let mut chain = self.chain.clone().lock_owned().await;
+
+        let (event_tx, event_rx) = mpsc::channel::<TranscriptionEvent>(32);
+        *self.event_rx.lock().await = Some(event_rx);
+
+        let mut session = Session::new(event_tx);
+        if let Err(e) = session.start_from(&mut chain, 0).await {
+            stop_providers(&mut chain, session.failed).await;
+            return Err(e);
+        }
+
+        let (audio_tx, audio_rx) = mpsc::channel::<AudioChunk>(32);
+        self.audio_tx = Some(audio_tx);
+        self.session_task = Some(tokio::spawn(session.run(chain, audio_rx)));
+        Ok(())
+    }
+
+    async fn send_audio(&mut self, chunk: AudioChunk) -> Result<()> {
+        let audio_tx = self
+            .audio_tx
+            .as_ref()
+            .ok_or_else(|| MurmurError::Stt("Session not started".to_string()))?;
+        audio_tx
+            .send(chunk)
+            .await
+            .map_err(|_| MurmurError::Stt("Failover session ended".to_string()))
+    }
+
+    async fn stop_session(&mut self) -> Result<()> {
+        // Closing the audio channel makes the session stop the active provider
+        self.audio_tx = None;
+        if let Some(task) = self.session_task.take() {
+            let _ = task.await;
+        }
+        Ok(())
+    }
+
+    async fn subscribe_events(&self) -> mpsc::Receiver<TranscriptionEvent> {
+        let mut rx_lock = self.event_rx.lock().await;
+        rx_lock
+            .take()
+            .expect("subscribe_events called multiple times")
+    }
+
+    fn input_format(&self) -> AudioFormat {
+        self.input_format
+    }
+
+    fn capabilities(&self) -> SttCapabilities {
+        self.capabilities.clone()
+    }
+
+    /// Healthy as soon as one provider in the chain is; otherwise the
+    /// primary's failure is reported
+    async fn health_check(&self) -> Result<SttHealth> {
+        let chain = self
+            .chain
+            .try_lock()
+            .map_err(|_| MurmurError::Stt("A transcription session is in progress".to_string()))?;
+        let mut first_error = None;
+        for link in chain.iter() {
+            match link.provider.health_check().await {
+                Ok(health) => return Ok(health),
+                Err(e) => {
+                    warn!("Connection test of {} failed: {}", link.id, e);
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        Err(first_error.unwrap_or_else(|| MurmurError::Stt("No STT provider".to_string())))
+    }
+}
+
+/// Captured audio with its offset in the session
+struct BufferedChunk {
+    offset_ms: u64,
+    chunk: AudioChunk,
+}
+
+impl BufferedChunk {
+    fn end_ms(&self) -> u64 {
+        self.offset_ms + self.chunk.format().duration_ms(self.chunk.data.len())
+    }
+}
+
+/// State of one session across the providers it ran on
+struct Session {
+    event_tx: mpsc::Sender<TranscriptionEvent>,
+    /// Audio not yet covered by a commit (plus the replay overlap)
+    buffer: VecDeque<BufferedChunk>,
+    /// Session audio received so far
+    audio_ms: u64,
+    /// Session offset of the first audio the active provider received
+    replay_origin_ms: u64,
+    /// Index of the provider the session runs on
+    active: Option<usize>,
+    /// Converts captured audio to the active provider's format
+    resampler: Resampler,
+    events: Option<mpsc::Receiver<TranscriptionEvent>>,
+    /// Providers that failed and still have to be stopped
+    failed: Vec<usize>,
+    /// Text committed so far, for de-duplicating replayed audio
+    committed_text: String,
+    /// Set after a failover until the new provider's first commit
+    dedupe_seam: bool,
+    /// Added to segment ids so a new provider does not reuse earlier ones
+    segment_offset: u64,
+    next_segment_id: u64,
+}
+
+impl Session {
+    fn new(event_tx: mpsc::Sender<TranscriptionEvent>) -> Self {
+        Self {
+            event_tx,
+            buffer: VecDeque::new(),
+            audio_ms: 0,
+            replay_origin_ms: 0,
+            active: None,
+            resampler: Resampler::new(AudioFormat::default()),
+            events: None,
+            failed: Vec::new(),
+            committed_text: String::new(),
+            dedupe_seam: false,
+            segment_offset: 0,
+            next_segment_id: 0,
+        }
+    }
+
+    /// Start the first provider from `from` on that connects and accepts the
+    /// buffered audio
+    async fn start_from(&mut self, chain: &mut [ChainLink], from: usize) -> Result<()> {
+        let mut last_error = None;
+        for (index, link) in chain.iter_mut().enumerate().skip(from) {
+            match self.start(link).await {
+                Ok(events) => {
+                    info!("STT session running on {}", link.id);
+                    self.active = Some(index);
+                    self.events = Some(events);
+                    return Ok(());
+                }
+                Err(e) => {
+                    warn!("STT provider {} unavailable: {}", link.id, e);
+                    self.failed.push(index);
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| MurmurError::Stt("No STT provider left".to_string())))
+    }
+
+    async fn start(&mut self, link: &mut ChainLink) -> Result<mpsc::Receiver<TranscriptionEvent>> {
+        link.provider.start_session().await?;
+        let events = link.provider.subscribe_events().await;
+        self.resampler = Resampler::new(link.provider.input_format());
+        self.replay_origin_ms = self.buffer.front().map_or(self.audio_ms, |b| b.offset_ms);
+        for buffered in &self.buffer {
+            link.provider
+                .send_audio(self.resampler.convert(&buffered.chunk))
+                .await?;
+        }
+        if !self.buffer.is_empty() {
+            info!(
+                "Replayed {} ms of audio to {}",
+                self.audio_ms - self.buffer[0].offset_ms,
+                link.id
+            );
+        }
+        Ok(events)
+    }
+
+    async fn run(
+        mut self,
+        mut chain: OwnedMutexGuard<Vec<ChainLink>>,
+        mut audio_rx: mpsc::Receiver<AudioChunk>,
+    ) {
+        loop {
+            tokio::select! {
+                chunk = audio_rx.recv() => match chunk {
+                    Some(chunk) => self.audio(&mut chain, chunk).await,
+                    None => break,
+                },
+                event = next_event(&mut self.events) => match event {
+                    Some(event) => {
+                        self.event(&mut chain, event).await;
+                    }
+                    None => self.events = None,
+                },
+            }
+        }
+
+        self.finish(&mut chain).await;
+
+        // Let the consumer see the end of the session before the failed
+        // providers are shut down
+        let failed = std::mem::take(&mut self.failed);
+        drop(self);
+        stop_providers(&mut chain, failed).await;
+    }
+
+    async fn audio(&mut self, chain: &mut [ChainLink], chunk: AudioChunk) {
+        let duration_ms = chunk.format().duration_ms(chunk.data.len());
+        self.buffer.push_back(BufferedChunk {
+            offset_ms: self.audio_ms,
+            chunk,
+        });
+        self.audio_ms += duration_ms;
+        self.release_until(self.audio_ms.saturating_sub(REPLAY_BUFFER_MS));
+
+        let Some(active) = self.active else {
+            return;
+        };
+        let chunk = self.resampler.convert(&self.buffer.back().unwrap().chunk);
+        let provider = &mut chain[active].provider;
+        if let Err(e) = provider.send_audio(chunk).await {
+            self.fail_over(chain, error_event(&e)).await;
+        }
+    }
+
+    /// Forward an event of the active provider; returns false when the
+    /// provider failed and the session moved on (or ended)
+    async fn event(&mut self, chain: &mut [ChainLink], event: TranscriptionEvent) -> bool {
+        let Some(active) = self.active else {
+            return false;
+        };
+        let event = match event {
+            TranscriptionEvent::Partial {
+                text,
+                timestamp_ms,
+                mut metadata,
+            } => {
+                metadata.provider = Some(chain[active].id.clone());
+                metadata.segment_id = metadata.segment_id.map(|id| self.segment_id(id));
+                let text = self.dedupe(text);
+                TranscriptionEvent::Partial {
+                    text,
+                    timestamp_ms,
+                    metadata,
+                }
+            }
+            TranscriptionEvent::Committed {
+                text,
+                timestamp_ms,
+                mut metadata,
+            } => {
+                metadata.provider = Some(chain[active].id.clone());
+                metadata.segment_id = metadata.segment_id.map(|id| self.segment_id(id));
+                let text = self.dedupe(text);
+                self.dedupe_seam = false;
+                self.commit(&text, timestamp_ms, &metadata);
+                TranscriptionEvent::Committed {
+                    text,
+                    timestamp_ms,
+                    metadata,
+                }
+            }
+            error @ TranscriptionEvent::Error { .. } => {
+                self.fail_over(chain, error).await;
+                return false;
+            }
+        };
+        let _ = self.event_tx.send(event).await;
+        true
+    }
+
+    fn segment_id(&mut self, id: u64) -> u64 {
+        let id = id + self.segment_offset;
+        self.next_segment_id = self.next_segment_id.max(id + 1);
+        id
+    }
+
+    /// Drop what replayed audio repeats of the text committed before the
+    /// failover
+    fn dedupe(&self, text: String) -> String {
+        if self.dedupe_seam {
+            stitch::dedupe_seam(&self.committed_text, &text).to_string()
+        } else {
+            text
+        }
+    }
+
+    /// Record committed text and release the audio it covers
+    fn commit(&mut self, text: &str, timestamp_ms: u64, metadata: &TranscriptMetadata) {
+        if !text.is_empty() {
+            if !self.committed_text.is_empty() {
+                self.committed_text.push(' ');
+            }
+            self.committed_text.push_str(text);
+        }
+
+        if let Some(covered_ms) = self.covered_until(timestamp_ms, metadata) {
+            self.release_until(covered_ms.saturating_sub(REPLAY_OVERLAP_MS));
+        }
+    }
+
+    /// Session offset the audio of a commit ends at: its last word's end
+    /// (timed from the first audio the provider received), else the end of
+    /// the captured chunk its timestamp names. None when the commit carries
+    /// neither, so it releases nothing.
+    fn covered_until(&self, timestamp_ms: u64, metadata: &TranscriptMetadata) -> Option<u64> {
+        let covered_ms = match metadata.words.last() {
+            Some(word) if word.end_ms > 0 => self.replay_origin_ms + word.end_ms,
+            _ if timestamp_ms > 0 => self
+                .buffer
+                .iter()
+                .rev()
+                .find(|b| b.chunk.timestamp_ms <= timestamp_ms)?
+                .end_ms(),
+            _ => return None,
+        };
+        Some(covered_ms.min(self.audio_ms))
+    }
+
+    /// Drop buffered chunks that end at or before `until_ms`
+    fn release_until(&mut self, until_ms: u64) {
+        while self.buffer.front().is_some_and(|b| b.end_ms() <= until_ms) {
+            self.buffer.pop_front();
+        }
+    }
+
+    /// Move the session to the next provider in the chain. Returns true when
+    /// one took over; otherwise `error` is passed on and the session is over.
+    async fn fail_over(&mut self, chain: &mut [ChainLink], error: TranscriptionEvent) -> bool {
+        let Some(failed) = self.active.take() else {
+            return false;
+        };
+        self.events = None;
+        self.failed.push(failed);
+        if let TranscriptionEvent::Error { message, .. } = &error {
+            warn!("STT provider {} failed: {}", chain[failed].id, message);
+        }
+
+        if self.start_from(chain, failed + 1).await.is_ok() {
+            self.dedupe_seam = !self.committed_text.is_empty();
+            self.segment_offset = self.next_segment_id;
+            true
+        } else {
+            let _ = self.event_tx.send(error).await;
+            false
+        }
+    }
+
+    /// Stop the active provider and pass on its last events, failing over
+    /// (and replaying) again if it errors while finishing
+    async fn finish(&mut self, chain: &mut [ChainLink]) {
+        while let Some(active) = self.active {
+            let mut events = self.events.take();
+            let mut pending = Vec::new();
+            let stopped = {
+                // Keep reading while the provider flushes so it never blocks
+                // on a full event channel
+                let stop = chain[active].provider.stop_session();
+                tokio::pin!(stop);
+                loop {
+                    tokio::select! {
+                        result = &mut stop => break result,
+                        Some(event) = next_event(&mut events) => pending.push(event),
+                    }
+                }
+            };
+            if let Some(events) = events.as_mut() {
+                while let Ok(event) = events.try_recv() {
+                    pending.push(event);
+                }
+            }
+            if let Err(e) = stopped {
+                pending.push(error_event(&e));
+            }
+
+            let mut failed = false;
+            for event in pending {
+                if !self.event(chain, event).await {
+                    failed = true;
+                    break;
+                }
+            }
+            // A provider that took over has the replay and still needs
+            // stopping; otherwise the session is done
+            if !failed {
+                self.active = None;
+            }
+        }
+    }
+}
+
+/// Stop providers that failed during a session; their events are no longer
+/// read
+async fn stop_providers(chain: &mut [ChainLink], indices: Vec<usize>) {
+    for index in indices {
+        let _ = chain[index].provider.stop_session().await;
+    }
+}
+
+/// Next event of the active provider; pending while there is none
+async fn next_event(
+    events: &mut Option<mpsc::Receiver<TranscriptionEvent>>,
+) -> Option<TranscriptionEvent> {
+    match events {
+        Some(events) => events.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+fn error_event(error: &MurmurError) -> TranscriptionEvent {
+    TranscriptionEvent::Error {
+        message: error.to_string(),
+        detail: error.provider_error().cloned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lt_core::error::{ProviderError, ProviderErrorKind};
+    use lt_core::stt::TranscriptWord;
+
+    /// Provider that commits scripted text after given chunk counts and can
+    /// refuse to connect or error out mid-session. A commit covers the audio
+    /// up to the end of a given chunk, named by that chunk's timestamp or,
+    /// with `word_timings`, by a word ending there.
+    struct ScriptedProvider {
+        connects: bool,
+        fail_at_chunk: Option<usize>,
+        /// (after chunk, covering up to chunk, text)
+        commits: Vec<(usize, usize, &'static str)>,
+        word_timings: bool,
+        format: AudioFormat,
+        received: Arc<std::sync::Mutex<Vec<AudioChunk>>>,
+        event_tx: Option<mpsc::Sender<TranscriptionEvent>>,
+        event_rx: std::sync::Mutex<Option<mpsc::Receiver<TranscriptionEvent>>>,
+    }
+
+    impl ScriptedProvider {
+        fn new() -> Self {
+            Self {
+                connects: true,
+                fail_at_chunk: None,
+                commits: Vec::new(),
+                word_timings: false,
+                format: AudioFormat::default(),
+                received: Arc::new(std::sync::Mutex::new(Vec::new())),
+                event_tx: None,
+                event_rx: std::sync::Mutex::new(None),
+            }
+        }
+
+        fn offline() -> Self {
+            Self {
+                connects: false,
+                ..Self::new()
+            }
+        }
+
+        fn failing_at(chunk: usize) -> Self {
+            Self {
+                fail_at_chunk: Some(chunk),
+                ..Self::new()
+            }
+        }
+
+        fn committing(self, after_chunk: usize, text: &'static str) -> Self {
+            self.committing_late(after_chunk, after_chunk, text)
+        }
+
+        /// Commit after `after_chunk` covering only the audio up to
+        /// `covers_chunk`, like an upload that finishes while later chunks
+        /// are in flight
+        fn committing_late(
+            mut self,
+            after_chunk: usize,
+            covers_chunk: usize,
+            text: &'static str,
+        ) -> Self {
+            self.commits.push((after_chunk, covers_chunk, text));
+            self
+        }
+
+        fn received(&self) -> Arc<std::sync::Mutex<Vec<AudioChunk>>> {
+            self.received.clone()
+        }
+
+        fn emit(&self, event: TranscriptionEvent) {
+            if let Some(tx) = &self.event_tx {
+                tx.try_send(event).unwrap();
+            }
+        }
+    }
+
+    #[async_trait]
+    impl SttProvider for ScriptedProvider {
+        async fn start_session(&mut self) -> Result<()> {
+            if !self.connects {
+                return Err(ProviderError::new(
+                    ProviderErrorKind::Network,
+                    "scripted",
+                    "Connection refused",
+                )
+                .into());
+            }
+            let (tx, rx) = mpsc::channel(32);
+            self.event_tx = Some(tx);
+            *self.event_rx.lock().unwrap() = Some(rx);
+            Ok(())
+        }
+
+        async fn send_audio(&mut self, chunk: AudioChunk) -> Result<()> {
+            let (count, received) = {
+                let mut received = self.received.lock().unwrap();
+                received.push(chunk);
+                (received.len(), received.clone())
+            };
+            if self.fail_at_chunk == Some(count) {
+                self.emit(TranscriptionEvent::Error {
+                    message: "Quota exceeded".to_string(),
+                    detail: Some(ProviderError::new(
+                        ProviderErrorKind::Quota,
+                        "scripted",
+                        "Quota exceeded",
+                    )),
+                });
+            }
+            for (_, covers, text) in self.commits.iter().filter(|(n, _, _)| *n == count) {
+                let covered = &received[..*covers];
+                let mut metadata = TranscriptMetadata {
+                    segment_id: Some(0),
+                    ..Default::default()
+                };
+                let timestamp_ms = if self.word_timings {
+                    let end_ms = covered
+                        .iter()
+                        .map(|c| c.format().duration_ms(c.data.len()))
+                        .sum();
+                    metadata.words.push(TranscriptWord {
+                        text: text.to_string(),
+                        start_ms: 0,
+                        end_ms,
+                        confidence: None,
+                    });
+                    0
+                } else {
+                    covered.last().unwrap().timestamp_ms
+                };
+                self.emit(TranscriptionEvent::Committed {
+                    text: text.to_string(),
+                    timestamp_ms,
+                    metadata,
+                });
+            }
+            Ok(())
+        }
+
+        async fn stop_session(&mut self) -> Result<()> {
+            self.event_tx = None;
+            Ok(())
+        }
+
+        async fn subscribe_events(&self) -> mpsc::Receiver<TranscriptionEvent> {
+            self.event_rx.lock().unwrap().take().unwrap()
+        }
+
+        fn input_format(&self) -> AudioFormat {
+            self.format
+        }
+    }
+
+    /// One second of 16 kHz mono audio, each sample set to `marker`
+    fn second(marker: i16) -> AudioChunk {
+        AudioChunk {
+            data: vec![marker; 16000],
+            timestamp_ms: marker as u64 * 1000,
+            sample_rate: 16000,
+            channels: 1,
+        }
+    }
+
+    /// Run a session over `chunks` seconds of audio and collect its events
+    async fn run(provider: &mut FailoverSttProvider, chunks: i16) -> Vec<TranscriptionEvent> {
+        provider.start_session().await.unwrap();
+        let mut events = provider.subscribe_events().await;
+        for marker in 0..chunks {
+            provider.send_audio(second(marker)).await.unwrap();
+        }
+        provider.stop_session().await.unwrap();
+
+        let mut collected = Vec::new();
+        while let Some(event) = events.recv().await {
+            collected.push(event);
+        }
+        collected
+    }
+
+    fn committed(events: &[TranscriptionEvent]) -> Vec<(String, String, u64)> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                TranscriptionEvent::Committed { text, metadata, .. } => Some((
+                    text.clone(),
+                    metadata.provider.clone().unwrap(),
+                    metadata.segment_id.unwrap(),
+                )),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn markers(received: &std::sync::Mutex<Vec<AudioChunk>>) -> Vec<i16> {
+        received.lock().unwrap().iter().map(|c| c.data[0]).collect()
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_when_primary_cannot_connect() {
+        let backup = ScriptedProvider::new().committing(2, "hello");
+        let received = backup.received();
+        let mut provider =
+            FailoverSttProvider::new("primary", Box::new(ScriptedProvider::offline()))
+                .with_fallback("backup", Box::new(backup));
+
+        let events = run(&mut provider, 2).await;
+        assert_eq!(
+            committed(&events),
+            vec![("hello".to_string(), "backup".to_string(), 0)]
+        );
+        assert_eq!(markers(&received), vec![0, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_mid_session_error_replays_buffered_audio() {
+        let primary = ScriptedProvider::failing_at(3);
+        let backup = ScriptedProvider::new().committing(4, "all four seconds");
+        let received = backup.received();
+        let mut provider = FailoverSttProvider::new("primary", Box::new(primary))
+            .with_fallback("backup", Box::new(backup));
+
+        let events = run(&mut provider, 4).await;
+        assert!(!events
+            .iter()
+            .any(|e| matches!(e, TranscriptionEvent::Error { .. })));
+        assert_eq!(
+            committed(&events),
+            vec![("all four seconds".to_string(), "backup".to_string(), 0)]
+        );
+        assert_eq!(markers(&received), vec![0, 1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_replay_skips_committed_audio_and_repeated_words() {
+        // Commit after 5 s keeps the last 3 s for the replay
+        let primary = ScriptedProvider::failing_at(6).committing(5, "the quick brown fox jumps");
+        let backup = ScriptedProvider::new().committing(5, "brown fox jumps over the dog");
+        let received = backup.received();
+        let mut provider = FailoverSttProvider::new("primary", Box::new(primary))
+            .with_fallback("backup", Box::new(backup));
+
+        provider.start_session().await.unwrap();
+        let mut events = provider.subscribe_events().await;
+        let mut collected = Vec::new();
+        for marker in 0..7 {
+            provider.send_audio(second(marker)).await.unwrap();
+            if marker == 4 {
+                // Let the commit land before more audio arrives
+                collected.push(events.recv().await.unwrap());
+            }
+        }
+        provider.stop_session().await.unwrap();
+        while let Some(event) = events.recv().await {
+            collected.push(event);
+        }
+        let events = collected;
+
+        assert_eq!(
+            committed(&events),
+            vec![
+                (
+                    "the quick brown fox jumps".to_string(),
+                    "primary".to_string(),
+                    0
+                ),
+                ("over the dog".to_string(), "backup".to_string(), 1),
+            ]
+        );
+        assert_eq!(markers(&received), vec![2, 3, 4, 5, 6]);
+    }
+
+    #[tokio::test]
+    async fn test_error_passed_on_when_chain_is_exhausted() {
+        let mut provider =
+            FailoverSttProvider::new("primary", Box::new(ScriptedProvider::failing_at(1)))
+                .with_fallback("backup", Box::new(ScriptedProvider::offline()));
+
+        let events = run(&mut provider, 2).await;
+        match events.as_slice() {
+            [TranscriptionEvent::Error { detail, .. }] => {
+                assert_eq!(detail.as_ref().unwrap().kind, ProviderErrorKind::Quota);
+            }
+            other => panic!("Unexpected events {:?}", other),
+        }
+
+        let mut offline =
+            FailoverSttProvider::new("primary", Box::new(ScriptedProvider::offline()));
+        assert!(offline.start_session().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_each_session_starts_on_the_primary() {
+        let primary = ScriptedProvider::new()
+            .committing(1, "first")
+            .committing(2, "second");
+        let received = primary.received();
+        let mut provider = FailoverSttProvider::new("primary", Box::new(primary))
+            .with_fallback("backup", Box::new(ScriptedProvider::new()));
+
+        run(&mut provider, 1).await;
+        let events = run(&mut provider, 1).await;
+        assert_eq!(
+            committed(&events),
+            vec![("second".to_string(), "primary".to_string(), 0)]
+        );
+        assert_eq!(markers(&received), vec![0, 0]);
+    }
+
+    #[tokio::test]
+    async fn test_replay_is_capped_at_a_minute() {
+        let primary = ScriptedProvider::failing_at(65);
+        let backup = ScriptedProvider::new();
+        let received = backup.received();
+        let mut provider = FailoverSttProvider::new("primary", Box::new(primary))
+            .with_fallback("backup", Box::new(backup));
+
+        run(&mut provider, 65).await;
+        assert_eq!(markers(&received), (5..65).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_fallback_gets_its_own_format() {
+        let primary = ScriptedProvider::failing_at(2);
+        let mut backup = ScriptedProvider::new();
+        backup.format = AudioFormat::mono(24000);
+        let received = backup.received();
+        let mut provider = FailoverSttProvider::new("primary", Box::new(primary))
+            .with_fallback("backup", Box::new(backup));
+
+        run(&mut provider, 3).await;
+        let received = received.lock().unwrap();
+        assert!(received.iter().all(|c| c.sample_rate == 24000));
+        // The resampler runs across chunks; only the last sample waits for
+        // a neighbour that never comes
+        let samples: usize = received.iter().map(|c| c.data.len()).sum();
+        assert_eq!(samples, 3 * 24000 - 1);
+    }
+
+    #[tokio::test]
+    async fn test_replay_keeps_audio_still_in_flight_at_a_commit() {
+        for word_timings in [false, true] {
+            // The upload covering 4 s finishes only after 7 s were sent
+            let mut primary =
+                ScriptedProvider::failing_at(8).committing_late(7, 4, "the quick brown fox");
+            primary.word_timings = word_timings;
+            let backup = ScriptedProvider::new();
+            let received = backup.received();
+            let mut provider = FailoverSttProvider::new("primary", Box::new(primary))
+                .with_fallback("backup", Box::new(backup));
+
+            provider.start_session().await.unwrap();
+            let mut events = provider.subscribe_events().await;
+            for marker in 0..8 {
+                provider.send_audio(second(marker)).await.unwrap();
+                if marker == 6 {
+                    events.recv().await.unwrap();
+                }
+            }
+            provider.stop_session().await.unwrap();
+
+            // Only what ends 3 s before the committed audio is released
+            assert_eq!(markers(&received), (1..8).collect::<Vec<_>>());
+        }
+    }
+}
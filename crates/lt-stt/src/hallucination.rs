@@ -0,0 +1,321 @@
+//! Hallucination filter for Whisper-style transcripts
+//!
+//! Whisper models invent text for silence and noise: closing lines from the
+//! subtitle data they were trained on ("Thank you for watching.", "Subtitles
+//! by …") and the same few words repeated over and over. [`HallucinationFilter`]
+//! removes both from a chunk's text before it is accumulated.
+
+use crate::stitch;
+
+/// Phrases Whisper is known to produce from silence. A trailing `…` makes
+/// the phrase a prefix ("Subtitles by …" matches any credit line).
+pub const DEFAULT_PHRASES: &[&str] = &[
+    "Thank you for watching",
+    "Thanks for watching",
+    "Thank you so much for watching",
+    "Thank you for watching and see you next time",
+    "Please subscribe to my channel",
+    "Please like and subscribe",
+    "Like and subscribe",
+    "Don't forget to like and subscribe",
+    "See you in the next video",
+    "Subtitles by …",
+    "Subtitles made by …",
+    "Subtitles created by …",
+    "Captions by …",
+    "Transcribed by …",
+    "Translated by …",
+    "Amara.org …",
+    "you",
+];
+
+/// Consecutive repetitions of the same words that count as a loop
+pub const DEFAULT_LOOP_THRESHOLD: usize = 4;
+
+/// Longest repeated word sequence considered when looking for loops
+const MAX_LOOP_TOKENS: usize = 6;
+
+/// Characters that end a sentence
+const SENTENCE_TERMINATORS: &[char] = &['.', '!', '?', '…', '。', '！', '？'];
+
+/// A phantom phrase in comparison form
+#[derive(Debug, Clone)]
+struct Phrase {
+    key: String,
+    prefix: bool,
+}
+
+impl Phrase {
+    fn parse(phrase: &str) -> Option<Self> {
+        let trimmed = phrase.trim();
+        let (body, prefix) = match trimmed
+            .strip_suffix('…')
+            .or_else(|| trimmed.strip_suffix("..."))
+        {
+            Some(body) => (body, true),
+            None => (trimmed, false),
+        };
+        let key = normalize(body);
+        (!key.is_empty()).then_some(Self { key, prefix })
+    }
+
+    fn matches(&self, sentence_key: &str) -> bool {
+        if self.prefix {
+            sentence_key == self.key
+                || sentence_key
+                    .strip_prefix(&self.key)
+                    .is_some_and(|rest| rest.starts_with(' '))
+        } else {
+            sentence_key == self.key
+        }
+    }
+}
+
+/// Result of filtering one chunk of text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilteredText {
+    /// Text with hallucinations removed (whitespace collapsed if anything
+    /// was removed)
+    pub text: String,
+    /// Number of phrases and loops removed
+    pub removed: u32,
+}
+
+/// Drops phantom phrases and repetition loops from transcribed text
+#[derive(Debug, Clone)]
+pub struct HallucinationFilter {
+    phrases: Vec<Phrase>,
+    loop_threshold: usize,
+}
+
+impl Default for HallucinationFilter {
+    /// The built-in phrase list with loop detection
+    fn default() -> Self {
+        Self::disabled()
+            .with_phrases(DEFAULT_PHRASES.iter().copied())
+            .with_loop_threshold(DEFAULT_LOOP_THRESHOLD)
+    }
+}
+
+impl HallucinationFilter {
+    /// A filter that leaves all text untouched
+    pub fn disabled() -> Self {
+        Self {
+            phrases: Vec::new(),
+            loop_threshold: 0,
+        }
+    }
+
+    /// Also drop sentences matching these phrases (case and punctuation are
+    /// ignored; end a phrase with `…` to match it as a prefix)
+    pub fn with_phrases<I, S>(mut self, phrases: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.phrases.extend(
+            phrases
+                .into_iter()
+                .filter_map(|p| Phrase::parse(p.as_ref())),
+        );
+        self
+    }
+
+    /// Collapse word sequences repeated `threshold` or more times in a row
+    /// to a single occurrence (0 = off)
+    pub fn with_loop_threshold(mut self, threshold: usize) -> Self {
+        self.loop_threshold = threshold;
+        self
+    }
+
+    /// True when the filter can never change any text
+    pub fn is_disabled(&self) -> bool {
+        self.phrases.is_empty() && self.loop_threshold < 2
+    }
+
+    /// Remove phantom sentences, then repetition loops, from `text`
+    pub fn apply(&self, text: &str) -> FilteredText {
+        let mut removed = 0;
+        let mut text = text.to_string();
+
+        if !self.phrases.is_empty() {
+            let ranges: Vec<_> = sentences(&text)
+                .into_iter()
+                .filter(|&(start, end)| {
+                    let key = normalize(&text[start..end]);
+                    self.phrases.iter().any(|p| p.matches(&key))
+                })
+                .collect();
+            removed += ranges.len() as u32;
+            text = remove_ranges(&text, &ranges);
+        }
+
+        if self.loop_threshold >= 2 {
+            let ranges = loop_ranges(&text, self.loop_threshold);
+            removed += ranges.len() as u32;
+            text = remove_ranges(&text, &ranges);
+        }
+
+        FilteredText { text, removed }
+    }
+}
+
+/// Comparison form of `text`: lowercase words without punctuation
+fn normalize(text: &str) -> String {
+    stitch::tokenize(text)
+        .into_iter()
+        .map(|t| t.key)
+        .filter(|k| !k.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Byte ranges of the sentences in `text`
+fn sentences(text: &str) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut start = None;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if start.is_none() && !c.is_whitespace() {
+            start = Some(i);
+        }
+        let ends_sentence = SENTENCE_TERMINATORS.contains(&c)
+            && chars.peek().is_none_or(|&(_, next)| next.is_whitespace());
+        if let (true, Some(s)) = (ends_sentence, start) {
+            ranges.push((s, i + c.len_utf8()));
+            start = None;
+        }
+    }
+    if let Some(s) = start {
+        ranges.push((s, text.trim_end().len()));
+    }
+    ranges
+}
+
+/// Byte ranges covering all but the last repetition of each loop in `text`
+fn loop_ranges(text: &str, threshold: usize) -> Vec<(usize, usize)> {
+    let tokens = stitch::tokenize(text);
+    let mut ranges = Vec::new();
+    let mut i = 0;
+    'outer: while i < tokens.len() {
+        for n in 1..=MAX_LOOP_TOKENS {
+            if i + n * threshold > tokens.len() {
+                break;
+            }
+            let unit = &tokens[i..i + n];
+            if unit.iter().any(|t| t.key.is_empty()) {
+                break;
+            }
+            let mut repeats = 1;
+            while i + (repeats + 1) * n <= tokens.len()
+                && tokens[i + repeats * n..i + (repeats + 1) * n]
+                    .iter()
+                    .zip(unit)
+                    .all(|(a, b)| a.key == b.key)
+            {
+                repeats += 1;
+            }
+            if repeats >= threshold {
+                // Keep only the last occurrence, with its punctuation
+                ranges.push((tokens[i].start, tokens[i + (repeats - 1) * n].start));
+                i += repeats * n;
+                continue 'outer;
+            }
+        }
+        i += 1;
+    }
+    ranges
+}
+
+/// `text` without the given (sorted, disjoint) byte ranges, whitespace
+/// collapsed
+fn remove_ranges(text: &str, ranges: &[(usize, usize)]) -> String {
+    if ranges.is_empty() {
+        return text.to_string();
+    }
+    let mut kept = String::with_capacity(text.len());
+    let mut position = 0;
+    for &(start, end) in ranges {
+        kept.push_str(&text[position..start]);
+        kept.push(' ');
+        position = end;
+    }
+    kept.push_str(&text[position..]);
+    kept.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drops_known_phantom_phrases() {
+        let filter = HallucinationFilter::default();
+        assert_eq!(
+            filter.apply("Thank you for watching."),
+            FilteredText {
+                text: String::new(),
+                removed: 1
+            }
+        );
+        assert_eq!(
+            filter.apply("Subtitles by the Amara.org community").text,
+            ""
+        );
+        assert_eq!(filter.apply(" you ").text, "");
+    }
+
+    #[test]
+    fn test_keeps_real_speech_around_phantoms() {
+        let filter = HallucinationFilter::default();
+        let filtered = filter.apply("Send the report today. Thanks for watching!");
+        assert_eq!(filtered.text, "Send the report today.");
+        assert_eq!(filtered.removed, 1);
+
+        // Phrases only match whole sentences
+        let text = "Thank you for watching the kids yesterday.";
+        assert_eq!(filter.apply(text).text, text);
+        assert_eq!(filter.apply("Are you there?").removed, 0);
+    }
+
+    #[test]
+    fn test_collapses_repetition_loops() {
+        let filter = HallucinationFilter::default();
+        let filtered = filter.apply("so I think I think I think I think I think.");
+        assert_eq!(filtered.text, "so I think.");
+        assert_eq!(filtered.removed, 1);
+
+        // Short genuine repetitions are left alone
+        assert_eq!(filter.apply("no no no, not that").removed, 0);
+    }
+
+    #[test]
+    fn test_custom_phrases_and_thresholds() {
+        let filter = HallucinationFilter::disabled()
+            .with_phrases(["Untertitel im Auftrag des ZDF…"])
+            .with_loop_threshold(3);
+        assert_eq!(
+            filter
+                .apply("Untertitel im Auftrag des ZDF, 2021. Hallo zusammen.")
+                .text,
+            "Hallo zusammen."
+        );
+        assert_eq!(filter.apply("no no no").text, "no");
+        // Built-in phrases are not included
+        assert_eq!(filter.apply("Thank you for watching.").removed, 0);
+    }
+
+    #[test]
+    fn test_disabled_filter_is_noop() {
+        let filter = HallucinationFilter::disabled();
+        assert!(filter.is_disabled());
+        let text = "Thank you for watching.  you you you you";
+        assert_eq!(
+            filter.apply(text),
+            FilteredText {
+                text: text.to_string(),
+                removed: 0
+            }
+        );
+    }
+}
@@ -1,16 +1,36 @@
+pub mod assemblyai;
 pub mod chunker;
-pub mod custom;
+pub mod deepgram;
 pub mod elevenlabs;
-pub mod groq;
-pub mod openai;
+pub mod encoding;
+mod error;
+pub mod failover;
+pub mod hallucination;
+pub mod openai_realtime;
+mod resample;
+pub mod rest;
+pub mod stitch;
+pub mod streaming;
+mod whisper;
+mod ws;
 
 #[cfg(target_os = "macos")]
 pub mod apple;
 
-pub use custom::CustomSttProvider;
+#[cfg(feature = "local-whisper")]
+pub mod local_whisper;
+
+pub use assemblyai::AssemblyAiProvider;
+pub use deepgram::DeepgramProvider;
 pub use elevenlabs::ElevenLabsProvider;
-pub use groq::GroqProvider;
-pub use openai::OpenAIProvider;
+pub use failover::FailoverSttProvider;
+pub use hallucination::HallucinationFilter;
+pub use openai_realtime::OpenAIRealtimeProvider;
+pub use rest::{RestEndpoint, RestSttProvider};
+pub use streaming::{StreamingEndpoint, StreamingSttProvider};
 
 #[cfg(target_os = "macos")]
 pub use apple::AppleSttProvider;
+
+#[cfg(feature = "local-whisper")]
+pub use local_whisper::LocalWhisperProvider;
@@ -0,0 +1,789 @@
+//! On-device transcription with whisper.cpp (`local-whisper` feature)
+//!
+//! A GGML/GGUF Whisper model is loaded once per process and run on the CPU.
+//! The open segment is decoded again every step for partials and committed
+//! at a pause, or when it reaches the window length; a segment cut without a
+//! pause keeps a little overlap so the word at the cut is heard whole. Each
+//! decode is prompted with the text committed so far, as the REST Whisper
+//! providers do between chunks.
+
+use async_trait::async_trait;
+use lt_core::config::expand_home;
+use lt_core::error::{MurmurError, ProviderError, ProviderErrorKind, Result};
+use lt_core::stt::{
+    AudioChunk, AudioFormat, SessionMetrics, SttCapabilities, SttHealth, SttMode, SttProvider,
+    TranscriptMetadata, TranscriptionEvent,
+};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::{mpsc, Mutex};
+use tracing::{debug, error, info, warn};
+use whisper_rs::{
+    FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters, WhisperState,
+};
+
+use crate::hallucination::HallucinationFilter;
+use crate::whisper::{self, ChunkTranscript};
+
+/// Provider id, used in errors and events
+const PROVIDER_ID: &str = "local_whisper";
+
+/// Sample rate whisper.cpp models take
+const SAMPLE_RATE: u32 = 16000;
+
+/// How often the open segment is decoded unless configured
+pub const DEFAULT_STEP_MS: u64 = 1000;
+
+/// Longest segment unless configured
+pub const DEFAULT_LENGTH_MS: u64 = 10_000;
+
+/// Decoder threads are capped here unless configured (whisper.cpp's default)
+const MAX_DEFAULT_THREADS: usize = 4;
+
+/// Trailing quiet audio that commits the open segment
+const PAUSE_MS: u64 = 600;
+
+/// Quiet audio kept ahead of the first speech of a segment
+const LEAD_IN_MS: u64 = 200;
+
+/// Audio repeated at the start of the next segment when one is cut without
+/// a pause
+const KEEP_MS: u64 = 200;
+
+/// Length of the frames checked for speech
+const FRAME_MS: u64 = 20;
+
+/// RMS level (0.0-1.0) below which a frame counts as quiet
+const SILENCE_THRESHOLD: f32 = 0.01;
+
+/// whisper.cpp returns nothing for input under a second, so shorter
+/// segments are padded with silence up to this length
+const MIN_INPUT_MS: u64 = 1100;
+
+/// Model kept loaded between sessions
+struct LoadedModel {
+    path: PathBuf,
+    context: Arc<WhisperContext>,
+}
+
+static LOADED_MODEL: std::sync::Mutex<Option<LoadedModel>> = std::sync::Mutex::new(None);
+
+/// Load the model at `path`, or reuse it if it is the one already loaded.
+/// Blocks for as long as reading the file takes.
+fn load_model(path: &Path) -> Result<Arc<WhisperContext>> {
+    let mut loaded = LOADED_MODEL.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(model) = loaded.as_ref().filter(|m| m.path == path) {
+        return Ok(model.context.clone());
+    }
+
+    if path.as_os_str().is_empty() {
+        return Err(ProviderError::new(
+            ProviderErrorKind::Unavailable,
+            PROVIDER_ID,
+            "No Whisper model configured. Set local_whisper_config.model_path",
+        )
+        .into());
+    }
+    if !path.is_file() {
+        return Err(ProviderError::new(
+            ProviderErrorKind::Unavailable,
+            PROVIDER_ID,
+            format!("Whisper model not found at {}", path.display()),
+        )
+        .into());
+    }
+
+    // Route whisper.cpp's own logging through tracing instead of stderr
+    whisper_rs::install_logging_hooks();
+
+    info!("Loading Whisper model from {}", path.display());
+    let started = Instant::now();
+    // Drop the previous model first so two are never held at once
+    *loaded = None;
+    let context = WhisperContext::new_with_params(path, WhisperContextParameters::default())
+        .map_err(|e| {
+            ProviderError::new(
+                ProviderErrorKind::Unavailable,
+                PROVIDER_ID,
+                format!("Failed to load Whisper model {}: {}", path.display(), e),
+            )
+        })?;
+    info!(
+        "Whisper model loaded in {}ms",
+        started.elapsed().as_millis()
+    );
+
+    let context = Arc::new(context);
+    *loaded = Some(LoadedModel {
+        path: path.to_path_buf(),
+        context: context.clone(),
+    });
+    Ok(context)
+}
+
+/// Root-mean-square level of `samples` (already in -1.0..1.0)
+fn rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let sum: f32 = samples.iter().map(|s| s * s).sum();
+    (sum / samples.len() as f32).sqrt()
+}
+
+/// What to do with the open segment after new audio arrived
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WindowAction {
+    /// Not enough new audio since the last decode, or no speech yet
+    Wait,
+    /// Decode the segment and report it as a partial
+    Partial,
+    /// Decode the segment and commit it
+    Commit,
+}
+
+/// Audio of the open segment and the rules for decoding it
+struct SlidingWindow {
+    format: AudioFormat,
+    /// Segment audio, normalized to -1.0..1.0
+    samples: Vec<f32>,
+    /// Session position of the first sample, in samples
+    start: u64,
+    /// Session position (ms) where audio not heard by the previous segment
+    /// begins, when this one starts with overlap
+    seam_ms: Option<u64>,
+    /// Samples added since the segment was last decoded
+    undecoded: usize,
+    /// Whether the segment holds anything louder than silence
+    has_speech: bool,
+    step: usize,
+    length: usize,
+}
+
+impl SlidingWindow {
+    fn new(step_ms: u64, length_ms: u64) -> Self {
+        let format = AudioFormat::mono(SAMPLE_RATE);
+        Self {
+            format,
+            samples: Vec::new(),
+            start: 0,
+            seam_ms: None,
+            undecoded: 0,
+            has_speech: false,
+            step: format.samples_for_ms(step_ms).max(1),
+            length: format.samples_for_ms(length_ms.max(step_ms)).max(1),
+        }
+    }
+
+    /// Append 16-bit mono PCM. Until speech arrives only a short lead-in is
+    /// kept, so silence is never decoded.
+    fn push(&mut self, pcm: &[i16]) {
+        let added = self.samples.len();
+        self.samples
+            .extend(pcm.iter().map(|&s| s as f32 / i16::MAX as f32));
+        self.undecoded += pcm.len();
+
+        if !self.has_speech {
+            self.has_speech = self.contains_speech(added);
+        }
+        if !self.has_speech {
+            let lead_in = self.format.samples_for_ms(LEAD_IN_MS);
+            let excess = self.samples.len().saturating_sub(lead_in);
+            self.samples.drain(..excess);
+            self.start += excess as u64;
+            self.undecoded = 0;
+            self.seam_ms = None;
+        }
+    }
+
+    /// True when any frame from `from` on is louder than silence
+    fn contains_speech(&self, from: usize) -> bool {
+        let frame = self.format.samples_for_ms(FRAME_MS).max(1);
+        self.samples[from..]
+            .chunks(frame)
+            .any(|f| rms(f) >= SILENCE_THRESHOLD)
+    }
+
+    /// True when the trailing pause of the segment is quiet
+    fn ends_in_pause(&self) -> bool {
+        let pause = self.format.samples_for_ms(PAUSE_MS);
+        self.samples.len() >= pause
+            && rms(&self.samples[self.samples.len() - pause..]) < SILENCE_THRESHOLD
+    }
+
+    fn next_action(&self) -> WindowAction {
+        if !self.has_speech {
+            WindowAction::Wait
+        } else if self.samples.len() >= self.length || self.ends_in_pause() {
+            WindowAction::Commit
+        } else if self.undecoded >= self.step {
+            WindowAction::Partial
+        } else {
+            WindowAction::Wait
+        }
+    }
+
+    /// Segment audio as whisper.cpp takes it, padded to the minimum length
+    fn input(&self) -> Vec<f32> {
+        let mut input = self.samples.clone();
+        let min = self.format.samples_for_ms(MIN_INPUT_MS);
+        if input.len() < min {
+            input.resize(min, 0.0);
+        }
+        input
+    }
+
+    /// Session offset of `position` samples in milliseconds
+    fn position_ms(&self, position: u64) -> u64 {
+        position * 1000 / self.format.sample_rate as u64
+    }
+
+    fn mark_decoded(&mut self) {
+        self.undecoded = 0;
+    }
+
+    /// Start the next segment after a commit. A segment cut mid-speech
+    /// hands its last `KEEP_MS` to the next one.
+    fn advance(&mut self) {
+        let end = self.start + self.samples.len() as u64;
+        let keep = if self.ends_in_pause() {
+            0
+        } else {
+            self.format.samples_for_ms(KEEP_MS).min(self.samples.len())
+        };
+        self.samples.drain(..self.samples.len() - keep);
+        self.start = end - keep as u64;
+        self.seam_ms = (keep > 0).then(|| self.position_ms(end));
+        self.undecoded = keep;
+        self.has_speech = keep > 0 && self.contains_speech(0);
+    }
+}
+
+/// Settings every decode of a session uses
+#[derive(Debug, Clone)]
+struct DecodeSettings {
+    /// Pinned language, or `None` to detect it
+    language: Option<String>,
+    threads: usize,
+    /// Style prompt and vocabulary put ahead of the committed text
+    prompt_prefix: Option<String>,
+}
+
+/// Runs whisper.cpp on the session audio and turns the results into events
+struct Decoder {
+    state: WhisperState,
+    settings: DecodeSettings,
+    hallucination_filter: HallucinationFilter,
+    event_tx: mpsc::Sender<TranscriptionEvent>,
+    /// Text committed so far, for prompts and overlap trimming
+    committed: String,
+    segment_id: u64,
+    /// Whether the open segment was shown as a partial
+    partial_sent: bool,
+    /// Counters of the open segment
+    metrics: SessionMetrics,
+    last_timestamp_ms: u64,
+}
+
+impl Decoder {
+    /// Decode `audio` with the committed text as prompt
+    fn decode(&mut self, audio: &[f32]) -> Result<ChunkTranscript> {
+        let prompt =
+            whisper::context_prompt(self.settings.prompt_prefix.as_deref(), &self.committed);
+        let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+        params.set_n_threads(self.settings.threads as i32);
+        params.set_language(Some(self.settings.language.as_deref().unwrap_or("auto")));
+        // Context is carried by the prompt, not by the decoder state
+        params.set_no_context(true);
+        params.set_suppress_blank(true);
+        params.set_print_special(false);
+        params.set_print_progress(false);
+        params.set_print_realtime(false);
+        params.set_print_timestamps(false);
+        if let Some(prompt) = &prompt {
+            params.set_initial_prompt(prompt);
+        }
+
+        self.state
+            .full(params, audio)
+            .map_err(|e| MurmurError::Stt(format!("Local Whisper decoding failed: {}", e)))?;
+
+        let text = self
+            .state
+            .as_iter()
+            .filter_map(|segment| segment.to_str_lossy().ok())
+            .map(|text| text.trim().to_string())
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let language =
+            whisper_rs::get_lang_str(self.state.full_lang_id_from_state()).map(str::to_string);
+
+        Ok(ChunkTranscript {
+            text,
+            metadata: TranscriptMetadata {
+                language,
+                ..Default::default()
+            },
+        })
+    }
+
+    /// Decode the open segment and report it, as a partial or committed
+    fn process(&mut self, window: &SlidingWindow, commit: bool) {
+        let transcript = match self.decode(&window.input()) {
+            Ok(transcript) => transcript,
+            Err(e) => {
+                error!("Local Whisper transcription failed: {}", e);
+                let _ = self.event_tx.blocking_send(TranscriptionEvent::Error {
+                    message: format!("{} transcription error: {}", PROVIDER_ID, e),
+                    detail: e.provider_error().cloned(),
+                });
+                return;
+            }
+        };
+
+        // Partials are decoded again later; only the commit is counted
+        let mut partial_metrics = SessionMetrics::default();
+        let metrics = if commit {
+            &mut self.metrics
+        } else {
+            &mut partial_metrics
+        };
+        let ChunkTranscript { text, metadata } = transcript
+            .trim_overlap(&self.committed, window.seam_ms)
+            .filter_hallucinations(&self.hallucination_filter, metrics);
+        let metadata = TranscriptMetadata {
+            segment_id: Some(self.segment_id),
+            ..metadata
+        };
+
+        let event = if commit {
+            // An empty commit still clears a partial shown for the segment
+            if text.is_empty() && !self.partial_sent {
+                return;
+            }
+            debug!(
+                "Local Whisper committed segment {}: {}",
+                self.segment_id, text
+            );
+            if !text.is_empty() {
+                if !self.committed.is_empty() {
+                    self.committed.push(' ');
+                }
+                self.committed.push_str(&text);
+            }
+            self.segment_id += 1;
+            self.partial_sent = false;
+            TranscriptionEvent::Committed {
+                text,
+                timestamp_ms: self.last_timestamp_ms,
+                metadata: TranscriptMetadata {
+                    metrics: std::mem::take(&mut self.metrics),
+                    ..metadata
+                },
+            }
+        } else {
+            if text.is_empty() {
+                return;
+            }
+            self.partial_sent = true;
+            TranscriptionEvent::Partial {
+                text,
+                timestamp_ms: self.last_timestamp_ms,
+                metadata,
+            }
+        };
+
+        if let Err(e) = self.event_tx.blocking_send(event) {
+            error!("Failed to send local Whisper event: {}", e);
+        }
+    }
+
+    /// Decode audio as it arrives until the sender is dropped, then commit
+    /// what is left. Runs on a blocking thread.
+    fn run(mut self, mut audio_rx: mpsc::Receiver<AudioChunk>, mut window: SlidingWindow) {
+        while let Some(chunk) = audio_rx.blocking_recv() {
+            self.last_timestamp_ms = chunk.timestamp_ms;
+            window.push(&chunk.data);
+            // Catch up on audio that queued during a slow decode, so the
+            // next partial covers all of it, without running past a commit
+            while window.next_action() != WindowAction::Commit {
+                let Ok(chunk) = audio_rx.try_recv() else {
+                    break;
+                };
+                self.last_timestamp_ms = chunk.timestamp_ms;
+                window.push(&chunk.data);
+            }
+
+            match window.next_action() {
+                WindowAction::Wait => {}
+                WindowAction::Partial => {
+                    window.mark_decoded();
+                    self.process(&window, false);
+                }
+                WindowAction::Commit => {
+                    self.process(&window, true);
+                    window.advance();
+                }
+            }
+        }
+
+        if window.has_speech {
+            self.process(&window, true);
+        }
+        info!("Local Whisper transcription task finished");
+    }
+}
+
+/// Offline speech-to-text with a whisper.cpp model on this machine
+pub struct LocalWhisperProvider {
+    model_path: PathBuf,
+    language: Option<String>,
+    threads: usize,
+    step_ms: u64,
+    length_ms: u64,
+    style_prompt: Option<String>,
+    vocabulary: Vec<String>,
+    hallucination_filter: HallucinationFilter,
+    audio_tx: Arc<Mutex<Option<mpsc::Sender<AudioChunk>>>>,
+    event_rx: Arc<Mutex<Option<mpsc::Receiver<TranscriptionEvent>>>>,
+    worker: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
+}
+
+impl LocalWhisperProvider {
+    /// Provider for the model file at `model_path` (a leading `~` is the
+    /// home directory). The model is loaded when the first session starts.
+    pub fn new(model_path: &str) -> Self {
+        let threads = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+            .min(MAX_DEFAULT_THREADS);
+        Self {
+            model_path: expand_home(model_path.trim()),
+            language: None,
+            threads,
+            step_ms: DEFAULT_STEP_MS,
+            length_ms: DEFAULT_LENGTH_MS,
+            style_prompt: None,
+            vocabulary: Vec::new(),
+            hallucination_filter: HallucinationFilter::default(),
+            audio_tx: Arc::new(Mutex::new(None)),
+            event_rx: Arc::new(Mutex::new(None)),
+            worker: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Pin the language (ISO-639-1); `None` or "auto" detects it
+    pub fn with_language(mut self, language: Option<String>) -> Self {
+        self.language = language.filter(|l| !l.is_empty() && l != "auto");
+        self
+    }
+
+    /// CPU threads used for decoding (`None` keeps the default)
+    pub fn with_threads(mut self, threads: Option<usize>) -> Self {
+        if let Some(threads) = threads {
+            self.threads = threads.max(1);
+        }
+        self
+    }
+
+    /// How often partials are decoded and the longest segment before it is
+    /// committed without a pause (`None` keeps the default)
+    pub fn with_window(mut self, step_ms: Option<u64>, length_ms: Option<u64>) -> Self {
+        if let Some(step_ms) = step_ms {
+            self.step_ms = step_ms.max(FRAME_MS);
+        }
+        if let Some(length_ms) = length_ms {
+            self.length_ms = length_ms;
+        }
+        self
+    }
+
+    /// Prompt put ahead of the previous text with every decode, e.g. to set
+    /// spelling or punctuation style
+    pub fn with_style_prompt(mut self, prompt: Option<String>) -> Self {
+        self.style_prompt = prompt.filter(|p| !p.trim().is_empty());
+        self
+    }
+
+    /// Terms whose spelling the model should follow (e.g. the personal
+    /// dictionary), added to the prompt
+    pub fn with_vocabulary<I, S>(mut self, terms: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        for term in terms {
+            let term = term.into().trim().to_string();
+            if !term.is_empty() && !self.vocabulary.contains(&term) {
+                self.vocabulary.push(term);
+            }
+        }
+        self
+    }
+
+    /// Replace the default hallucination filter applied to each segment
+    pub fn with_hallucination_filter(mut self, filter: HallucinationFilter) -> Self {
+        self.hallucination_filter = filter;
+        self
+    }
+
+    /// Model file this provider loads
+    pub fn model_path(&self) -> &Path {
+        &self.model_path
+    }
+
+    /// Style prompt followed by the vocabulary
+    fn prompt_prefix(&self) -> Option<String> {
+        let vocabulary = self.vocabulary.join(", ");
+        let prefix = [
+            self.style_prompt.as_deref().unwrap_or_default(),
+            &vocabulary,
+        ]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+        (!prefix.is_empty()).then_some(prefix)
+    }
+
+    /// Load the model (or reuse the loaded one) without blocking the runtime
+    async fn context(&self) -> Result<Arc<WhisperContext>> {
+        let path = self.model_path.clone();
+        tokio::task::spawn_blocking(move || load_model(&path))
+            .await
+            .map_err(|e| MurmurError::Stt(format!("Whisper model loading panicked: {}", e)))?
+    }
+}
+
+#[async_trait]
+impl SttProvider for LocalWhisperProvider {
+    async fn start_session(&mut self) -> Result<()> {
+        info!(
+            "Starting local Whisper session ({}, {} threads)",
+            self.model_path.display(),
+            self.threads
+        );
+
+        let state = self.context().await?.create_state().map_err(|e| {
+            ProviderError::new(
+                ProviderErrorKind::Unavailable,
+                PROVIDER_ID,
+                format!("Failed to create Whisper state: {}", e),
+            )
+        })?;
+
+        let (audio_tx, audio_rx) = mpsc::channel::<AudioChunk>(256);
+        *self.audio_tx.lock().await = Some(audio_tx);
+
+        let (event_tx, event_rx) = mpsc::channel::<TranscriptionEvent>(64);
+        *self.event_rx.lock().await = Some(event_rx);
+
+        let decoder = Decoder {
+            state,
+            settings: DecodeSettings {
+                language: self.language.clone(),
+                threads: self.threads,
+                prompt_prefix: self.prompt_prefix(),
+            },
+            hallucination_filter: self.hallucination_filter.clone(),
+            event_tx,
+            committed: String::new(),
+            segment_id: 0,
+            partial_sent: false,
+            metrics: SessionMetrics::default(),
+            last_timestamp_ms: 0,
+        };
+        let window = SlidingWindow::new(self.step_ms, self.length_ms);
+        let worker = tokio::task::spawn_blocking(move || decoder.run(audio_rx, window));
+        *self.worker.lock().await = Some(worker);
+
+        info!("Local Whisper session started");
+        Ok(())
+    }
+
+    async fn send_audio(&mut self, chunk: AudioChunk) -> Result<()> {
+        let audio_tx = self.audio_tx.lock().await;
+        let tx = audio_tx
+            .as_ref()
+            .ok_or_else(|| MurmurError::Stt("Session not started".to_string()))?;
+        if chunk.format() != self.input_format() {
+            warn!(
+                "Local Whisper expects 16 kHz mono audio, got {:?}",
+                chunk.format()
+            );
+        }
+        tx.send(chunk)
+            .await
+            .map_err(|_| MurmurError::Stt("Local Whisper transcription task stopped".to_string()))
+    }
+
+    async fn stop_session(&mut self) -> Result<()> {
+        info!("Stopping local Whisper session");
+
+        // Closing the channel makes the worker commit what is left and exit
+        self.audio_tx.lock().await.take();
+        if let Some(worker) = self.worker.lock().await.take() {
+            if let Err(e) = worker.await {
+                error!("Local Whisper transcription task panicked: {}", e);
+            }
+        }
+
+        info!("Local Whisper session stopped");
+        Ok(())
+    }
+
+    async fn subscribe_events(&self) -> mpsc::Receiver<TranscriptionEvent> {
+        let mut rx_lock = self.event_rx.lock().await;
+        rx_lock
+            .take()
+            .expect("subscribe_events called multiple times")
+    }
+
+    fn input_format(&self) -> AudioFormat {
+        AudioFormat::mono(SAMPLE_RATE)
+    }
+
+    async fn health_check(&self) -> Result<SttHealth> {
+        // Nothing to reach over the network: the test passes once the model
+        // is loaded (which keeps it warm for the next session)
+        let started = Instant::now();
+        self.context().await?;
+        Ok(SttHealth {
+            latency_ms: started.elapsed().as_millis() as u64,
+        })
+    }
+
+    fn capabilities(&self) -> SttCapabilities {
+        SttCapabilities {
+            mode: SttMode::Streaming,
+            partial_results: true,
+            languages: self.language.iter().cloned().collect(),
+            word_timestamps: false,
+            vocabulary_hints: true,
+            diarization: false,
+            local: true,
+            requires_api_key: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 400 Hz tone ("speech") or digital silence
+    fn audio(speech: bool, duration_ms: u64) -> Vec<i16> {
+        let samples = AudioFormat::mono(SAMPLE_RATE).samples_for_ms(duration_ms);
+        (0..samples)
+            .map(|i| {
+                if speech {
+                    let t = i as f32 / SAMPLE_RATE as f32;
+                    ((t * 400.0 * std::f32::consts::TAU).sin() * 8000.0) as i16
+                } else {
+                    0
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_silence_is_never_decoded() {
+        let mut window = SlidingWindow::new(1000, 10_000);
+        for _ in 0..30 {
+            window.push(&audio(false, 100));
+            assert_eq!(window.next_action(), WindowAction::Wait);
+        }
+        // Only the lead-in is held, positioned at the end of the silence
+        assert_eq!(window.samples.len(), 3200);
+        assert_eq!(window.position_ms(window.start), 2800);
+    }
+
+    #[test]
+    fn test_partials_every_step_and_commit_at_pause() {
+        let mut window = SlidingWindow::new(1000, 10_000);
+        window.push(&audio(false, 500));
+        window.push(&audio(true, 500));
+        assert_eq!(window.next_action(), WindowAction::Wait);
+        window.push(&audio(true, 500));
+        // A step of audio since speech began
+        assert_eq!(window.next_action(), WindowAction::Partial);
+        window.mark_decoded();
+        window.push(&audio(true, 400));
+        assert_eq!(window.next_action(), WindowAction::Wait);
+
+        window.push(&audio(false, 600));
+        assert_eq!(window.next_action(), WindowAction::Commit);
+        window.advance();
+        // Nothing is carried over a pause
+        assert!(window.samples.is_empty());
+        assert!(!window.has_speech);
+        assert_eq!(window.seam_ms, None);
+        assert_eq!(window.position_ms(window.start), 2500);
+    }
+
+    #[test]
+    fn test_long_speech_is_cut_with_overlap() {
+        let mut window = SlidingWindow::new(1000, 3000);
+        for _ in 0..3 {
+            window.push(&audio(true, 1000));
+            if window.next_action() == WindowAction::Partial {
+                window.mark_decoded();
+            }
+        }
+        assert_eq!(window.next_action(), WindowAction::Commit);
+        window.advance();
+
+        // The next segment starts with the last 200 ms of this one
+        assert_eq!(window.samples.len(), 3200);
+        assert!(window.has_speech);
+        assert_eq!(window.position_ms(window.start), 2800);
+        assert_eq!(window.seam_ms, Some(3000));
+
+        // Silence right after the cut still leaves the seam in place
+        window.push(&audio(false, 100));
+        assert_eq!(window.seam_ms, Some(3000));
+    }
+
+    #[test]
+    fn test_short_segments_are_padded() {
+        let mut window = SlidingWindow::new(1000, 10_000);
+        window.push(&audio(true, 300));
+        assert_eq!(window.input().len(), 17600);
+        window.push(&audio(true, 1500));
+        assert_eq!(window.input().len(), 28800);
+    }
+
+    #[test]
+    fn test_prompt_prefix_and_capabilities() {
+        let provider = LocalWhisperProvider::new("model.bin")
+            .with_style_prompt(Some("Use British spelling.".to_string()))
+            .with_vocabulary(["Murmur", " Tauri ", "Murmur", ""])
+            .with_language(Some("auto".to_string()));
+        assert_eq!(
+            provider.prompt_prefix().as_deref(),
+            Some("Use British spelling. Murmur, Tauri")
+        );
+        assert_eq!(LocalWhisperProvider::new("m").prompt_prefix(), None);
+
+        let caps = provider.capabilities();
+        assert!(caps.local);
+        assert!(!caps.requires_api_key);
+        assert!(caps.languages.is_empty());
+        let pinned = provider.with_language(Some("de".to_string()));
+        assert_eq!(pinned.capabilities().languages, ["de"]);
+    }
+
+    #[tokio::test]
+    async fn test_missing_model_is_unavailable() {
+        let provider = LocalWhisperProvider::new("/nonexistent/ggml-tiny.bin");
+        let err = provider.health_check().await.unwrap_err();
+        let detail = err.provider_error().expect("structured error");
+        assert_eq!(detail.kind, ProviderErrorKind::Unavailable);
+        assert_eq!(detail.provider, PROVIDER_ID);
+        assert!(detail.message.contains("/nonexistent/ggml-tiny.bin"));
+
+        let mut provider = LocalWhisperProvider::new("");
+        assert!(provider.start_session().await.is_err());
+    }
+}
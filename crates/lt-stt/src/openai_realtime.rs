@@ -0,0 +1,834 @@
+//! OpenAI Realtime transcription over WebSocket
+//!
+//! The session is configured once with `transcription_session.update`, then
+//! 24 kHz PCM is streamed with `input_audio_buffer.append`. Server VAD cuts
+//! the buffer into items at pauses; each item streams `delta` events while it
+//! is transcribed and ends with `completed`. Items can finish out of order,
+//! so commits are released in the order the audio was committed.
+
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use futures_util::{SinkExt, StreamExt};
+use lt_core::error::{MurmurError, ProviderError, ProviderErrorKind, Result};
+use lt_core::stt::{
+    AudioChunk, AudioFormat, SttCapabilities, SttHealth, SttMode, SttProvider, TranscriptMetadata,
+    TranscriptionEvent,
+};
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot, Mutex};
+use tokio_tungstenite::tungstenite::{handshake::client::Request, Message};
+use tracing::{debug, error, info, warn};
+use url::Url;
+
+use crate::resample::Resampler;
+use crate::ws::{self, HEALTH_CHECK_TIMEOUT};
+
+/// OpenAI; the path and `intent=transcription` are appended
+pub const DEFAULT_BASE_URL: &str = "wss://api.openai.com";
+
+/// Model used when none is configured
+pub const DEFAULT_MODEL: &str = "gpt-4o-transcribe";
+
+/// The Realtime API only takes 24 kHz mono PCM
+const INPUT_SAMPLE_RATE: u32 = 24000;
+
+/// How long to wait for the last items after the final commit
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Error code for committing an empty buffer (server VAD already took it)
+const COMMIT_EMPTY_CODE: &str = "input_audio_buffer_commit_empty";
+
+/// Server-side voice activity detection settings
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ServerVad {
+    /// Activation threshold, 0.0 - 1.0
+    pub threshold: f32,
+    /// Audio kept before detected speech
+    pub prefix_padding_ms: u32,
+    /// Silence that ends a turn
+    pub silence_duration_ms: u32,
+}
+
+impl Default for ServerVad {
+    fn default() -> Self {
+        Self {
+            threshold: 0.5,
+            prefix_padding_ms: 300,
+            silence_duration_ms: 500,
+        }
+    }
+}
+
+/// Error object of `error` and `...transcription.failed` events
+#[derive(Debug, Default, Deserialize)]
+struct RealtimeError {
+    #[serde(default, rename = "type")]
+    kind: String,
+    #[serde(default)]
+    code: Option<String>,
+    #[serde(default)]
+    message: String,
+}
+
+impl RealtimeError {
+    fn is_commit_empty(&self) -> bool {
+        self.code.as_deref() == Some(COMMIT_EMPTY_CODE)
+    }
+
+    fn to_provider_error(&self) -> ProviderError {
+        let kind = match (self.kind.as_str(), self.code.as_deref()) {
+            (_, Some("invalid_api_key")) | ("authentication_error", _) => ProviderErrorKind::Auth,
+            (_, Some("insufficient_quota")) => ProviderErrorKind::Quota,
+            (_, Some("rate_limit_exceeded")) | ("rate_limit_error", _) => {
+                ProviderErrorKind::RateLimited {
+                    retry_after_secs: None,
+                }
+            }
+            ("invalid_request_error", _) => ProviderErrorKind::BadRequest,
+            _ => ProviderErrorKind::Unavailable,
+        };
+        let message = if self.message.is_empty() {
+            self.code.clone().unwrap_or_else(|| self.kind.clone())
+        } else {
+            self.message.clone()
+        };
+        ProviderError::new(kind, "openai_realtime", message)
+    }
+}
+
+/// Server events this client acts on
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type")]
+enum RealtimeEvent {
+    #[serde(
+        rename = "transcription_session.created",
+        alias = "transcription_session.updated",
+        alias = "session.created",
+        alias = "session.updated"
+    )]
+    SessionReady {},
+
+    /// A stretch of audio became an item (by server VAD or our commit)
+    #[serde(rename = "input_audio_buffer.committed")]
+    Committed { item_id: String },
+
+    /// Answer to the `clear` sent after the final commit
+    #[serde(rename = "input_audio_buffer.cleared")]
+    Cleared {},
+
+    #[serde(rename = "conversation.item.input_audio_transcription.delta")]
+    Delta {
+        item_id: String,
+        #[serde(default)]
+        delta: String,
+    },
+
+    #[serde(rename = "conversation.item.input_audio_transcription.completed")]
+    Completed {
+        item_id: String,
+        #[serde(default)]
+        transcript: String,
+    },
+
+    #[serde(rename = "conversation.item.input_audio_transcription.failed")]
+    Failed {
+        item_id: String,
+        #[serde(default)]
+        error: RealtimeError,
+    },
+
+    #[serde(rename = "error")]
+    Error {
+        #[serde(default)]
+        error: RealtimeError,
+    },
+
+    /// Speech start/stop, rate limits and the like
+    #[serde(other)]
+    Other,
+}
+
+/// Item waiting to be committed as an event
+#[derive(Debug)]
+struct PendingItem {
+    item_id: String,
+    segment_id: u64,
+    text: String,
+    completed: bool,
+}
+
+/// Turns item events into transcription events, releasing commits in the
+/// order the audio was committed
+#[derive(Debug, Default)]
+struct ItemTracker {
+    next_segment_id: u64,
+    pending: VecDeque<PendingItem>,
+}
+
+impl ItemTracker {
+    /// The pending item with this id, registered if it is new
+    fn item_mut(&mut self, item_id: &str) -> &mut PendingItem {
+        match self.pending.iter().position(|i| i.item_id == item_id) {
+            Some(index) => &mut self.pending[index],
+            None => {
+                self.pending.push_back(PendingItem {
+                    item_id: item_id.to_string(),
+                    segment_id: self.next_segment_id,
+                    text: String::new(),
+                    completed: false,
+                });
+                self.next_segment_id += 1;
+                self.pending.back_mut().unwrap()
+            }
+        }
+    }
+
+    fn committed(&mut self, item_id: &str) {
+        self.item_mut(item_id);
+    }
+
+    fn delta(&mut self, item_id: &str, delta: &str) -> Option<TranscriptionEvent> {
+        let item = self.item_mut(item_id);
+        if item.completed {
+            return None;
+        }
+        item.text.push_str(delta);
+        let text = item.text.trim();
+        (!text.is_empty()).then(|| TranscriptionEvent::Partial {
+            text: text.to_string(),
+            timestamp_ms: 0,
+            metadata: TranscriptMetadata {
+                segment_id: Some(item.segment_id),
+                ..Default::default()
+            },
+        })
+    }
+
+    fn completed(&mut self, item_id: &str, transcript: String) -> Vec<TranscriptionEvent> {
+        let item = self.item_mut(item_id);
+        item.text = transcript;
+        item.completed = true;
+        self.release(false)
+    }
+
+    /// Drop a failed item so later ones are not held back
+    fn failed(&mut self, item_id: &str) -> Vec<TranscriptionEvent> {
+        let item = self.item_mut(item_id);
+        item.text.clear();
+        item.completed = true;
+        self.release(false)
+    }
+
+    /// Commit finished items from the front; with `all`, commit whatever
+    /// text every pending item has
+    fn release(&mut self, all: bool) -> Vec<TranscriptionEvent> {
+        let mut events = Vec::new();
+        while self.pending.front().is_some_and(|i| all || i.completed) {
+            let item = self.pending.pop_front().unwrap();
+            let text = item.text.trim();
+            if !text.is_empty() {
+                events.push(TranscriptionEvent::Committed {
+                    text: text.to_string(),
+                    timestamp_ms: 0,
+                    metadata: TranscriptMetadata {
+                        segment_id: Some(item.segment_id),
+                        ..Default::default()
+                    },
+                });
+            }
+        }
+        events
+    }
+
+    fn is_idle(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+/// OpenAI Realtime API transcription client
+pub struct OpenAIRealtimeProvider {
+    api_key: String,
+    model: String,
+    language: Option<String>,
+    prompt: Option<String>,
+    server_vad: Option<ServerVad>,
+    base_url: String,
+    ws_tx: Arc<Mutex<Option<mpsc::Sender<AudioChunk>>>>,
+    event_tx: Arc<Mutex<Option<mpsc::Sender<TranscriptionEvent>>>>,
+    event_rx: Arc<Mutex<Option<mpsc::Receiver<TranscriptionEvent>>>>,
+    ws_task: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
+}
+
+impl OpenAIRealtimeProvider {
+    /// Create a provider for OpenAI with the default model and server VAD
+    pub fn new(api_key: String) -> Self {
+        Self {
+            api_key,
+            model: DEFAULT_MODEL.to_string(),
+            language: None,
+            prompt: None,
+            server_vad: Some(ServerVad::default()),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            ws_tx: Arc::new(Mutex::new(None)),
+            event_tx: Arc::new(Mutex::new(None)),
+            event_rx: Arc::new(Mutex::new(None)),
+            ws_task: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Use `model` instead of the default (empty keeps the default)
+    pub fn with_model(mut self, model: Option<String>) -> Self {
+        if let Some(model) = model.filter(|m| !m.is_empty()) {
+            self.model = model;
+        }
+        self
+    }
+
+    /// Pin the language ("auto" or empty leaves it to the model)
+    pub fn with_language(mut self, language: Option<String>) -> Self {
+        self.language = language.filter(|l| !l.is_empty() && l != "auto");
+        self
+    }
+
+    /// Prompt to steer spelling and vocabulary
+    pub fn with_prompt(mut self, prompt: Option<String>) -> Self {
+        self.prompt = prompt.filter(|p| !p.trim().is_empty());
+        self
+    }
+
+    /// Server VAD settings; `None` sends the whole recording as one item
+    pub fn with_server_vad(mut self, server_vad: Option<ServerVad>) -> Self {
+        self.server_vad = server_vad;
+        self
+    }
+
+    /// Connect somewhere other than OpenAI (e.g. a proxy)
+    pub fn with_base_url(mut self, base_url: Option<String>) -> Self {
+        if let Some(base_url) = base_url.filter(|u| !u.is_empty()) {
+            self.base_url = base_url;
+        }
+        self
+    }
+
+    /// Build WebSocket URL
+    fn build_ws_url(&self) -> Result<Url> {
+        Url::parse(&format!(
+            "{}/v1/realtime?intent=transcription",
+            self.base_url.trim_end_matches('/')
+        ))
+        .map_err(|e| MurmurError::Stt(format!("Invalid URL: {}", e)))
+    }
+
+    /// Authenticated WebSocket handshake request
+    fn connect_request(&self) -> Result<Request> {
+        let mut request = ws::handshake_request(
+            self.build_ws_url()?.as_str(),
+            Some(("Authorization", &format!("Bearer {}", self.api_key))),
+        )?;
+        request
+            .headers_mut()
+            .insert("OpenAI-Beta", "realtime=v1".parse().unwrap());
+        Ok(request)
+    }
+
+    /// `transcription_session.update` for the configured model and VAD
+    fn session_update(&self) -> serde_json::Value {
+        let mut transcription = serde_json::json!({ "model": self.model });
+        if let Some(language) = &self.language {
+            transcription["language"] = language.clone().into();
+        }
+        if let Some(prompt) = &self.prompt {
+            transcription["prompt"] = prompt.clone().into();
+        }
+        let turn_detection = match self.server_vad {
+            Some(vad) => serde_json::json!({
+                "type": "server_vad",
+                "threshold": vad.threshold,
+                "prefix_padding_ms": vad.prefix_padding_ms,
+                "silence_duration_ms": vad.silence_duration_ms,
+            }),
+            None => serde_json::Value::Null,
+        };
+        serde_json::json!({
+            "type": "transcription_session.update",
+            "session": {
+                "input_audio_format": "pcm16",
+                "input_audio_transcription": transcription,
+                "turn_detection": turn_detection,
+            }
+        })
+    }
+}
+
+#[async_trait]
+impl SttProvider for OpenAIRealtimeProvider {
+    async fn start_session(&mut self) -> Result<()> {
+        info!(
+            "Starting OpenAI Realtime STT session (model {})",
+            self.model
+        );
+
+        let (audio_tx, mut audio_rx) = mpsc::channel::<AudioChunk>(32);
+        *self.ws_tx.lock().await = Some(audio_tx);
+
+        let (event_tx, event_rx) = mpsc::channel::<TranscriptionEvent>(32);
+        *self.event_tx.lock().await = Some(event_tx.clone());
+        *self.event_rx.lock().await = Some(event_rx);
+
+        let ws_stream = ws::connect("openai_realtime", self.connect_request()?).await?;
+        info!("WebSocket connected to OpenAI Realtime");
+
+        let (mut ws_write, mut ws_read) = ws_stream.split();
+        ws_write
+            .send(Message::Text(self.session_update().to_string().into()))
+            .await
+            .map_err(|e| {
+                MurmurError::from(ProviderError::new(
+                    ProviderErrorKind::Network,
+                    "openai_realtime",
+                    format!("Failed to configure session: {}", e),
+                ))
+            })?;
+
+        let (closing_tx, mut closing_rx) = oneshot::channel::<()>();
+
+        let task = tokio::spawn(async move {
+            let event_tx_clone = event_tx.clone();
+            let receiver_task = tokio::spawn(async move {
+                let mut items = ItemTracker::default();
+                let mut closing = false;
+                let mut awaiting_final_clear = false;
+                let deadline = tokio::time::sleep(Duration::MAX);
+                tokio::pin!(deadline);
+
+                loop {
+                    let msg = tokio::select! {
+                        // The close signal is sent before the final commit, so
+                        // it is handled before anything answering that commit
+                        biased;
+                        _ = &mut closing_rx, if !closing => {
+                            closing = true;
+                            awaiting_final_clear = true;
+                            deadline
+                                .as_mut()
+                                .reset(tokio::time::Instant::now() + CLOSE_TIMEOUT);
+                            continue;
+                        }
+                        _ = &mut deadline, if closing => {
+                            warn!("Timed out waiting for the last transcription items");
+                            break;
+                        }
+                        msg = ws_read.next() => msg,
+                    };
+
+                    let events = match msg {
+                        Some(Ok(Message::Text(text))) => {
+                            debug!("Received message: {}", text);
+                            match serde_json::from_str::<RealtimeEvent>(&text) {
+                                Ok(RealtimeEvent::SessionReady {}) => {
+                                    info!("OpenAI Realtime session ready");
+                                    Vec::new()
+                                }
+                                Ok(RealtimeEvent::Committed { item_id }) => {
+                                    items.committed(&item_id);
+                                    Vec::new()
+                                }
+                                Ok(RealtimeEvent::Cleared {}) => {
+                                    // Events are answered in order, so the
+                                    // final commit's item is known by now
+                                    awaiting_final_clear = false;
+                                    Vec::new()
+                                }
+                                Ok(RealtimeEvent::Delta { item_id, delta }) => {
+                                    items.delta(&item_id, &delta).into_iter().collect()
+                                }
+                                Ok(RealtimeEvent::Completed {
+                                    item_id,
+                                    transcript,
+                                }) => items.completed(&item_id, transcript),
+                                Ok(RealtimeEvent::Failed { item_id, error }) => {
+                                    let detail = error.to_provider_error();
+                                    error!("OpenAI Realtime transcription failed: {}", detail);
+                                    let mut events = items.failed(&item_id);
+                                    events.push(TranscriptionEvent::Error {
+                                        message: detail.message.clone(),
+                                        detail: Some(detail),
+                                    });
+                                    events
+                                }
+                                Ok(RealtimeEvent::Error { error }) if error.is_commit_empty() => {
+                                    debug!("Nothing left to commit");
+                                    Vec::new()
+                                }
+                                Ok(RealtimeEvent::Error { error }) => {
+                                    let detail = error.to_provider_error();
+                                    error!("OpenAI Realtime error: {}", detail);
+                                    vec![TranscriptionEvent::Error {
+                                        message: detail.message.clone(),
+                                        detail: Some(detail),
+                                    }]
+                                }
+                                Ok(RealtimeEvent::Other) => Vec::new(),
+                                Err(e) => {
+                                    warn!("Failed to parse message: {} - {}", e, text);
+                                    Vec::new()
+                                }
+                            }
+                        }
+                        Some(Ok(Message::Close(_))) | None => {
+                            info!("WebSocket closed by server");
+                            break;
+                        }
+                        Some(Ok(_)) => {
+                            debug!("Received non-text message");
+                            Vec::new()
+                        }
+                        Some(Err(e)) => {
+                            error!("WebSocket error: {}", e);
+                            let message = format!("WebSocket error: {}", e);
+                            let event = TranscriptionEvent::Error {
+                                detail: Some(ProviderError::new(
+                                    ProviderErrorKind::Network,
+                                    "openai_realtime",
+                                    message.clone(),
+                                )),
+                                message,
+                            };
+                            let _ = event_tx_clone.send(event).await;
+                            break;
+                        }
+                    };
+
+                    for event in events {
+                        if let Err(e) = event_tx_clone.send(event).await {
+                            error!("Failed to send transcription event: {}", e);
+                        }
+                    }
+
+                    if closing && !awaiting_final_clear && items.is_idle() {
+                        break;
+                    }
+                }
+
+                for event in items.release(true) {
+                    let _ = event_tx_clone.send(event).await;
+                }
+                debug!("WebSocket receiver task finished");
+            });
+
+            let mut resampler = Resampler::new(AudioFormat::mono(INPUT_SAMPLE_RATE));
+            while let Some(chunk) = audio_rx.recv().await {
+                let samples = resampler.process(&chunk);
+                if samples.is_empty() {
+                    continue;
+                }
+                let pcm_bytes: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+                let append = serde_json::json!({
+                    "type": "input_audio_buffer.append",
+                    "audio": BASE64.encode(&pcm_bytes),
+                });
+                if let Err(e) = ws_write
+                    .send(Message::Text(append.to_string().into()))
+                    .await
+                {
+                    error!("Failed to send audio chunk: {}", e);
+                    break;
+                }
+            }
+
+            // Commit the audio server VAD has not cut off yet (or all of it
+            // without VAD); an empty buffer is answered with an error. The
+            // clear that follows is a no-op whose answer marks the point
+            // after which no more items will be committed.
+            debug!("Audio sender finished, committing the input buffer");
+            let _ = closing_tx.send(());
+            for message_type in ["input_audio_buffer.commit", "input_audio_buffer.clear"] {
+                let message = serde_json::json!({ "type": message_type });
+                if let Err(e) = ws_write
+                    .send(Message::Text(message.to_string().into()))
+                    .await
+                {
+                    warn!("Failed to send {}: {}", message_type, e);
+                }
+            }
+
+            // The receiver bounds its own wait with CLOSE_TIMEOUT
+            if let Err(e) = receiver_task.await {
+                warn!("Receiver task error: {}", e);
+            }
+
+            debug!("Closing WebSocket");
+            let _ = ws_write.close().await;
+            info!("WebSocket task finished");
+        });
+
+        *self.ws_task.lock().await = Some(task);
+
+        Ok(())
+    }
+
+    async fn send_audio(&mut self, chunk: AudioChunk) -> Result<()> {
+        let tx_lock = self.ws_tx.lock().await;
+        if let Some(tx) = tx_lock.as_ref() {
+            tx.send(chunk)
+                .await
+                .map_err(|e| MurmurError::Stt(format!("Failed to send audio chunk: {}", e)))?;
+            Ok(())
+        } else {
+            Err(MurmurError::Stt("Session not started".to_string()))
+        }
+    }
+
+    async fn stop_session(&mut self) -> Result<()> {
+        info!("Stopping OpenAI Realtime STT session");
+
+        // Closing the audio channel makes the task commit and drain
+        *self.ws_tx.lock().await = None;
+
+        if let Some(task) = self.ws_task.lock().await.take() {
+            let _ = task.await;
+        }
+
+        info!("OpenAI Realtime STT session stopped");
+        Ok(())
+    }
+
+    async fn subscribe_events(&self) -> mpsc::Receiver<TranscriptionEvent> {
+        let mut rx_lock = self.event_rx.lock().await;
+        rx_lock
+            .take()
+            .expect("subscribe_events called multiple times")
+    }
+
+    fn input_format(&self) -> AudioFormat {
+        AudioFormat::mono(INPUT_SAMPLE_RATE)
+    }
+
+    async fn health_check(&self) -> Result<SttHealth> {
+        let timed_out = || ws::health_check_timed_out("openai_realtime");
+
+        // Open the socket once and wait for the server's first message:
+        // the session announcement on success, or an error
+        let started = Instant::now();
+        let mut ws_stream = tokio::time::timeout(
+            HEALTH_CHECK_TIMEOUT,
+            ws::connect("openai_realtime", self.connect_request()?),
+        )
+        .await
+        .map_err(|_| timed_out())??;
+        let first = tokio::time::timeout(HEALTH_CHECK_TIMEOUT, ws_stream.next())
+            .await
+            .map_err(|_| timed_out())?;
+        let latency_ms = started.elapsed().as_millis() as u64;
+        let _ = ws_stream.close(None).await;
+
+        match first {
+            Some(Ok(Message::Text(text))) => match serde_json::from_str::<RealtimeEvent>(&text) {
+                Ok(RealtimeEvent::Error { error }) => Err(error.to_provider_error().into()),
+                _ => Ok(SttHealth { latency_ms }),
+            },
+            Some(Ok(Message::Close(frame))) => Err(ProviderError::new(
+                ProviderErrorKind::Unavailable,
+                "openai_realtime",
+                format!(
+                    "Server closed the connection: {}",
+                    frame.map(|f| f.reason.to_string()).unwrap_or_default()
+                ),
+            )
+            .into()),
+            Some(Ok(_)) => Ok(SttHealth { latency_ms }),
+            Some(Err(e)) => Err(ProviderError::new(
+                ProviderErrorKind::Network,
+                "openai_realtime",
+                format!("WebSocket error: {}", e),
+            )
+            .into()),
+            None => Err(ProviderError::new(
+                ProviderErrorKind::Network,
+                "openai_realtime",
+                "Connection closed before the session started",
+            )
+            .into()),
+        }
+    }
+
+    fn capabilities(&self) -> SttCapabilities {
+        SttCapabilities {
+            mode: SttMode::Streaming,
+            partial_results: true,
+            // A pinned language is all the session will be asked for
+            languages: self.language.iter().cloned().collect(),
+            word_timestamps: false,
+            vocabulary_hints: false,
+            diarization: false,
+            local: false,
+            requires_api_key: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment_and_text(event: &TranscriptionEvent) -> (&'static str, u64, String) {
+        match event {
+            TranscriptionEvent::Partial { text, metadata, .. } => {
+                ("partial", metadata.segment_id.unwrap(), text.clone())
+            }
+            TranscriptionEvent::Committed { text, metadata, .. } => {
+                ("committed", metadata.segment_id.unwrap(), text.clone())
+            }
+            TranscriptionEvent::Error { message, .. } => ("error", 0, message.clone()),
+        }
+    }
+
+    #[test]
+    fn test_items_commit_in_audio_order() {
+        let mut items = ItemTracker::default();
+        items.committed("item_a");
+        items.committed("item_b");
+
+        let partial = items.delta("item_b", "World").unwrap();
+        assert_eq!(
+            segment_and_text(&partial),
+            ("partial", 1, "World".to_string())
+        );
+        // The later item finished first and waits for the earlier one
+        assert!(items.completed("item_b", "World.".to_string()).is_empty());
+
+        items.delta("item_a", "Hel");
+        let partial = items.delta("item_a", "lo").unwrap();
+        assert_eq!(
+            segment_and_text(&partial),
+            ("partial", 0, "Hello".to_string())
+        );
+
+        let events = items.completed("item_a", "Hello.".to_string());
+        let events: Vec<_> = events.iter().map(segment_and_text).collect();
+        assert_eq!(
+            events,
+            [
+                ("committed", 0, "Hello.".to_string()),
+                ("committed", 1, "World.".to_string())
+            ]
+        );
+        assert!(items.is_idle());
+    }
+
+    #[test]
+    fn test_failed_items_do_not_block_later_ones() {
+        let mut items = ItemTracker::default();
+        items.committed("item_a");
+        items.committed("item_b");
+        assert!(items
+            .completed("item_b", "Still here.".to_string())
+            .is_empty());
+        let events = items.failed("item_a");
+        assert_eq!(events.len(), 1);
+        assert_eq!(segment_and_text(&events[0]).2, "Still here.");
+
+        // Unfinished items keep their partial text when the session ends
+        items.committed("item_c");
+        items.delta("item_c", "trailing");
+        let events = items.release(true);
+        assert_eq!(
+            segment_and_text(&events[0]),
+            ("committed", 2, "trailing".to_string())
+        );
+    }
+
+    #[test]
+    fn test_session_update_reflects_settings() {
+        let update = OpenAIRealtimeProvider::new("key".to_string())
+            .with_language(Some("fr".to_string()))
+            .with_prompt(Some("Murmur, Tauri".to_string()))
+            .session_update();
+        assert_eq!(update["type"], "transcription_session.update");
+        let session = &update["session"];
+        assert_eq!(session["input_audio_format"], "pcm16");
+        assert_eq!(
+            session["input_audio_transcription"]["model"],
+            "gpt-4o-transcribe"
+        );
+        assert_eq!(session["input_audio_transcription"]["language"], "fr");
+        assert_eq!(
+            session["input_audio_transcription"]["prompt"],
+            "Murmur, Tauri"
+        );
+        assert_eq!(session["turn_detection"]["type"], "server_vad");
+        assert_eq!(session["turn_detection"]["silence_duration_ms"], 500);
+
+        let update = OpenAIRealtimeProvider::new("key".to_string())
+            .with_model(Some("gpt-4o-mini-transcribe".to_string()))
+            .with_language(Some("auto".to_string()))
+            .with_server_vad(None)
+            .session_update();
+        let session = &update["session"];
+        assert_eq!(
+            session["input_audio_transcription"]["model"],
+            "gpt-4o-mini-transcribe"
+        );
+        assert!(session["input_audio_transcription"]
+            .get("language")
+            .is_none());
+        assert!(session["turn_detection"].is_null());
+    }
+
+    #[test]
+    fn test_server_errors_are_classified() {
+        let cases = [
+            (
+                r#"{"type":"error","error":{"type":"invalid_request_error","code":"invalid_api_key","message":"bad key"}}"#,
+                ProviderErrorKind::Auth,
+            ),
+            (
+                r#"{"type":"error","error":{"type":"invalid_request_error","code":"insufficient_quota","message":"no credits"}}"#,
+                ProviderErrorKind::Quota,
+            ),
+            (
+                r#"{"type":"error","error":{"type":"invalid_request_error","message":"bad audio"}}"#,
+                ProviderErrorKind::BadRequest,
+            ),
+            (
+                r#"{"type":"error","error":{"type":"server_error","message":"oops"}}"#,
+                ProviderErrorKind::Unavailable,
+            ),
+        ];
+
+        for (json, expected) in cases {
+            let Ok(RealtimeEvent::Error { error }) = serde_json::from_str(json) else {
+                panic!("not an error event: {}", json);
+            };
+            let detail = error.to_provider_error();
+            assert_eq!(detail.kind, expected, "{}", json);
+            assert_eq!(detail.provider, "openai_realtime");
+        }
+
+        let Ok(RealtimeEvent::Error { error }) = serde_json::from_str(
+            r#"{"type":"error","error":{"type":"invalid_request_error","code":"input_audio_buffer_commit_empty"}}"#,
+        ) else {
+            panic!("not an error event");
+        };
+        assert!(error.is_commit_empty());
+
+        assert!(matches!(
+            serde_json::from_str(
+                r#"{"type":"input_audio_buffer.speech_started","audio_start_ms":10}"#
+            ),
+            Ok(RealtimeEvent::Other)
+        ));
+    }
+
+    #[test]
+    fn test_capabilities() {
+        let provider = OpenAIRealtimeProvider::new("key".to_string());
+        let caps = provider.capabilities();
+        assert_eq!(caps.mode, SttMode::Streaming);
+        assert!(caps.requires_api_key);
+        assert_eq!(provider.input_format(), AudioFormat::mono(24000));
+    }
+}
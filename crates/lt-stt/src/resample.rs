@@ -0,0 +1,164 @@
+//! Linear resampler for providers that want another format than the capture
+
+use lt_core::stt::{AudioChunk, AudioFormat};
+
+/// Converts chunks to a target format, carrying its position across chunks
+/// so consecutive chunks join without a seam. Channels are averaged to mono,
+/// resampled with linear interpolation, then duplicated to the target
+/// channel count.
+#[derive(Debug)]
+pub(crate) struct Resampler {
+    target: AudioFormat,
+    source_rate: u32,
+    /// Read position in the current chunk, relative to `last`
+    position: f64,
+    /// Final sample of the previous chunk
+    last: Option<i16>,
+}
+
+impl Resampler {
+    pub(crate) fn new(target: AudioFormat) -> Self {
+        Self {
+            target,
+            source_rate: 0,
+            position: 0.0,
+            last: None,
+        }
+    }
+
+    /// Samples of `chunk` in the target format. The last input sample is
+    /// held back until the next chunk gives it a right neighbour.
+    pub(crate) fn process(&mut self, chunk: &AudioChunk) -> Vec<i16> {
+        if chunk.format() == self.target {
+            return chunk.data.clone();
+        }
+
+        let channels = usize::from(chunk.channels.max(1));
+        let mono: Vec<i16> = chunk
+            .data
+            .chunks(channels)
+            .map(|frame| {
+                (frame.iter().map(|&s| i32::from(s)).sum::<i32>() / frame.len() as i32) as i16
+            })
+            .collect();
+
+        let resampled = if chunk.sample_rate == self.target.sample_rate || chunk.sample_rate == 0 {
+            mono
+        } else {
+            self.interpolate(chunk.sample_rate, mono)
+        };
+
+        let out_channels = usize::from(self.target.channels.max(1));
+        if out_channels == 1 {
+            return resampled;
+        }
+        resampled
+            .into_iter()
+            .flat_map(|s| std::iter::repeat_n(s, out_channels))
+            .collect()
+    }
+
+    /// `chunk` converted to the target format
+    pub(crate) fn convert(&mut self, chunk: &AudioChunk) -> AudioChunk {
+        AudioChunk {
+            data: self.process(chunk),
+            timestamp_ms: chunk.timestamp_ms,
+            sample_rate: self.target.sample_rate,
+            channels: self.target.channels,
+        }
+    }
+
+    fn interpolate(&mut self, source_rate: u32, mono: Vec<i16>) -> Vec<i16> {
+        if source_rate != self.source_rate {
+            *self = Self {
+                source_rate,
+                ..Self::new(self.target)
+            };
+        }
+
+        let samples: Vec<i16> = self.last.into_iter().chain(mono).collect();
+        if samples.len() < 2 {
+            self.last = samples.last().copied().or(self.last);
+            return Vec::new();
+        }
+
+        let step = f64::from(source_rate) / f64::from(self.target.sample_rate);
+        let end = (samples.len() - 1) as f64;
+        let mut output = Vec::with_capacity((end / step) as usize + 1);
+        while self.position < end {
+            let index = self.position as usize;
+            let frac = self.position - index as f64;
+            let a = f64::from(samples[index]);
+            let b = f64::from(samples[index + 1]);
+            output.push((a + (b - a) * frac).round() as i16);
+            self.position += step;
+        }
+        self.position -= end;
+        self.last = samples.last().copied();
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(data: Vec<i16>, sample_rate: u32, channels: u16) -> AudioChunk {
+        AudioChunk {
+            data,
+            timestamp_ms: 7,
+            sample_rate,
+            channels,
+        }
+    }
+
+    #[test]
+    fn test_upsamples_continuously() {
+        let mut resampler = Resampler::new(AudioFormat::mono(24000));
+        let first = resampler.process(&chunk(vec![1000; 160], 16000, 1));
+        let second = resampler.process(&chunk(vec![1000; 160], 16000, 1));
+        // 320 input samples are 480 output samples, give or take the one
+        // still waiting for its right neighbour
+        assert_eq!(first.len() + second.len(), 479);
+        assert!(first.iter().chain(&second).all(|&s| s == 1000));
+
+        // A ramp stays a ramp across the chunk boundary
+        let mut resampler = Resampler::new(AudioFormat::mono(24000));
+        let ramp: Vec<i16> = (0..320).map(|i| i * 3).collect();
+        let mut out = resampler.process(&chunk(ramp[..160].to_vec(), 16000, 1));
+        out.extend(resampler.process(&chunk(ramp[160..].to_vec(), 16000, 1)));
+        assert!(out.windows(2).all(|w| w[1] - w[0] == 2));
+    }
+
+    #[test]
+    fn test_passes_target_rate_and_converts_channels() {
+        let mut resampler = Resampler::new(AudioFormat::mono(24000));
+        assert_eq!(
+            resampler.process(&chunk(vec![1, 2, 3], 24000, 1)),
+            vec![1, 2, 3]
+        );
+        assert_eq!(
+            resampler.process(&chunk(vec![100, 300, -50, 50], 24000, 2)),
+            vec![200, 0]
+        );
+
+        let mut resampler = Resampler::new(AudioFormat {
+            sample_rate: 16000,
+            channels: 2,
+        });
+        assert_eq!(
+            resampler.process(&chunk(vec![5, -5], 16000, 1)),
+            vec![5, 5, -5, -5]
+        );
+    }
+
+    #[test]
+    fn test_convert_downsamples_and_downmixes() {
+        let stereo = chunk([100i16, 300].repeat(480), 48000, 2);
+        let mono = Resampler::new(AudioFormat::default()).convert(&stereo);
+        assert_eq!(mono.format(), AudioFormat::default());
+        assert_eq!(mono.data.len(), 160);
+        assert!(mono.data.iter().all(|&s| s == 200));
+        assert_eq!(mono.timestamp_ms, 7);
+    }
+}
@@ -0,0 +1,1686 @@
+//! Configurable REST transcription engine for Whisper-compatible endpoints
+//!
+//! OpenAI, Groq, Fireworks, Together, Azure OpenAI, Mistral and self-hosted
+//! servers (whisper.cpp, faster-whisper, LocalAI) all take the same multipart
+//! upload and differ only in URL, auth, model and response format.
+//! [`RestEndpoint`] holds those settings, with the hosted vendors as entries
+//! in a preset table (further endpoints are defined in config), and
+//! [`RestSttProvider`] runs the shared chunk, upload and accumulate loop for
+//! any of them.
+
+use async_trait::async_trait;
+use lt_core::config::{RestSttEndpointConfig, SttAudioEncoding, SttAuthScheme, SttResponseFormat};
+use lt_core::error::{MurmurError, ProviderError, ProviderErrorKind, Result};
+use lt_core::retry::RetryPolicy;
+use lt_core::stt::{
+    AudioChunk, SttCapabilities, SttHealth, SttMode, SttProvider, TranscriptMetadata,
+    TranscriptionEvent,
+};
+use reqwest::multipart::{Form, Part};
+use std::collections::BTreeMap;
+use std::{sync::Arc, time::Duration};
+use tokio::sync::{mpsc, Mutex};
+use tracing::{debug, error, info, warn};
+
+use crate::chunker::{AudioChunker, ChunkBoundaryPolicy};
+use crate::encoding;
+use crate::error;
+use crate::hallucination::HallucinationFilter;
+use crate::whisper::{self, ChunkTranscript, WhisperResponse};
+
+/// Model sent to self-hosted endpoints when none is configured
+pub const DEFAULT_CUSTOM_MODEL: &str = "whisper-1";
+/// Transcription path under an OpenAI-compatible base URL
+pub const DEFAULT_TRANSCRIPTION_PATH: &str = "/audio/transcriptions";
+/// Flushed chunks that may wait for upload before new ones are dropped
+const MAX_PENDING_TRANSCRIPTION_CHUNKS: usize = 4;
+/// Per-request timeout unless the endpoint config sets one
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A built-in endpoint
+struct Preset {
+    id: &'static str,
+    /// Shown in the provider picker
+    name: &'static str,
+    /// Transcription URL; empty when it has to come from config
+    url: &'static str,
+    model: Option<&'static str>,
+    /// Header carrying the raw key; `None` sends a bearer token
+    key_header: Option<&'static str>,
+    response_format: SttResponseFormat,
+    chunk_ms: u64,
+    requires_api_key: bool,
+}
+
+/// OpenAI-compatible transcription endpoints known out of the box
+const PRESETS: &[Preset] = &[
+    Preset {
+        id: "openai",
+        name: "OpenAI Whisper",
+        url: "https://api.openai.com/v1/audio/transcriptions",
+        model: Some("whisper-1"),
+        key_header: None,
+        response_format: SttResponseFormat::VerboseJson,
+        chunk_ms: 4000,
+        requires_api_key: true,
+    },
+    Preset {
+        id: "groq",
+        name: "Groq Whisper Turbo",
+        url: "https://api.groq.com/openai/v1/audio/transcriptions",
+        model: Some("whisper-large-v3-turbo"),
+        key_header: None,
+        response_format: SttResponseFormat::VerboseJson,
+        // Groq answers fast enough for shorter chunks
+        chunk_ms: 3000,
+        requires_api_key: true,
+    },
+    Preset {
+        id: "fireworks",
+        name: "Fireworks Whisper",
+        url: "https://audio-prod.us-virginia-1.direct.fireworks.ai/v1/audio/transcriptions",
+        model: Some("whisper-v3"),
+        key_header: None,
+        response_format: SttResponseFormat::VerboseJson,
+        chunk_ms: 4000,
+        requires_api_key: true,
+    },
+    Preset {
+        id: "together",
+        name: "Together AI Whisper",
+        url: "https://api.together.xyz/v1/audio/transcriptions",
+        model: Some("openai/whisper-large-v3"),
+        key_header: None,
+        response_format: SttResponseFormat::Json,
+        chunk_ms: 4000,
+        requires_api_key: true,
+    },
+    Preset {
+        // The deployment is part of the URL, so it is configured per user
+        id: "azure_openai",
+        name: "Azure OpenAI Whisper",
+        url: "",
+        model: None,
+        key_header: Some("api-key"),
+        response_format: SttResponseFormat::Json,
+        chunk_ms: 4000,
+        requires_api_key: true,
+    },
+    Preset {
+        id: "mistral",
+        name: "Mistral Voxtral",
+        url: "https://api.mistral.ai/v1/audio/transcriptions",
+        model: Some("voxtral-mini-latest"),
+        key_header: None,
+        response_format: SttResponseFormat::Json,
+        chunk_ms: 4000,
+        requires_api_key: true,
+    },
+    Preset {
+        // Self-hosted servers usually run without auth
+        id: "custom_stt",
+        name: "Custom Endpoint",
+        url: "",
+        model: Some(DEFAULT_CUSTOM_MODEL),
+        key_header: None,
+        response_format: SttResponseFormat::Json,
+        chunk_ms: 4000,
+        requires_api_key: false,
+    },
+];
+
+/// Everything needed to call one transcription endpoint
+#[derive(Debug, Clone)]
+pub struct RestEndpoint {
+    /// Provider id, used in errors and as the `api_keys` slot
+    pub id: String,
+    /// Shown in the provider picker
+    pub name: String,
+    /// Full transcription URL (empty until configured)
+    pub url: String,
+    /// How `api_key` is sent
+    pub auth: SttAuthScheme,
+    pub api_key: Option<String>,
+    /// Model form field; omitted when `None`
+    pub model: Option<String>,
+    /// Language hint form field; omitted when `None`
+    pub language: Option<String>,
+    pub response_format: SttResponseFormat,
+    /// Upper bound for one upload round trip
+    pub timeout: Duration,
+    /// Uploads allowed in flight at once; results are still applied in order
+    pub concurrency: usize,
+    pub chunk_policy: ChunkBoundaryPolicy,
+    /// Codec of uploaded chunks
+    pub encoding: SttAudioEncoding,
+    /// How failed uploads are retried
+    pub retry: RetryPolicy,
+    /// The endpoint rejects requests without a key
+    pub requires_api_key: bool,
+}
+
+impl RestEndpoint {
+    /// Built-in endpoint for a provider id ("openai", "groq", "mistral", ...)
+    pub fn preset(id: &str) -> Option<Self> {
+        let preset = PRESETS.iter().find(|p| p.id == id)?;
+        Some(Self {
+            id: preset.id.to_string(),
+            name: preset.name.to_string(),
+            url: preset.url.to_string(),
+            auth: match preset.key_header {
+                Some(name) => SttAuthScheme::Header {
+                    name: name.to_string(),
+                },
+                None => SttAuthScheme::Bearer,
+            },
+            api_key: None,
+            model: preset.model.map(str::to_string),
+            language: None,
+            response_format: preset.response_format,
+            timeout: DEFAULT_REQUEST_TIMEOUT,
+            concurrency: 1,
+            chunk_policy: ChunkBoundaryPolicy::new(preset.chunk_ms),
+            encoding: SttAudioEncoding::Wav,
+            retry: RetryPolicy::default(),
+            requires_api_key: preset.requires_api_key,
+        })
+    }
+
+    /// Ids of all built-in endpoints
+    pub fn preset_ids() -> impl Iterator<Item = &'static str> {
+        PRESETS.iter().map(|p| p.id)
+    }
+
+    /// Endpoint without a preset, defined by `[stt_endpoints.<id>]`
+    /// overrides. It needs a URL, and a key unless the overrides say not.
+    pub fn new(id: &str) -> Self {
+        let mut endpoint = Self::preset("custom_stt").expect("custom_stt preset");
+        endpoint.id = id.to_string();
+        endpoint.name = id.to_string();
+        endpoint.requires_api_key = true;
+        endpoint
+    }
+
+    /// Self-hosted endpoint; requests go to `{base_url}/audio/transcriptions`
+    pub fn custom(
+        base_url: &str,
+        api_key: Option<String>,
+        model: Option<String>,
+        language: Option<String>,
+    ) -> Self {
+        let mut endpoint = Self::preset("custom_stt").expect("custom_stt preset");
+        if !base_url.is_empty() {
+            endpoint.url = join_url(base_url, DEFAULT_TRANSCRIPTION_PATH);
+        }
+        if let Some(model) = model.filter(|m| !m.is_empty()) {
+            endpoint.model = Some(model);
+        }
+        endpoint.language = language.filter(|l| !l.is_empty());
+        endpoint.with_api_key(api_key)
+    }
+
+    pub fn with_api_key(mut self, api_key: Option<String>) -> Self {
+        self.api_key = api_key.filter(|k| !k.is_empty());
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Repeat `overlap_ms` of each chunk at the start of the next one and
+    /// remove the words transcribed twice at the seam
+    pub fn with_chunk_overlap(mut self, overlap_ms: u64) -> Self {
+        self.chunk_policy = self.chunk_policy.with_overlap(overlap_ms);
+        self
+    }
+
+    /// Skip chunks whose share of non-quiet audio is below `ratio` instead
+    /// of uploading them
+    pub fn with_min_speech_ratio(mut self, ratio: f32) -> Self {
+        self.chunk_policy = self.chunk_policy.with_min_speech_ratio(ratio);
+        self
+    }
+
+    /// Upload chunks as FLAC or Ogg Opus instead of WAV
+    pub fn with_encoding(mut self, encoding: SttAudioEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Apply the settings a user configured for this endpoint
+    pub fn with_overrides(mut self, overrides: &RestSttEndpointConfig) -> Self {
+        if let Some(name) = overrides.display_name.as_ref().filter(|n| !n.is_empty()) {
+            self.name = name.clone();
+        }
+        if let Some(url) = overrides.url.as_ref().filter(|u| !u.is_empty()) {
+            self.url = url.clone();
+        } else if let Some(base_url) = overrides.base_url.as_ref().filter(|u| !u.is_empty()) {
+            let path = overrides
+                .path
+                .as_deref()
+                .unwrap_or(DEFAULT_TRANSCRIPTION_PATH);
+            self.url = join_url(base_url, path);
+        }
+        if let Some(required) = overrides.requires_api_key {
+            self.requires_api_key = required;
+        }
+        if let Some(auth) = &overrides.auth {
+            self.auth = auth.clone();
+        }
+        if let Some(model) = &overrides.model {
+            // An empty model removes the field (e.g. for Azure deployments)
+            self.model = Some(model.clone()).filter(|m| !m.is_empty());
+        }
+        if let Some(language) = &overrides.language {
+            self.language = Some(language.clone()).filter(|l| !l.is_empty());
+        }
+        if let Some(format) = overrides.response_format {
+            self.response_format = format;
+        }
+        if let Some(secs) = overrides.timeout_secs {
+            self.timeout = Duration::from_secs(secs.max(1));
+        }
+        if let Some(concurrency) = overrides.concurrency {
+            self = self.with_concurrency(concurrency);
+        }
+        if let Some(chunk_ms) = overrides.chunk_ms {
+            self.chunk_policy = ChunkBoundaryPolicy::new(chunk_ms)
+                .with_overlap(self.chunk_policy.overlap_ms)
+                .with_min_speech_ratio(self.chunk_policy.min_speech_ratio);
+        }
+        if let Some(overlap_ms) = overrides.chunk_overlap_ms {
+            self = self.with_chunk_overlap(overlap_ms);
+        }
+        if let Some(encoding) = overrides.encoding {
+            self.encoding = encoding;
+        }
+        if let Some(retry) = &overrides.retry {
+            self.retry = self.retry.with_overrides(retry);
+        }
+        self
+    }
+
+    /// True when the endpoint has a URL and, if it needs one, a key
+    pub fn is_configured(&self) -> bool {
+        !self.url.is_empty() && (!self.requires_api_key || self.api_key.is_some())
+    }
+
+    fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match (&self.auth, &self.api_key) {
+            (SttAuthScheme::Bearer, Some(key)) => {
+                request.header("Authorization", format!("Bearer {}", key))
+            }
+            (SttAuthScheme::Header { name }, Some(key)) => request.header(name.as_str(), key),
+            _ => request,
+        }
+    }
+
+    /// Upload one encoded chunk and parse the transcript, retrying uploads
+    /// that were rate limited or turned away
+    async fn transcribe(
+        &self,
+        client: &reqwest::Client,
+        audio_bytes: Vec<u8>,
+        offset_ms: u64,
+        prompt: Option<String>,
+    ) -> Result<ChunkTranscript> {
+        self.retry
+            .run(|| self.attempt(client, audio_bytes.clone(), offset_ms, prompt.clone()))
+            .await
+    }
+
+    /// One upload within the endpoint timeout
+    async fn attempt(
+        &self,
+        client: &reqwest::Client,
+        audio_bytes: Vec<u8>,
+        offset_ms: u64,
+        prompt: Option<String>,
+    ) -> Result<ChunkTranscript> {
+        tokio::time::timeout(
+            self.timeout,
+            self.upload(client, audio_bytes, offset_ms, prompt),
+        )
+        .await
+        .map_err(|_| {
+            ProviderError::new(
+                ProviderErrorKind::Timeout,
+                &self.id,
+                format!("{} request timed out after {:?}", self.id, self.timeout),
+            )
+        })?
+    }
+
+    async fn upload(
+        &self,
+        client: &reqwest::Client,
+        audio_bytes: Vec<u8>,
+        offset_ms: u64,
+        prompt: Option<String>,
+    ) -> Result<ChunkTranscript> {
+        let part = Part::bytes(audio_bytes)
+            .file_name(self.encoding.file_name())
+            .mime_str(self.encoding.mime_type())
+            .map_err(|e| MurmurError::Stt(format!("Failed to create multipart part: {}", e)))?;
+
+        let mut form = Form::new().part("file", part);
+        if let Some(model) = &self.model {
+            form = form.text("model", model.clone());
+        }
+        if let Some(language) = &self.language {
+            form = form.text("language", language.clone());
+        }
+        let form = match self.response_format {
+            // verbose_json adds language, segment confidence and word timings
+            SttResponseFormat::VerboseJson => whisper::request_verbose_json(form),
+            SttResponseFormat::Json => form.text("response_format", "json"),
+        };
+        let form = whisper::with_prompt(form, prompt);
+
+        let response = self
+            .authorize(client.post(&self.url))
+            .multipart(form)
+            .send()
+            .await
+            .map_err(|e| error::from_request_error(&self.id, &e))?;
+
+        if !response.status().is_success() {
+            return Err(error::from_response(&self.id, response).await.into());
+        }
+
+        let whisper_response: WhisperResponse = response.json().await.map_err(|e| {
+            MurmurError::Stt(format!("Failed to parse {} response: {}", self.id, e))
+        })?;
+
+        // Plain `json` only has text, but some servers add words/language anyway
+        Ok(whisper_response.into_chunk_transcript(offset_ms))
+    }
+}
+
+/// Flushed audio waiting for upload
+struct PendingChunk {
+    audio_bytes: Vec<u8>,
+    /// Timestamp of the chunk that triggered the flush
+    timestamp_ms: u64,
+    /// Position of the chunk in the session audio
+    offset_ms: u64,
+    /// Where new audio starts, when the chunk begins with overlap
+    seam_ms: Option<u64>,
+}
+
+/// A finished upload, tagged with its place in the session
+struct Uploaded {
+    seq: u64,
+    timestamp_ms: u64,
+    seam_ms: Option<u64>,
+    result: Result<ChunkTranscript>,
+}
+
+/// Text and detail accumulated over a session
+#[derive(Default)]
+struct SessionTranscript {
+    text: String,
+    metadata: TranscriptMetadata,
+    segment_id: u64,
+    last_timestamp_ms: u64,
+}
+
+/// Uploads flushed chunks and turns the responses into events
+struct Uploader {
+    client: reqwest::Client,
+    endpoint: Arc<RestEndpoint>,
+    hallucination_filter: HallucinationFilter,
+    style_prompt: Option<String>,
+    chunker: Arc<Mutex<AudioChunker>>,
+    event_tx: mpsc::Sender<TranscriptionEvent>,
+}
+
+impl Uploader {
+    async fn run(self, mut wav_rx: mpsc::Receiver<PendingChunk>) {
+        let (done_tx, mut done_rx) = mpsc::unbounded_channel::<Uploaded>();
+        let mut transcript = SessionTranscript::default();
+        let mut finished = BTreeMap::new();
+        let mut next_seq = 0u64;
+        let mut next_to_apply = 0u64;
+        let mut in_flight = 0usize;
+        let mut input_open = true;
+
+        while input_open || in_flight > 0 {
+            tokio::select! {
+                pending = wav_rx.recv(), if input_open && in_flight < self.endpoint.concurrency => {
+                    match pending {
+                        Some(pending) => {
+                            // With several uploads in flight the prompt only
+                            // sees text that has already come back
+                            let prompt = whisper::context_prompt(
+                                self.style_prompt.as_deref(),
+                                &transcript.text,
+                            );
+                            let client = self.client.clone();
+                            let endpoint = self.endpoint.clone();
+                            let done_tx = done_tx.clone();
+                            let seq = next_seq;
+                            next_seq += 1;
+                            in_flight += 1;
+                            tokio::spawn(async move {
+                                let result = endpoint
+                                    .transcribe(&client, pending.audio_bytes, pending.offset_ms, prompt)
+                                    .await;
+                                let _ = done_tx.send(Uploaded {
+                                    seq,
+                                    timestamp_ms: pending.timestamp_ms,
+                                    seam_ms: pending.seam_ms,
+                                    result,
+                                });
+                            });
+                        }
+                        None => input_open = false,
+                    }
+                }
+                Some(uploaded) = done_rx.recv(), if in_flight > 0 => {
+                    in_flight -= 1;
+                    finished.insert(uploaded.seq, uploaded);
+                    // Apply in upload order so seams and accumulated text line up
+                    while let Some(uploaded) = finished.remove(&next_to_apply) {
+                        next_to_apply += 1;
+                        self.apply(&mut transcript, uploaded).await;
+                    }
+                }
+            }
+        }
+
+        // Silent chunks never reach this task; the chunker counted them
+        transcript.metadata.metrics.skipped_silent_chunks =
+            self.chunker.lock().await.skipped_silent_chunks();
+        if !transcript.metadata.metrics.is_empty() {
+            info!(
+                "{} session dropped {} silent chunks and {} hallucinations",
+                self.endpoint.id,
+                transcript.metadata.metrics.skipped_silent_chunks,
+                transcript.metadata.metrics.filtered_hallucinations
+            );
+        }
+
+        if !transcript.text.trim().is_empty() {
+            let event = TranscriptionEvent::Committed {
+                text: transcript.text,
+                timestamp_ms: transcript.last_timestamp_ms,
+                metadata: transcript.metadata,
+            };
+
+            if let Err(e) = self.event_tx.send(event).await {
+                error!("Failed to send committed event: {}", e);
+            }
+        }
+
+        info!("{} transcription task finished", self.endpoint.id);
+    }
+
+    /// Add one upload's text to the session and report it as a partial
+    async fn apply(&self, transcript: &mut SessionTranscript, uploaded: Uploaded) {
+        transcript.last_timestamp_ms = uploaded.timestamp_ms;
+
+        match uploaded.result.map(|t| {
+            t.trim_overlap(&transcript.text, uploaded.seam_ms)
+                .filter_hallucinations(&self.hallucination_filter, &mut transcript.metadata.metrics)
+        }) {
+            Ok(ChunkTranscript { text, metadata }) => {
+                if text.trim().is_empty() {
+                    return;
+                }
+                debug!("{} transcription result: {}", self.endpoint.id, text);
+
+                transcript.metadata.metrics.skipped_silent_chunks =
+                    self.chunker.lock().await.skipped_silent_chunks();
+                if !transcript.text.is_empty() {
+                    transcript.text.push(' ');
+                }
+                transcript.text.push_str(&text);
+                transcript.metadata.append(&TranscriptMetadata {
+                    segment_id: Some(transcript.segment_id),
+                    ..metadata
+                });
+                transcript.segment_id += 1;
+
+                let event = TranscriptionEvent::Partial {
+                    text: transcript.text.clone(),
+                    timestamp_ms: uploaded.timestamp_ms,
+                    metadata: transcript.metadata.clone(),
+                };
+
+                if let Err(e) = self.event_tx.send(event).await {
+                    error!("Failed to send partial event: {}", e);
+                }
+            }
+            Err(e) => {
+                error!("{} transcription failed: {}", self.endpoint.id, e);
+                let event = TranscriptionEvent::Error {
+                    message: format!("{} transcription error: {}", self.endpoint.id, e),
+                    detail: e.provider_error().cloned(),
+                };
+                let _ = self.event_tx.send(event).await;
+            }
+        }
+    }
+}
+
+/// Chunked transcription over any Whisper-compatible REST endpoint
+pub struct RestSttProvider {
+    endpoint: RestEndpoint,
+    hallucination_filter: HallucinationFilter,
+    style_prompt: Option<String>,
+    chunker: Arc<Mutex<AudioChunker>>,
+    audio_tx: Arc<Mutex<Option<mpsc::Sender<AudioChunk>>>>,
+    event_tx: Arc<Mutex<Option<mpsc::Sender<TranscriptionEvent>>>>,
+    event_rx: Arc<Mutex<Option<mpsc::Receiver<TranscriptionEvent>>>>,
+    processing_task: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
+}
+
+impl RestSttProvider {
+    pub fn new(mut endpoint: RestEndpoint) -> Self {
+        if !encoding::is_supported(endpoint.encoding) {
+            warn!(
+                "{:?} uploads are not available in this build; {} falls back to WAV",
+                endpoint.encoding, endpoint.id
+            );
+            endpoint.encoding = SttAudioEncoding::Wav;
+        }
+        Self {
+            chunker: Arc::new(Mutex::new(
+                AudioChunker::with_policy(endpoint.chunk_policy).with_encoding(endpoint.encoding),
+            )),
+            endpoint,
+            hallucination_filter: HallucinationFilter::default(),
+            style_prompt: None,
+            audio_tx: Arc::new(Mutex::new(None)),
+            event_tx: Arc::new(Mutex::new(None)),
+            event_rx: Arc::new(Mutex::new(None)),
+            processing_task: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Replace the default hallucination filter applied to each chunk
+    pub fn with_hallucination_filter(mut self, filter: HallucinationFilter) -> Self {
+        self.hallucination_filter = filter;
+        self
+    }
+
+    /// Prompt sent ahead of the previous text with every chunk, e.g. to set
+    /// spelling or punctuation style
+    pub fn with_style_prompt(mut self, prompt: Option<String>) -> Self {
+        self.style_prompt = prompt.filter(|p| !p.trim().is_empty());
+        self
+    }
+
+    /// The endpoint this provider uploads to
+    pub fn endpoint(&self) -> &RestEndpoint {
+        &self.endpoint
+    }
+}
+
+#[async_trait]
+impl SttProvider for RestSttProvider {
+    async fn start_session(&mut self) -> Result<()> {
+        let id = self.endpoint.id.clone();
+        info!(
+            "Starting {} transcription session ({})",
+            id, self.endpoint.url
+        );
+
+        *self.chunker.lock().await = AudioChunker::with_policy(self.endpoint.chunk_policy)
+            .with_encoding(self.endpoint.encoding);
+
+        let (audio_tx, mut audio_rx) = mpsc::channel::<AudioChunk>(32);
+        *self.audio_tx.lock().await = Some(audio_tx);
+
+        let (event_tx, event_rx) = mpsc::channel::<TranscriptionEvent>(32);
+        *self.event_tx.lock().await = Some(event_tx.clone());
+        *self.event_rx.lock().await = Some(event_rx);
+
+        let chunker = self.chunker.clone();
+        let uploader = Uploader {
+            client: lt_http::client(),
+            endpoint: Arc::new(self.endpoint.clone()),
+            hallucination_filter: self.hallucination_filter.clone(),
+            style_prompt: self.style_prompt.clone(),
+            chunker: chunker.clone(),
+            event_tx,
+        };
+
+        let task = tokio::spawn(async move {
+            let (wav_tx, wav_rx) = mpsc::channel::<PendingChunk>(MAX_PENDING_TRANSCRIPTION_CHUNKS);
+            let upload_task = tokio::spawn(uploader.run(wav_rx));
+
+            let mut last_timestamp_ms = 0u64;
+
+            while let Some(chunk) = audio_rx.recv().await {
+                last_timestamp_ms = chunk.timestamp_ms;
+
+                let (audio_bytes, offset_ms, seam_ms) = {
+                    let mut chunker_guard = chunker.lock().await;
+                    chunker_guard.add_chunk(&chunk);
+                    let offset_ms = chunker_guard.buffer_offset_ms();
+                    let seam_ms = chunker_guard.seam_ms();
+
+                    if chunker_guard.should_flush(chunk.timestamp_ms) {
+                        debug!("Flushing audio chunk for {} transcription", id);
+                        (Some(chunker_guard.flush_chunk()), offset_ms, seam_ms)
+                    } else {
+                        (None, offset_ms, seam_ms)
+                    }
+                };
+
+                match audio_bytes {
+                    Some(Ok(audio_bytes)) if audio_bytes.is_empty() => {
+                        debug!("Empty audio chunk, skipping transcription");
+                    }
+                    Some(Ok(audio_bytes)) => {
+                        let pending = PendingChunk {
+                            audio_bytes,
+                            timestamp_ms: chunk.timestamp_ms,
+                            offset_ms,
+                            seam_ms,
+                        };
+                        match wav_tx.try_send(pending) {
+                            Ok(()) => {}
+                            Err(mpsc::error::TrySendError::Full(_)) => {
+                                warn!("{} transcription backlog full; dropping audio chunk", id);
+                            }
+                            Err(mpsc::error::TrySendError::Closed(_)) => {
+                                error!("{} transcription task stopped unexpectedly", id);
+                                break;
+                            }
+                        }
+                    }
+                    Some(Err(e)) => {
+                        error!("Failed to flush audio buffer: {}", e);
+                    }
+                    None => {}
+                }
+            }
+
+            debug!("Audio stream ended, flushing remaining audio");
+            let (final_audio_bytes, final_offset_ms, final_seam_ms) = {
+                let mut chunker_guard = chunker.lock().await;
+                let offset_ms = chunker_guard.buffer_offset_ms();
+                let seam_ms = chunker_guard.seam_ms();
+                (chunker_guard.flush(), offset_ms, seam_ms)
+            };
+
+            match final_audio_bytes {
+                Ok(audio_bytes) if audio_bytes.is_empty() => {
+                    debug!("Empty final audio chunk, skipping transcription");
+                }
+                Ok(audio_bytes) => {
+                    let pending = PendingChunk {
+                        audio_bytes,
+                        timestamp_ms: last_timestamp_ms,
+                        offset_ms: final_offset_ms,
+                        seam_ms: final_seam_ms,
+                    };
+                    match wav_tx.try_send(pending) {
+                        Ok(()) => {}
+                        Err(mpsc::error::TrySendError::Full(_)) => {
+                            warn!("{} transcription backlog full; dropping final audio", id);
+                        }
+                        Err(mpsc::error::TrySendError::Closed(_)) => {
+                            error!("{} transcription task stopped before final audio", id);
+                        }
+                    }
+                }
+                Err(e) => {
+                    error!("Failed to flush final audio buffer: {}", e);
+                }
+            }
+
+            drop(wav_tx);
+
+            if let Err(e) = upload_task.await {
+                error!("{} transcription task join failed: {}", id, e);
+            }
+
+            info!("{} processing task finished", id);
+        });
+
+        *self.processing_task.lock().await = Some(task);
+
+        Ok(())
+    }
+
+    async fn send_audio(&mut self, chunk: AudioChunk) -> Result<()> {
+        let tx_lock = self.audio_tx.lock().await;
+        if let Some(tx) = tx_lock.as_ref() {
+            tx.send(chunk)
+                .await
+                .map_err(|e| MurmurError::Stt(format!("Failed to send audio chunk: {}", e)))?;
+            Ok(())
+        } else {
+            Err(MurmurError::Stt("Session not started".to_string()))
+        }
+    }
+
+    async fn stop_session(&mut self) -> Result<()> {
+        info!("Stopping {} transcription session", self.endpoint.id);
+
+        *self.audio_tx.lock().await = None;
+
+        if let Some(task) = self.processing_task.lock().await.take() {
+            let _ = task.await;
+        }
+
+        info!("{} transcription session stopped", self.endpoint.id);
+        Ok(())
+    }
+
+    async fn subscribe_events(&self) -> mpsc::Receiver<TranscriptionEvent> {
+        let mut rx_lock = self.event_rx.lock().await;
+        rx_lock
+            .take()
+            .expect("subscribe_events called multiple times")
+    }
+
+    async fn health_check(&self) -> Result<SttHealth> {
+        let client = lt_http::client();
+        whisper::probe(&self.endpoint.id, self.endpoint.encoding, |clip| {
+            // One attempt: the test should report a rate limit, not wait it out
+            self.endpoint.attempt(&client, clip, 0, None)
+        })
+        .await
+    }
+
+    fn capabilities(&self) -> SttCapabilities {
+        SttCapabilities {
+            mode: SttMode::Chunked,
+            // Accumulated text is re-emitted as a partial after every chunk
+            partial_results: true,
+            // A pinned language is all the endpoint will be asked for
+            languages: self.endpoint.language.iter().cloned().collect(),
+            // Plain json responses carry no word timings
+            word_timestamps: self.endpoint.response_format == SttResponseFormat::VerboseJson,
+            vocabulary_hints: false,
+            diarization: false,
+            local: is_loopback_url(&self.endpoint.url),
+            requires_api_key: self.endpoint.requires_api_key,
+        }
+    }
+}
+
+/// `base_url` and `path` with exactly one slash between them
+fn join_url(base_url: &str, path: &str) -> String {
+    format!(
+        "{}/{}",
+        base_url.trim_end_matches('/'),
+        path.trim_start_matches('/')
+    )
+}
+
+/// True if `url` points at this machine (e.g. a local whisper.cpp server)
+pub(crate) fn is_loopback_url(url: &str) -> bool {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|u| {
+            u.host_str()
+                .map(|h| matches!(h, "localhost" | "127.0.0.1" | "[::1]"))
+        })
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lt_core::config::RetryConfig;
+    use std::sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    };
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio::sync::{oneshot, Notify};
+    use tokio::time::{timeout, Duration};
+
+    /// Self-hosted endpoint with a short timeout so hung servers fail fast
+    fn test_provider(base_url: String) -> RestSttProvider {
+        RestSttProvider::new(test_endpoint(base_url))
+    }
+
+    fn test_endpoint(base_url: String) -> RestEndpoint {
+        RestEndpoint::custom(&base_url, None, None, None).with_timeout(Duration::from_millis(500))
+    }
+
+    #[test]
+    fn test_custom_endpoint_creation() {
+        let endpoint = RestEndpoint::custom("http://localhost:8080/v1/", None, None, None);
+        assert_eq!(
+            endpoint.url,
+            "http://localhost:8080/v1/audio/transcriptions"
+        );
+        assert_eq!(endpoint.model.as_deref(), Some("whisper-1"));
+        assert!(endpoint.api_key.is_none());
+        assert!(endpoint.language.is_none());
+        assert!(endpoint.is_configured());
+    }
+
+    #[test]
+    fn test_custom_endpoint_with_options() {
+        let endpoint = RestEndpoint::custom(
+            "http://localhost:8080/v1",
+            Some("my-key".to_string()),
+            Some("large-v3".to_string()),
+            Some("en".to_string()),
+        );
+        assert_eq!(endpoint.model.as_deref(), Some("large-v3"));
+        assert_eq!(endpoint.api_key.as_deref(), Some("my-key"));
+        assert_eq!(endpoint.language.as_deref(), Some("en"));
+    }
+
+    #[test]
+    fn test_empty_strings_become_none() {
+        let endpoint = RestEndpoint::custom(
+            "http://localhost:8080/v1",
+            Some("".to_string()),
+            Some("".to_string()),
+            Some("".to_string()),
+        );
+        assert_eq!(endpoint.model.as_deref(), Some("whisper-1"));
+        assert!(endpoint.api_key.is_none());
+        assert!(endpoint.language.is_none());
+    }
+
+    #[test]
+    fn test_presets() {
+        let openai = RestEndpoint::preset("openai").unwrap();
+        assert_eq!(openai.model.as_deref(), Some("whisper-1"));
+        assert_eq!(openai.response_format, SttResponseFormat::VerboseJson);
+        assert!(!openai.is_configured());
+        assert!(openai.with_api_key(Some("sk".to_string())).is_configured());
+
+        let groq = RestEndpoint::preset("groq").unwrap();
+        assert_eq!(groq.model.as_deref(), Some("whisper-large-v3-turbo"));
+        assert_eq!(groq.chunk_policy.target_ms, 3000);
+
+        // Azure needs the deployment URL from config
+        let azure = RestEndpoint::preset("azure_openai")
+            .unwrap()
+            .with_api_key(Some("key".to_string()));
+        assert!(!azure.is_configured());
+        assert_eq!(
+            azure.auth,
+            SttAuthScheme::Header {
+                name: "api-key".to_string()
+            }
+        );
+
+        assert!(RestEndpoint::preset("unknown").is_none());
+        for id in RestEndpoint::preset_ids() {
+            assert!(RestEndpoint::preset(id).is_some(), "{}", id);
+        }
+    }
+
+    #[test]
+    fn test_overrides_replace_only_what_is_set() {
+        let endpoint = RestEndpoint::preset("fireworks")
+            .unwrap()
+            .with_chunk_overlap(300)
+            .with_overrides(&RestSttEndpointConfig {
+                model: Some("whisper-v3-turbo".to_string()),
+                language: Some("fr".to_string()),
+                response_format: Some(SttResponseFormat::Json),
+                timeout_secs: Some(10),
+                concurrency: Some(0),
+                chunk_ms: Some(6000),
+                ..Default::default()
+            });
+        assert!(endpoint.url.contains("fireworks.ai"));
+        assert_eq!(endpoint.model.as_deref(), Some("whisper-v3-turbo"));
+        assert_eq!(endpoint.language.as_deref(), Some("fr"));
+        assert_eq!(endpoint.response_format, SttResponseFormat::Json);
+        assert_eq!(endpoint.timeout, Duration::from_secs(10));
+        assert_eq!(endpoint.concurrency, 1);
+        assert_eq!(endpoint.chunk_policy.target_ms, 6000);
+        // Overlap set before the override survives a new chunk length
+        assert_eq!(endpoint.chunk_policy.overlap_ms, 300);
+
+        assert_eq!(endpoint.encoding, SttAudioEncoding::Wav);
+
+        let endpoint = endpoint.with_overrides(&RestSttEndpointConfig {
+            model: Some(String::new()),
+            chunk_overlap_ms: Some(0),
+            encoding: Some(SttAudioEncoding::Flac),
+            ..Default::default()
+        });
+        assert!(endpoint.model.is_none());
+        assert_eq!(endpoint.chunk_policy.overlap_ms, 0);
+        assert_eq!(endpoint.encoding, SttAudioEncoding::Flac);
+    }
+
+    #[test]
+    fn test_endpoint_defined_in_config() {
+        let endpoint = RestEndpoint::new("my_whisper");
+        assert!(!endpoint.with_api_key(Some("k".to_string())).is_configured());
+
+        let endpoint = RestEndpoint::new("my_whisper")
+            .with_api_key(Some("k".to_string()))
+            .with_overrides(&RestSttEndpointConfig {
+                display_name: Some("My Whisper".to_string()),
+                base_url: Some("https://stt.example.com/v1/".to_string()),
+                auth: Some(SttAuthScheme::Header {
+                    name: "x-api-key".to_string(),
+                }),
+                model: Some("large-v3".to_string()),
+                ..Default::default()
+            });
+        assert_eq!(endpoint.id, "my_whisper");
+        assert_eq!(endpoint.name, "My Whisper");
+        assert_eq!(
+            endpoint.url,
+            "https://stt.example.com/v1/audio/transcriptions"
+        );
+        assert_eq!(endpoint.model.as_deref(), Some("large-v3"));
+        assert!(endpoint.is_configured());
+
+        let endpoint = RestEndpoint::new("lan").with_overrides(&RestSttEndpointConfig {
+            base_url: Some("http://10.0.0.5:8000".to_string()),
+            path: Some("inference".to_string()),
+            requires_api_key: Some(false),
+            ..Default::default()
+        });
+        assert_eq!(endpoint.url, "http://10.0.0.5:8000/inference");
+        assert!(endpoint.is_configured());
+
+        // A full url wins over base_url and path
+        let endpoint =
+            RestEndpoint::preset("openai")
+                .unwrap()
+                .with_overrides(&RestSttEndpointConfig {
+                    url: Some("https://proxy.example.com/stt".to_string()),
+                    base_url: Some("https://ignored.example.com".to_string()),
+                    ..Default::default()
+                });
+        assert_eq!(endpoint.url, "https://proxy.example.com/stt");
+        assert_eq!(endpoint.name, "OpenAI Whisper");
+    }
+
+    #[test]
+    fn test_capabilities_follow_endpoint() {
+        let local = RestSttProvider::new(RestEndpoint::custom(
+            "http://127.0.0.1:8080/v1",
+            None,
+            None,
+            Some("de".to_string()),
+        ))
+        .capabilities();
+        assert_eq!(local.mode, SttMode::Chunked);
+        assert!(local.local);
+        assert!(!local.requires_api_key);
+        assert!(!local.word_timestamps);
+        assert!(local.supports_language("de"));
+        assert!(!local.supports_language("en"));
+
+        let remote = RestSttProvider::new(RestEndpoint::custom(
+            "https://stt.example.com/v1",
+            None,
+            None,
+            None,
+        ))
+        .capabilities();
+        assert!(!remote.local);
+        assert!(remote.supports_language("en"));
+
+        let openai = RestSttProvider::new(RestEndpoint::preset("openai").unwrap()).capabilities();
+        assert!(openai.word_timestamps);
+        assert!(openai.requires_api_key);
+        assert!(!openai.local);
+    }
+
+    #[tokio::test]
+    async fn send_audio_does_not_block_while_transcription_request_is_in_flight() {
+        let server = HangingTranscriptionServer::start().await;
+        let mut provider = test_provider(server.base_url());
+
+        provider.start_session().await.unwrap();
+        let _events = provider.subscribe_events().await;
+
+        provider.send_audio(test_chunk(1)).await.unwrap();
+        provider.send_audio(test_chunk(4001)).await.unwrap();
+        server.wait_for_request().await;
+
+        let mut blocked_at = None;
+        for i in 0..33 {
+            let send = provider.send_audio(empty_test_chunk(4010 + i));
+            match timeout(Duration::from_millis(100), send).await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => panic!("send_audio returned error: {}", e),
+                Err(_) => {
+                    blocked_at = Some(i);
+                    break;
+                }
+            }
+            tokio::task::yield_now().await;
+        }
+
+        server.release_response();
+        provider.stop_session().await.unwrap();
+
+        assert!(
+            blocked_at.is_none(),
+            "send_audio blocked at queued chunk {:?} while a transcription request was in flight",
+            blocked_at
+        );
+    }
+
+    #[tokio::test]
+    async fn transcription_handoff_has_bounded_backlog_while_request_is_in_flight() {
+        let server = CountingTranscriptionServer::start().await;
+        let mut provider = test_provider(server.base_url());
+
+        provider.start_session().await.unwrap();
+        let _events = provider.subscribe_events().await;
+
+        provider.send_audio(test_chunk(1)).await.unwrap();
+        provider.send_audio(test_chunk(4001)).await.unwrap();
+        server.wait_for_requests(1).await;
+
+        let queued_flushes = MAX_PENDING_TRANSCRIPTION_CHUNKS + 3;
+        let mut timestamp_ms = 8002;
+        for _ in 0..queued_flushes {
+            provider.send_audio(test_chunk(timestamp_ms)).await.unwrap();
+            provider
+                .send_audio(test_chunk(timestamp_ms + 4000))
+                .await
+                .unwrap();
+            timestamp_ms += 4001;
+        }
+
+        server.release_first_response();
+        timeout(
+            Duration::from_secs(2),
+            server.wait_for_requests(1 + MAX_PENDING_TRANSCRIPTION_CHUNKS),
+        )
+        .await
+        .unwrap();
+        provider.stop_session().await.unwrap();
+
+        assert_eq!(
+            server.request_count(),
+            1 + MAX_PENDING_TRANSCRIPTION_CHUNKS,
+            "transcription requests should be capped while the endpoint is backlogged"
+        );
+    }
+
+    #[tokio::test]
+    async fn stop_session_returns_when_transcription_endpoint_hangs() {
+        let server = HangingTranscriptionServer::start().await;
+        let mut provider = test_provider(server.base_url());
+
+        provider.start_session().await.unwrap();
+        let mut events = provider.subscribe_events().await;
+
+        provider.send_audio(test_chunk(1)).await.unwrap();
+        provider.send_audio(test_chunk(4001)).await.unwrap();
+        server.wait_for_request().await;
+
+        timeout(Duration::from_secs(2), provider.stop_session())
+            .await
+            .expect("stop_session should not wait forever for a hung transcription request")
+            .unwrap();
+
+        let event = timeout(Duration::from_secs(2), events.recv())
+            .await
+            .expect("timeout should emit a transcription error event")
+            .expect("event channel should remain open");
+        assert!(
+            matches!(
+                event,
+                TranscriptionEvent::Error { ref message, ref detail }
+                    if message.contains("timed out")
+                        && detail.as_ref().map(|d| d.kind) == Some(ProviderErrorKind::Timeout)
+            ),
+            "expected timeout error event, got {:?}",
+            event
+        );
+    }
+
+    #[tokio::test]
+    async fn uploads_carry_style_prompt_and_previous_text() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (request_tx, mut request_rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let _ = request_tx.send(read_http_request(&mut stream).await);
+                write_transcription_response(&mut stream).await;
+            }
+        });
+
+        let mut provider = test_provider(format!("http://{}/v1", addr))
+            .with_style_prompt(Some("Use British spelling.".to_string()));
+        provider.start_session().await.unwrap();
+        let _events = provider.subscribe_events().await;
+
+        provider.send_audio(test_chunk(1)).await.unwrap();
+        provider.send_audio(test_chunk(4001)).await.unwrap();
+        let first = timeout(Duration::from_secs(2), request_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        provider.send_audio(test_chunk(4002)).await.unwrap();
+        provider.send_audio(test_chunk(8002)).await.unwrap();
+        let second = timeout(Duration::from_secs(2), request_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        provider.stop_session().await.unwrap();
+
+        assert!(first.contains("name=\"prompt\"\r\n\r\nUse British spelling.\r\n"));
+        // The second chunk is primed with the first chunk's text ("ok")
+        assert!(second.contains("name=\"prompt\"\r\n\r\nUse British spelling. ok\r\n"));
+    }
+
+    #[tokio::test]
+    async fn health_check_reports_latency_for_reachable_endpoint() {
+        let server = CountingTranscriptionServer::start().await;
+        server.release_first_response();
+        let provider = test_provider(server.base_url());
+
+        let health = provider.health_check().await.unwrap();
+        assert_eq!(server.request_count(), 1);
+        assert!(health.latency_ms < 15_000);
+    }
+
+    #[tokio::test]
+    async fn health_check_classifies_rejected_credentials() {
+        let base_url = respond_once("401 Unauthorized", r#"{"error":"invalid api key"}"#).await;
+        let provider =
+            RestSttProvider::new(test_endpoint(base_url).with_api_key(Some("wrong".to_string())));
+
+        let err = provider.health_check().await.unwrap_err();
+        let detail = err.provider_error().expect("structured error");
+        assert_eq!(detail.kind, ProviderErrorKind::Auth);
+        assert_eq!(detail.status, Some(401));
+    }
+
+    #[tokio::test]
+    async fn health_check_fails_for_unreachable_endpoint() {
+        // Bind and drop to get a port nobody listens on
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let provider = test_provider(format!("http://{}/v1", addr));
+
+        let err = provider.health_check().await.unwrap_err();
+        assert!(err.is_retryable(), "unexpected error: {}", err);
+    }
+
+    #[tokio::test]
+    async fn header_auth_sends_raw_key_to_configured_url() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (request_tx, mut request_rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let _ = request_tx.send(read_http_request(&mut stream).await);
+                write_transcription_response(&mut stream).await;
+            }
+        });
+
+        let endpoint = RestEndpoint::preset("azure_openai")
+            .unwrap()
+            .with_api_key(Some("azure-key".to_string()))
+            .with_overrides(&RestSttEndpointConfig {
+                url: Some(format!(
+                    "http://{}/openai/deployments/whisper/audio/transcriptions?api-version=2024-06-01",
+                    addr
+                )),
+                ..Default::default()
+            });
+        assert!(endpoint.is_configured());
+        RestSttProvider::new(endpoint).health_check().await.unwrap();
+
+        let request = request_rx.recv().await.unwrap();
+        assert!(request.starts_with(
+            "POST /openai/deployments/whisper/audio/transcriptions?api-version=2024-06-01 "
+        ));
+        assert!(request.contains("api-key: azure-key\r\n"));
+        assert!(!request.to_lowercase().contains("authorization:"));
+        // Azure takes the model from the deployment
+        assert!(!request.contains("name=\"model\""));
+    }
+
+    #[tokio::test]
+    async fn flac_uploads_carry_matching_file_name_and_mime_type() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (request_tx, mut request_rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let _ = request_tx.send(read_http_request(&mut stream).await);
+                write_transcription_response(&mut stream).await;
+            }
+        });
+
+        let endpoint =
+            test_endpoint(format!("http://{}/v1", addr)).with_encoding(SttAudioEncoding::Flac);
+        let mut provider = RestSttProvider::new(endpoint);
+        provider.health_check().await.unwrap();
+        provider.start_session().await.unwrap();
+        let _events = provider.subscribe_events().await;
+        provider.send_audio(test_chunk(1)).await.unwrap();
+        provider.stop_session().await.unwrap();
+
+        for _ in 0..2 {
+            let request = timeout(Duration::from_secs(2), request_rx.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            assert!(request.contains("filename=\"audio.flac\""));
+            assert!(request.contains("Content-Type: audio/flac\r\n\r\nfLaC"));
+        }
+    }
+
+    #[cfg(not(feature = "opus"))]
+    #[test]
+    fn test_ogg_opus_falls_back_to_wav_without_feature() {
+        let endpoint = RestEndpoint::preset("groq")
+            .unwrap()
+            .with_encoding(SttAudioEncoding::OggOpus);
+        let provider = RestSttProvider::new(endpoint);
+        assert_eq!(provider.endpoint().encoding, SttAudioEncoding::Wav);
+    }
+
+    #[tokio::test]
+    async fn concurrent_uploads_are_applied_in_order() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let request_count = Arc::new(AtomicUsize::new(0));
+        let first_done = Arc::new(AtomicBool::new(false));
+        let overlapped = Arc::new(AtomicBool::new(false));
+        {
+            let (request_count, first_done, overlapped) = (
+                request_count.clone(),
+                first_done.clone(),
+                overlapped.clone(),
+            );
+            tokio::spawn(async move {
+                while let Ok((mut stream, _)) = listener.accept().await {
+                    let (request_count, first_done, overlapped) = (
+                        request_count.clone(),
+                        first_done.clone(),
+                        overlapped.clone(),
+                    );
+                    tokio::spawn(async move {
+                        read_http_request(&mut stream).await;
+                        let n = request_count.fetch_add(1, Ordering::SeqCst);
+                        if n == 0 {
+                            // The first chunk answers last
+                            tokio::time::sleep(Duration::from_millis(300)).await;
+                            first_done.store(true, Ordering::SeqCst);
+                        } else if !first_done.load(Ordering::SeqCst) {
+                            overlapped.store(true, Ordering::SeqCst);
+                        }
+                        write_text_response(&mut stream, &format!("chunk {}", n)).await;
+                    });
+                }
+            });
+        }
+
+        let mut provider = RestSttProvider::new(
+            RestEndpoint::custom(&format!("http://{}/v1", addr), None, None, None)
+                .with_concurrency(2),
+        );
+        provider.start_session().await.unwrap();
+        let mut events = provider.subscribe_events().await;
+
+        provider.send_audio(test_chunk(1)).await.unwrap();
+        provider.send_audio(test_chunk(4001)).await.unwrap();
+        provider.send_audio(test_chunk(4002)).await.unwrap();
+        provider.send_audio(test_chunk(8002)).await.unwrap();
+        timeout(Duration::from_secs(5), provider.stop_session())
+            .await
+            .unwrap()
+            .unwrap();
+
+        let mut texts = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            match event {
+                TranscriptionEvent::Partial { text, .. }
+                | TranscriptionEvent::Committed { text, .. } => texts.push(text),
+                other => panic!("unexpected event {:?}", other),
+            }
+        }
+        assert!(overlapped.load(Ordering::SeqCst), "uploads did not overlap");
+        assert_eq!(texts, ["chunk 0", "chunk 0 chunk 1", "chunk 0 chunk 1"]);
+    }
+
+    #[tokio::test]
+    async fn rate_limited_uploads_are_retried_after_requested_delay() {
+        let (base_url, requests) = respond_in_sequence(vec![
+            ("429 Too Many Requests", "retry-after-ms: 50\r\n"),
+            ("503 Service Unavailable", "Retry-After: 0\r\n"),
+        ])
+        .await;
+        let mut provider = RestSttProvider::new(test_endpoint(base_url));
+        provider.start_session().await.unwrap();
+        let mut events = provider.subscribe_events().await;
+
+        provider.send_audio(test_chunk(1)).await.unwrap();
+        timeout(Duration::from_secs(5), provider.stop_session())
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+        match events.try_recv().unwrap() {
+            TranscriptionEvent::Partial { text, .. } => assert_eq!(text, "ok"),
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn long_retry_after_fails_the_chunk_without_waiting() {
+        let (base_url, requests) =
+            respond_in_sequence(vec![("429 Too Many Requests", "Retry-After: 120\r\n")]).await;
+        let mut provider = RestSttProvider::new(test_endpoint(base_url));
+        provider.start_session().await.unwrap();
+        let mut events = provider.subscribe_events().await;
+
+        provider.send_audio(test_chunk(1)).await.unwrap();
+        timeout(Duration::from_secs(2), provider.stop_session())
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+        match events.try_recv().unwrap() {
+            TranscriptionEvent::Error { detail, .. } => {
+                let detail = detail.unwrap();
+                assert_eq!(
+                    detail.kind,
+                    ProviderErrorKind::RateLimited {
+                        retry_after_secs: Some(120)
+                    }
+                );
+                assert_eq!(detail.retry_after_ms, Some(120_000));
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn retry_attempts_follow_endpoint_config() {
+        let (base_url, requests) = respond_in_sequence(vec![
+            ("503 Service Unavailable", ""),
+            ("503 Service Unavailable", ""),
+        ])
+        .await;
+        let endpoint = test_endpoint(base_url).with_overrides(&RestSttEndpointConfig {
+            retry: Some(RetryConfig {
+                max_attempts: Some(2),
+                base_delay_ms: Some(10),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        let mut provider = RestSttProvider::new(endpoint);
+        provider.start_session().await.unwrap();
+        let mut events = provider.subscribe_events().await;
+
+        provider.send_audio(test_chunk(1)).await.unwrap();
+        timeout(Duration::from_secs(2), provider.stop_session())
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            TranscriptionEvent::Error { .. }
+        ));
+    }
+
+    /// Answer requests with the given statuses and extra headers in turn,
+    /// then with a transcript; returns the base URL and a request counter
+    async fn respond_in_sequence(
+        responses: Vec<(&'static str, &'static str)>,
+    ) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(AtomicUsize::new(0));
+        let served = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                read_http_request(&mut stream).await;
+                let n = served.fetch_add(1, Ordering::SeqCst);
+                match responses.get(n) {
+                    Some((status, headers)) => {
+                        let body = r#"{"error":{"message":"busy"}}"#;
+                        let response = format!(
+                            "HTTP/1.1 {}\r\n{}Content-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                            status,
+                            headers,
+                            body.len(),
+                            body
+                        );
+                        let _ = stream.write_all(response.as_bytes()).await;
+                    }
+                    None => write_transcription_response(&mut stream).await,
+                }
+            }
+        });
+        (format!("http://{}/v1", addr), requests)
+    }
+
+    /// Serve a single request with a fixed status and JSON body
+    async fn respond_once(status: &'static str, body: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            read_http_request(&mut stream).await;
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+        });
+        format!("http://{}/v1", addr)
+    }
+
+    fn test_chunk(timestamp_ms: u64) -> AudioChunk {
+        AudioChunk {
+            data: vec![0; 160],
+            timestamp_ms,
+            sample_rate: 16000,
+            channels: 1,
+        }
+    }
+
+    fn empty_test_chunk(timestamp_ms: u64) -> AudioChunk {
+        AudioChunk {
+            data: Vec::new(),
+            timestamp_ms,
+            sample_rate: 16000,
+            channels: 1,
+        }
+    }
+
+    struct CountingTranscriptionServer {
+        base_url: String,
+        first_response_release_tx: Mutex<Option<oneshot::Sender<()>>>,
+        request_count: Arc<AtomicUsize>,
+        request_notify: Arc<Notify>,
+    }
+
+    impl CountingTranscriptionServer {
+        async fn start() -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let (first_response_release_tx, first_response_release_rx) = oneshot::channel();
+            let request_count = Arc::new(AtomicUsize::new(0));
+            let request_notify = Arc::new(Notify::new());
+
+            let task_request_count = request_count.clone();
+            let task_request_notify = request_notify.clone();
+            tokio::spawn(async move {
+                let mut first_response_release_rx = Some(first_response_release_rx);
+
+                loop {
+                    let Ok((mut stream, _)) = listener.accept().await else {
+                        break;
+                    };
+
+                    read_http_request(&mut stream).await;
+                    let count = task_request_count.fetch_add(1, Ordering::SeqCst) + 1;
+                    task_request_notify.notify_one();
+
+                    if count == 1 {
+                        if let Some(rx) = first_response_release_rx.take() {
+                            let _ = rx.await;
+                        }
+                    }
+
+                    write_transcription_response(&mut stream).await;
+                }
+            });
+
+            Self {
+                base_url: format!("http://{}/v1", addr),
+                first_response_release_tx: Mutex::new(Some(first_response_release_tx)),
+                request_count,
+                request_notify,
+            }
+        }
+
+        fn base_url(&self) -> String {
+            self.base_url.clone()
+        }
+
+        async fn wait_for_requests(&self, expected: usize) {
+            while self.request_count() < expected {
+                self.request_notify.notified().await;
+            }
+        }
+
+        fn request_count(&self) -> usize {
+            self.request_count.load(Ordering::SeqCst)
+        }
+
+        fn release_first_response(&self) {
+            if let Ok(mut guard) = self.first_response_release_tx.try_lock() {
+                if let Some(tx) = guard.take() {
+                    let _ = tx.send(());
+                }
+            }
+        }
+    }
+
+    struct HangingTranscriptionServer {
+        base_url: String,
+        request_seen_rx: Mutex<Option<oneshot::Receiver<()>>>,
+        release_tx: Mutex<Option<oneshot::Sender<()>>>,
+    }
+
+    impl HangingTranscriptionServer {
+        async fn start() -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let (request_seen_tx, request_seen_rx) = oneshot::channel();
+            let (release_tx, release_rx) = oneshot::channel();
+
+            tokio::spawn(async move {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                read_http_request(&mut stream).await;
+                let _ = request_seen_tx.send(());
+                let _ = release_rx.await;
+
+                write_transcription_response(&mut stream).await;
+            });
+
+            Self {
+                base_url: format!("http://{}/v1", addr),
+                request_seen_rx: Mutex::new(Some(request_seen_rx)),
+                release_tx: Mutex::new(Some(release_tx)),
+            }
+        }
+
+        fn base_url(&self) -> String {
+            self.base_url.clone()
+        }
+
+        async fn wait_for_request(&self) {
+            let rx = self.request_seen_rx.lock().await.take().unwrap();
+            rx.await.unwrap();
+        }
+
+        fn release_response(&self) {
+            if let Ok(mut guard) = self.release_tx.try_lock() {
+                if let Some(tx) = guard.take() {
+                    let _ = tx.send(());
+                }
+            }
+        }
+    }
+
+    async fn write_transcription_response(stream: &mut tokio::net::TcpStream) {
+        write_text_response(stream, "ok").await;
+    }
+
+    async fn write_text_response(stream: &mut tokio::net::TcpStream, text: &str) {
+        let body = serde_json::json!({ "text": text }).to_string();
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        let _ = stream.write_all(response.as_bytes()).await;
+    }
+
+    /// Read one HTTP request and return it (lossily decoded)
+    async fn read_http_request(stream: &mut tokio::net::TcpStream) -> String {
+        let mut buf = Vec::new();
+        let mut header_end = None;
+        let mut tmp = [0u8; 4096];
+
+        while header_end.is_none() {
+            let n = stream.read(&mut tmp).await.unwrap();
+            assert!(n > 0, "connection closed before HTTP headers");
+            buf.extend_from_slice(&tmp[..n]);
+            header_end = buf.windows(4).position(|window| window == b"\r\n\r\n");
+        }
+
+        let header_end = header_end.unwrap() + 4;
+        let headers = String::from_utf8_lossy(&buf[..header_end]);
+        let content_length = headers
+            .lines()
+            .find_map(|line| line.strip_prefix("content-length:"))
+            .or_else(|| {
+                headers
+                    .lines()
+                    .find_map(|line| line.strip_prefix("Content-Length:"))
+            })
+            .and_then(|value| value.trim().parse::<usize>().ok())
+            .unwrap_or(0);
+
+        while buf.len() - header_end < content_length {
+            let n = stream.read(&mut tmp).await.unwrap();
+            assert!(n > 0, "connection closed before HTTP body");
+            buf.extend_from_slice(&tmp[..n]);
+        }
+        String::from_utf8_lossy(&buf).into_owned()
+    }
+}
@@ -0,0 +1,176 @@
+//! Seam de-duplication for overlapping REST chunks
+//!
+//! When each upload repeats the tail of the previous chunk, the words spoken
+//! in that overlap come back twice. [`dedupe_seam`] aligns the end of the text
+//! so far with the start of the new chunk's text and drops the repeat.
+
+/// How many tokens at each side of the seam are compared
+const SEAM_WINDOW_TOKENS: usize = 12;
+
+/// The matching run must start within this many tokens of the new text
+const MAX_LEADING_TOKENS: usize = 4;
+
+/// The matching run must end within this many tokens of the previous text
+const MAX_TRAILING_TOKENS: usize = 3;
+
+/// Single-token seams of this length or shorter are treated as ambiguous
+/// ("the", "that", "and")
+const MAX_FUNCTION_WORD_CHARS: usize = 4;
+
+/// A word of the input: byte range in the original string plus its
+/// comparison form (lowercase, punctuation stripped)
+pub(crate) struct Token {
+    pub start: usize,
+    pub end: usize,
+    pub key: String,
+}
+
+/// Split `text` at whitespace into comparable tokens
+pub(crate) fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (i, c) in text
+        .char_indices()
+        .chain(std::iter::once((text.len(), ' ')))
+    {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some(i),
+            (true, Some(s)) => {
+                let key: String = text[s..i]
+                    .chars()
+                    .filter(|c| c.is_alphanumeric())
+                    .flat_map(char::to_lowercase)
+                    .collect();
+                tokens.push(Token {
+                    start: s,
+                    end: i,
+                    key,
+                });
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    tokens
+}
+
+/// Return `next` without the words it repeats from the end of `previous`.
+///
+/// The longest common run of tokens between the tail of `previous` and the
+/// head of `next` is taken as the overlap; everything in `next` up to the
+/// end of that run is dropped. Runs of a single token only count when they
+/// sit exactly at the seam and are not a short function word, so genuine
+/// repetitions ("that that") survive. Without a convincing match `next` is
+/// returned unchanged (trimmed).
+pub fn dedupe_seam<'a>(previous: &str, next: &'a str) -> &'a str {
+    let prev_tokens = tokenize(previous);
+    let next_tokens = tokenize(next);
+    if prev_tokens.is_empty() || next_tokens.is_empty() {
+        return next.trim();
+    }
+
+    let tail = &prev_tokens[prev_tokens.len().saturating_sub(SEAM_WINDOW_TOKENS)..];
+    let head = &next_tokens[..next_tokens.len().min(SEAM_WINDOW_TOKENS)];
+
+    // Longest common substring over tokens: (length, end in tail, end in head)
+    let mut best: Option<(usize, usize, usize)> = None;
+    let mut run = vec![vec![0usize; head.len() + 1]; tail.len() + 1];
+    for i in 1..=tail.len() {
+        for j in 1..=head.len() {
+            if tail[i - 1].key.is_empty() || tail[i - 1].key != head[j - 1].key {
+                continue;
+            }
+            run[i][j] = run[i - 1][j - 1] + 1;
+            let len = run[i][j];
+            let starts_near_seam = j - len <= MAX_LEADING_TOKENS;
+            let ends_near_seam = tail.len() - i <= MAX_TRAILING_TOKENS;
+            if !starts_near_seam || !ends_near_seam {
+                continue;
+            }
+            // Prefer longer runs, then the one closest to the start of `next`
+            let better = match best {
+                None => true,
+                Some((best_len, _, best_j)) => len > best_len || (len == best_len && j < best_j),
+            };
+            if better {
+                best = Some((len, i, j));
+            }
+        }
+    }
+
+    let Some((len, tail_end, head_end)) = best else {
+        return next.trim();
+    };
+    let convincing = len >= 2
+        || (tail_end == tail.len()
+            && head_end == 1
+            && head[0].key.chars().count() > MAX_FUNCTION_WORD_CHARS);
+    if !convincing {
+        return next.trim();
+    }
+
+    next[head[head_end - 1].end..].trim()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_removes_repeated_overlap() {
+        let previous = "we should ship the release on Friday";
+        let next = "on Friday, after the final review.";
+        assert_eq!(dedupe_seam(previous, next), "after the final review.");
+    }
+
+    #[test]
+    fn test_ignores_case_and_punctuation() {
+        let previous = "Let me check the numbers.";
+        let next = "The numbers look fine";
+        assert_eq!(dedupe_seam(previous, next), "look fine");
+    }
+
+    #[test]
+    fn test_drops_garbled_leading_fragment() {
+        // The overlap starts mid-word, so the new chunk opens with a fragment
+        let previous = "the meeting is scheduled for tomorrow morning";
+        let next = "ow tomorrow morning at nine";
+        assert_eq!(dedupe_seam(previous, next), "at nine");
+    }
+
+    #[test]
+    fn test_single_word_seam() {
+        assert_eq!(
+            dedupe_seam("send it to Stephanie", "Stephanie and Mark"),
+            "and Mark"
+        );
+        // Short words at the seam are ambiguous and are kept
+        assert_eq!(dedupe_seam("I said that", "that is fine"), "that is fine");
+    }
+
+    #[test]
+    fn test_no_overlap_returns_text_unchanged() {
+        assert_eq!(
+            dedupe_seam("first sentence here.", " Second sentence there. "),
+            "Second sentence there."
+        );
+        assert_eq!(dedupe_seam("", "hello world"), "hello world");
+        assert_eq!(dedupe_seam("hello world", ""), "");
+    }
+
+    #[test]
+    fn test_match_far_from_seam_is_ignored() {
+        // "the report" appears in both, but not at the seam
+        let previous = "I read the report yesterday and then went home early today";
+        let next = "because tomorrow we discuss the report";
+        assert_eq!(dedupe_seam(previous, next), next);
+    }
+
+    #[test]
+    fn test_handles_multibyte_text() {
+        assert_eq!(
+            dedupe_seam("wir treffen uns in München", "in München um zehn"),
+            "um zehn"
+        );
+    }
+}
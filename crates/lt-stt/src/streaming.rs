@@ -0,0 +1,883 @@
+//! Configurable WebSocket engine for self-hosted streaming STT servers
+//!
+//! WhisperLive, faster-whisper-server and home-grown servers all take PCM
+//! over a socket and push JSON back, but differ in sample format, handshake
+//! messages and where the text sits in their replies. [`StreamingEndpoint`]
+//! holds those settings, with each known protocol as a preset, and
+//! [`StreamingSttProvider`] maps server messages to partial and committed
+//! text through the paths in a [`MessageMapping`].
+
+use async_trait::async_trait;
+use futures_util::{SinkExt, StreamExt};
+use lt_core::config::{
+    StreamingAudioEncoding, StreamingSttConfig, StreamingSttProtocol, SttAuthScheme,
+};
+use lt_core::error::{MurmurError, ProviderError, ProviderErrorKind, Result};
+use lt_core::stt::{
+    AudioChunk, AudioFormat, SttCapabilities, SttHealth, SttMode, SttProvider, TranscriptMetadata,
+    TranscriptionEvent,
+};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::{mpsc, oneshot, Mutex};
+use tokio_tungstenite::tungstenite::{handshake::client::Request, Message};
+use tracing::{debug, error, info, warn};
+
+use crate::rest::is_loopback_url;
+use crate::ws::{self, HEALTH_CHECK_TIMEOUT};
+
+/// Provider id, used in errors and as the `api_keys` slot
+const PROVIDER_ID: &str = "custom_streaming";
+
+/// PCM rate unless the endpoint config sets one
+const DEFAULT_SAMPLE_RATE: u32 = 16000;
+
+/// After the last audio, the session ends once the server has been quiet
+/// this long (servers that close the socket themselves end it sooner)
+const CLOSE_IDLE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Upper bound for the wait after the last audio
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Where partial and committed text sit in server messages
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageMapping {
+    /// Array of segments; `None` treats each message as one segment
+    pub segments: Option<String>,
+    /// Text within a segment
+    pub text: String,
+    /// Flag that makes a segment final; `None` commits the last text when
+    /// the session ends
+    pub is_final: Option<String>,
+    /// Value identifying a segment that is repeated across messages, so a
+    /// final segment is committed once
+    pub segment_key: Option<String>,
+    /// Error message
+    pub error: Option<String>,
+}
+
+/// Frame sent to tell the server the audio is over
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EndMessage {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+impl EndMessage {
+    fn to_message(&self) -> Message {
+        match self {
+            Self::Text(text) => Message::Text(text.clone().into()),
+            Self::Binary(bytes) => Message::Binary(bytes.clone().into()),
+        }
+    }
+}
+
+/// Everything needed to talk to one streaming server
+#[derive(Debug, Clone)]
+pub struct StreamingEndpoint {
+    /// WebSocket URL (empty until configured)
+    pub url: String,
+    /// How `api_key` is sent
+    pub auth: SttAuthScheme,
+    pub api_key: Option<String>,
+    pub model: Option<String>,
+    pub language: Option<String>,
+    pub sample_rate: u32,
+    pub encoding: StreamingAudioEncoding,
+    /// Query parameters added to the URL; values may be `{model}`,
+    /// `{language}` or `{sample_rate}`, and pairs whose value is unset are
+    /// left out
+    pub query: Vec<(String, String)>,
+    /// JSON sent after connecting; `{model}`, `{language}`, `{sample_rate}`
+    /// and `{uid}` are replaced with JSON values
+    pub start_message: Option<String>,
+    pub end_message: Option<EndMessage>,
+    pub mapping: MessageMapping,
+}
+
+impl StreamingEndpoint {
+    /// Defaults for a protocol
+    pub fn preset(protocol: StreamingSttProtocol) -> Self {
+        let endpoint = Self {
+            url: String::new(),
+            auth: SttAuthScheme::Bearer,
+            api_key: None,
+            model: None,
+            language: None,
+            sample_rate: DEFAULT_SAMPLE_RATE,
+            encoding: StreamingAudioEncoding::PcmS16le,
+            query: Vec::new(),
+            start_message: None,
+            end_message: None,
+            mapping: MessageMapping {
+                segments: None,
+                text: "text".to_string(),
+                is_final: None,
+                segment_key: None,
+                error: None,
+            },
+        };
+        match protocol {
+            StreamingSttProtocol::Json => Self {
+                end_message: Some(EndMessage::Text(r#"{"type":"end"}"#.to_string())),
+                mapping: MessageMapping {
+                    is_final: Some("is_final".to_string()),
+                    error: Some("error".to_string()),
+                    ..endpoint.mapping.clone()
+                },
+                ..endpoint
+            },
+            // The server keeps a window of recent segments and marks the
+            // ones that will not change as completed
+            StreamingSttProtocol::WhisperLive => Self {
+                model: Some("small".to_string()),
+                encoding: StreamingAudioEncoding::PcmF32le,
+                start_message: Some(
+                    r#"{"uid": {uid}, "language": {language}, "task": "transcribe", "model": {model}, "use_vad": true}"#
+                        .to_string(),
+                ),
+                end_message: Some(EndMessage::Binary(b"END_OF_AUDIO".to_vec())),
+                mapping: MessageMapping {
+                    segments: Some("segments".to_string()),
+                    is_final: Some("completed".to_string()),
+                    segment_key: Some("start".to_string()),
+                    ..endpoint.mapping.clone()
+                },
+                ..endpoint
+            },
+            // Every message carries the whole transcript so far
+            StreamingSttProtocol::FasterWhisper => Self {
+                query: vec![
+                    ("model".to_string(), "{model}".to_string()),
+                    ("language".to_string(), "{language}".to_string()),
+                    ("response_format".to_string(), "json".to_string()),
+                ],
+                ..endpoint
+            },
+        }
+    }
+
+    pub fn with_api_key(mut self, api_key: Option<String>) -> Self {
+        self.api_key = api_key.filter(|k| !k.is_empty());
+        self
+    }
+
+    /// Apply the settings a user configured for the server
+    pub fn with_overrides(mut self, config: &StreamingSttConfig) -> Self {
+        // An empty string turns an optional setting off
+        let optional = |value: &String| Some(value.clone()).filter(|v| !v.is_empty());
+
+        if let Some(url) = config.url.as_ref().filter(|u| !u.is_empty()) {
+            self.url = url.clone();
+        }
+        if let Some(auth) = &config.auth {
+            self.auth = auth.clone();
+        }
+        if let Some(model) = &config.model {
+            self.model = optional(model);
+        }
+        if let Some(language) = &config.language {
+            self.language = optional(language).filter(|l| l != "auto");
+        }
+        if let Some(sample_rate) = config.sample_rate.filter(|r| *r > 0) {
+            self.sample_rate = sample_rate;
+        }
+        if let Some(encoding) = config.encoding {
+            self.encoding = encoding;
+        }
+        if let Some(start_message) = &config.start_message {
+            self.start_message = optional(start_message);
+        }
+        if let Some(end_message) = &config.end_message {
+            self.end_message = optional(end_message).map(|m| {
+                if config.end_message_binary.unwrap_or(false) {
+                    EndMessage::Binary(m.into_bytes())
+                } else {
+                    EndMessage::Text(m)
+                }
+            });
+        }
+        let mapping = &mut self.mapping;
+        if let Some(path) = &config.segments_path {
+            mapping.segments = optional(path);
+        }
+        if let Some(path) = config.text_path.as_ref().filter(|p| !p.is_empty()) {
+            mapping.text = path.clone();
+        }
+        if let Some(path) = &config.final_path {
+            mapping.is_final = optional(path);
+        }
+        if let Some(path) = &config.segment_key_path {
+            mapping.segment_key = optional(path);
+        }
+        if let Some(path) = &config.error_path {
+            mapping.error = optional(path);
+        }
+        self
+    }
+
+    /// True when the endpoint has a URL
+    pub fn is_configured(&self) -> bool {
+        !self.url.is_empty()
+    }
+
+    /// Value of a `{placeholder}` as JSON (`Null` when unset)
+    fn placeholder(&self, name: &str) -> Option<Value> {
+        match name {
+            "model" => Some(self.model.clone().map_or(Value::Null, Value::from)),
+            "language" => Some(self.language.clone().map_or(Value::Null, Value::from)),
+            "sample_rate" => Some(Value::from(self.sample_rate)),
+            "uid" => {
+                let nanos = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |d| d.as_nanos());
+                Some(Value::from(format!("murmur-{:x}", nanos)))
+            }
+            _ => None,
+        }
+    }
+
+    /// The start message with placeholders filled in; an error if the
+    /// result is not valid JSON
+    fn start_message(&self) -> Result<Option<String>> {
+        let Some(template) = &self.start_message else {
+            return Ok(None);
+        };
+        let mut message = template.clone();
+        for name in ["model", "language", "sample_rate", "uid"] {
+            let pattern = format!("{{{}}}", name);
+            if message.contains(&pattern) {
+                let value = self.placeholder(name).unwrap_or(Value::Null);
+                message = message.replace(&pattern, &value.to_string());
+            }
+        }
+        serde_json::from_str::<Value>(&message)
+            .map_err(|e| MurmurError::Stt(format!("Invalid streaming STT start message: {}", e)))?;
+        Ok(Some(message))
+    }
+
+    /// WebSocket URL with the preset's query parameters
+    fn build_ws_url(&self) -> Result<url::Url> {
+        let mut url = url::Url::parse(&self.url)
+            .map_err(|e| MurmurError::Stt(format!("Invalid URL: {}", e)))?;
+        for (key, value) in &self.query {
+            let value = match value.strip_prefix('{').and_then(|v| v.strip_suffix('}')) {
+                Some(name) => match self.placeholder(name) {
+                    Some(Value::String(s)) => s,
+                    Some(Value::Number(n)) => n.to_string(),
+                    _ => continue,
+                },
+                None => value.clone(),
+            };
+            url.query_pairs_mut().append_pair(key, &value);
+        }
+        Ok(url)
+    }
+
+    /// Authenticated WebSocket handshake request
+    fn connect_request(&self) -> Result<Request> {
+        let header = match (&self.auth, &self.api_key) {
+            (SttAuthScheme::Bearer, Some(key)) => {
+                Some(("Authorization", format!("Bearer {}", key)))
+            }
+            (SttAuthScheme::Header { name }, Some(key)) => Some((name.as_str(), key.clone())),
+            _ => None,
+        };
+        ws::handshake_request(
+            self.build_ws_url()?.as_str(),
+            header.as_ref().map(|(name, value)| (*name, value.as_str())),
+        )
+    }
+
+    /// Encode PCM in the server's sample format
+    fn encode(&self, samples: &[i16]) -> Vec<u8> {
+        match self.encoding {
+            StreamingAudioEncoding::PcmS16le => {
+                samples.iter().flat_map(|s| s.to_le_bytes()).collect()
+            }
+            StreamingAudioEncoding::PcmF32le => samples
+                .iter()
+                .flat_map(|s| (*s as f32 / 32768.0).to_le_bytes())
+                .collect(),
+        }
+    }
+}
+
+/// Look up a dot-separated path ("segments.-1.text"). Numeric parts index
+/// arrays, negative ones from the end; an empty path is the value itself.
+pub fn json_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    if path.is_empty() {
+        return Some(value);
+    }
+    path.split('.').try_fold(value, |value, part| match value {
+        Value::Object(map) => map.get(part),
+        Value::Array(items) => {
+            let index: i64 = part.parse().ok()?;
+            let index = if index < 0 {
+                items.len().checked_sub(index.unsigned_abs() as usize)?
+            } else {
+                index as usize
+            };
+            items.get(index)
+        }
+        _ => None,
+    })
+}
+
+/// Truthy flag: `true`, a non-zero number or the string "true"
+fn is_truthy(value: Option<&Value>) -> bool {
+    match value {
+        Some(Value::Bool(b)) => *b,
+        Some(Value::Number(n)) => n.as_f64().is_some_and(|n| n != 0.0),
+        Some(Value::String(s)) => s.eq_ignore_ascii_case("true"),
+        _ => false,
+    }
+}
+
+/// Turns server messages into events through a [`MessageMapping`]
+#[derive(Debug)]
+struct SegmentTracker {
+    mapping: MessageMapping,
+    segment_id: u64,
+    /// Keys of final segments already committed
+    committed: HashSet<String>,
+    /// Text of the segments that are not final yet
+    partial: String,
+}
+
+impl SegmentTracker {
+    fn new(mapping: MessageMapping) -> Self {
+        Self {
+            mapping,
+            segment_id: 0,
+            committed: HashSet::new(),
+            partial: String::new(),
+        }
+    }
+
+    fn metadata(&self) -> TranscriptMetadata {
+        TranscriptMetadata {
+            segment_id: Some(self.segment_id),
+            ..Default::default()
+        }
+    }
+
+    fn message(&mut self, message: &Value, timestamp_ms: u64) -> Vec<TranscriptionEvent> {
+        if let Some(error) = self
+            .mapping
+            .error
+            .as_deref()
+            .and_then(|path| json_path(message, path))
+            .filter(|e| !e.is_null() && *e != &Value::Bool(false))
+        {
+            let text = error
+                .as_str()
+                .map_or_else(|| error.to_string(), str::to_string);
+            let detail = ProviderError::new(ProviderErrorKind::Unavailable, PROVIDER_ID, text);
+            return vec![TranscriptionEvent::Error {
+                message: detail.message.clone(),
+                detail: Some(detail),
+            }];
+        }
+
+        let segments: Vec<&Value> = match &self.mapping.segments {
+            Some(path) => match json_path(message, path).and_then(Value::as_array) {
+                Some(segments) => segments.iter().collect(),
+                // Status messages (e.g. "SERVER_READY") carry no segments
+                None => return Vec::new(),
+            },
+            None => vec![message],
+        };
+
+        let mut events = Vec::new();
+        let mut partials = Vec::new();
+        let mut has_text = false;
+        for segment in segments {
+            let Some(text) = json_path(segment, &self.mapping.text).and_then(Value::as_str) else {
+                continue;
+            };
+            has_text = true;
+            let text = text.trim();
+            let is_final = self
+                .mapping
+                .is_final
+                .as_deref()
+                .is_some_and(|path| is_truthy(json_path(segment, path)));
+            if !is_final {
+                if !text.is_empty() {
+                    partials.push(text);
+                }
+                continue;
+            }
+
+            let key = self
+                .mapping
+                .segment_key
+                .as_deref()
+                .and_then(|path| json_path(segment, path))
+                .map(Value::to_string);
+            if key.is_some_and(|key| !self.committed.insert(key)) || text.is_empty() {
+                continue;
+            }
+            events.push(TranscriptionEvent::Committed {
+                text: text.to_string(),
+                timestamp_ms,
+                metadata: self.metadata(),
+            });
+            self.segment_id += 1;
+        }
+        if !has_text {
+            return events;
+        }
+
+        let partial = partials.join(" ");
+        if partial != self.partial {
+            self.partial = partial;
+            if !self.partial.is_empty() {
+                events.push(TranscriptionEvent::Partial {
+                    text: self.partial.clone(),
+                    timestamp_ms,
+                    metadata: self.metadata(),
+                });
+            }
+        }
+        events
+    }
+
+    /// Commit the text that never became final
+    fn finish(&mut self, timestamp_ms: u64) -> Option<TranscriptionEvent> {
+        if self.partial.is_empty() {
+            return None;
+        }
+        let event = TranscriptionEvent::Committed {
+            text: std::mem::take(&mut self.partial),
+            timestamp_ms,
+            metadata: self.metadata(),
+        };
+        self.segment_id += 1;
+        Some(event)
+    }
+}
+
+/// Streaming STT client for any [`StreamingEndpoint`]
+pub struct StreamingSttProvider {
+    endpoint: StreamingEndpoint,
+    ws_tx: Arc<Mutex<Option<mpsc::Sender<AudioChunk>>>>,
+    event_tx: Arc<Mutex<Option<mpsc::Sender<TranscriptionEvent>>>>,
+    event_rx: Arc<Mutex<Option<mpsc::Receiver<TranscriptionEvent>>>>,
+    ws_task: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
+}
+
+impl StreamingSttProvider {
+    pub fn new(endpoint: StreamingEndpoint) -> Self {
+        Self {
+            endpoint,
+            ws_tx: Arc::new(Mutex::new(None)),
+            event_tx: Arc::new(Mutex::new(None)),
+            event_rx: Arc::new(Mutex::new(None)),
+            ws_task: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+#[async_trait]
+impl SttProvider for StreamingSttProvider {
+    async fn start_session(&mut self) -> Result<()> {
+        info!("Starting streaming STT session ({})", self.endpoint.url);
+        if !self.endpoint.is_configured() {
+            return Err(MurmurError::Stt(
+                "Streaming STT endpoint URL not configured".to_string(),
+            ));
+        }
+        let start_message = self.endpoint.start_message()?;
+
+        let (audio_tx, mut audio_rx) = mpsc::channel::<AudioChunk>(32);
+        *self.ws_tx.lock().await = Some(audio_tx);
+
+        let (event_tx, event_rx) = mpsc::channel::<TranscriptionEvent>(32);
+        *self.event_tx.lock().await = Some(event_tx.clone());
+        *self.event_rx.lock().await = Some(event_rx);
+
+        let ws_stream = ws::connect(PROVIDER_ID, self.endpoint.connect_request()?).await?;
+        info!("WebSocket connected to streaming STT server");
+
+        let (mut ws_write, mut ws_read) = ws_stream.split();
+        if let Some(message) = start_message {
+            ws_write
+                .send(Message::Text(message.into()))
+                .await
+                .map_err(|e| {
+                    MurmurError::from(ProviderError::new(
+                        ProviderErrorKind::Network,
+                        PROVIDER_ID,
+                        format!("Failed to send start message: {}", e),
+                    ))
+                })?;
+        }
+
+        let (closing_tx, mut closing_rx) = oneshot::channel::<()>();
+        // Events carry the timestamp of the latest audio sent
+        let audio_ms = Arc::new(AtomicU64::new(0));
+        let receiver_audio_ms = audio_ms.clone();
+        let endpoint = self.endpoint.clone();
+
+        let task = tokio::spawn(async move {
+            let event_tx_clone = event_tx.clone();
+            let mapping = endpoint.mapping.clone();
+            let receiver_task = tokio::spawn(async move {
+                let mut segments = SegmentTracker::new(mapping);
+                let mut closing = false;
+                let mut deadline = tokio::time::Instant::now() + CLOSE_TIMEOUT;
+                let idle = tokio::time::sleep(Duration::MAX);
+                tokio::pin!(idle);
+
+                loop {
+                    let msg = tokio::select! {
+                        biased;
+                        _ = &mut closing_rx, if !closing => {
+                            closing = true;
+                            deadline = tokio::time::Instant::now() + CLOSE_TIMEOUT;
+                            idle.as_mut().reset(tokio::time::Instant::now() + CLOSE_IDLE_TIMEOUT);
+                            continue;
+                        }
+                        _ = &mut idle, if closing => {
+                            debug!("Streaming STT server went quiet after the last audio");
+                            break;
+                        }
+                        msg = ws_read.next() => msg,
+                    };
+                    if closing {
+                        let next = (tokio::time::Instant::now() + CLOSE_IDLE_TIMEOUT).min(deadline);
+                        idle.as_mut().reset(next);
+                    }
+
+                    let timestamp_ms = receiver_audio_ms.load(Ordering::Relaxed);
+                    let events = match msg {
+                        Some(Ok(Message::Text(text))) => {
+                            debug!("Received message: {}", text);
+                            match serde_json::from_str::<Value>(&text) {
+                                Ok(message) => segments.message(&message, timestamp_ms),
+                                Err(e) => {
+                                    warn!("Failed to parse message: {} - {}", e, text);
+                                    Vec::new()
+                                }
+                            }
+                        }
+                        Some(Ok(Message::Close(_))) | None => {
+                            info!("WebSocket closed by server");
+                            break;
+                        }
+                        Some(Ok(_)) => {
+                            debug!("Received non-text message");
+                            Vec::new()
+                        }
+                        Some(Err(e)) => {
+                            error!("WebSocket error: {}", e);
+                            let message = format!("WebSocket error: {}", e);
+                            let event = TranscriptionEvent::Error {
+                                detail: Some(ProviderError::new(
+                                    ProviderErrorKind::Network,
+                                    PROVIDER_ID,
+                                    message.clone(),
+                                )),
+                                message,
+                            };
+                            let _ = event_tx_clone.send(event).await;
+                            break;
+                        }
+                    };
+
+                    for event in events {
+                        if let Err(e) = event_tx_clone.send(event).await {
+                            error!("Failed to send transcription event: {}", e);
+                        }
+                    }
+                }
+
+                if let Some(event) = segments.finish(receiver_audio_ms.load(Ordering::Relaxed)) {
+                    let _ = event_tx_clone.send(event).await;
+                }
+                debug!("WebSocket receiver task finished");
+            });
+
+            while let Some(chunk) = audio_rx.recv().await {
+                audio_ms.store(chunk.timestamp_ms, Ordering::Relaxed);
+                let bytes = endpoint.encode(&chunk.data);
+                if let Err(e) = ws_write.send(Message::Binary(bytes.into())).await {
+                    error!("Failed to send audio chunk: {}", e);
+                    break;
+                }
+            }
+
+            debug!("Audio sender finished, waiting for the last results");
+            let _ = closing_tx.send(());
+            if let Some(end_message) = &endpoint.end_message {
+                if let Err(e) = ws_write.send(end_message.to_message()).await {
+                    warn!("Failed to send end message: {}", e);
+                }
+            }
+
+            // The receiver bounds its own wait with CLOSE_TIMEOUT
+            if let Err(e) = receiver_task.await {
+                warn!("Receiver task error: {}", e);
+            }
+
+            let _ = ws_write.close().await;
+            info!("WebSocket task finished");
+        });
+
+        *self.ws_task.lock().await = Some(task);
+
+        Ok(())
+    }
+
+    async fn send_audio(&mut self, chunk: AudioChunk) -> Result<()> {
+        let tx_lock = self.ws_tx.lock().await;
+        if let Some(tx) = tx_lock.as_ref() {
+            tx.send(chunk)
+                .await
+                .map_err(|e| MurmurError::Stt(format!("Failed to send audio chunk: {}", e)))?;
+            Ok(())
+        } else {
+            Err(MurmurError::Stt("Session not started".to_string()))
+        }
+    }
+
+    async fn stop_session(&mut self) -> Result<()> {
+        info!("Stopping streaming STT session");
+
+        // Closing the audio channel makes the task send the end message
+        *self.ws_tx.lock().await = None;
+
+        if let Some(task) = self.ws_task.lock().await.take() {
+            let _ = task.await;
+        }
+
+        info!("Streaming STT session stopped");
+        Ok(())
+    }
+
+    async fn subscribe_events(&self) -> mpsc::Receiver<TranscriptionEvent> {
+        let mut rx_lock = self.event_rx.lock().await;
+        rx_lock
+            .take()
+            .expect("subscribe_events called multiple times")
+    }
+
+    fn input_format(&self) -> AudioFormat {
+        AudioFormat::mono(self.endpoint.sample_rate)
+    }
+
+    async fn health_check(&self) -> Result<SttHealth> {
+        // Servers differ in what they say before audio arrives, so a
+        // successful handshake is all that is checked
+        let started = Instant::now();
+        let mut ws_stream = tokio::time::timeout(
+            HEALTH_CHECK_TIMEOUT,
+            ws::connect(PROVIDER_ID, self.endpoint.connect_request()?),
+        )
+        .await
+        .map_err(|_| ws::health_check_timed_out(PROVIDER_ID))??;
+        let latency_ms = started.elapsed().as_millis() as u64;
+        let _ = ws_stream.close(None).await;
+        Ok(SttHealth { latency_ms })
+    }
+
+    fn capabilities(&self) -> SttCapabilities {
+        SttCapabilities {
+            mode: SttMode::Streaming,
+            partial_results: true,
+            // A pinned language is all the server will be asked for
+            languages: self.endpoint.language.iter().cloned().collect(),
+            word_timestamps: false,
+            vocabulary_hints: false,
+            diarization: false,
+            local: is_loopback_url(&self.endpoint.url),
+            requires_api_key: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn describe(events: Vec<TranscriptionEvent>) -> Vec<String> {
+        events
+            .into_iter()
+            .map(|event| match event {
+                TranscriptionEvent::Partial { text, metadata, .. } => {
+                    format!("partial {} {}", metadata.segment_id.unwrap(), text)
+                }
+                TranscriptionEvent::Committed { text, metadata, .. } => {
+                    format!("committed {} {}", metadata.segment_id.unwrap(), text)
+                }
+                TranscriptionEvent::Error { message, .. } => format!("error {}", message),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_json_path() {
+        let value = serde_json::json!({
+            "result": {"alternatives": [{"transcript": "a"}, {"transcript": "b"}]},
+            "segments": [1, 2, 3]
+        });
+        assert_eq!(
+            json_path(&value, "result.alternatives.0.transcript"),
+            Some(&Value::from("a"))
+        );
+        assert_eq!(
+            json_path(&value, "result.alternatives.-1.transcript"),
+            Some(&Value::from("b"))
+        );
+        assert_eq!(json_path(&value, "segments.-4"), None);
+        assert_eq!(json_path(&value, "segments.x"), None);
+        assert_eq!(json_path(&value, "missing.path"), None);
+        assert_eq!(json_path(&value, ""), Some(&value));
+    }
+
+    #[test]
+    fn test_json_protocol_messages() {
+        let endpoint = StreamingEndpoint::preset(StreamingSttProtocol::Json);
+        let mut segments = SegmentTracker::new(endpoint.mapping);
+        let mut feed = |json: Value| describe(segments.message(&json, 0));
+
+        assert_eq!(
+            feed(serde_json::json!({"text": "hel", "is_final": false})),
+            ["partial 0 hel"]
+        );
+        assert!(feed(serde_json::json!({"text": "hel", "is_final": false})).is_empty());
+        assert_eq!(
+            feed(serde_json::json!({"text": "hello", "is_final": true})),
+            ["committed 0 hello"]
+        );
+        // Without a key, repeated text is a new segment
+        assert_eq!(
+            feed(serde_json::json!({"text": "hello", "is_final": true})),
+            ["committed 1 hello"]
+        );
+        assert_eq!(
+            feed(serde_json::json!({"error": "model not loaded"})),
+            ["error model not loaded"]
+        );
+        assert!(feed(serde_json::json!({"status": "ready", "error": null})).is_empty());
+    }
+
+    #[test]
+    fn test_whisper_live_segments_commit_once() {
+        let endpoint = StreamingEndpoint::preset(StreamingSttProtocol::WhisperLive);
+        let mut segments = SegmentTracker::new(endpoint.mapping);
+
+        let events = segments.message(
+            &serde_json::json!({"uid": "x", "message": "SERVER_READY", "backend": "faster_whisper"}),
+            0,
+        );
+        assert!(events.is_empty());
+
+        let events = segments.message(
+            &serde_json::json!({"uid": "x", "segments": [
+                {"start": "0.000", "end": "1.000", "text": " Hello there.", "completed": true},
+                {"start": "1.000", "end": "1.500", "text": " How", "completed": false}
+            ]}),
+            1500,
+        );
+        assert_eq!(
+            describe(events),
+            ["committed 0 Hello there.", "partial 1 How"]
+        );
+
+        let events = segments.message(
+            &serde_json::json!({"uid": "x", "segments": [
+                {"start": "0.000", "end": "1.000", "text": " Hello there.", "completed": true},
+                {"start": "1.000", "end": "2.000", "text": " How are you?", "completed": true}
+            ]}),
+            2000,
+        );
+        assert_eq!(describe(events), ["committed 1 How are you?"]);
+        assert!(segments.finish(2000).is_none());
+    }
+
+    #[test]
+    fn test_cumulative_text_is_committed_at_the_end() {
+        let endpoint = StreamingEndpoint::preset(StreamingSttProtocol::FasterWhisper);
+        let mut segments = SegmentTracker::new(endpoint.mapping);
+        segments.message(&serde_json::json!({"text": "one"}), 100);
+        let events = segments.message(&serde_json::json!({"text": "one two"}), 200);
+        assert_eq!(describe(events), ["partial 0 one two"]);
+        assert_eq!(
+            describe(segments.finish(300).into_iter().collect()),
+            ["committed 0 one two"]
+        );
+    }
+
+    #[test]
+    fn test_overrides_replace_preset_settings() {
+        let config = StreamingSttConfig {
+            url: Some("ws://localhost:9000/stream".to_string()),
+            model: Some("".to_string()),
+            language: Some("de".to_string()),
+            sample_rate: Some(24000),
+            encoding: Some(StreamingAudioEncoding::PcmF32le),
+            end_message: Some("EOS".to_string()),
+            end_message_binary: Some(true),
+            text_path: Some("result.text".to_string()),
+            final_path: Some("".to_string()),
+            ..Default::default()
+        };
+        let endpoint =
+            StreamingEndpoint::preset(StreamingSttProtocol::Json).with_overrides(&config);
+        assert!(endpoint.is_configured());
+        assert_eq!(endpoint.model, None);
+        assert_eq!(endpoint.language.as_deref(), Some("de"));
+        assert_eq!(endpoint.sample_rate, 24000);
+        assert_eq!(
+            endpoint.end_message,
+            Some(EndMessage::Binary(b"EOS".to_vec()))
+        );
+        assert_eq!(endpoint.mapping.text, "result.text");
+        assert_eq!(endpoint.mapping.is_final, None);
+        assert_eq!(endpoint.mapping.error.as_deref(), Some("error"));
+
+        let provider = StreamingSttProvider::new(endpoint);
+        assert_eq!(provider.input_format(), AudioFormat::mono(24000));
+        let caps = provider.capabilities();
+        assert!(caps.local);
+        assert!(!caps.requires_api_key);
+        assert!(caps.supports_language("de"));
+    }
+
+    #[test]
+    fn test_start_message_and_query_placeholders() {
+        let endpoint = StreamingEndpoint::preset(StreamingSttProtocol::WhisperLive);
+        let start: Value =
+            serde_json::from_str(&endpoint.start_message().unwrap().unwrap()).unwrap();
+        assert_eq!(start["model"], "small");
+        assert_eq!(start["language"], Value::Null);
+        assert!(start["uid"].as_str().unwrap().starts_with("murmur-"));
+
+        let mut endpoint = StreamingEndpoint::preset(StreamingSttProtocol::FasterWhisper);
+        endpoint.url = "ws://localhost:8000/v1/audio/transcriptions".to_string();
+        endpoint.language = Some("en".to_string());
+        assert_eq!(
+            endpoint.build_ws_url().unwrap().as_str(),
+            "ws://localhost:8000/v1/audio/transcriptions?language=en&response_format=json"
+        );
+
+        endpoint.start_message = Some("{\"model\": {model".to_string());
+        assert!(endpoint.start_message().is_err());
+    }
+
+    #[test]
+    fn test_audio_encoding() {
+        let mut endpoint = StreamingEndpoint::preset(StreamingSttProtocol::Json);
+        assert_eq!(endpoint.encode(&[1, -2]), [1, 0, 0xfe, 0xff]);
+        endpoint.encoding = StreamingAudioEncoding::PcmF32le;
+        let bytes = endpoint.encode(&[16384, i16::MIN]);
+        assert_eq!(f32::from_le_bytes(bytes[0..4].try_into().unwrap()), 0.5);
+        assert_eq!(f32::from_le_bytes(bytes[4..8].try_into().unwrap()), -1.0);
+    }
+}
@@ -0,0 +1,406 @@
+//! Response parsing and connection testing shared by the Whisper-compatible
+//! REST providers
+
+use lt_core::config::SttAudioEncoding;
+use lt_core::error::{ProviderError, ProviderErrorKind, Result};
+use lt_core::stt::{SessionMetrics, SttHealth, TranscriptMetadata, TranscriptWord};
+use reqwest::multipart::Form;
+use serde::Deserialize;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use crate::chunker;
+use crate::hallucination::HallucinationFilter;
+use crate::stitch;
+
+/// Length of the silent clip uploaded as a connection test
+const HEALTH_CHECK_CLIP_MS: u64 = 500;
+/// Upper bound for a connection test round trip
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(15);
+/// Prompt budget of Whisper models (half the 448-token decoder context)
+const MAX_PROMPT_TOKENS: usize = 224;
+
+/// Whisper transcription response.
+///
+/// Plain `json` responses only carry `text`; `verbose_json` adds the detected
+/// language, segments (with `avg_logprob`) and, when requested through
+/// `timestamp_granularities[]=word`, word timings.
+#[derive(Debug, Deserialize)]
+pub(crate) struct WhisperResponse {
+    text: String,
+    #[serde(default)]
+    language: Option<String>,
+    #[serde(default)]
+    segments: Vec<WhisperSegment>,
+    #[serde(default)]
+    words: Vec<WhisperWord>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WhisperSegment {
+    start: f64,
+    end: f64,
+    #[serde(default)]
+    avg_logprob: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WhisperWord {
+    word: String,
+    start: f64,
+    end: f64,
+    /// Not part of the OpenAI schema, but returned by faster-whisper based servers
+    #[serde(default)]
+    probability: Option<f64>,
+}
+
+/// Text and detail for one transcribed chunk
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ChunkTranscript {
+    pub text: String,
+    pub metadata: TranscriptMetadata,
+}
+
+impl ChunkTranscript {
+    /// Drop what this chunk repeats from the previous one when its audio
+    /// began with overlap; `seam_ms` is where the new audio starts.
+    pub(crate) fn trim_overlap(self, previous_text: &str, seam_ms: Option<u64>) -> Self {
+        let Some(seam_ms) = seam_ms else {
+            return self;
+        };
+        let text = stitch::dedupe_seam(previous_text, &self.text).to_string();
+        let mut metadata = self.metadata;
+        // Words centred before the seam were reported with the previous chunk
+        metadata
+            .words
+            .retain(|w| (w.start_ms + w.end_ms) / 2 >= seam_ms);
+        Self { text, metadata }
+    }
+
+    /// Remove phantom phrases and repetition loops, counting them in
+    /// `metrics`. Words no longer in the text are dropped from the metadata.
+    pub(crate) fn filter_hallucinations(
+        self,
+        filter: &HallucinationFilter,
+        metrics: &mut SessionMetrics,
+    ) -> Self {
+        let filtered = filter.apply(&self.text);
+        if filtered.removed == 0 {
+            return self;
+        }
+        metrics.filtered_hallucinations += filtered.removed;
+
+        // Walk the words in order, keeping those that line up with the
+        // remaining text
+        let kept = stitch::tokenize(&filtered.text);
+        let mut kept = kept.iter().filter(|t| !t.key.is_empty()).peekable();
+        let mut metadata = self.metadata;
+        metadata.words.retain(|word| {
+            let key = stitch::tokenize(&word.text)
+                .into_iter()
+                .map(|t| t.key)
+                .collect::<String>();
+            if kept.peek().is_some_and(|t| t.key == key) {
+                kept.next();
+                true
+            } else {
+                false
+            }
+        });
+
+        Self {
+            text: filtered.text,
+            metadata,
+        }
+    }
+}
+
+/// Ask for `verbose_json` with word and segment timestamps
+pub(crate) fn request_verbose_json(form: Form) -> Form {
+    form.text("response_format", "verbose_json")
+        .text("timestamp_granularities[]", "word")
+        .text("timestamp_granularities[]", "segment")
+}
+
+/// Add the `prompt` field, if there is one
+pub(crate) fn with_prompt(form: Form, prompt: Option<String>) -> Form {
+    match prompt {
+        Some(prompt) => form.text("prompt", prompt),
+        None => form,
+    }
+}
+
+/// Rough token count: about four characters per token for ASCII text, one
+/// per character otherwise (errs on the high side for most scripts)
+fn estimate_tokens(text: &str) -> usize {
+    let ascii = text.chars().filter(char::is_ascii).count();
+    ascii.div_ceil(4) + (text.chars().count() - ascii)
+}
+
+/// `prompt` for the next chunk: the user's style prompt followed by the end
+/// of the text transcribed so far, together within Whisper's prompt budget.
+///
+/// Whisper continues the prompt's spelling, capitalization and language,
+/// so carrying the previous text keeps long dictations consistent.
+pub(crate) fn context_prompt(style_prompt: Option<&str>, previous_text: &str) -> Option<String> {
+    let mut budget = MAX_PROMPT_TOKENS;
+
+    // The style prompt comes first and keeps its beginning if too long
+    let mut style = Vec::new();
+    for word in style_prompt.unwrap_or_default().split_whitespace() {
+        let cost = estimate_tokens(word) + 1;
+        if cost > budget {
+            break;
+        }
+        budget -= cost;
+        style.push(word);
+    }
+
+    // The transcript keeps its end, nearest to the new audio
+    let mut tail = Vec::new();
+    for word in previous_text.split_whitespace().rev() {
+        let cost = estimate_tokens(word) + 1;
+        if cost > budget {
+            // Scripts without spaces arrive as one long "word"; keep its end
+            if tail.is_empty() {
+                let start = word
+                    .char_indices()
+                    .map(|(i, _)| i)
+                    .find(|&i| estimate_tokens(&word[i..]) < budget);
+                tail.extend(start.map(|i| &word[i..]));
+            }
+            break;
+        }
+        budget -= cost;
+        tail.push(word);
+    }
+    tail.reverse();
+
+    let prompt = [style.join(" "), tail.join(" ")]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    (!prompt.is_empty()).then_some(prompt)
+}
+
+impl WhisperResponse {
+    /// Convert to a chunk transcript whose word timings are shifted by
+    /// `offset_ms` (the chunk's position in the session audio).
+    ///
+    /// Whisper does not score individual words, so words without their own
+    /// probability inherit the confidence of the segment they fall in.
+    pub(crate) fn into_chunk_transcript(self, offset_ms: u64) -> ChunkTranscript {
+        let to_ms = |secs: f64| offset_ms + (secs.max(0.0) * 1000.0).round() as u64;
+
+        let words = self
+            .words
+            .iter()
+            .filter(|w| !w.word.trim().is_empty())
+            .map(|w| {
+                let confidence = w.probability.or_else(|| {
+                    let mid = (w.start + w.end) / 2.0;
+                    self.segments
+                        .iter()
+                        .find(|s| mid >= s.start && mid <= s.end)
+                        .and_then(|s| s.avg_logprob)
+                        .map(f64::exp)
+                });
+                TranscriptWord {
+                    text: w.word.trim().to_string(),
+                    start_ms: to_ms(w.start),
+                    end_ms: to_ms(w.end),
+                    confidence: confidence.map(|c| c.clamp(0.0, 1.0) as f32),
+                }
+            })
+            .collect();
+
+        ChunkTranscript {
+            text: self.text,
+            metadata: TranscriptMetadata {
+                words,
+                language: self.language.filter(|l| !l.is_empty()),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Upload a short silent clip through `transcribe` and time the round trip.
+///
+/// Whatever text comes back is ignored; only a successful response counts.
+pub(crate) async fn probe<F, Fut>(
+    provider: &str,
+    encoding: SttAudioEncoding,
+    transcribe: F,
+) -> Result<SttHealth>
+where
+    F: FnOnce(Vec<u8>) -> Fut,
+    Fut: Future<Output = Result<ChunkTranscript>>,
+{
+    let clip = chunker::silent_clip(encoding, HEALTH_CHECK_CLIP_MS)?;
+    let started = Instant::now();
+    match tokio::time::timeout(HEALTH_CHECK_TIMEOUT, transcribe(clip)).await {
+        Ok(result) => {
+            result?;
+            Ok(SttHealth {
+                latency_ms: started.elapsed().as_millis() as u64,
+            })
+        }
+        Err(_) => Err(ProviderError::new(
+            ProviderErrorKind::Timeout,
+            provider,
+            format!(
+                "Connection test timed out after {}s",
+                HEALTH_CHECK_TIMEOUT.as_secs()
+            ),
+        )
+        .into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_json_has_no_detail() {
+        let response: WhisperResponse = serde_json::from_str(r#"{"text":"hello"}"#).unwrap();
+        let transcript = response.into_chunk_transcript(0);
+        assert_eq!(transcript.text, "hello");
+        assert!(transcript.metadata.is_empty());
+    }
+
+    #[test]
+    fn test_verbose_json_words_are_offset_and_scored() {
+        let json = r#"{
+            "task": "transcribe",
+            "language": "english",
+            "duration": 2.0,
+            "text": "Hello there.",
+            "segments": [
+                {"id": 0, "start": 0.0, "end": 2.0, "text": "Hello there.", "avg_logprob": -0.2}
+            ],
+            "words": [
+                {"word": "Hello", "start": 0.1, "end": 0.5},
+                {"word": " there.", "start": 0.6, "end": 1.0, "probability": 0.4}
+            ]
+        }"#;
+        let response: WhisperResponse = serde_json::from_str(json).unwrap();
+        let transcript = response.into_chunk_transcript(4000);
+
+        let meta = transcript.metadata;
+        assert_eq!(meta.language.as_deref(), Some("english"));
+        assert_eq!(meta.words.len(), 2);
+        assert_eq!(meta.words[0].text, "Hello");
+        assert_eq!(meta.words[0].start_ms, 4100);
+        assert_eq!(meta.words[0].end_ms, 4500);
+        let inherited = meta.words[0].confidence.unwrap();
+        assert!((inherited - (-0.2f32).exp()).abs() < 1e-4);
+        assert_eq!(meta.words[1].text, "there.");
+        assert_eq!(meta.words[1].confidence, Some(0.4));
+    }
+
+    #[test]
+    fn test_trim_overlap_drops_repeated_words() {
+        let json = r#"{
+            "text": "on Friday after review",
+            "words": [
+                {"word": "on", "start": 0.0, "end": 0.1},
+                {"word": "Friday", "start": 0.1, "end": 0.28},
+                {"word": "after", "start": 0.4, "end": 0.6},
+                {"word": "review", "start": 0.6, "end": 1.0}
+            ]
+        }"#;
+        let response: WhisperResponse = serde_json::from_str(json).unwrap();
+        // Chunk audio starts at 4100 ms; new audio begins at 4400 ms
+        let transcript = response
+            .into_chunk_transcript(4100)
+            .trim_overlap("ship it on Friday", Some(4400));
+
+        assert_eq!(transcript.text, "after review");
+        let words: Vec<_> = transcript
+            .metadata
+            .words
+            .iter()
+            .map(|w| w.text.as_str())
+            .collect();
+        assert_eq!(words, ["after", "review"]);
+    }
+
+    #[test]
+    fn test_filter_hallucinations_drops_text_words_and_counts() {
+        let json = r#"{
+            "text": "Call me back. Thank you for watching.",
+            "words": [
+                {"word": "Call", "start": 0.0, "end": 0.2},
+                {"word": "me", "start": 0.2, "end": 0.3},
+                {"word": "back", "start": 0.3, "end": 0.6},
+                {"word": "Thank", "start": 2.0, "end": 2.2},
+                {"word": "you", "start": 2.2, "end": 2.3},
+                {"word": "for", "start": 2.3, "end": 2.4},
+                {"word": "watching", "start": 2.4, "end": 2.9}
+            ]
+        }"#;
+        let response: WhisperResponse = serde_json::from_str(json).unwrap();
+        let mut metrics = SessionMetrics::default();
+        let transcript = response
+            .into_chunk_transcript(0)
+            .filter_hallucinations(&HallucinationFilter::default(), &mut metrics);
+
+        assert_eq!(transcript.text, "Call me back.");
+        let words: Vec<_> = transcript
+            .metadata
+            .words
+            .iter()
+            .map(|w| w.text.as_str())
+            .collect();
+        assert_eq!(words, ["Call", "me", "back"]);
+        assert_eq!(metrics.filtered_hallucinations, 1);
+    }
+
+    #[test]
+    fn test_context_prompt_combines_style_and_tail() {
+        assert_eq!(context_prompt(None, ""), None);
+        assert_eq!(context_prompt(Some("  "), " "), None);
+        assert_eq!(
+            context_prompt(Some("Use British spelling."), "We met at the centre."),
+            Some("Use British spelling. We met at the centre.".to_string())
+        );
+        assert_eq!(
+            context_prompt(None, "  first chunk  "),
+            Some("first chunk".to_string())
+        );
+    }
+
+    #[test]
+    fn test_context_prompt_keeps_recent_text_within_budget() {
+        let previous: Vec<String> = (0..400).map(|i| format!("w{}", i)).collect();
+        let prompt = context_prompt(Some("Style."), &previous.join(" ")).unwrap();
+
+        assert!(prompt.starts_with("Style. "));
+        assert!(prompt.ends_with(" w399"));
+        assert!(!prompt.contains(" w0 "));
+        assert!(estimate_tokens(&prompt) <= MAX_PROMPT_TOKENS);
+
+        // Non-Latin text is budgeted per character
+        for previous in [
+            "日本語の文章です".repeat(60),
+            vec!["日本語の文章です"; 60].join(" "),
+        ] {
+            let prompt = context_prompt(None, &previous).unwrap();
+            assert!(prompt.ends_with("文章です"));
+            assert!(estimate_tokens(&prompt) <= MAX_PROMPT_TOKENS);
+        }
+    }
+
+    #[test]
+    fn test_trim_overlap_without_seam_is_noop() {
+        let transcript = ChunkTranscript {
+            text: "on Friday".to_string(),
+            metadata: TranscriptMetadata::default(),
+        }
+        .trim_overlap("on Friday", None);
+        assert_eq!(transcript.text, "on Friday");
+    }
+}
@@ -0,0 +1,405 @@
+//! Handshake, connection and error helpers shared by the WebSocket providers
+//!
+//! Connections follow the `[network]` settings like the shared HTTP client:
+//! they are tunnelled through the proxy (HTTP CONNECT or SOCKS5) unless
+//! `no_proxy` lists the host, and TLS trusts the `ca_bundle` roots.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use lt_core::config::NetworkConfig;
+use lt_core::error::{MurmurError, ProviderError, ProviderErrorKind, Result};
+use std::io;
+use std::net::IpAddr;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::{
+    self,
+    client::IntoClientRequest,
+    handshake::client::Request,
+    http::{HeaderName, Uri},
+};
+use tokio_tungstenite::Connector;
+use url::Url;
+
+/// Upper bound for each step of a connection test
+pub(crate) const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub(crate) type WsStream =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+/// Authenticated WebSocket handshake request; `auth` is a header name and
+/// value
+pub(crate) fn handshake_request(url: &str, auth: Option<(&str, &str)>) -> Result<Request> {
+    let mut request = url
+        .into_client_request()
+        .map_err(|e| MurmurError::Stt(format!("Failed to build request: {}", e)))?;
+    if let Some((name, value)) = auth {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| MurmurError::Stt(format!("Invalid auth header name: {}", name)))?;
+        request.headers_mut().insert(
+            name,
+            value
+                .parse()
+                .map_err(|_| MurmurError::Stt("Invalid API key header value".to_string()))?,
+        );
+    }
+    Ok(request)
+}
+
+/// Longest proxy response head read while opening a tunnel
+const MAX_PROXY_RESPONSE: usize = 8 * 1024;
+
+/// Open the WebSocket for `request` with the `[network]` settings,
+/// classifying a failed handshake
+pub(crate) async fn connect(
+    provider: &str,
+    request: Request,
+) -> std::result::Result<WsStream, ProviderError> {
+    let network = lt_http::network();
+    let connector = tls_connector(&network)
+        .map_err(|e| ProviderError::new(ProviderErrorKind::Network, provider, e.to_string()))?;
+    let connect_timeout = Duration::from_secs(
+        network
+            .connect_timeout_secs
+            .unwrap_or(lt_http::DEFAULT_CONNECT_TIMEOUT_SECS),
+    );
+    let stream = tokio::time::timeout(connect_timeout, open_stream(&network, request.uri()))
+        .await
+        .unwrap_or_else(|_| Err(io::Error::new(io::ErrorKind::TimedOut, "connect timed out")))
+        .map_err(|e| classify_ws_connect_error(provider, &tungstenite::Error::Io(e)))?;
+    tokio_tungstenite::client_async_tls_with_config(request, stream, None, connector)
+        .await
+        .map(|(stream, _)| stream)
+        .map_err(|e| classify_ws_connect_error(provider, &e))
+}
+
+/// TLS connector that also trusts the `ca_bundle` roots (or only them);
+/// None keeps the defaults
+fn tls_connector(network: &NetworkConfig) -> Result<Option<Connector>> {
+    let Some(path) = network
+        .ca_bundle
+        .as_deref()
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+    else {
+        return Ok(None);
+    };
+    let pem = lt_http::read_ca_bundle(path)?;
+    let pem = String::from_utf8_lossy(&pem);
+
+    let mut builder = native_tls::TlsConnector::builder();
+    builder.disable_built_in_roots(network.ca_bundle_only.unwrap_or(false));
+    let mut found = false;
+    for block in pem
+        .split_inclusive("-----END CERTIFICATE-----")
+        .filter(|block| block.contains("-----BEGIN CERTIFICATE-----"))
+    {
+        let cert = native_tls::Certificate::from_pem(block.as_bytes())
+            .map_err(|e| MurmurError::Config(format!("Invalid CA bundle {}: {}", path, e)))?;
+        builder.add_root_certificate(cert);
+        found = true;
+    }
+    if !found {
+        return Err(MurmurError::Config(format!(
+            "No certificates found in CA bundle {}",
+            path
+        )));
+    }
+    let connector = builder
+        .build()
+        .map_err(|e| MurmurError::Config(format!("Failed to build TLS connector: {}", e)))?;
+    Ok(Some(Connector::NativeTls(connector)))
+}
+
+/// TCP stream to the host of `uri`, tunnelled through its proxy if it has one
+async fn open_stream(network: &NetworkConfig, uri: &Uri) -> io::Result<TcpStream> {
+    let secure = uri.scheme_str() == Some("wss");
+    let host = uri
+        .host()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "URL has no host"))?;
+    let port = uri.port_u16().unwrap_or(if secure { 443 } else { 80 });
+    let Some(proxy) = lt_http::proxy_for(network, host, secure) else {
+        return TcpStream::connect((unbracket(host), port)).await;
+    };
+
+    let proxy = Url::parse(&proxy).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid proxy URL '{}': {}", proxy, e),
+        )
+    })?;
+    let proxy_host = proxy.host_str().unwrap_or_default();
+    let proxy_port = proxy.port_or_known_default().unwrap_or(1080);
+    let mut stream = TcpStream::connect((unbracket(proxy_host), proxy_port)).await?;
+    match proxy.scheme() {
+        "http" => http_connect(&mut stream, host, port, &proxy).await?,
+        "socks5" => socks5_connect(&mut stream, host, port, &proxy, false).await?,
+        "socks5h" => socks5_connect(&mut stream, host, port, &proxy, true).await?,
+        scheme => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Unsupported proxy scheme '{}'", scheme),
+            ))
+        }
+    }
+    Ok(stream)
+}
+
+/// Open a tunnel to `host:port` with an HTTP CONNECT request
+async fn http_connect(
+    stream: &mut TcpStream,
+    host: &str,
+    port: u16,
+    proxy: &Url,
+) -> io::Result<()> {
+    let authority = format!("{}:{}", bracket(host), port);
+    let mut head = format!("CONNECT {authority} HTTP/1.1\r\nHost: {authority}\r\n");
+    if !proxy.username().is_empty() {
+        let credentials = format!("{}:{}", proxy.username(), proxy.password().unwrap_or(""));
+        head.push_str(&format!(
+            "Proxy-Authorization: Basic {}\r\n",
+            BASE64.encode(credentials)
+        ));
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes()).await?;
+
+    // Byte by byte, so nothing after the response head is consumed
+    let mut response = Vec::new();
+    while !response.ends_with(b"\r\n\r\n") {
+        if response.len() >= MAX_PROXY_RESPONSE {
+            return Err(io::Error::other("Proxy response too long"));
+        }
+        response.push(stream.read_u8().await?);
+    }
+    let response = String::from_utf8_lossy(&response);
+    let status_line = response.lines().next().unwrap_or_default();
+    let status = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse::<u16>().ok());
+    match status {
+        Some(200..=299) => Ok(()),
+        _ => Err(io::Error::other(format!(
+            "Proxy refused the tunnel: {}",
+            status_line
+        ))),
+    }
+}
+
+/// Open a tunnel to `host:port` through a SOCKS5 proxy; with `remote_dns`
+/// (socks5h) the proxy resolves the host name
+async fn socks5_connect(
+    stream: &mut TcpStream,
+    host: &str,
+    port: u16,
+    proxy: &Url,
+    remote_dns: bool,
+) -> io::Result<()> {
+    let credentials =
+        (!proxy.username().is_empty()).then(|| (proxy.username(), proxy.password().unwrap_or("")));
+    // No authentication, or username/password
+    let method = if credentials.is_some() { 2 } else { 0 };
+    stream.write_all(&[5, 1, method]).await?;
+    let mut reply = [0u8; 2];
+    stream.read_exact(&mut reply).await?;
+    if reply != [5, method] {
+        return Err(io::Error::other(
+            "SOCKS proxy refused the authentication method",
+        ));
+    }
+    if let Some((username, password)) = credentials {
+        let mut auth = vec![1];
+        for field in [username, password] {
+            auth.push(socks_len(field)?);
+            auth.extend_from_slice(field.as_bytes());
+        }
+        stream.write_all(&auth).await?;
+        stream.read_exact(&mut reply).await?;
+        if reply[1] != 0 {
+            return Err(io::Error::other("SOCKS proxy rejected the credentials"));
+        }
+    }
+
+    let host = unbracket(host);
+    let address = match host.parse::<IpAddr>() {
+        Ok(ip) => Some(ip),
+        Err(_) if remote_dns => None,
+        Err(_) => Some(
+            tokio::net::lookup_host((host, port))
+                .await?
+                .next()
+                .ok_or_else(|| io::Error::other(format!("No address for {}", host)))?
+                .ip(),
+        ),
+    };
+    let mut request = vec![5, 1, 0];
+    match address {
+        Some(IpAddr::V4(ip)) => {
+            request.push(1);
+            request.extend_from_slice(&ip.octets());
+        }
+        Some(IpAddr::V6(ip)) => {
+            request.push(4);
+            request.extend_from_slice(&ip.octets());
+        }
+        None => {
+            request.push(3);
+            request.push(socks_len(host)?);
+            request.extend_from_slice(host.as_bytes());
+        }
+    }
+    request.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&request).await?;
+
+    let mut head = [0u8; 4];
+    stream.read_exact(&mut head).await?;
+    if head[1] != 0 {
+        return Err(io::Error::other(format!(
+            "SOCKS proxy could not connect to {} (reply {})",
+            host, head[1]
+        )));
+    }
+    // Skip the address the proxy bound, and its port
+    let bound_len = match head[3] {
+        1 => 4,
+        4 => 16,
+        3 => usize::from(stream.read_u8().await?),
+        _ => return Err(io::Error::other("Invalid SOCKS proxy reply")),
+    };
+    let mut bound = vec![0u8; bound_len + 2];
+    stream.read_exact(&mut bound).await?;
+    Ok(())
+}
+
+/// Length prefix of a SOCKS field, which holds at most 255 bytes
+fn socks_len(field: &str) -> io::Result<u8> {
+    u8::try_from(field.len()).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "SOCKS proxy field longer than 255 bytes",
+        )
+    })
+}
+
+/// IPv6 literal without its URL brackets
+fn unbracket(host: &str) -> &str {
+    host.trim_start_matches('[').trim_end_matches(']')
+}
+
+/// Host as written in an authority, IPv6 literals in brackets
+fn bracket(host: &str) -> String {
+    let host = unbracket(host);
+    if host.contains(':') {
+        format!("[{}]", host)
+    } else {
+        host.to_string()
+    }
+}
+
+/// Classify a WebSocket handshake failure
+pub(crate) fn classify_ws_connect_error(provider: &str, err: &tungstenite::Error) -> ProviderError {
+    match err {
+        tungstenite::Error::Http(response) => {
+            let body = response
+                .body()
+                .as_ref()
+                .map(|b| String::from_utf8_lossy(b).to_string())
+                .unwrap_or_default();
+            ProviderError::from_http_status(provider, response.status().as_u16(), None, &body)
+        }
+        _ => ProviderError::new(
+            ProviderErrorKind::Network,
+            provider,
+            format!("WebSocket connection failed: {}", err),
+        ),
+    }
+}
+
+/// Error for a connection test step that exceeded [`HEALTH_CHECK_TIMEOUT`]
+pub(crate) fn health_check_timed_out(provider: &str) -> MurmurError {
+    ProviderError::new(
+        ProviderErrorKind::Timeout,
+        provider,
+        "Connection test timed out",
+    )
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_tungstenite::tungstenite::http::Response;
+
+    #[test]
+    fn test_classify_ws_connect_error() {
+        let response = Response::builder()
+            .status(401)
+            .body(Some(b"invalid key".to_vec()))
+            .unwrap();
+        let err =
+            classify_ws_connect_error("deepgram", &tungstenite::Error::Http(Box::new(response)));
+        assert_eq!(err.kind, ProviderErrorKind::Auth);
+        assert_eq!(err.provider, "deepgram");
+
+        let err = classify_ws_connect_error("assemblyai", &tungstenite::Error::ConnectionClosed);
+        assert_eq!(err.kind, ProviderErrorKind::Network);
+        assert!(err.retryable);
+    }
+
+    #[test]
+    fn test_tls_connector_loads_ca_bundle() {
+        // Self-signed P-256 certificate, only parsed, never connected to
+        let ca = "-----BEGIN CERTIFICATE-----
+MIIBijCCAS+gAwIBAgIUFWKbYv6grYoHW/woxBUXXlXWa2AwCgYIKoZIzj0EAwIw
+GTEXMBUGA1UEAwwOTXVybXVyIFRlc3QgQ0EwIBcNMjYxMDE4MTgzOTA0WhgPMjEy
+NjA5MjQxODM5MDRaMBkxFzAVBgNVBAMMDk11cm11ciBUZXN0IENBMFkwEwYHKoZI
+zj0CAQYIKoZIzj0DAQcDQgAEdFavvveFbBe5W3gv4z35bUB3fsa3ZqyDXYEAtvm8
+24G2kNe7YZfroUZHyAaPGKM0g2WDZx6nKWIvGStoJ4VTjKNTMFEwHQYDVR0OBBYE
+FGrDAI5mtOlOmaKcp82ISqiXE0orMB8GA1UdIwQYMBaAFGrDAI5mtOlOmaKcp82I
+SqiXE0orMA8GA1UdEwEB/wQFMAMBAf8wCgYIKoZIzj0EAwIDSQAwRgIhAML1qoYr
+NFw2OGJ4J4btf8txRnHfmRe/6EWMmpDWeQreAiEA/omhb9fnVQ2YMX1wS3dNCDa5
+kXhRRMZHaxVVRmkD7k0=
+-----END CERTIFICATE-----
+";
+        let bundle = |contents: &str, only: bool| {
+            let file = tempfile::NamedTempFile::new().unwrap();
+            std::fs::write(file.path(), contents).unwrap();
+            let network = NetworkConfig {
+                ca_bundle: Some(file.path().to_string_lossy().into_owned()),
+                ca_bundle_only: Some(only),
+                ..Default::default()
+            };
+            tls_connector(&network)
+        };
+
+        assert!(tls_connector(&NetworkConfig::default()).unwrap().is_none());
+        assert!(matches!(
+            bundle(ca, false),
+            Ok(Some(Connector::NativeTls(_)))
+        ));
+        assert!(matches!(
+            bundle(ca, true),
+            Ok(Some(Connector::NativeTls(_)))
+        ));
+        let err = bundle("not a certificate\n", false).err().unwrap();
+        assert!(matches!(&err, MurmurError::Config(msg) if msg.contains("No certificates found")));
+    }
+
+    #[test]
+    fn test_proxy_authority_brackets_ipv6() {
+        assert_eq!(bracket("[::1]"), "[::1]");
+        assert_eq!(bracket("::1"), "[::1]");
+        assert_eq!(bracket("api.deepgram.com"), "api.deepgram.com");
+        assert_eq!(unbracket("[::1]"), "::1");
+    }
+
+    #[test]
+    fn test_handshake_request_headers() {
+        let request =
+            handshake_request("wss://example.com/listen", Some(("xi-api-key", "k"))).unwrap();
+        assert_eq!(request.headers()["xi-api-key"], "k");
+        assert!(handshake_request("wss://example.com", Some(("bad header", "k"))).is_err());
+        assert!(handshake_request("wss://example.com", Some(("x-key", "bad\nvalue"))).is_err());
+    }
+}
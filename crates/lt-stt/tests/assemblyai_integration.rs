@@ -0,0 +1,200 @@
+mod common;
+
+use common::{event, Handshake};
+use futures_util::{SinkExt, StreamExt};
+use lt_core::error::ProviderErrorKind;
+use lt_core::stt::SttProvider;
+use lt_stt::AssemblyAiProvider;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::time::{timeout, Duration};
+use tokio_tungstenite::accept_async;
+use tokio_tungstenite::tungstenite::protocol::{frame::coding::CloseCode, CloseFrame};
+use tokio_tungstenite::tungstenite::Message;
+
+/// What the mock AssemblyAI server saw during a session
+#[derive(Debug, Default)]
+struct Recorded {
+    handshake: Handshake,
+    audio_frames: Vec<usize>,
+    terminated: bool,
+}
+
+fn turn(order: u64, transcript: &str, end_of_turn: bool, formatted: bool) -> Message {
+    event(serde_json::json!({
+        "type": "Turn",
+        "turn_order": order,
+        "transcript": transcript,
+        "end_of_turn": end_of_turn,
+        "turn_is_formatted": formatted,
+        "words": []
+    }))
+}
+
+/// Mock AssemblyAI server: turns the first audio frame into a formatted
+/// turn and finishes a second one when the client sends Terminate
+async fn start_mock_assemblyai() -> (String, Arc<Mutex<Recorded>>) {
+    let (listener, url) = common::listen().await;
+    let recorded = Arc::new(Mutex::new(Recorded::default()));
+
+    let server_recorded = recorded.clone();
+    tokio::spawn(async move {
+        let (ws_stream, handshake) = common::accept_recorded(&listener).await;
+        server_recorded.lock().unwrap().handshake = handshake;
+        let (mut write, mut read) = ws_stream.split();
+        write
+            .send(event(serde_json::json!({"type": "Begin", "id": "session"})))
+            .await
+            .unwrap();
+
+        while let Some(Ok(msg)) = read.next().await {
+            match msg {
+                Message::Binary(bytes) => {
+                    let first = {
+                        let mut recorded = server_recorded.lock().unwrap();
+                        recorded.audio_frames.push(bytes.len());
+                        recorded.audio_frames.len() == 1
+                    };
+                    if first {
+                        for reply in [
+                            turn(0, "", false, false),
+                            turn(0, "hello", false, false),
+                            turn(0, "hello world", true, false),
+                            turn(0, "Hello world.", true, true),
+                        ] {
+                            write.send(reply).await.unwrap();
+                        }
+                    }
+                }
+                Message::Text(text) if text.contains("Terminate") => {
+                    server_recorded.lock().unwrap().terminated = true;
+                    for reply in [
+                        turn(1, "goodbye", true, false),
+                        turn(1, "Goodbye.", true, true),
+                        event(serde_json::json!({"type": "Termination"})),
+                    ] {
+                        write.send(reply).await.unwrap();
+                    }
+                    let _ = write
+                        .send(Message::Close(Some(CloseFrame {
+                            code: CloseCode::Normal,
+                            reason: "".into(),
+                        })))
+                        .await;
+                    break;
+                }
+                _ => {}
+            }
+        }
+    });
+
+    (url, recorded)
+}
+
+#[tokio::test]
+async fn test_assemblyai_session_commits_formatted_turns() {
+    let (base_url, recorded) = start_mock_assemblyai().await;
+    let mut provider = AssemblyAiProvider::new("aai-key".to_string())
+        .with_base_url(Some(base_url))
+        .with_keyterms(["Murmur"]);
+
+    provider.start_session().await.unwrap();
+    let mut events = provider.subscribe_events().await;
+    // 60 ms goes out as one frame; the last 10 ms is padded to 50 ms
+    for i in 0..6 {
+        provider
+            .send_audio(common::chunk(160, i * 10))
+            .await
+            .unwrap();
+    }
+    timeout(Duration::from_secs(5), provider.stop_session())
+        .await
+        .expect("stop_session should return once the session terminates")
+        .unwrap();
+
+    assert_eq!(
+        common::received(&mut events),
+        [
+            "partial 0 hello",
+            "partial 0 hello world",
+            "committed 0 Hello world.",
+            "partial 1 goodbye",
+            "committed 1 Goodbye.",
+        ]
+    );
+
+    let recorded = recorded.lock().unwrap();
+    let uri = &recorded.handshake.uri;
+    assert!(uri.starts_with("/v3/ws?"));
+    assert!(uri.contains("sample_rate=16000"));
+    assert!(uri.contains("format_turns=true"));
+    assert!(uri.contains("keyterms_prompt=%5B%22Murmur%22%5D"));
+    assert_eq!(recorded.handshake.header("authorization"), "aai-key");
+    assert_eq!(recorded.audio_frames, [800 * 2, 800 * 2]);
+    assert!(recorded.terminated);
+}
+
+#[tokio::test]
+async fn test_assemblyai_temporary_token_replaces_key() {
+    let (listener, url) = common::listen().await;
+    let handshake = Arc::new(Mutex::new(Handshake::default()));
+    let token_request = Arc::new(Mutex::new(String::new()));
+
+    let server_handshake = handshake.clone();
+    let server_token_request = token_request.clone();
+    tokio::spawn(async move {
+        // First connection: GET /v3/token
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buf = vec![0u8; 4096];
+        let n = stream.read(&mut buf).await.unwrap();
+        *server_token_request.lock().unwrap() = String::from_utf8_lossy(&buf[..n]).to_string();
+        let body = r#"{"token":"tmp-123"}"#;
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).await.unwrap();
+        drop(stream);
+
+        // Second connection: the session itself
+        let (mut ws_stream, session_handshake) = common::accept_recorded(&listener).await;
+        *server_handshake.lock().unwrap() = session_handshake;
+        ws_stream
+            .send(event(serde_json::json!({"type": "Begin", "id": "session"})))
+            .await
+            .unwrap();
+        while let Some(Ok(_)) = ws_stream.next().await {}
+    });
+
+    let provider = AssemblyAiProvider::new("aai-key".to_string())
+        .with_base_url(Some(url))
+        .with_temporary_token(true);
+    provider.health_check().await.unwrap();
+
+    let token_request = token_request.lock().unwrap().to_lowercase();
+    assert!(token_request.starts_with("get /v3/token?expires_in_seconds=60 "));
+    assert!(token_request.contains("authorization: aai-key"));
+    let handshake = handshake.lock().unwrap();
+    assert!(handshake.uri.ends_with("&token=tmp-123"));
+    assert!(handshake.header("authorization").is_empty());
+}
+
+#[tokio::test]
+async fn test_assemblyai_unauthorized_close_is_classified() {
+    let (listener, url) = common::listen().await;
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws_stream = accept_async(stream).await.unwrap();
+        let _ = ws_stream
+            .send(Message::Close(Some(CloseFrame {
+                code: CloseCode::Policy,
+                reason: "Unauthorized connection: Invalid API key".into(),
+            })))
+            .await;
+    });
+
+    let provider = AssemblyAiProvider::new("wrong".to_string()).with_base_url(Some(url));
+    let err = provider.health_check().await.unwrap_err();
+    common::assert_provider_error(&err, ProviderErrorKind::Auth, "assemblyai");
+}
@@ -0,0 +1,129 @@
+//! Mock WebSocket server harness shared by the provider integration tests
+
+// Each test binary uses a different part of the harness
+#![allow(dead_code)]
+// tungstenite handshake callbacks must return its large `ErrorResponse`
+#![allow(clippy::result_large_err)]
+
+use lt_core::error::{MurmurError, ProviderErrorKind};
+use lt_core::stt::{AudioChunk, TranscriptionEvent};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{accept_hdr_async, WebSocketStream};
+
+pub type ServerStream = WebSocketStream<TcpStream>;
+
+/// What the client sent in its WebSocket handshake
+#[derive(Debug, Default, Clone)]
+pub struct Handshake {
+    pub uri: String,
+    /// Keyed by lowercase header name
+    pub headers: HashMap<String, String>,
+}
+
+impl Handshake {
+    /// Value of header `name` (lowercase), empty when it was not sent
+    pub fn header(&self, name: &str) -> &str {
+        self.headers.get(name).map(String::as_str).unwrap_or("")
+    }
+}
+
+/// Listener on a free local port, with its `ws://` URL
+pub async fn listen() -> (TcpListener, String) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    (listener, url)
+}
+
+/// Accept one WebSocket session, recording its handshake
+pub async fn accept_recorded(listener: &TcpListener) -> (ServerStream, Handshake) {
+    let (stream, _) = listener.accept().await.unwrap();
+    let handshake = Arc::new(Mutex::new(Handshake::default()));
+    let callback_handshake = handshake.clone();
+    let callback = move |request: &Request, response: Response| {
+        let mut handshake = callback_handshake.lock().unwrap();
+        handshake.uri = request.uri().to_string();
+        handshake.headers = request
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+        Ok(response)
+    };
+    let ws_stream = accept_hdr_async(stream, callback).await.unwrap();
+    let handshake = handshake.lock().unwrap().clone();
+    (ws_stream, handshake)
+}
+
+/// Refuse the WebSocket handshake on `stream` with an HTTP error
+pub async fn reject(stream: TcpStream, status: u16, body: &str) {
+    let body = body.to_string();
+    let callback = move |_: &Request, _: Response| -> Result<Response, ErrorResponse> {
+        Err(http::Response::builder()
+            .status(status)
+            .body(Some(body))
+            .unwrap())
+    };
+    let _ = accept_hdr_async(stream, callback).await;
+}
+
+/// Server that refuses its first handshake with `status` and `body`;
+/// returns its URL
+pub async fn start_rejecting_server(status: u16, body: &str) -> String {
+    let (listener, url) = listen().await;
+    let body = body.to_string();
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        reject(stream, status, &body).await;
+    });
+    url
+}
+
+/// JSON text frame
+pub fn event(json: serde_json::Value) -> Message {
+    Message::Text(json.to_string().into())
+}
+
+/// 16 kHz mono audio of `samples` samples
+pub fn chunk(samples: usize, timestamp_ms: u64) -> AudioChunk {
+    AudioChunk {
+        data: vec![100; samples],
+        timestamp_ms,
+        sample_rate: 16000,
+        channels: 1,
+    }
+}
+
+/// Event as "partial <segment> <text>", "committed <segment> <text>" or
+/// "error <message>"
+pub fn describe(event: TranscriptionEvent) -> String {
+    match event {
+        TranscriptionEvent::Partial { text, metadata, .. } => {
+            format!("partial {} {}", metadata.segment_id.unwrap(), text)
+        }
+        TranscriptionEvent::Committed { text, metadata, .. } => {
+            format!("committed {} {}", metadata.segment_id.unwrap(), text)
+        }
+        TranscriptionEvent::Error { message, .. } => format!("error {}", message),
+    }
+}
+
+/// Every event received so far, described
+pub fn received(events: &mut tokio::sync::mpsc::Receiver<TranscriptionEvent>) -> Vec<String> {
+    let mut seen = Vec::new();
+    while let Ok(event) = events.try_recv() {
+        seen.push(describe(event));
+    }
+    seen
+}
+
+/// Assert `err` is a structured `kind` error from `provider`
+pub fn assert_provider_error(err: &MurmurError, kind: ProviderErrorKind, provider: &str) {
+    let detail = err.provider_error().expect("structured error");
+    assert_eq!(detail.kind, kind);
+    assert_eq!(detail.provider, provider);
+}
@@ -0,0 +1,159 @@
+mod common;
+
+use common::Handshake;
+use futures_util::{SinkExt, StreamExt};
+use lt_core::error::ProviderErrorKind;
+use lt_core::stt::SttProvider;
+use lt_stt::DeepgramProvider;
+use std::sync::{Arc, Mutex};
+use tokio::time::{timeout, Duration};
+use tokio_tungstenite::accept_async;
+use tokio_tungstenite::tungstenite::protocol::{frame::coding::CloseCode, CloseFrame};
+use tokio_tungstenite::tungstenite::Message;
+
+/// What the mock Deepgram server saw during a session
+#[derive(Debug, Default)]
+struct Recorded {
+    handshake: Handshake,
+    audio_bytes: usize,
+    close_stream: bool,
+}
+
+fn results(transcript: &str, is_final: bool, speech_final: bool) -> Message {
+    let json = serde_json::json!({
+        "type": "Results",
+        "start": 0.0,
+        "duration": 1.0,
+        "is_final": is_final,
+        "speech_final": speech_final,
+        "channel": {"alternatives": [{"transcript": transcript, "words": []}]}
+    });
+    common::event(json)
+}
+
+/// Mock Deepgram server: transcribes the first audio frame as one utterance
+/// and flushes a second one when the client sends CloseStream
+async fn start_mock_deepgram() -> (String, Arc<Mutex<Recorded>>) {
+    let (listener, url) = common::listen().await;
+    let recorded = Arc::new(Mutex::new(Recorded::default()));
+
+    let server_recorded = recorded.clone();
+    tokio::spawn(async move {
+        let (ws_stream, handshake) = common::accept_recorded(&listener).await;
+        server_recorded.lock().unwrap().handshake = handshake;
+        let (mut write, mut read) = ws_stream.split();
+
+        let mut answered = false;
+        while let Some(Ok(msg)) = read.next().await {
+            match msg {
+                Message::Binary(bytes) => {
+                    server_recorded.lock().unwrap().audio_bytes += bytes.len();
+                    if !answered {
+                        answered = true;
+                        for reply in [
+                            results("hel", false, false),
+                            results("hello world", true, false),
+                            results("", true, true),
+                        ] {
+                            write.send(reply).await.unwrap();
+                        }
+                    }
+                }
+                Message::Text(text) if text.contains("CloseStream") => {
+                    server_recorded.lock().unwrap().close_stream = true;
+                    write.send(results("goodbye", true, false)).await.unwrap();
+                    write
+                        .send(Message::Text(
+                            r#"{"type":"Metadata","request_id":"test"}"#.into(),
+                        ))
+                        .await
+                        .unwrap();
+                    let _ = write
+                        .send(Message::Close(Some(CloseFrame {
+                            code: CloseCode::Normal,
+                            reason: "".into(),
+                        })))
+                        .await;
+                    break;
+                }
+                _ => {}
+            }
+        }
+    });
+
+    (url, recorded)
+}
+
+#[tokio::test]
+async fn test_deepgram_session_maps_results_and_closes_stream() {
+    let (base_url, recorded) = start_mock_deepgram().await;
+    let mut provider = DeepgramProvider::new("dg-key".to_string())
+        .with_base_url(Some(base_url))
+        .with_language(Some("en".to_string()))
+        .with_keywords(["Murmur"]);
+
+    provider.start_session().await.unwrap();
+    let mut events = provider.subscribe_events().await;
+    provider.send_audio(common::chunk(160, 0)).await.unwrap();
+    provider.send_audio(common::chunk(160, 10)).await.unwrap();
+    timeout(Duration::from_secs(5), provider.stop_session())
+        .await
+        .expect("stop_session should return once the server closes")
+        .unwrap();
+
+    assert_eq!(
+        common::received(&mut events),
+        [
+            "partial 0 hel",
+            "partial 0 hello world",
+            "committed 0 hello world",
+            "partial 1 goodbye",
+            "committed 1 goodbye",
+        ]
+    );
+
+    let recorded = recorded.lock().unwrap();
+    let uri = &recorded.handshake.uri;
+    assert!(uri.starts_with("/v1/listen?"));
+    assert!(uri.contains("model=nova-3"));
+    assert!(uri.contains("language=en"));
+    assert!(uri.contains("interim_results=true"));
+    assert!(uri.contains("keyterm=Murmur"));
+    assert_eq!(recorded.handshake.header("authorization"), "Token dg-key");
+    assert_eq!(recorded.audio_bytes, 2 * 160 * 2);
+    assert!(recorded.close_stream);
+}
+
+#[tokio::test]
+async fn test_deepgram_rejected_key_is_classified() {
+    let url = common::start_rejecting_server(
+        401,
+        r#"{"err_code":"INVALID_AUTH","err_msg":"Invalid credentials."}"#,
+    )
+    .await;
+
+    let mut provider = DeepgramProvider::new("wrong".to_string()).with_base_url(Some(url));
+    let err = provider.start_session().await.unwrap_err();
+    common::assert_provider_error(&err, ProviderErrorKind::Auth, "deepgram");
+}
+
+#[tokio::test]
+async fn test_deepgram_health_check_waits_for_metadata() {
+    let (listener, url) = common::listen().await;
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws_stream = accept_async(stream).await.unwrap();
+        while let Some(Ok(msg)) = ws_stream.next().await {
+            if matches!(&msg, Message::Text(text) if text.contains("CloseStream")) {
+                let _ = ws_stream
+                    .send(Message::Text(r#"{"type":"Metadata"}"#.into()))
+                    .await;
+                break;
+            }
+        }
+    });
+
+    let provider = DeepgramProvider::new("key".to_string()).with_base_url(Some(url));
+    let health = provider.health_check().await.unwrap();
+    assert!(health.latency_ms < 10_000);
+}
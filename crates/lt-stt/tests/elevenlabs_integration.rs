@@ -0,0 +1,223 @@
+mod common;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use common::event;
+use futures_util::{SinkExt, StreamExt};
+use lt_core::error::ProviderErrorKind;
+use lt_core::stt::{AudioChunk, SttProvider, TranscriptionEvent};
+use lt_stt::ElevenLabsProvider;
+use std::sync::{Arc, Mutex};
+use tokio::time::{timeout, Duration};
+use tokio_tungstenite::accept_async;
+use tokio_tungstenite::tungstenite::Message;
+
+/// What the mock server does with one connection
+enum Script {
+    /// Take `audio` chunks, optionally commit text, then drop the socket
+    /// without a close handshake
+    DropAfter {
+        audio: usize,
+        commit: Option<&'static str>,
+    },
+    /// Commit `text` when the client asks for the final commit
+    CommitOnRequest { text: &'static str },
+    /// Refuse the handshake as unauthorized
+    Reject,
+}
+
+/// First sample of an `input_audio_chunk`, used as a marker
+fn marker(text: &str) -> Option<i16> {
+    let value: serde_json::Value = serde_json::from_str(text).ok()?;
+    let bytes = BASE64.decode(value["audio_base_64"].as_str()?).ok()?;
+    Some(i16::from_le_bytes([*bytes.first()?, *bytes.get(1)?]))
+}
+
+/// Mock ElevenLabs realtime server; returns its URL and the audio markers
+/// each connection received
+async fn start_mock_elevenlabs(scripts: Vec<Script>) -> (String, Arc<Mutex<Vec<Vec<i16>>>>) {
+    let (listener, url) = common::listen().await;
+    let received = Arc::new(Mutex::new(Vec::new()));
+
+    let server_received = received.clone();
+    tokio::spawn(async move {
+        for (index, script) in scripts.into_iter().enumerate() {
+            let (stream, _) = listener.accept().await.unwrap();
+            if let Script::Reject = script {
+                common::reject(stream, 401, "invalid api key").await;
+                continue;
+            }
+
+            let mut ws_stream = accept_async(stream).await.unwrap();
+            server_received.lock().unwrap().push(Vec::new());
+            ws_stream
+                .send(event(serde_json::json!({
+                    "message_type": "session_started",
+                    "session_id": format!("session-{}", index)
+                })))
+                .await
+                .unwrap();
+
+            while let Some(Ok(Message::Text(text))) = ws_stream.next().await {
+                if let Some(marker) = marker(&text) {
+                    let count = {
+                        let mut received = server_received.lock().unwrap();
+                        received[index].push(marker);
+                        received[index].len()
+                    };
+                    if let Script::DropAfter { audio, commit } = &script {
+                        if count == *audio {
+                            if let Some(text) = commit {
+                                ws_stream
+                                    .send(event(serde_json::json!({
+                                        "message_type": "committed_transcript",
+                                        "text": text
+                                    })))
+                                    .await
+                                    .unwrap();
+                            }
+                            break;
+                        }
+                    }
+                } else if text.contains("\"commit\":true") {
+                    if let Script::CommitOnRequest { text } = &script {
+                        for reply in [
+                            serde_json::json!({"message_type": "committed_transcript", "text": text}),
+                            serde_json::json!({
+                                "message_type": "committed_transcript_with_timestamps",
+                                "text": text,
+                                "words": []
+                            }),
+                        ] {
+                            ws_stream.send(event(reply)).await.unwrap();
+                        }
+                    }
+                }
+            }
+            // Dropping the stream without closing it is the connection loss
+        }
+    });
+
+    (url, received)
+}
+
+fn chunk(marker: i16) -> AudioChunk {
+    AudioChunk {
+        data: vec![marker; 1600],
+        timestamp_ms: marker as u64 * 100,
+        sample_rate: 16000,
+        channels: 1,
+    }
+}
+
+fn provider(base_url: String) -> ElevenLabsProvider {
+    ElevenLabsProvider::new("xi-key".to_string()).with_base_url(Some(base_url))
+}
+
+/// Collect events until the session's event stream goes quiet
+async fn drain(events: &mut tokio::sync::mpsc::Receiver<TranscriptionEvent>) -> Vec<String> {
+    let mut seen = Vec::new();
+    while let Ok(Some(event)) = timeout(Duration::from_millis(500), events.recv()).await {
+        seen.push(match event {
+            TranscriptionEvent::Partial { text, .. } => format!("partial {}", text),
+            TranscriptionEvent::Committed { text, .. } => format!("committed {}", text),
+            TranscriptionEvent::Error { detail, .. } => {
+                format!("error {:?}", detail.map(|d| d.kind))
+            }
+        });
+    }
+    seen
+}
+
+#[tokio::test]
+async fn test_elevenlabs_reconnects_and_replays_uncommitted_audio() {
+    let (base_url, received) = start_mock_elevenlabs(vec![
+        Script::DropAfter {
+            audio: 2,
+            commit: None,
+        },
+        Script::CommitOnRequest {
+            text: "hello world",
+        },
+    ])
+    .await;
+    let mut provider = provider(base_url);
+
+    provider.start_session().await.unwrap();
+    let mut events = provider.subscribe_events().await;
+    provider.send_audio(chunk(1)).await.unwrap();
+    provider.send_audio(chunk(2)).await.unwrap();
+    // Let the drop happen before the rest of the audio
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    provider.send_audio(chunk(3)).await.unwrap();
+    timeout(Duration::from_secs(10), provider.stop_session())
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(drain(&mut events).await, vec!["committed hello world"]);
+    let received = received.lock().unwrap();
+    assert_eq!(received.len(), 2);
+    assert_eq!(received[0], vec![1, 2]);
+    assert_eq!(received[1], vec![1, 2, 3]);
+}
+
+#[tokio::test]
+async fn test_elevenlabs_reconnect_drops_repeated_committed_text() {
+    let (base_url, received) = start_mock_elevenlabs(vec![
+        Script::DropAfter {
+            audio: 2,
+            commit: Some("the quick brown fox"),
+        },
+        Script::CommitOnRequest {
+            text: "brown fox jumps over",
+        },
+    ])
+    .await;
+    let mut provider = provider(base_url);
+
+    provider.start_session().await.unwrap();
+    let mut events = provider.subscribe_events().await;
+    provider.send_audio(chunk(1)).await.unwrap();
+    provider.send_audio(chunk(2)).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    provider.send_audio(chunk(3)).await.unwrap();
+    timeout(Duration::from_secs(10), provider.stop_session())
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(
+        drain(&mut events).await,
+        vec!["committed the quick brown fox", "committed jumps over"]
+    );
+    assert_eq!(received.lock().unwrap()[1], vec![1, 2, 3]);
+}
+
+#[tokio::test]
+async fn test_elevenlabs_reports_error_when_reconnect_is_refused() {
+    let (base_url, _) = start_mock_elevenlabs(vec![
+        Script::DropAfter {
+            audio: 1,
+            commit: None,
+        },
+        Script::Reject,
+    ])
+    .await;
+    let mut provider = provider(base_url);
+
+    provider.start_session().await.unwrap();
+    let mut events = provider.subscribe_events().await;
+    provider.send_audio(chunk(1)).await.unwrap();
+
+    let event = timeout(Duration::from_secs(5), events.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    match event {
+        TranscriptionEvent::Error { detail, .. } => {
+            assert_eq!(detail.unwrap().kind, ProviderErrorKind::Auth);
+        }
+        other => panic!("Unexpected event {:?}", other),
+    }
+    provider.stop_session().await.unwrap();
+}
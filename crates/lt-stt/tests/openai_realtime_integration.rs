@@ -0,0 +1,202 @@
+mod common;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use common::{event, Handshake};
+use futures_util::{SinkExt, StreamExt};
+use lt_core::error::ProviderErrorKind;
+use lt_core::stt::SttProvider;
+use lt_stt::OpenAIRealtimeProvider;
+use std::sync::{Arc, Mutex};
+use tokio::time::{timeout, Duration};
+use tokio_tungstenite::accept_async;
+use tokio_tungstenite::tungstenite::Message;
+
+/// What the mock Realtime server saw during a session
+#[derive(Debug, Default)]
+struct Recorded {
+    handshake: Handshake,
+    session_update: Option<serde_json::Value>,
+    audio_bytes: usize,
+    commits: usize,
+}
+
+/// Mock Realtime server: after the first audio it commits two items that
+/// finish out of order, and it turns the client's final commit into a
+/// third item (answering the clear that follows it only afterwards)
+async fn start_mock_realtime() -> (String, Arc<Mutex<Recorded>>) {
+    let (listener, url) = common::listen().await;
+    let recorded = Arc::new(Mutex::new(Recorded::default()));
+
+    let server_recorded = recorded.clone();
+    tokio::spawn(async move {
+        let (ws_stream, handshake) = common::accept_recorded(&listener).await;
+        server_recorded.lock().unwrap().handshake = handshake;
+        let (mut write, mut read) = ws_stream.split();
+        write
+            .send(event(
+                serde_json::json!({"type": "transcription_session.created"}),
+            ))
+            .await
+            .unwrap();
+
+        let mut answered = false;
+        while let Some(Ok(Message::Text(text))) = read.next().await {
+            let message: serde_json::Value = serde_json::from_str(&text).unwrap();
+            match message["type"].as_str().unwrap() {
+                "transcription_session.update" => {
+                    server_recorded.lock().unwrap().session_update = Some(message);
+                    write
+                        .send(event(
+                            serde_json::json!({"type": "transcription_session.updated"}),
+                        ))
+                        .await
+                        .unwrap();
+                }
+                "input_audio_buffer.append" => {
+                    let audio = BASE64.decode(message["audio"].as_str().unwrap()).unwrap();
+                    server_recorded.lock().unwrap().audio_bytes += audio.len();
+                    if answered {
+                        continue;
+                    }
+                    answered = true;
+                    let delta = "conversation.item.input_audio_transcription.delta";
+                    let completed = "conversation.item.input_audio_transcription.completed";
+                    for reply in [
+                        serde_json::json!({"type": "input_audio_buffer.speech_started"}),
+                        serde_json::json!({"type": "input_audio_buffer.committed", "item_id": "item_1"}),
+                        serde_json::json!({"type": "input_audio_buffer.committed", "item_id": "item_2"}),
+                        serde_json::json!({"type": delta, "item_id": "item_1", "delta": "Hel"}),
+                        serde_json::json!({"type": delta, "item_id": "item_2", "delta": "World"}),
+                        serde_json::json!({"type": completed, "item_id": "item_2", "transcript": "World."}),
+                        serde_json::json!({"type": delta, "item_id": "item_1", "delta": "lo"}),
+                        serde_json::json!({"type": completed, "item_id": "item_1", "transcript": "Hello."}),
+                    ] {
+                        write.send(event(reply)).await.unwrap();
+                    }
+                }
+                "input_audio_buffer.commit" => {
+                    server_recorded.lock().unwrap().commits += 1;
+                    for reply in [
+                        serde_json::json!({"type": "input_audio_buffer.committed", "item_id": "item_3"}),
+                        serde_json::json!({
+                            "type": "conversation.item.input_audio_transcription.completed",
+                            "item_id": "item_3",
+                            "transcript": "Bye."
+                        }),
+                    ] {
+                        write.send(event(reply)).await.unwrap();
+                    }
+                }
+                "input_audio_buffer.clear" => {
+                    write
+                        .send(event(
+                            serde_json::json!({"type": "input_audio_buffer.cleared"}),
+                        ))
+                        .await
+                        .unwrap();
+                }
+                _ => {}
+            }
+        }
+    });
+
+    (url, recorded)
+}
+
+#[tokio::test]
+async fn test_realtime_session_orders_items_and_commits_on_stop() {
+    let (base_url, recorded) = start_mock_realtime().await;
+    let mut provider = OpenAIRealtimeProvider::new("sk-test".to_string())
+        .with_base_url(Some(base_url))
+        .with_language(Some("en".to_string()));
+
+    provider.start_session().await.unwrap();
+    let mut events = provider.subscribe_events().await;
+    provider.send_audio(common::chunk(160, 0)).await.unwrap();
+    provider.send_audio(common::chunk(160, 10)).await.unwrap();
+    timeout(Duration::from_secs(5), provider.stop_session())
+        .await
+        .expect("stop_session should return once the last item completes")
+        .unwrap();
+
+    assert_eq!(
+        common::received(&mut events),
+        [
+            "partial 0 Hel",
+            "partial 1 World",
+            "partial 0 Hello",
+            "committed 0 Hello.",
+            "committed 1 World.",
+            "committed 2 Bye.",
+        ]
+    );
+
+    let recorded = recorded.lock().unwrap();
+    assert_eq!(recorded.handshake.uri, "/v1/realtime?intent=transcription");
+    assert_eq!(recorded.handshake.header("authorization"), "Bearer sk-test");
+    assert_eq!(recorded.handshake.header("openai-beta"), "realtime=v1");
+    let update = recorded.session_update.as_ref().expect("session update");
+    assert_eq!(
+        update["session"]["input_audio_transcription"]["language"],
+        "en"
+    );
+    assert_eq!(update["session"]["turn_detection"]["type"], "server_vad");
+    // 320 samples at 16 kHz arrive as (about) 480 samples at 24 kHz
+    assert!(
+        (476 * 2..=480 * 2).contains(&recorded.audio_bytes),
+        "unexpected audio size {}",
+        recorded.audio_bytes
+    );
+    assert_eq!(recorded.commits, 1);
+}
+
+#[tokio::test]
+async fn test_realtime_empty_final_commit_ends_session() {
+    let (listener, url) = common::listen().await;
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws_stream = accept_async(stream).await.unwrap();
+        while let Some(Ok(Message::Text(text))) = ws_stream.next().await {
+            if text.contains("input_audio_buffer.commit") {
+                let _ = ws_stream
+                    .send(event(serde_json::json!({
+                        "type": "error",
+                        "error": {
+                            "type": "invalid_request_error",
+                            "code": "input_audio_buffer_commit_empty",
+                            "message": "buffer too small"
+                        }
+                    })))
+                    .await;
+            } else if text.contains("input_audio_buffer.clear") {
+                let _ = ws_stream
+                    .send(event(
+                        serde_json::json!({"type": "input_audio_buffer.cleared"}),
+                    ))
+                    .await;
+            }
+        }
+    });
+
+    let mut provider = OpenAIRealtimeProvider::new("sk-test".to_string()).with_base_url(Some(url));
+    provider.start_session().await.unwrap();
+    let mut events = provider.subscribe_events().await;
+    timeout(Duration::from_secs(2), provider.stop_session())
+        .await
+        .expect("an empty buffer should not hold up stop_session")
+        .unwrap();
+    assert!(events.try_recv().is_err(), "no events expected");
+}
+
+#[tokio::test]
+async fn test_realtime_rejected_key_is_classified() {
+    let url = common::start_rejecting_server(
+        401,
+        r#"{"error":{"code":"invalid_api_key","message":"Incorrect API key"}}"#,
+    )
+    .await;
+
+    let provider = OpenAIRealtimeProvider::new("wrong".to_string()).with_base_url(Some(url));
+    let err = provider.health_check().await.unwrap_err();
+    common::assert_provider_error(&err, ProviderErrorKind::Auth, "openai_realtime");
+}
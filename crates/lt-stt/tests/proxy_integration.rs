@@ -0,0 +1,191 @@
+mod common;
+
+use common::event;
+use futures_util::{SinkExt, StreamExt};
+use lt_core::config::{NetworkConfig, StreamingSttConfig, StreamingSttProtocol};
+use lt_core::stt::SttProvider;
+use lt_stt::{StreamingEndpoint, StreamingSttProvider};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::{timeout, Duration};
+use tokio_tungstenite::tungstenite::Message;
+
+/// The network settings are process-wide; tests that change them take turns
+static NETWORK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Host only the mock proxies can reach: it is not resolvable, so a direct
+/// connection fails
+const UNREACHABLE_HOST: &str = "stt.murmur.invalid";
+
+/// Mock streaming server: commits "hello" on the first audio and closes
+/// after the end message
+async fn start_mock_server() -> u16 {
+    let (listener, _) = common::listen().await;
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        let (ws_stream, _) = common::accept_recorded(&listener).await;
+        let (mut write, mut read) = ws_stream.split();
+        let mut answered = false;
+        while let Some(Ok(msg)) = read.next().await {
+            match msg {
+                Message::Binary(_) if !answered => {
+                    answered = true;
+                    write
+                        .send(event(
+                            serde_json::json!({"text": "hello", "is_final": true}),
+                        ))
+                        .await
+                        .unwrap();
+                }
+                Message::Text(_) => {
+                    let _ = write.send(Message::Close(None)).await;
+                    break;
+                }
+                _ => {}
+            }
+        }
+    });
+    port
+}
+
+/// HTTP proxy that answers one CONNECT by tunnelling to the mock server;
+/// returns its URL and the request head it received
+async fn start_connect_proxy(server_port: u16) -> (String, Arc<Mutex<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://user:secret@{}", listener.local_addr().unwrap());
+    let request = Arc::new(Mutex::new(String::new()));
+    let recorded = request.clone();
+    tokio::spawn(async move {
+        let (mut client, _) = listener.accept().await.unwrap();
+        let mut head = Vec::new();
+        while !head.ends_with(b"\r\n\r\n") {
+            head.push(client.read_u8().await.unwrap());
+        }
+        *recorded.lock().unwrap() = String::from_utf8_lossy(&head).to_string();
+        let mut server = TcpStream::connect(("127.0.0.1", server_port))
+            .await
+            .unwrap();
+        client
+            .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+            .await
+            .unwrap();
+        let _ = tokio::io::copy_bidirectional(&mut client, &mut server).await;
+    });
+    (url, request)
+}
+
+/// SOCKS5 proxy without authentication that tunnels one connection to the
+/// mock server; returns its URL and the host name it was asked for
+async fn start_socks_proxy(server_port: u16) -> (String, Arc<Mutex<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("socks5h://{}", listener.local_addr().unwrap());
+    let target = Arc::new(Mutex::new(String::new()));
+    let recorded = target.clone();
+    tokio::spawn(async move {
+        let (mut client, _) = listener.accept().await.unwrap();
+        let mut greeting = [0u8; 3];
+        client.read_exact(&mut greeting).await.unwrap();
+        assert_eq!(greeting, [5, 1, 0]);
+        client.write_all(&[5, 0]).await.unwrap();
+
+        let mut head = [0u8; 5];
+        client.read_exact(&mut head).await.unwrap();
+        assert_eq!(head[..4], [5, 1, 0, 3], "CONNECT by host name");
+        let mut host = vec![0u8; usize::from(head[4])];
+        client.read_exact(&mut host).await.unwrap();
+        let port = client.read_u16().await.unwrap();
+        *recorded.lock().unwrap() = format!("{}:{}", String::from_utf8(host).unwrap(), port);
+
+        let mut server = TcpStream::connect(("127.0.0.1", server_port))
+            .await
+            .unwrap();
+        client
+            .write_all(&[5, 0, 0, 1, 127, 0, 0, 1, 0, 0])
+            .await
+            .unwrap();
+        let _ = tokio::io::copy_bidirectional(&mut client, &mut server).await;
+    });
+    (url, target)
+}
+
+/// Run a one-chunk session against `url` and return what was committed
+async fn run_session(url: String) -> Vec<String> {
+    let config = StreamingSttConfig {
+        url: Some(url),
+        ..Default::default()
+    };
+    let endpoint = StreamingEndpoint::preset(StreamingSttProtocol::Json).with_overrides(&config);
+    let mut provider = StreamingSttProvider::new(endpoint);
+
+    provider.start_session().await.unwrap();
+    let mut events = provider.subscribe_events().await;
+    provider.send_audio(common::chunk(160, 0)).await.unwrap();
+    timeout(Duration::from_secs(5), provider.stop_session())
+        .await
+        .expect("stop_session should return once the server closes")
+        .unwrap();
+    common::received(&mut events)
+}
+
+#[tokio::test]
+async fn test_session_tunnels_through_http_proxy() {
+    let _network = NETWORK.lock().await;
+    let port = start_mock_server().await;
+    let (proxy, request) = start_connect_proxy(port).await;
+    lt_http::configure(&NetworkConfig {
+        proxy: Some(proxy),
+        ..Default::default()
+    })
+    .unwrap();
+
+    let events = run_session(format!("ws://{}:{}/stream", UNREACHABLE_HOST, port)).await;
+    lt_http::configure(&NetworkConfig::default()).unwrap();
+
+    assert_eq!(events, ["committed 0 hello"]);
+    let request = request.lock().unwrap();
+    let authority = format!("{}:{}", UNREACHABLE_HOST, port);
+    assert!(request.starts_with(&format!("CONNECT {} HTTP/1.1\r\n", authority)));
+    assert!(request.contains(&format!("Host: {}\r\n", authority)));
+    // "user:secret"
+    assert!(request.contains("Proxy-Authorization: Basic dXNlcjpzZWNyZXQ=\r\n"));
+}
+
+#[tokio::test]
+async fn test_session_tunnels_through_socks_proxy() {
+    let _network = NETWORK.lock().await;
+    let port = start_mock_server().await;
+    let (proxy, target) = start_socks_proxy(port).await;
+    lt_http::configure(&NetworkConfig {
+        proxy: Some(proxy),
+        ..Default::default()
+    })
+    .unwrap();
+
+    let events = run_session(format!("ws://{}:{}/stream", UNREACHABLE_HOST, port)).await;
+    lt_http::configure(&NetworkConfig::default()).unwrap();
+
+    assert_eq!(events, ["committed 0 hello"]);
+    assert_eq!(
+        *target.lock().unwrap(),
+        format!("{}:{}", UNREACHABLE_HOST, port)
+    );
+}
+
+#[tokio::test]
+async fn test_no_proxy_hosts_connect_directly() {
+    let _network = NETWORK.lock().await;
+    let port = start_mock_server().await;
+    lt_http::configure(&NetworkConfig {
+        // Nothing listens here; a proxied connection would fail
+        proxy: Some("http://127.0.0.1:9".to_string()),
+        no_proxy: Some("localhost,127.0.0.1".to_string()),
+        ..Default::default()
+    })
+    .unwrap();
+
+    let events = run_session(format!("ws://127.0.0.1:{}/stream", port)).await;
+    lt_http::configure(&NetworkConfig::default()).unwrap();
+
+    assert_eq!(events, ["committed 0 hello"]);
+}
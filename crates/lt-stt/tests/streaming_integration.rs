@@ -0,0 +1,213 @@
+mod common;
+
+use common::{event, Handshake};
+use futures_util::{SinkExt, StreamExt};
+use lt_core::config::{StreamingSttConfig, StreamingSttProtocol, SttAuthScheme};
+use lt_core::error::ProviderErrorKind;
+use lt_core::stt::SttProvider;
+use lt_stt::{StreamingEndpoint, StreamingSttProvider};
+use std::sync::{Arc, Mutex};
+use tokio::time::{timeout, Duration, Instant};
+use tokio_tungstenite::accept_async;
+use tokio_tungstenite::tungstenite::Message;
+
+/// What the mock server saw during a session
+#[derive(Debug, Default)]
+struct Recorded {
+    handshake: Handshake,
+    start_message: Option<serde_json::Value>,
+    audio_bytes: usize,
+    end_message: Option<Message>,
+}
+
+/// Mock server speaking the documented JSON framing: it answers the first
+/// audio with a partial and a final, and the end message with one more
+/// final before closing the socket
+async fn start_mock_json_server() -> (String, Arc<Mutex<Recorded>>) {
+    let (listener, url) = common::listen().await;
+    let recorded = Arc::new(Mutex::new(Recorded::default()));
+
+    let server_recorded = recorded.clone();
+    tokio::spawn(async move {
+        let (ws_stream, handshake) = common::accept_recorded(&listener).await;
+        server_recorded.lock().unwrap().handshake = handshake;
+        let (mut write, mut read) = ws_stream.split();
+
+        while let Some(Ok(msg)) = read.next().await {
+            match msg {
+                Message::Binary(bytes) => {
+                    let first = {
+                        let mut recorded = server_recorded.lock().unwrap();
+                        recorded.audio_bytes += bytes.len();
+                        recorded.audio_bytes == bytes.len()
+                    };
+                    if first {
+                        for reply in [
+                            event(serde_json::json!({"result": {"text": "hel", "final": false}})),
+                            event(serde_json::json!({"result": {"text": "hello", "final": true}})),
+                        ] {
+                            write.send(reply).await.unwrap();
+                        }
+                    }
+                }
+                Message::Text(text) if text.contains("\"start\"") => {
+                    server_recorded.lock().unwrap().start_message =
+                        Some(serde_json::from_str(&text).unwrap());
+                }
+                msg @ Message::Text(_) => {
+                    server_recorded.lock().unwrap().end_message = Some(msg);
+                    write
+                        .send(event(
+                            serde_json::json!({"result": {"text": "bye", "final": true}}),
+                        ))
+                        .await
+                        .unwrap();
+                    let _ = write.send(Message::Close(None)).await;
+                    break;
+                }
+                _ => {}
+            }
+        }
+    });
+
+    (format!("{}/stream", url), recorded)
+}
+
+#[tokio::test]
+async fn test_json_server_session_with_custom_mapping() {
+    let (url, recorded) = start_mock_json_server().await;
+    let config = StreamingSttConfig {
+        url: Some(url),
+        protocol: Some(StreamingSttProtocol::Json),
+        auth: Some(SttAuthScheme::Header {
+            name: "x-api-key".to_string(),
+        }),
+        language: Some("en".to_string()),
+        start_message: Some(
+            r#"{"start": true, "language": {language}, "rate": {sample_rate}}"#.to_string(),
+        ),
+        text_path: Some("result.text".to_string()),
+        final_path: Some("result.final".to_string()),
+        ..Default::default()
+    };
+    let endpoint = StreamingEndpoint::preset(StreamingSttProtocol::Json)
+        .with_overrides(&config)
+        .with_api_key(Some("secret".to_string()));
+    let mut provider = StreamingSttProvider::new(endpoint);
+
+    provider.start_session().await.unwrap();
+    let mut events = provider.subscribe_events().await;
+    provider.send_audio(common::chunk(160, 0)).await.unwrap();
+    provider.send_audio(common::chunk(160, 10)).await.unwrap();
+    timeout(Duration::from_secs(5), provider.stop_session())
+        .await
+        .expect("stop_session should return once the server closes")
+        .unwrap();
+
+    assert_eq!(
+        common::received(&mut events),
+        ["partial 0 hel", "committed 0 hello", "committed 1 bye"]
+    );
+
+    let recorded = recorded.lock().unwrap();
+    assert_eq!(recorded.handshake.uri, "/stream");
+    assert_eq!(recorded.handshake.header("x-api-key"), "secret");
+    let start = recorded.start_message.as_ref().expect("start message");
+    assert_eq!(start["language"], "en");
+    assert_eq!(start["rate"], 16000);
+    assert_eq!(recorded.audio_bytes, 2 * 160 * 2);
+    assert_eq!(
+        recorded.end_message,
+        Some(Message::Text(r#"{"type":"end"}"#.into()))
+    );
+}
+
+#[tokio::test]
+async fn test_whisper_live_session_commits_completed_segments() {
+    let (listener, url) = common::listen().await;
+    let audio_bytes = Arc::new(Mutex::new(0usize));
+    let server_audio_bytes = audio_bytes.clone();
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws_stream = accept_async(stream).await.unwrap();
+        while let Some(Ok(msg)) = ws_stream.next().await {
+            match msg {
+                Message::Text(text) => {
+                    let options: serde_json::Value = serde_json::from_str(&text).unwrap();
+                    assert_eq!(options["model"], "small");
+                    ws_stream
+                        .send(event(serde_json::json!({
+                            "uid": options["uid"], "message": "SERVER_READY", "backend": "faster_whisper"
+                        })))
+                        .await
+                        .unwrap();
+                }
+                Message::Binary(bytes) if &bytes[..] == b"END_OF_AUDIO" => {
+                    // Finalize the open segment and leave the socket open,
+                    // as WhisperLive does
+                    ws_stream
+                        .send(event(serde_json::json!({"segments": [
+                            {"start": "0.000", "end": "1.000", "text": " Hello.", "completed": true},
+                            {"start": "1.000", "end": "2.000", "text": " Bye now.", "completed": true}
+                        ]})))
+                        .await
+                        .unwrap();
+                }
+                Message::Binary(bytes) => {
+                    *server_audio_bytes.lock().unwrap() += bytes.len();
+                    ws_stream
+                        .send(event(serde_json::json!({"segments": [
+                            {"start": "0.000", "end": "1.000", "text": " Hello.", "completed": true},
+                            {"start": "1.000", "end": "1.500", "text": " Bye", "completed": false}
+                        ]})))
+                        .await
+                        .unwrap();
+                }
+                _ => {}
+            }
+        }
+    });
+
+    let config = StreamingSttConfig {
+        url: Some(url),
+        ..Default::default()
+    };
+    let endpoint =
+        StreamingEndpoint::preset(StreamingSttProtocol::WhisperLive).with_overrides(&config);
+    let mut provider = StreamingSttProvider::new(endpoint);
+
+    provider.start_session().await.unwrap();
+    let mut events = provider.subscribe_events().await;
+    provider.send_audio(common::chunk(160, 0)).await.unwrap();
+    provider.send_audio(common::chunk(160, 10)).await.unwrap();
+    let stopping = Instant::now();
+    provider.stop_session().await.unwrap();
+    // The server never closes, so the session ends once it goes quiet
+    assert!(stopping.elapsed() < Duration::from_secs(4));
+
+    assert_eq!(
+        common::received(&mut events),
+        [
+            "committed 0 Hello.",
+            "partial 1 Bye",
+            "committed 1 Bye now."
+        ]
+    );
+    // Float samples take four bytes each
+    assert_eq!(*audio_bytes.lock().unwrap(), 2 * 160 * 4);
+}
+
+#[tokio::test]
+async fn test_rejected_handshake_is_classified() {
+    let url = common::start_rejecting_server(403, "forbidden").await;
+
+    let config = StreamingSttConfig {
+        url: Some(url),
+        ..Default::default()
+    };
+    let provider = StreamingSttProvider::new(
+        StreamingEndpoint::preset(StreamingSttProtocol::Json).with_overrides(&config),
+    );
+    let err = provider.health_check().await.unwrap_err();
+    common::assert_provider_error(&err, ProviderErrorKind::Permission, "custom_streaming");
+}
@@ -5,22 +5,35 @@ mod diagnostics;
 mod permissions;
 mod sound;
 
-use lt_core::config::{LlmProcessorType, SttProviderType};
-use lt_core::llm::LlmProcessor;
+use lt_core::config::{LlmProcessorSpec, LlmProcessorType, SttProviderType};
+use lt_core::llm::{LlmCapabilities, LlmModelInfo, LlmProcessor, ProcessingTask};
 use lt_core::output::OutputMode;
-use lt_core::stt::SttProvider;
+use lt_core::retry::RetryPolicy;
+use lt_core::stt::{SttCapabilities, SttMode, SttProvider, TranscriptMetadata};
 use lt_core::{AppConfig, PersonalDictionary, TranscriptionHistory};
 #[cfg(target_os = "macos")]
 use lt_llm::AppleLlmProcessor;
 use lt_llm::{
-    CopilotProcessor, GeminiProcessor, HttpLlmProcessor, PromptManager, PromptName, PromptSet,
-    PromptStore,
+    CompositeProcessor, CopilotProcessor, GeminiProcessor, HttpLlmProcessor, ModelCache,
+    OllamaProcessor, PromptManager, PromptName, PromptSet, PromptStore,
 };
 use lt_output::CombinedOutput;
-use lt_pipeline::{PipelineEvent, PipelineOrchestrator, PipelineState};
+use lt_pipeline::queue::{DEFAULT_MAX_ITEMS, DEFAULT_RETRY_INTERVAL_SECS};
+use lt_pipeline::{
+    OfflineQueue, PipelineEvent, PipelineOrchestrator, PipelineState, QueueOutcome, QueuePass,
+    QueuedDictation,
+};
+use lt_stt::chunker::DEFAULT_MIN_SPEECH_RATIO;
+use lt_stt::openai_realtime::ServerVad;
 #[cfg(target_os = "macos")]
 use lt_stt::AppleSttProvider;
-use lt_stt::{CustomSttProvider, ElevenLabsProvider, GroqProvider, OpenAIProvider};
+#[cfg(feature = "local-whisper")]
+use lt_stt::LocalWhisperProvider;
+use lt_stt::{
+    AssemblyAiProvider, DeepgramProvider, ElevenLabsProvider, FailoverSttProvider,
+    HallucinationFilter, OpenAIRealtimeProvider, RestEndpoint, RestSttProvider, StreamingEndpoint,
+    StreamingSttProvider,
+};
 use std::sync::Arc;
 use tauri::menu::{MenuBuilder, MenuItemBuilder};
 use tauri::tray::{TrayIconBuilder, TrayIconEvent};
@@ -36,6 +49,9 @@ struct AppState {
     pipeline: Arc<Mutex<PipelineOrchestrator>>,
     event_task: Arc<Mutex<Option<tauri::async_runtime::JoinHandle<()>>>>,
     prompts: PromptManager,
+    offline_queue: Arc<OfflineQueue>,
+    /// Model lists of the LLM processors, for the model picker
+    llm_models: ModelCache,
 }
 
 #[derive(Clone, serde::Serialize)]
@@ -55,18 +71,46 @@ struct AudioLevelEvent {
 struct TranscriptionEvent {
     text: String,
     timestamp_ms: u64,
+    #[serde(skip_serializing_if = "TranscriptMetadata::is_empty")]
+    metadata: TranscriptMetadata,
 }
 
 #[derive(Clone, serde::Serialize)]
 struct FinalResultEvent {
     text: String,
     processing_time_ms: u64,
+    /// LLM processor that produced `text` (None = raw transcription)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    llm_processor: Option<String>,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct QueuedEvent {
+    id: String,
+    reason: String,
+    timestamp_ms: u64,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct QueueItemProcessedEvent {
+    id: String,
+    text: String,
+    command_name: Option<String>,
+    processing_time_ms: u64,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct QueueItemFailedEvent {
+    id: String,
+    message: String,
 }
 
 #[derive(Clone, serde::Serialize)]
 struct ErrorEvent {
     message: String,
     recoverable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    detail: Option<lt_core::ProviderError>,
 }
 
 #[tauri::command]
@@ -91,6 +135,9 @@ async fn save_config(config: AppConfig) -> Result<(), String> {
     let config_path = AppConfig::default_config_file()
         .map_err(|e| format!("Failed to get config path: {}", e))?;
 
+    // Rejects a bad proxy or CA bundle before it is written to disk
+    lt_http::configure(&config.network).map_err(|e| format!("Invalid network settings: {}", e))?;
+
     config
         .save_to_file(&config_path)
         .map_err(|e| format!("Failed to save config: {}", e))
@@ -120,14 +167,7 @@ async fn set_stt_provider(provider: String) -> Result<(), String> {
     };
 
     // Parse provider string to SttProviderType
-    let provider_type = match provider.to_lowercase().as_str() {
-        "elevenlabs" => SttProviderType::ElevenLabs,
-        "openai" => SttProviderType::OpenAI,
-        "groq" => SttProviderType::Groq,
-        "apple_stt" => SttProviderType::AppleStt,
-        "custom_stt" => SttProviderType::CustomStt,
-        _ => return Err(format!("Unknown STT provider: {}", provider)),
-    };
+    let provider_type = parse_stt_provider(&provider, &config)?;
 
     config.stt_provider = provider_type;
 
@@ -137,7 +177,11 @@ async fn set_stt_provider(provider: String) -> Result<(), String> {
 }
 
 #[tauri::command]
-async fn save_api_key(provider: String, api_key: String) -> Result<(), String> {
+async fn save_api_key(
+    provider: String,
+    api_key: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
     let config_path = AppConfig::default_config_file()
         .map_err(|e| format!("Failed to get config path: {}", e))?;
 
@@ -152,7 +196,11 @@ async fn save_api_key(provider: String, api_key: String) -> Result<(), String> {
 
     config
         .save_to_file(&config_path)
-        .map_err(|e| format!("Failed to save config: {}", e))
+        .map_err(|e| format!("Failed to save config: {}", e))?;
+
+    // A new key can unlock a different set of models
+    state.llm_models.clear().await;
+    Ok(())
 }
 
 #[derive(Clone, serde::Serialize)]
@@ -163,82 +211,478 @@ struct SttProviderInfo {
     configured: bool,
     requires_api_key: bool,
     model_status: Option<String>,
+    capabilities: SttCapabilities,
+}
+
+/// Parse an STT provider id as sent by the frontend. An endpoint id needs
+/// a preset or an `[stt_endpoints.<id>]` table.
+fn parse_stt_provider(provider: &str, config: &AppConfig) -> Result<SttProviderType, String> {
+    let provider_type = SttProviderType::from_id(provider);
+    if let SttProviderType::Endpoint(id) = &provider_type {
+        if rest_stt_endpoint(&provider_type, config).is_none() {
+            return Err(format!("Unknown STT provider: {}", id));
+        }
+    }
+    Ok(provider_type)
+}
+
+/// Display name of an STT provider
+fn stt_provider_name(provider_type: &SttProviderType, config: &AppConfig) -> String {
+    let name = match provider_type {
+        SttProviderType::ElevenLabs => "ElevenLabs Scribe",
+        SttProviderType::Deepgram => "Deepgram Nova",
+        SttProviderType::AppleStt => "Apple Speech",
+        SttProviderType::OpenAIRealtime => "OpenAI Realtime",
+        SttProviderType::AssemblyAI => "AssemblyAI Universal",
+        SttProviderType::LocalWhisper => "Whisper (on-device)",
+        SttProviderType::CustomStreaming => config
+            .streaming_stt_config
+            .display_name
+            .as_deref()
+            .unwrap_or("Custom Streaming Endpoint"),
+        // REST endpoints carry their own name
+        _ => {
+            return rest_stt_endpoint(provider_type, config)
+                .map(|endpoint| endpoint.name)
+                .unwrap_or_else(|| provider_type.id().to_string())
+        }
+    };
+    name.to_string()
+}
+
+/// API key of an STT provider; OpenAI Realtime falls back to the `openai` key
+fn stt_api_key(config: &AppConfig, id: &str) -> Option<String> {
+    config
+        .api_keys
+        .get(id)
+        .or_else(|| match id {
+            "openai_realtime" => config.api_keys.get("openai"),
+            _ => None,
+        })
+        .cloned()
+}
+
+/// REST endpoint for a Whisper-compatible provider: the built-in preset (or
+/// the custom base URL) plus the key, global chunking settings and any
+/// `[stt_endpoints.<id>]` overrides. An id without a preset is defined by
+/// its table alone. `None` for non-REST providers and unknown ids.
+fn rest_stt_endpoint(provider_type: &SttProviderType, config: &AppConfig) -> Option<RestEndpoint> {
+    let id = provider_type.id();
+    let http = &config.http_stt_config;
+    let endpoint = match provider_type {
+        SttProviderType::ElevenLabs
+        | SttProviderType::Deepgram
+        | SttProviderType::OpenAIRealtime
+        | SttProviderType::AssemblyAI
+        | SttProviderType::CustomStreaming
+        | SttProviderType::LocalWhisper
+        | SttProviderType::AppleStt => return None,
+        SttProviderType::CustomStt => {
+            let mut endpoint = RestEndpoint::custom(
+                http.custom_base_url.as_deref().unwrap_or_default(),
+                config.api_keys.get(id).cloned(),
+                http.custom_model.clone(),
+                http.language.clone(),
+            );
+            if let Some(name) = &http.custom_display_name {
+                endpoint.name = name.clone();
+            }
+            endpoint
+        }
+        _ => match RestEndpoint::preset(id) {
+            Some(preset) => preset,
+            None if config.stt_endpoints.contains_key(id) => RestEndpoint::new(id),
+            None => return None,
+        }
+        .with_api_key(config.api_keys.get(id).cloned()),
+    }
+    .with_chunk_overlap(http.chunk_overlap_ms.unwrap_or(0))
+    .with_min_speech_ratio(http.min_speech_ratio.unwrap_or(DEFAULT_MIN_SPEECH_RATIO));
+
+    Some(match config.stt_endpoints.get(id) {
+        Some(overrides) => endpoint.with_overrides(overrides),
+        None => endpoint,
+    })
+}
+
+/// Self-hosted streaming endpoint: the protocol preset plus the
+/// `[streaming_stt_config]` overrides and the `custom_streaming` key
+fn streaming_stt_endpoint(config: &AppConfig) -> StreamingEndpoint {
+    let streaming = &config.streaming_stt_config;
+    StreamingEndpoint::preset(streaming.protocol.unwrap_or_default())
+        .with_overrides(streaming)
+        .with_api_key(config.api_keys.get("custom_streaming").cloned())
+}
+
+/// Terms of the personal dictionary, for providers that take vocabulary hints
+fn dictionary_terms() -> Vec<String> {
+    AppConfig::default_config_dir()
+        .map(|dir| dir.join("dictionary.json"))
+        .ok()
+        .filter(|path| path.exists())
+        .and_then(|path| match PersonalDictionary::load_from_file(&path) {
+            Ok(dict) => Some(dict.get_terms()),
+            Err(e) => {
+                tracing::warn!("Failed to load dictionary for STT hints: {}", e);
+                None
+            }
+        })
+        .unwrap_or_default()
+}
+
+/// Build an STT provider from config. Credentials are not validated here;
+/// callers check `capabilities().requires_api_key` where it matters.
+fn create_stt_provider(
+    provider_type: &SttProviderType,
+    config: &AppConfig,
+) -> Result<Box<dyn SttProvider>, String> {
+    let http = &config.http_stt_config;
+    let hallucination_filter = if http.filter_hallucinations.unwrap_or(true) {
+        HallucinationFilter::default().with_phrases(&http.hallucination_phrases)
+    } else {
+        HallucinationFilter::disabled()
+    };
+    let diarize = config.diarization.unwrap_or(false);
+
+    let provider: Box<dyn SttProvider> = match provider_type {
+        SttProviderType::ElevenLabs => Box::new(
+            ElevenLabsProvider::with_config(
+                config
+                    .api_keys
+                    .get("elevenlabs")
+                    .cloned()
+                    .unwrap_or_default(),
+                "scribe_v2_realtime".to_string(),
+                config.elevenlabs_language.clone(),
+            )
+            .with_diarize(diarize),
+        ),
+        SttProviderType::Deepgram => {
+            let deepgram = &config.deepgram_config;
+            Box::new(
+                DeepgramProvider::new(config.api_keys.get("deepgram").cloned().unwrap_or_default())
+                    .with_model(deepgram.model.clone())
+                    .with_language(deepgram.language.clone())
+                    .with_keywords(&deepgram.keywords)
+                    .with_diarize(diarize)
+                    .with_base_url(deepgram.base_url.clone()),
+            )
+        }
+        SttProviderType::OpenAIRealtime => {
+            let realtime = &config.openai_realtime_config;
+            let server_vad = realtime.server_vad.unwrap_or(true).then(|| {
+                let defaults = ServerVad::default();
+                ServerVad {
+                    threshold: realtime.vad_threshold.unwrap_or(defaults.threshold),
+                    silence_duration_ms: realtime
+                        .silence_duration_ms
+                        .unwrap_or(defaults.silence_duration_ms),
+                    ..defaults
+                }
+            });
+            Box::new(
+                OpenAIRealtimeProvider::new(
+                    stt_api_key(config, "openai_realtime").unwrap_or_default(),
+                )
+                .with_model(realtime.model.clone())
+                .with_language(realtime.language.clone())
+                .with_prompt(realtime.prompt.clone())
+                .with_server_vad(server_vad)
+                .with_base_url(realtime.base_url.clone()),
+            )
+        }
+        SttProviderType::AssemblyAI => {
+            let assemblyai = &config.assemblyai_config;
+            Box::new(
+                AssemblyAiProvider::new(
+                    config
+                        .api_keys
+                        .get("assemblyai")
+                        .cloned()
+                        .unwrap_or_default(),
+                )
+                .with_speech_model(assemblyai.speech_model.clone())
+                .with_keyterms(
+                    assemblyai
+                        .keyterms
+                        .iter()
+                        .cloned()
+                        .chain(dictionary_terms()),
+                )
+                .with_format_turns(assemblyai.format_turns.unwrap_or(true))
+                .with_turn_detection(
+                    assemblyai.end_of_turn_confidence_threshold,
+                    assemblyai.max_turn_silence_ms,
+                )
+                .with_temporary_token(assemblyai.temporary_token.unwrap_or(false))
+                .with_speaker_labels(diarize)
+                .with_base_url(assemblyai.base_url.clone()),
+            )
+        }
+        SttProviderType::CustomStreaming => {
+            Box::new(StreamingSttProvider::new(streaming_stt_endpoint(config)))
+        }
+        SttProviderType::AppleStt => {
+            #[cfg(target_os = "macos")]
+            {
+                Box::new(AppleSttProvider::new(config.apple_stt_locale.clone()))
+            }
+            #[cfg(not(target_os = "macos"))]
+            {
+                return Err("Apple STT is only available on macOS 26+".to_string());
+            }
+        }
+        SttProviderType::LocalWhisper => {
+            #[cfg(feature = "local-whisper")]
+            {
+                let local = &config.local_whisper_config;
+                Box::new(
+                    LocalWhisperProvider::new(local.model_path.as_deref().unwrap_or_default())
+                        .with_language(local.language.clone())
+                        .with_threads(local.threads.map(|t| t as usize))
+                        .with_window(local.step_ms, local.length_ms)
+                        .with_style_prompt(http.style_prompt.clone())
+                        .with_vocabulary(dictionary_terms())
+                        .with_hallucination_filter(hallucination_filter),
+                )
+            }
+            #[cfg(not(feature = "local-whisper"))]
+            {
+                return Err(
+                    "On-device Whisper needs a build with the local-whisper feature".to_string(),
+                );
+            }
+        }
+        _ => {
+            let endpoint = rest_stt_endpoint(provider_type, config)
+                .ok_or_else(|| format!("Unknown STT provider: {}", provider_type.id()))?;
+            Box::new(
+                RestSttProvider::new(endpoint)
+                    .with_hallucination_filter(hallucination_filter)
+                    .with_style_prompt(http.style_prompt.clone()),
+            )
+        }
+    };
+    Ok(provider)
+}
+
+/// Build an STT provider and check it has the API key or endpoint it needs
+fn create_ready_stt_provider(
+    provider_type: &SttProviderType,
+    config: &AppConfig,
+) -> Result<Box<dyn SttProvider>, String> {
+    let stt = create_stt_provider(provider_type, config)?;
+    let provider_name = stt_provider_name(provider_type, config);
+    if stt.capabilities().requires_api_key && stt_api_key(config, provider_type.id()).is_none() {
+        return Err(format!(
+            "{} API key not configured. Please add your API key in Settings",
+            provider_name
+        ));
+    }
+    let missing_url = match provider_type {
+        SttProviderType::CustomStreaming => !streaming_stt_endpoint(config).is_configured(),
+        provider_type => rest_stt_endpoint(provider_type, config).is_some_and(|e| e.url.is_empty()),
+    };
+    if missing_url {
+        return Err(format!(
+            "{} endpoint not configured. Please set its URL in Settings",
+            provider_name
+        ));
+    }
+    Ok(stt)
+}
+
+/// Wrap `primary` in a failover chain of the configured fallback providers
+/// that are ready to use; without any it is returned as is
+fn with_stt_fallbacks(primary: Box<dyn SttProvider>, config: &AppConfig) -> Box<dyn SttProvider> {
+    let mut seen = vec![&config.stt_provider];
+    let mut fallbacks = Vec::new();
+    for provider_type in &config.stt_fallback_providers {
+        if seen.contains(&provider_type) {
+            continue;
+        }
+        seen.push(provider_type);
+        match create_ready_stt_provider(provider_type, config) {
+            Ok(provider) => fallbacks.push((provider_type.id(), provider)),
+            Err(e) => tracing::warn!("Skipping STT fallback {}: {}", provider_type.id(), e),
+        }
+    }
+    if fallbacks.is_empty() {
+        return primary;
+    }
+
+    let chain = fallbacks.into_iter().fold(
+        FailoverSttProvider::new(config.stt_provider.id(), primary),
+        |chain, (id, provider)| chain.with_fallback(id, provider),
+    );
+    Box::new(chain)
+}
+
+/// UI grouping for a provider, derived from what it can do
+fn stt_provider_kind(capabilities: &SttCapabilities) -> &'static str {
+    if capabilities.local {
+        "local"
+    } else {
+        match capabilities.mode {
+            SttMode::Streaming => "streaming",
+            SttMode::Chunked => "batch",
+        }
+    }
 }
 
 #[tauri::command]
 async fn get_stt_providers() -> Result<Vec<SttProviderInfo>, String> {
     let config = get_config().await?;
 
-    let mut providers = vec![
-        SttProviderInfo {
-            name: "ElevenLabs Scribe".to_string(),
-            id: "elevenlabs".to_string(),
-            provider_type: "streaming".to_string(),
-            configured: config.api_keys.contains_key("elevenlabs"),
-            requires_api_key: true,
-            model_status: None,
-        },
-        SttProviderInfo {
-            name: "OpenAI Whisper".to_string(),
-            id: "openai".to_string(),
-            provider_type: "batch".to_string(),
-            configured: config.api_keys.contains_key("openai"),
-            requires_api_key: true,
-            model_status: None,
-        },
-        SttProviderInfo {
-            name: "Groq Whisper Turbo".to_string(),
-            id: "groq".to_string(),
-            provider_type: "batch".to_string(),
-            configured: config.api_keys.contains_key("groq"),
-            requires_api_key: true,
-            model_status: None,
-        },
-    ];
+    let mut provider_types: Vec<SttProviderType> = SttProviderType::BUILT_IN
+        .iter()
+        .filter(|provider_type| match provider_type {
+            SttProviderType::AppleStt => cfg!(target_os = "macos"),
+            SttProviderType::LocalWhisper => cfg!(feature = "local-whisper"),
+            _ => true,
+        })
+        .cloned()
+        .collect();
 
-    // Add Apple STT on macOS
-    #[cfg(target_os = "macos")]
+    // Vendor presets and the endpoints defined in config go after the
+    // built-in hosted providers
+    let mut config_ids: Vec<String> = config.stt_endpoints.keys().cloned().collect();
+    config_ids.sort();
+    let mut endpoints = Vec::new();
+    for id in RestEndpoint::preset_ids()
+        .map(String::from)
+        .chain(config_ids)
     {
-        let available = lt_stt::apple::is_available();
-        let model_status = if !available {
-            "unavailable".to_string()
-        } else {
-            let check_locale = resolve_apple_locale(&config.apple_stt_locale);
-            match lt_stt::apple::check_model_status(&check_locale) {
-                lt_stt::apple::SpeechModelStatus::Installed => "installed".to_string(),
-                lt_stt::apple::SpeechModelStatus::NotInstalled => "not_installed".to_string(),
-                lt_stt::apple::SpeechModelStatus::Downloading => "downloading".to_string(),
-                lt_stt::apple::SpeechModelStatus::Unavailable => "unavailable".to_string(),
+        let provider_type = SttProviderType::from_id(&id);
+        if matches!(provider_type, SttProviderType::Endpoint(_))
+            && !endpoints.contains(&provider_type)
+        {
+            endpoints.push(provider_type);
+        }
+    }
+    let after_hosted = provider_types
+        .iter()
+        .position(|p| *p == SttProviderType::Groq)
+        .map_or(provider_types.len(), |i| i + 1);
+    provider_types.splice(after_hosted..after_hosted, endpoints);
+
+    let mut providers = Vec::with_capacity(provider_types.len());
+    for provider_type in &provider_types {
+        let id = provider_type.id();
+        let name = stt_provider_name(provider_type, &config);
+        let capabilities = create_stt_provider(provider_type, &config)?.capabilities();
+
+        let (configured, model_status) = match provider_type {
+            #[cfg(target_os = "macos")]
+            SttProviderType::AppleStt => {
+                let available = lt_stt::apple::is_available();
+                let status = if !available {
+                    "unavailable".to_string()
+                } else {
+                    let check_locale = resolve_apple_locale(&config.apple_stt_locale);
+                    match lt_stt::apple::check_model_status(&check_locale) {
+                        lt_stt::apple::SpeechModelStatus::Installed => "installed".to_string(),
+                        lt_stt::apple::SpeechModelStatus::NotInstalled => {
+                            "not_installed".to_string()
+                        }
+                        lt_stt::apple::SpeechModelStatus::Downloading => "downloading".to_string(),
+                        lt_stt::apple::SpeechModelStatus::Unavailable => "unavailable".to_string(),
+                    }
+                };
+                (available && status == "installed", Some(status))
+            }
+            SttProviderType::CustomStreaming => {
+                (streaming_stt_endpoint(&config).is_configured(), None)
             }
+            #[cfg(feature = "local-whisper")]
+            SttProviderType::LocalWhisper => {
+                let model_path = config.local_whisper_config.model_path.as_deref();
+                let provider = LocalWhisperProvider::new(model_path.unwrap_or_default());
+                (provider.model_path().is_file(), None)
+            }
+            _ => match rest_stt_endpoint(provider_type, &config) {
+                Some(endpoint) => (endpoint.is_configured(), None),
+                None => (
+                    !capabilities.requires_api_key || stt_api_key(&config, id).is_some(),
+                    None,
+                ),
+            },
         };
 
         providers.push(SttProviderInfo {
-            name: "Apple Speech".to_string(),
-            id: "apple_stt".to_string(),
-            provider_type: "local".to_string(),
-            configured: available && model_status == "installed",
-            requires_api_key: false,
-            model_status: Some(model_status),
+            name,
+            id: id.to_string(),
+            provider_type: stt_provider_kind(&capabilities).to_string(),
+            configured,
+            requires_api_key: capabilities.requires_api_key,
+            model_status,
+            capabilities,
         });
     }
 
-    // Add custom STT endpoint
-    providers.push(SttProviderInfo {
-        name: config
-            .http_stt_config
-            .custom_display_name
-            .clone()
-            .unwrap_or_else(|| "Custom Endpoint".to_string()),
-        id: "custom_stt".to_string(),
-        provider_type: "batch".to_string(),
-        configured: config.http_stt_config.custom_base_url.is_some(),
-        requires_api_key: false,
-        model_status: None,
-    });
-
     Ok(providers)
 }
 
+#[derive(Clone, serde::Serialize)]
+struct SttProviderTestResult {
+    id: String,
+    name: String,
+    ok: bool,
+    latency_ms: Option<u64>,
+    error: Option<String>,
+    detail: Option<lt_core::ProviderError>,
+}
+
+/// Run a connection test against every configured STT provider in parallel
+#[tauri::command]
+async fn test_stt_providers() -> Result<Vec<SttProviderTestResult>, String> {
+    let config = get_config().await?;
+
+    let mut checks = Vec::new();
+    for info in get_stt_providers().await? {
+        if !info.configured {
+            continue;
+        }
+        let provider = create_stt_provider(&parse_stt_provider(&info.id, &config)?, &config)?;
+        let check = tauri::async_runtime::spawn(async move { provider.health_check().await });
+        checks.push((info, check));
+    }
+
+    let mut results = Vec::with_capacity(checks.len());
+    for (info, check) in checks {
+        let outcome = check
+            .await
+            .map_err(|e| format!("Connection test for {} panicked: {}", info.name, e))?;
+        let result = match outcome {
+            Ok(health) => {
+                tracing::info!("{} reachable ({}ms)", info.name, health.latency_ms);
+                SttProviderTestResult {
+                    id: info.id,
+                    name: info.name,
+                    ok: true,
+                    latency_ms: Some(health.latency_ms),
+                    error: None,
+                    detail: None,
+                }
+            }
+            Err(e) => {
+                tracing::warn!("{} connection test failed: {}", info.name, e);
+                SttProviderTestResult {
+                    id: info.id,
+                    name: info.name,
+                    ok: false,
+                    latency_ms: None,
+                    error: Some(e.to_string()),
+                    detail: e.provider_error().cloned(),
+                }
+            }
+        };
+        results.push(result);
+    }
+
+    Ok(results)
+}
+
 // ============================================================================
 // Apple STT Commands (macOS only)
 // ============================================================================
@@ -328,109 +772,13 @@ async fn set_apple_stt_locale(locale: String) -> Result<(), String> {
 
 #[tauri::command]
 async fn get_elevenlabs_languages() -> Result<Vec<(String, String)>, String> {
-    // Full list of ElevenLabs Scribe v2 supported languages (ISO 639-3 codes)
-    Ok(vec![
-        ("auto".to_string(), "Auto-detect".to_string()),
-        ("afr".to_string(), "Afrikaans".to_string()),
-        ("amh".to_string(), "Amharic".to_string()),
-        ("ara".to_string(), "Arabic".to_string()),
-        ("hye".to_string(), "Armenian".to_string()),
-        ("asm".to_string(), "Assamese".to_string()),
-        ("ast".to_string(), "Asturian".to_string()),
-        ("aze".to_string(), "Azerbaijani".to_string()),
-        ("bel".to_string(), "Belarusian".to_string()),
-        ("ben".to_string(), "Bengali".to_string()),
-        ("bos".to_string(), "Bosnian".to_string()),
-        ("bul".to_string(), "Bulgarian".to_string()),
-        ("mya".to_string(), "Burmese".to_string()),
-        ("yue".to_string(), "Cantonese".to_string()),
-        ("cat".to_string(), "Catalan".to_string()),
-        ("ceb".to_string(), "Cebuano".to_string()),
-        ("nya".to_string(), "Chichewa".to_string()),
-        ("hrv".to_string(), "Croatian".to_string()),
-        ("ces".to_string(), "Czech".to_string()),
-        ("dan".to_string(), "Danish".to_string()),
-        ("nld".to_string(), "Dutch".to_string()),
-        ("eng".to_string(), "English".to_string()),
-        ("est".to_string(), "Estonian".to_string()),
-        ("fil".to_string(), "Filipino".to_string()),
-        ("fin".to_string(), "Finnish".to_string()),
-        ("fra".to_string(), "French".to_string()),
-        ("ful".to_string(), "Fulah".to_string()),
-        ("glg".to_string(), "Galician".to_string()),
-        ("lug".to_string(), "Ganda".to_string()),
-        ("kat".to_string(), "Georgian".to_string()),
-        ("deu".to_string(), "German".to_string()),
-        ("ell".to_string(), "Greek".to_string()),
-        ("guj".to_string(), "Gujarati".to_string()),
-        ("hau".to_string(), "Hausa".to_string()),
-        ("heb".to_string(), "Hebrew".to_string()),
-        ("hin".to_string(), "Hindi".to_string()),
-        ("hun".to_string(), "Hungarian".to_string()),
-        ("isl".to_string(), "Icelandic".to_string()),
-        ("ibo".to_string(), "Igbo".to_string()),
-        ("ind".to_string(), "Indonesian".to_string()),
-        ("gle".to_string(), "Irish".to_string()),
-        ("ita".to_string(), "Italian".to_string()),
-        ("jpn".to_string(), "Japanese".to_string()),
-        ("jav".to_string(), "Javanese".to_string()),
-        ("kea".to_string(), "Kabuverdianu".to_string()),
-        ("kan".to_string(), "Kannada".to_string()),
-        ("kaz".to_string(), "Kazakh".to_string()),
-        ("khm".to_string(), "Khmer".to_string()),
-        ("kor".to_string(), "Korean".to_string()),
-        ("kur".to_string(), "Kurdish".to_string()),
-        ("kir".to_string(), "Kyrgyz".to_string()),
-        ("lao".to_string(), "Lao".to_string()),
-        ("lav".to_string(), "Latvian".to_string()),
-        ("lin".to_string(), "Lingala".to_string()),
-        ("lit".to_string(), "Lithuanian".to_string()),
-        ("luo".to_string(), "Luo".to_string()),
-        ("ltz".to_string(), "Luxembourgish".to_string()),
-        ("mkd".to_string(), "Macedonian".to_string()),
-        ("msa".to_string(), "Malay".to_string()),
-        ("mal".to_string(), "Malayalam".to_string()),
-        ("mlt".to_string(), "Maltese".to_string()),
-        ("zho".to_string(), "Mandarin Chinese".to_string()),
-        ("mri".to_string(), "Māori".to_string()),
-        ("mar".to_string(), "Marathi".to_string()),
-        ("mon".to_string(), "Mongolian".to_string()),
-        ("nep".to_string(), "Nepali".to_string()),
-        ("nso".to_string(), "Northern Sotho".to_string()),
-        ("nor".to_string(), "Norwegian".to_string()),
-        ("oci".to_string(), "Occitan".to_string()),
-        ("ori".to_string(), "Odia".to_string()),
-        ("pus".to_string(), "Pashto".to_string()),
-        ("fas".to_string(), "Persian".to_string()),
-        ("pol".to_string(), "Polish".to_string()),
-        ("por".to_string(), "Portuguese".to_string()),
-        ("pan".to_string(), "Punjabi".to_string()),
-        ("ron".to_string(), "Romanian".to_string()),
-        ("rus".to_string(), "Russian".to_string()),
-        ("srp".to_string(), "Serbian".to_string()),
-        ("sna".to_string(), "Shona".to_string()),
-        ("snd".to_string(), "Sindhi".to_string()),
-        ("slk".to_string(), "Slovak".to_string()),
-        ("slv".to_string(), "Slovenian".to_string()),
-        ("som".to_string(), "Somali".to_string()),
-        ("spa".to_string(), "Spanish".to_string()),
-        ("swa".to_string(), "Swahili".to_string()),
-        ("swe".to_string(), "Swedish".to_string()),
-        ("tgk".to_string(), "Tajik".to_string()),
-        ("tam".to_string(), "Tamil".to_string()),
-        ("tel".to_string(), "Telugu".to_string()),
-        ("tha".to_string(), "Thai".to_string()),
-        ("tur".to_string(), "Turkish".to_string()),
-        ("ukr".to_string(), "Ukrainian".to_string()),
-        ("umb".to_string(), "Umbundu".to_string()),
-        ("urd".to_string(), "Urdu".to_string()),
-        ("uzb".to_string(), "Uzbek".to_string()),
-        ("vie".to_string(), "Vietnamese".to_string()),
-        ("cym".to_string(), "Welsh".to_string()),
-        ("wol".to_string(), "Wolof".to_string()),
-        ("xho".to_string(), "Xhosa".to_string()),
-        ("zul".to_string(), "Zulu".to_string()),
-    ])
+    let mut languages = vec![("auto".to_string(), "Auto-detect".to_string())];
+    languages.extend(
+        lt_stt::elevenlabs::SUPPORTED_LANGUAGES
+            .iter()
+            .map(|(code, name)| (code.to_string(), name.to_string())),
+    );
+    Ok(languages)
 }
 
 #[tauri::command]
@@ -462,6 +810,7 @@ struct LlmProcessorInfo {
     requires_api_key: bool,
     configured: bool,
     api_key_name: Option<String>,
+    capabilities: LlmCapabilities,
 }
 
 #[tauri::command]
@@ -486,6 +835,7 @@ async fn get_llm_processors() -> Result<Vec<LlmProcessorInfo>, String> {
             requires_api_key: false,
             configured: true,
             api_key_name: None,
+            capabilities: gemini.capabilities(),
         },
         LlmProcessorInfo {
             name: "Copilot CLI".to_string(),
@@ -496,60 +846,87 @@ async fn get_llm_processors() -> Result<Vec<LlmProcessorInfo>, String> {
             requires_api_key: false,
             configured: true,
             api_key_name: None,
+            capabilities: copilot.capabilities(),
         },
     ];
 
     #[cfg(target_os = "macos")]
     {
+        let capabilities = AppleLlmProcessor::new().capabilities();
         processors.push(LlmProcessorInfo {
             name: "Apple Intelligence".to_string(),
             id: "apple_llm".to_string(),
             available: AppleLlmProcessor::is_available(),
             default_model: lt_llm::apple::DEFAULT_MODEL.to_string(),
             provider_type: "local".to_string(),
-            requires_api_key: false,
+            requires_api_key: capabilities.requires_api_key,
             configured: true,
             api_key_name: None,
+            capabilities,
         });
     }
 
-    // HTTP API processors
-    let openai_configured = config.api_keys.contains_key("openai");
-    let anthropic_configured = config.api_keys.contains_key("anthropic");
-    let google_ai_configured = config.api_keys.contains_key("google_ai");
-    let custom_configured = config.api_keys.contains_key("custom_llm");
-
-    processors.push(LlmProcessorInfo {
-        name: "OpenAI API".to_string(),
-        id: "openai_api".to_string(),
-        available: openai_configured,
-        default_model: lt_llm::http_api::OPENAI_DEFAULT_MODEL.to_string(),
-        provider_type: "http".to_string(),
-        requires_api_key: true,
-        configured: openai_configured,
-        api_key_name: Some("openai".to_string()),
-    });
+    let ollama = ollama_processor(None, &config, &PromptManager::new());
     processors.push(LlmProcessorInfo {
-        name: "Claude API".to_string(),
-        id: "claude_api".to_string(),
-        available: anthropic_configured,
-        default_model: lt_llm::http_api::CLAUDE_DEFAULT_MODEL.to_string(),
-        provider_type: "http".to_string(),
-        requires_api_key: true,
-        configured: anthropic_configured,
-        api_key_name: Some("anthropic".to_string()),
-    });
-    processors.push(LlmProcessorInfo {
-        name: "Gemini API".to_string(),
-        id: "gemini_api".to_string(),
-        available: google_ai_configured,
-        default_model: lt_llm::http_api::GEMINI_API_DEFAULT_MODEL.to_string(),
-        provider_type: "http".to_string(),
-        requires_api_key: true,
-        configured: google_ai_configured,
-        api_key_name: Some("google_ai".to_string()),
+        name: "Ollama".to_string(),
+        id: "ollama".to_string(),
+        available: ollama.health_check().await.unwrap_or(false),
+        default_model: lt_llm::ollama::DEFAULT_MODEL.to_string(),
+        provider_type: "local".to_string(),
+        requires_api_key: false,
+        configured: true,
+        api_key_name: None,
+        capabilities: ollama.capabilities(),
     });
 
+    // HTTP API processors: requirements come from each processor's capabilities
+    let http_processors = [
+        (
+            "OpenAI API",
+            "openai_api",
+            "openai",
+            lt_llm::http_api::OPENAI_DEFAULT_MODEL,
+            HttpLlmProcessor::openai(String::new(), None).capabilities(),
+        ),
+        (
+            "Claude API",
+            "claude_api",
+            "anthropic",
+            lt_llm::http_api::CLAUDE_DEFAULT_MODEL,
+            HttpLlmProcessor::claude(String::new(), None).capabilities(),
+        ),
+        (
+            "Gemini API",
+            "gemini_api",
+            "google_ai",
+            lt_llm::http_api::GEMINI_API_DEFAULT_MODEL,
+            HttpLlmProcessor::gemini_api(String::new(), None).capabilities(),
+        ),
+    ];
+    for (name, id, key_name, default_model, capabilities) in http_processors {
+        let configured = !capabilities.requires_api_key || config.api_keys.contains_key(key_name);
+        processors.push(LlmProcessorInfo {
+            name: name.to_string(),
+            id: id.to_string(),
+            available: configured,
+            default_model: default_model.to_string(),
+            provider_type: "http".to_string(),
+            requires_api_key: capabilities.requires_api_key,
+            configured,
+            api_key_name: Some(key_name.to_string()),
+            capabilities,
+        });
+    }
+
+    let custom_base_url = config.http_llm_config.custom_base_url.clone();
+    let custom_capabilities = HttpLlmProcessor::custom(
+        custom_base_url.clone().unwrap_or_default(),
+        String::new(),
+        None,
+    )
+    .capabilities();
+    let custom_configured = custom_base_url.is_some()
+        && (!custom_capabilities.requires_api_key || config.api_keys.contains_key("custom_llm"));
     let custom_name = config
         .http_llm_config
         .custom_display_name
@@ -557,12 +934,13 @@ async fn get_llm_processors() -> Result<Vec<LlmProcessorInfo>, String> {
     processors.push(LlmProcessorInfo {
         name: custom_name,
         id: "custom_api".to_string(),
-        available: custom_configured && config.http_llm_config.custom_base_url.is_some(),
+        available: custom_configured,
         default_model: lt_llm::http_api::OPENAI_DEFAULT_MODEL.to_string(),
         provider_type: "custom".to_string(),
-        requires_api_key: true,
+        requires_api_key: custom_capabilities.requires_api_key,
         configured: custom_configured,
         api_key_name: Some("custom_llm".to_string()),
+        capabilities: custom_capabilities,
     });
 
     Ok(processors)
@@ -616,10 +994,10 @@ fn create_llm_processor(
         LlmProcessorType::OpenAiApi => {
             let api_key = config.api_keys.get("openai").cloned().unwrap_or_default();
             tracing::info!("Using OpenAI API as LLM processor");
-            Arc::new(HttpLlmProcessor::openai_with_prompts(
-                api_key,
-                model,
-                prompts.clone(),
+            Arc::new(with_http_llm_overrides(
+                HttpLlmProcessor::openai_with_prompts(api_key, model, prompts.clone()),
+                config,
+                "openai_api",
             ))
         }
         LlmProcessorType::ClaudeApi => {
@@ -629,10 +1007,10 @@ fn create_llm_processor(
                 .cloned()
                 .unwrap_or_default();
             tracing::info!("Using Claude API as LLM processor");
-            Arc::new(HttpLlmProcessor::claude_with_prompts(
-                api_key,
-                model,
-                prompts.clone(),
+            Arc::new(with_http_llm_overrides(
+                HttpLlmProcessor::claude_with_prompts(api_key, model, prompts.clone()),
+                config,
+                "claude_api",
             ))
         }
         LlmProcessorType::GeminiApi => {
@@ -642,10 +1020,10 @@ fn create_llm_processor(
                 .cloned()
                 .unwrap_or_default();
             tracing::info!("Using Gemini API as LLM processor");
-            Arc::new(HttpLlmProcessor::gemini_api_with_prompts(
-                api_key,
-                model,
-                prompts.clone(),
+            Arc::new(with_http_llm_overrides(
+                HttpLlmProcessor::gemini_api_with_prompts(api_key, model, prompts.clone()),
+                config,
+                "gemini_api",
             ))
         }
         LlmProcessorType::CustomApi => {
@@ -660,14 +1038,171 @@ fn create_llm_processor(
                 .clone()
                 .unwrap_or_else(|| "http://localhost:11434/v1".to_string());
             tracing::info!("Using custom endpoint ({}) as LLM processor", base_url);
-            Arc::new(HttpLlmProcessor::custom_with_prompts(
-                base_url,
-                api_key,
-                model,
-                prompts.clone(),
+            Arc::new(with_http_llm_overrides(
+                HttpLlmProcessor::custom_with_prompts(base_url, api_key, model, prompts.clone()),
+                config,
+                "custom_api",
             ))
         }
+        LlmProcessorType::Ollama => {
+            let processor = ollama_processor(model, config, prompts);
+            tracing::info!("Using Ollama as LLM processor");
+            Arc::new(processor)
+        }
+    }
+}
+
+/// Build the native Ollama processor from the `[ollama_config]` settings
+fn ollama_processor(
+    model: Option<String>,
+    config: &AppConfig,
+    prompts: &PromptManager,
+) -> OllamaProcessor {
+    let ollama = &config.ollama_config;
+    let mut processor =
+        OllamaProcessor::with_prompts(ollama.base_url.clone(), model, prompts.clone());
+    if let Some(keep_alive) = ollama.keep_alive.as_deref().filter(|k| !k.is_empty()) {
+        processor = processor.with_keep_alive(keep_alive);
+    }
+    if let Some(num_ctx) = ollama.num_ctx {
+        processor = processor.with_num_ctx(num_ctx);
+    }
+    if let Some(temperature) = ollama.temperature {
+        processor = processor.with_option("temperature", temperature.into());
+    }
+    for (name, value) in &ollama.options {
+        processor = processor.with_option(name.clone(), value.clone());
+    }
+    if let Some(timeout_secs) = ollama.timeout_secs {
+        processor = processor.with_timeout(timeout_secs);
+    }
+    if let Some(overrides) = config.http_llm_config.retry.get("ollama") {
+        processor = processor.with_retry(RetryPolicy::default().with_overrides(overrides));
+    }
+    processor
+}
+
+/// Parse an LLM processor id as sent by the frontend
+fn parse_llm_processor(processor: &str) -> Result<LlmProcessorType, String> {
+    match processor.to_lowercase().as_str() {
+        "gemini" => Ok(LlmProcessorType::Gemini),
+        "copilot" => Ok(LlmProcessorType::Copilot),
+        "apple_llm" => Ok(LlmProcessorType::AppleLlm),
+        "openai_api" => Ok(LlmProcessorType::OpenAiApi),
+        "claude_api" => Ok(LlmProcessorType::ClaudeApi),
+        "gemini_api" => Ok(LlmProcessorType::GeminiApi),
+        "custom_api" => Ok(LlmProcessorType::CustomApi),
+        "ollama" => Ok(LlmProcessorType::Ollama),
+        _ => Err(format!("Unknown LLM processor: {}", processor)),
+    }
+}
+
+/// The models of `processor_type`, from the cache unless `refresh`
+async fn llm_models(
+    processor_type: LlmProcessorType,
+    config: &AppConfig,
+    state: &AppState,
+    refresh: bool,
+) -> Result<Vec<LlmModelInfo>, String> {
+    let processor = create_llm_processor(&processor_type, None, config, &state.prompts);
+    state
+        .llm_models
+        .models(processor_type.id(), processor.as_ref(), refresh)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Whether `model` is one of the processor's models, refetching a cached
+/// list that lacks it. None when the processor can't list its models or
+/// can't be reached.
+async fn is_known_model(
+    processor_type: LlmProcessorType,
+    model: &str,
+    config: &AppConfig,
+    state: &AppState,
+) -> Option<bool> {
+    for refresh in [false, true] {
+        match llm_models(processor_type, config, state, refresh).await {
+            Ok(models) if models.is_empty() => return None,
+            Ok(models) => {
+                // Ollama resolves a bare name to its ":latest" tag
+                if models
+                    .iter()
+                    .any(|m| m.id == model || m.id.strip_suffix(":latest") == Some(model))
+                {
+                    return Some(true);
+                }
+            }
+            Err(e) => {
+                tracing::warn!("Could not list models to check '{}': {}", model, e);
+                return None;
+            }
+        }
+    }
+    Some(false)
+}
+
+/// Models offered by `processor` (default: the active one), for the model
+/// picker. Empty when the processor can't list them.
+#[tauri::command]
+async fn get_llm_models(
+    processor: Option<String>,
+    refresh: Option<bool>,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<LlmModelInfo>, String> {
+    let config = get_config().await?;
+    let processor_type = match processor {
+        Some(processor) => parse_llm_processor(&processor)?,
+        None => config.llm_processor,
+    };
+    llm_models(processor_type, &config, &state, refresh.unwrap_or(false)).await
+}
+
+/// The pipeline's LLM processor: the selected one, behind the task routes
+/// (`llm_task_routes`) and ahead of the fallbacks (`llm_fallback_processors`)
+fn create_pipeline_llm_processor(
+    config: &AppConfig,
+    prompts: &PromptManager,
+) -> Arc<dyn LlmProcessor> {
+    let build = |spec: &LlmProcessorSpec| {
+        create_llm_processor(&spec.processor, spec.model.clone(), config, prompts)
+    };
+    let primary = LlmProcessorSpec {
+        processor: config.llm_processor,
+        model: config.llm_model.clone(),
+    };
+    let mut composite = CompositeProcessor::new(primary.label(), build(&primary));
+    for spec in &config.llm_fallback_processors {
+        composite = composite.with_fallback(spec.label(), build(spec));
+    }
+    for (task_kind, spec) in &config.llm_task_routes {
+        if !ProcessingTask::KINDS.contains(&task_kind.as_str()) {
+            tracing::warn!(
+                "Ignoring LLM route for unknown task '{}' (expected one of {:?})",
+                task_kind,
+                ProcessingTask::KINDS
+            );
+            continue;
+        }
+        composite = composite.with_route(task_kind.clone(), spec.label(), build(spec));
     }
+    Arc::new(composite)
+}
+
+/// Apply the user's retry and timeout overrides to an HTTP LLM provider
+fn with_http_llm_overrides(
+    mut processor: HttpLlmProcessor,
+    config: &AppConfig,
+    provider_id: &str,
+) -> HttpLlmProcessor {
+    let llm_config = &config.http_llm_config;
+    if let Some(overrides) = llm_config.retry.get(provider_id) {
+        processor = processor.with_retry(RetryPolicy::default().with_overrides(overrides));
+    }
+    if let Some(&timeout_secs) = llm_config.timeout_secs.get(provider_id) {
+        processor = processor.with_timeout(timeout_secs);
+    }
+    processor
 }
 
 #[tauri::command]
@@ -685,17 +1220,7 @@ async fn set_llm_processor(
         AppConfig::default()
     };
 
-    // Parse processor string to LlmProcessorType
-    let processor_type = match processor.to_lowercase().as_str() {
-        "gemini" => LlmProcessorType::Gemini,
-        "copilot" => LlmProcessorType::Copilot,
-        "apple_llm" => LlmProcessorType::AppleLlm,
-        "openai_api" => LlmProcessorType::OpenAiApi,
-        "claude_api" => LlmProcessorType::ClaudeApi,
-        "gemini_api" => LlmProcessorType::GeminiApi,
-        "custom_api" => LlmProcessorType::CustomApi,
-        _ => return Err(format!("Unknown LLM processor: {}", processor)),
-    };
+    let processor_type = parse_llm_processor(&processor)?;
 
     config.llm_processor = processor_type;
 
@@ -704,12 +1229,7 @@ async fn set_llm_processor(
         .map_err(|e| format!("Failed to save config: {}", e))?;
 
     // Hot-swap the live pipeline's LLM processor
-    let new_processor = create_llm_processor(
-        &processor_type,
-        config.llm_model.clone(),
-        &config,
-        &state.prompts,
-    );
+    let new_processor = create_pipeline_llm_processor(&config, &state.prompts);
     let pipeline = state.pipeline.lock().await;
     pipeline.set_llm_processor(new_processor).await;
 
@@ -735,17 +1255,22 @@ async fn set_llm_model(model: String, state: tauri::State<'_, AppState>) -> Resu
         Some(model.trim().to_string())
     };
 
+    if let Some(model) = &config.llm_model {
+        if is_known_model(config.llm_processor, model, &config, &state).await == Some(false) {
+            return Err(format!(
+                "Unknown model '{}' for {}",
+                model,
+                config.llm_processor.id()
+            ));
+        }
+    }
+
     config
         .save_to_file(&config_path)
         .map_err(|e| format!("Failed to save config: {}", e))?;
 
     // Hot-swap the live pipeline's LLM processor with new model
-    let new_processor = create_llm_processor(
-        &config.llm_processor,
-        config.llm_model.clone(),
-        &config,
-        &state.prompts,
-    );
+    let new_processor = create_pipeline_llm_processor(&config, &state.prompts);
     let pipeline = state.pipeline.lock().await;
     pipeline.set_llm_processor(new_processor).await;
 
@@ -761,6 +1286,7 @@ async fn set_llm_model(model: String, state: tauri::State<'_, AppState>) -> Resu
 async fn set_custom_llm_endpoint(
     base_url: String,
     display_name: Option<String>,
+    state: tauri::State<'_, AppState>,
 ) -> Result<(), String> {
     let config_path = AppConfig::default_config_file()
         .map_err(|e| format!("Failed to get config path: {}", e))?;
@@ -781,7 +1307,10 @@ async fn set_custom_llm_endpoint(
 
     config
         .save_to_file(&config_path)
-        .map_err(|e| format!("Failed to save config: {}", e))
+        .map_err(|e| format!("Failed to save config: {}", e))?;
+
+    state.llm_models.clear().await;
+    Ok(())
 }
 
 #[tauri::command]
@@ -941,70 +1470,14 @@ async fn start_pipeline(
     };
 
     // Create STT provider based on config
-    let stt: Box<dyn SttProvider> = match config.stt_provider {
-        SttProviderType::ElevenLabs => {
-            let api_key = config
-                .api_keys
-                .get("elevenlabs")
-                .ok_or_else(|| {
-                    "ElevenLabs API key not configured. Please add your API key in Settings"
-                        .to_string()
-                })?
-                .clone();
-            Box::new(ElevenLabsProvider::with_config(
-                api_key,
-                "scribe_v2_realtime".to_string(),
-                config.elevenlabs_language.clone(),
-            ))
-        }
-        SttProviderType::OpenAI => {
-            let api_key = config
-                .api_keys
-                .get("openai")
-                .ok_or_else(|| {
-                    "OpenAI API key not configured. Please add your API key in Settings".to_string()
-                })?
-                .clone();
-            Box::new(OpenAIProvider::new(api_key))
-        }
-        SttProviderType::Groq => {
-            let api_key = config
-                .api_keys
-                .get("groq")
-                .ok_or_else(|| {
-                    "Groq API key not configured. Please add your API key in Settings".to_string()
-                })?
-                .clone();
-            Box::new(GroqProvider::new(api_key))
-        }
-        SttProviderType::AppleStt => {
-            #[cfg(target_os = "macos")]
-            {
-                Box::new(AppleSttProvider::new(config.apple_stt_locale.clone()))
-            }
-            #[cfg(not(target_os = "macos"))]
-            {
-                return Err("Apple STT is only available on macOS 26+".to_string());
-            }
-        }
-        SttProviderType::CustomStt => {
-            let base_url = config
-                .http_stt_config
-                .custom_base_url
-                .clone()
-                .ok_or_else(|| {
-                    "Custom STT endpoint not configured. Please set a base URL in Settings"
-                        .to_string()
-                })?;
-            let api_key = config.api_keys.get("custom_stt").cloned();
-            Box::new(CustomSttProvider::new(
-                base_url,
-                api_key,
-                config.http_stt_config.custom_model.clone(),
-                config.http_stt_config.language.clone(),
-            ))
-        }
-    };
+    let stt = create_ready_stt_provider(&config.stt_provider, &config)?;
+    let provider_id = config.stt_provider.id().to_string();
+    let stt = with_stt_fallbacks(stt, &config);
+
+    let queue_enabled = config.offline_queue.enabled.unwrap_or(false);
+    pipeline
+        .set_offline_queue(queue_enabled.then(|| state.offline_queue.clone()))
+        .await;
 
     // Subscribe to pipeline events before starting
     let mut event_rx = pipeline.subscribe_events();
@@ -1076,13 +1549,25 @@ async fn start_pipeline(
                         },
                     );
                 }
-                PipelineEvent::PartialTranscription { text, timestamp_ms } => {
+                PipelineEvent::PartialTranscription {
+                    text,
+                    timestamp_ms,
+                    metadata,
+                } => {
                     let _ = app_clone.emit(
                         "transcription-partial",
-                        TranscriptionEvent { text, timestamp_ms },
+                        TranscriptionEvent {
+                            text,
+                            timestamp_ms,
+                            metadata,
+                        },
                     );
                 }
-                PipelineEvent::CommittedTranscription { text, timestamp_ms } => {
+                PipelineEvent::CommittedTranscription {
+                    text,
+                    timestamp_ms,
+                    metadata,
+                } => {
                     // Accumulate raw transcription for history
                     if !raw_transcription.is_empty() {
                         raw_transcription.push(' ');
@@ -1091,7 +1576,11 @@ async fn start_pipeline(
 
                     let _ = app_clone.emit(
                         "transcription-committed",
-                        TranscriptionEvent { text, timestamp_ms },
+                        TranscriptionEvent {
+                            text,
+                            timestamp_ms,
+                            metadata,
+                        },
                     );
                 }
                 PipelineEvent::CommandDetected {
@@ -1112,6 +1601,8 @@ async fn start_pipeline(
                 PipelineEvent::FinalResult {
                     text,
                     processing_time_ms,
+                    raw_metadata,
+                    llm_processor,
                 } => {
                     tracing::info!(
                         "Pipeline completed: {} chars in {}ms",
@@ -1124,6 +1615,7 @@ async fn start_pipeline(
                         FinalResultEvent {
                             text: text.clone(),
                             processing_time_ms,
+                            llm_processor: llm_processor.clone(),
                         },
                     );
 
@@ -1150,23 +1642,37 @@ async fn start_pipeline(
                         timestamp_ms,
                         processing_time_ms,
                         command_name: cmd,
+                        words: raw_metadata.words,
+                        language: raw_metadata.language,
+                        stt_provider: raw_metadata
+                            .provider
+                            .or_else(|| Some(provider_id.to_string())),
+                        llm_processor,
                     };
-                    if let Ok(config_dir) = AppConfig::default_config_dir() {
-                        let history_path = config_dir.join("history.json");
-                        let mut history = if history_path.exists() {
-                            TranscriptionHistory::load_from_file(&history_path).unwrap_or_default()
-                        } else {
-                            TranscriptionHistory::new()
-                        };
-                        history.add_entry(entry);
-                        if let Err(e) = history.save_to_file(&history_path) {
-                            tracing::warn!("Failed to save history: {}", e);
-                        }
-                    }
+                    save_history_entry(entry);
+                }
+                PipelineEvent::Queued {
+                    id,
+                    reason,
+                    timestamp_ms,
+                } => {
+                    tracing::info!("Dictation {} queued: {}", id, reason);
+                    raw_transcription.clear();
+                    detected_command = None;
+
+                    let _ = app_clone.emit(
+                        "pipeline-queued",
+                        QueuedEvent {
+                            id,
+                            reason,
+                            timestamp_ms,
+                        },
+                    );
                 }
                 PipelineEvent::Error {
                     message,
                     recoverable,
+                    detail,
                 } => {
                     tracing::error!("Pipeline error: {} (recoverable: {})", message, recoverable);
 
@@ -1175,6 +1681,7 @@ async fn start_pipeline(
                         ErrorEvent {
                             message: message.clone(),
                             recoverable,
+                            detail,
                         },
                     );
 
@@ -1203,6 +1710,22 @@ async fn start_pipeline(
     Ok(())
 }
 
+fn save_history_entry(entry: lt_core::history::HistoryEntry) {
+    let Ok(config_dir) = AppConfig::default_config_dir() else {
+        return;
+    };
+    let history_path = config_dir.join("history.json");
+    let mut history = if history_path.exists() {
+        TranscriptionHistory::load_from_file(&history_path).unwrap_or_default()
+    } else {
+        TranscriptionHistory::new()
+    };
+    history.add_entry(entry);
+    if let Err(e) = history.save_to_file(&history_path) {
+        tracing::warn!("Failed to save history: {}", e);
+    }
+}
+
 #[tauri::command]
 async fn stop_pipeline(
     _app: tauri::AppHandle,
@@ -1413,7 +1936,8 @@ struct PromptInfo {
     name: PromptName,
     title: &'static str,
     description: &'static str,
-    required_placeholders: &'static [&'static str],
+    /// Each entry is met by any one of its alternatives
+    required_placeholders: &'static [&'static [&'static str]],
     task_variant: &'static str,
     content: String,
     is_override: bool,
@@ -1635,6 +2159,128 @@ async fn open_history_window(app: tauri::AppHandle) -> Result<(), String> {
     Ok(())
 }
 
+// ============================================================================
+// Offline Queue Commands
+// ============================================================================
+
+/// Run one pass over the offline queue with the configured providers and
+/// save what could be processed to history. Returns how many dictations
+/// were processed.
+async fn process_offline_queue_pass(
+    app: &tauri::AppHandle,
+    pass: QueuePass,
+) -> Result<usize, String> {
+    let state = app.state::<AppState>();
+    let config = get_config().await?;
+    let queue = state.offline_queue.clone();
+    queue.set_max_items(config.offline_queue.max_items.unwrap_or(DEFAULT_MAX_ITEMS));
+
+    let pending = queue
+        .list()
+        .map_err(|e| format!("Failed to read offline queue: {}", e))?;
+    if pending.is_empty() {
+        return Ok(0);
+    }
+
+    let llm = {
+        let pipeline = state.pipeline.lock().await;
+        // Leave the providers to a dictation in progress; the next pass
+        // picks the queue up again
+        if matches!(
+            pipeline.get_state().await,
+            PipelineState::Recording | PipelineState::Transcribing | PipelineState::Processing
+        ) {
+            return Ok(0);
+        }
+        pipeline.llm_processor().await
+    };
+
+    tracing::info!("Processing {} queued dictation(s)", pending.len());
+    let make_stt = || {
+        create_ready_stt_provider(&config.stt_provider, &config)
+            .map(|stt| with_stt_fallbacks(stt, &config))
+            .map_err(lt_core::MurmurError::Config)
+    };
+    let outcomes = queue
+        .process_pending(pass, make_stt, llm.as_ref(), &dictionary_terms())
+        .await
+        .map_err(|e| format!("Failed to process offline queue: {}", e))?;
+
+    let mut processed = 0;
+    for outcome in outcomes {
+        match outcome {
+            QueueOutcome::Processed(done) => {
+                processed += 1;
+                let id = done.item.id.clone();
+                if !done.final_text.is_empty() {
+                    save_history_entry(lt_core::history::HistoryEntry {
+                        id: done.item.created_ms.to_string(),
+                        final_text: done.final_text.clone(),
+                        raw_text: (!done.raw_text.is_empty()).then_some(done.raw_text),
+                        timestamp_ms: done.item.created_ms,
+                        processing_time_ms: done.processing_time_ms,
+                        command_name: done.command_name.clone(),
+                        words: done.metadata.words,
+                        language: done.metadata.language,
+                        stt_provider: done.metadata.provider,
+                        llm_processor: done.llm_processor,
+                    });
+                }
+                let _ = app.emit(
+                    "queue-item-processed",
+                    QueueItemProcessedEvent {
+                        id,
+                        text: done.final_text,
+                        command_name: done.command_name,
+                        processing_time_ms: done.processing_time_ms,
+                    },
+                );
+            }
+            QueueOutcome::Failed { item, error } => {
+                tracing::warn!("Queued dictation {} failed: {}", item.id, error);
+                let _ = app.emit(
+                    "queue-item-failed",
+                    QueueItemFailedEvent {
+                        id: item.id,
+                        message: error.to_string(),
+                    },
+                );
+            }
+        }
+    }
+    Ok(processed)
+}
+
+#[tauri::command]
+async fn get_offline_queue(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<QueuedDictation>, String> {
+    state
+        .offline_queue
+        .list()
+        .map_err(|e| format!("Failed to read offline queue: {}", e))
+}
+
+#[tauri::command]
+async fn process_offline_queue(app: tauri::AppHandle) -> Result<usize, String> {
+    process_offline_queue_pass(&app, QueuePass::Manual).await
+}
+
+#[tauri::command]
+async fn delete_queued_dictation(
+    id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    let removed = state
+        .offline_queue
+        .remove(&id)
+        .map_err(|e| format!("Failed to delete queued dictation: {}", e))?;
+    if !removed {
+        return Err(format!("Queued dictation not found: {}", id));
+    }
+    Ok(())
+}
+
 // ============================================================================
 // Permission Management Commands
 // ============================================================================
@@ -1759,6 +2405,10 @@ fn main() {
         })
         .unwrap_or_default();
 
+    if let Err(e) = lt_http::configure(&config.network) {
+        tracing::warn!("{}, using default network settings", e);
+    }
+
     let is_first_launch = AppConfig::default_config_file()
         .map(|path| !path.exists())
         .unwrap_or(false);
@@ -1782,12 +2432,7 @@ fn main() {
     };
 
     // Initialize LLM processor based on config
-    let llm_processor = create_llm_processor(
-        &config.llm_processor,
-        config.llm_model.clone(),
-        &config,
-        &prompts,
-    );
+    let llm_processor = create_pipeline_llm_processor(&config, &prompts);
 
     // Load dictionary (or create empty if not exists)
     let dictionary = {
@@ -1836,11 +2481,20 @@ fn main() {
         Arc::new(Mutex::new(dictionary)),
     );
 
+    // Dictations queued while offline, kept across restarts
+    let queue_dir = AppConfig::default_config_dir()
+        .map(|dir| dir.join("queue"))
+        .unwrap_or_else(|_| std::env::temp_dir().join("murmur-queue"));
+    let offline_queue = Arc::new(OfflineQueue::new(queue_dir));
+    offline_queue.set_max_items(config.offline_queue.max_items.unwrap_or(DEFAULT_MAX_ITEMS));
+
     // Create app state
     let app_state = AppState {
         pipeline: Arc::new(Mutex::new(pipeline)),
         event_task: Arc::new(Mutex::new(None)),
         prompts,
+        offline_queue,
+        llm_models: ModelCache::new(),
     };
 
     tauri::Builder::default()
@@ -1862,9 +2516,11 @@ fn main() {
             set_stt_provider,
             save_api_key,
             get_stt_providers,
+            test_stt_providers,
             get_llm_processors,
             set_llm_processor,
             set_llm_model,
+            get_llm_models,
             set_custom_llm_endpoint,
             set_custom_stt_endpoint,
             set_output_mode,
@@ -1883,6 +2539,9 @@ fn main() {
             delete_history_entry,
             clear_history,
             open_history_window,
+            get_offline_queue,
+            process_offline_queue,
+            delete_queued_dictation,
             check_permissions,
             request_microphone_permission,
             open_system_preferences,
@@ -2078,6 +2737,26 @@ fn main() {
                 }
             });
 
+            // Retry queued dictations until the providers can be reached.
+            // Runs even with the queue disabled so nothing queued earlier is
+            // left behind.
+            let queue_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    let interval = get_config()
+                        .await
+                        .ok()
+                        .and_then(|config| config.offline_queue.retry_interval_secs)
+                        .unwrap_or(DEFAULT_RETRY_INTERVAL_SECS);
+                    tokio::time::sleep(std::time::Duration::from_secs(interval.max(1))).await;
+                    if let Err(e) =
+                        process_offline_queue_pass(&queue_handle, QueuePass::Automatic).await
+                    {
+                        tracing::warn!("{}", e);
+                    }
+                }
+            });
+
             // Try to register global shortcut for pipeline toggle
             let app_handle = app.handle().clone();
 
@@ -1,3 +1,14 @@
+export interface SttCapabilities {
+  mode: 'streaming' | 'chunked';
+  partial_results: boolean;
+  languages: string[];
+  word_timestamps: boolean;
+  vocabulary_hints: boolean;
+  diarization: boolean;
+  local: boolean;
+  requires_api_key: boolean;
+}
+
 export interface Provider {
   id: string;
   name: string;
@@ -5,6 +16,7 @@ export interface Provider {
   provider_type: string;
   requires_api_key: boolean;
   model_status: string | null;
+  capabilities: SttCapabilities;
 }
 
 export interface ProviderGroups {
@@ -15,7 +27,9 @@ export interface ProviderGroups {
 
 export function groupSttProviders(providers: Provider[]): ProviderGroups {
   return {
-    localProviders: providers.filter((provider) => provider.provider_type === 'local'),
+    localProviders: providers.filter(
+      (provider) => provider.provider_type === 'local' && provider.id !== 'custom_stt'
+    ),
     cloudProviders: providers.filter(
       (provider) => provider.provider_type !== 'local' && provider.id !== 'custom_stt'
     ),